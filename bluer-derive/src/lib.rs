@@ -0,0 +1,431 @@
+//! Procedural macros for [BlueR](https://docs.rs/bluer).
+//!
+//! Use them through the `derive` feature of BlueR, which re-exports them
+//! in the `bluer::gatt::local` module.
+
+#![warn(missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Attribute, Error, Expr, ExprLit, FnArg, Ident, ImplItem, ImplItemFn, ItemImpl, Lit, LitBool, Result, Token,
+};
+
+/// Declares a local GATT service implemented by the methods of an `impl` block.
+///
+/// The service UUID is specified using `uuid = ...`, which accepts either a
+/// string literal or an expression of type `Uuid`.
+/// Use `primary = false` to declare a secondary service.
+///
+/// Each method marked with `#[characteristic(uuid = ..., flags...)]` handles one kind
+/// of request for the characteristic with the specified UUID.
+/// Methods sharing the same characteristic UUID are combined into one characteristic.
+/// The flags determine the kind of request and the required signature of the method:
+///
+///   * `read`, `encrypt_read`, `encrypt_authenticated_read`, `secure_read`:
+///     `async fn(&self, req: CharacteristicReadRequest) -> ReqResult<Vec<u8>>`
+///   * `write`, `write_without_response`, `reliable_write`, `authenticated_signed_writes`,
+///     `encrypt_write`, `encrypt_authenticated_write`, `secure_write`:
+///     `async fn(&self, value: Vec<u8>, req: CharacteristicWriteRequest) -> ReqResult<()>`
+///   * `notify`, `indicate`:
+///     `async fn(&self, notifier: CharacteristicNotifier)`,
+///     called when a client starts a notification session
+///
+/// The macro adds the method `fn gatt_service(self: Arc<Self>) -> Service` to the type,
+/// which builds the service definition for use in an `Application`.
+///
+/// # Example
+/// ```ignore
+/// use bluer::gatt::local::{
+///     gatt_service, CharacteristicNotifier, CharacteristicReadRequest, CharacteristicWriteRequest,
+///     ReqError, ReqResult,
+/// };
+/// use std::sync::{Arc, Mutex};
+///
+/// struct Counter {
+///     value: Mutex<u32>,
+/// }
+///
+/// #[gatt_service(uuid = "f7c8a3c6-1a4a-4c0e-9c6f-3f8b0c6b9a51")]
+/// impl Counter {
+///     #[characteristic(uuid = "f7c8a3c6-1a4a-4c0e-9c6f-3f8b0c6b9a52", read)]
+///     async fn value(&self, _req: CharacteristicReadRequest) -> ReqResult<Vec<u8>> {
+///         Ok(self.value.lock().unwrap().to_le_bytes().to_vec())
+///     }
+///
+///     #[characteristic(uuid = "f7c8a3c6-1a4a-4c0e-9c6f-3f8b0c6b9a52", write)]
+///     async fn set_value(&self, value: Vec<u8>, _req: CharacteristicWriteRequest) -> ReqResult<()> {
+///         let value = value.try_into().map_err(|_| ReqError::InvalidValueLength)?;
+///         *self.value.lock().unwrap() = u32::from_le_bytes(value);
+///         Ok(())
+///     }
+///
+///     #[characteristic(uuid = "f7c8a3c6-1a4a-4c0e-9c6f-3f8b0c6b9a52", notify)]
+///     async fn notify_value(&self, mut notifier: CharacteristicNotifier) {
+///         let value = *self.value.lock().unwrap();
+///         let _ = notifier.notify(value.to_le_bytes().to_vec()).await;
+///     }
+/// }
+///
+/// let service = Arc::new(Counter { value: Mutex::new(0) }).gatt_service();
+/// ```
+#[proc_macro_attribute]
+pub fn gatt_service(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr with Punctuated::<Arg, Token![,]>::parse_terminated);
+    let item = parse_macro_input!(item as ItemImpl);
+    match expand(args, item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Argument of an attribute: either `name = value` or a flag.
+enum Arg {
+    Value(Ident, Expr),
+    Flag(Ident),
+}
+
+impl Arg {
+    fn name(&self) -> &Ident {
+        match self {
+            Self::Value(name, _) | Self::Flag(name) => name,
+        }
+    }
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name: Ident = input.parse()?;
+        if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Ok(Self::Value(name, input.parse()?))
+        } else {
+            Ok(Self::Flag(name))
+        }
+    }
+}
+
+/// UUID specified in an attribute.
+#[derive(Clone)]
+enum UuidArg {
+    Literal(u128, Span),
+    Expr(Expr),
+}
+
+impl UuidArg {
+    fn parse(expr: Expr) -> Result<Self> {
+        match expr {
+            Expr::Lit(ExprLit { lit: Lit::Str(s), .. }) => {
+                let uuid = uuid::Uuid::parse_str(&s.value())
+                    .map_err(|err| Error::new(s.span(), format!("invalid UUID: {err}")))?;
+                Ok(Self::Literal(uuid.as_u128(), s.span()))
+            }
+            expr => Ok(Self::Expr(expr)),
+        }
+    }
+
+    /// Whether both specify the same UUID.
+    fn same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Literal(a, _), Self::Literal(b, _)) => a == b,
+            (Self::Expr(a), Self::Expr(b)) => a.to_token_stream().to_string() == b.to_token_stream().to_string(),
+            _ => false,
+        }
+    }
+}
+
+impl ToTokens for UuidArg {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match self {
+            Self::Literal(value, span) => {
+                let value = syn::LitInt::new(&format!("{value:#x}"), *span);
+                tokens.extend(quote! { ::bluer::Uuid::from_u128(#value) })
+            }
+            Self::Expr(expr) => expr.to_tokens(tokens),
+        }
+    }
+}
+
+/// Kind of request handled by a characteristic method.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Read,
+    Write,
+    Notify,
+}
+
+impl Kind {
+    fn of_flag(flag: &Ident) -> Result<Self> {
+        match flag.to_string().as_str() {
+            "read" | "encrypt_read" | "encrypt_authenticated_read" | "secure_read" => Ok(Self::Read),
+            "write"
+            | "write_without_response"
+            | "reliable_write"
+            | "authenticated_signed_writes"
+            | "encrypt_write"
+            | "encrypt_authenticated_write"
+            | "secure_write" => Ok(Self::Write),
+            "notify" | "indicate" => Ok(Self::Notify),
+            other => Err(Error::new(flag.span(), format!("unknown characteristic flag `{other}`"))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Notify => "notify",
+        }
+    }
+
+    fn arg_count(self) -> usize {
+        match self {
+            Self::Read | Self::Notify => 1,
+            Self::Write => 2,
+        }
+    }
+}
+
+/// Method handling requests of a characteristic.
+struct Handler {
+    method: Ident,
+    flags: Vec<Ident>,
+}
+
+/// Characteristic assembled from one or more methods.
+struct CharacteristicDef {
+    uuid: UuidArg,
+    read: Option<Handler>,
+    write: Option<Handler>,
+    notify: Option<Handler>,
+}
+
+impl CharacteristicDef {
+    fn handler_mut(&mut self, kind: Kind) -> &mut Option<Handler> {
+        match kind {
+            Kind::Read => &mut self.read,
+            Kind::Write => &mut self.write,
+            Kind::Notify => &mut self.notify,
+        }
+    }
+
+    fn to_tokens(&self) -> TokenStream2 {
+        let uuid = &self.uuid;
+
+        let read = self.read.as_ref().map(|Handler { method, flags }| {
+            quote! {
+                read: ::std::option::Option::Some(::bluer::gatt::local::CharacteristicRead {
+                    #(#flags: true,)*
+                    fun: {
+                        let this = ::std::sync::Arc::clone(&self);
+                        ::std::boxed::Box::new(move |req| {
+                            let this = ::std::sync::Arc::clone(&this);
+                            ::std::boxed::Box::pin(async move { this.#method(req).await })
+                        })
+                    },
+                    ..::std::default::Default::default()
+                }),
+            }
+        });
+
+        let write = self.write.as_ref().map(|Handler { method, flags }| {
+            quote! {
+                write: ::std::option::Option::Some(::bluer::gatt::local::CharacteristicWrite {
+                    #(#flags: true,)*
+                    method: ::bluer::gatt::local::CharacteristicWriteMethod::Fun({
+                        let this = ::std::sync::Arc::clone(&self);
+                        ::std::boxed::Box::new(move |value, req| {
+                            let this = ::std::sync::Arc::clone(&this);
+                            ::std::boxed::Box::pin(async move { this.#method(value, req).await })
+                        })
+                    }),
+                    ..::std::default::Default::default()
+                }),
+            }
+        });
+
+        let notify = self.notify.as_ref().map(|Handler { method, flags }| {
+            quote! {
+                notify: ::std::option::Option::Some(::bluer::gatt::local::CharacteristicNotify {
+                    #(#flags: true,)*
+                    method: ::bluer::gatt::local::CharacteristicNotifyMethod::Fun({
+                        let this = ::std::sync::Arc::clone(&self);
+                        ::std::boxed::Box::new(move |notifier| {
+                            let this = ::std::sync::Arc::clone(&this);
+                            ::std::boxed::Box::pin(async move { this.#method(notifier).await })
+                        })
+                    }),
+                    ..::std::default::Default::default()
+                }),
+            }
+        });
+
+        quote! {
+            ::bluer::gatt::local::Characteristic {
+                uuid: #uuid,
+                #read
+                #write
+                #notify
+                ..::std::default::Default::default()
+            }
+        }
+    }
+}
+
+/// Removes the `#[characteristic(...)]` attribute from the method and parses it.
+fn take_characteristic_attr(method: &mut ImplItemFn) -> Result<Option<(UuidArg, Kind, Vec<Ident>)>> {
+    let Some(pos) = method.attrs.iter().position(|attr| attr.path().is_ident("characteristic")) else {
+        return Ok(None);
+    };
+    let attr: Attribute = method.attrs.remove(pos);
+    if let Some(dup) = method.attrs.iter().find(|attr| attr.path().is_ident("characteristic")) {
+        return Err(Error::new(dup.span(), "duplicate `characteristic` attribute"));
+    }
+
+    let mut uuid = None;
+    let mut kind: Option<Kind> = None;
+    let mut flags = Vec::new();
+    for arg in attr.parse_args_with(Punctuated::<Arg, Token![,]>::parse_terminated)? {
+        match arg {
+            Arg::Value(name, value) if name == "uuid" => uuid = Some(UuidArg::parse(value)?),
+            Arg::Value(name, _) => {
+                return Err(Error::new(name.span(), format!("unknown characteristic argument `{name}`")))
+            }
+            Arg::Flag(flag) => {
+                let flag_kind = Kind::of_flag(&flag)?;
+                match kind {
+                    Some(kind) if kind != flag_kind => {
+                        return Err(Error::new(
+                            flag.span(),
+                            format!(
+                                "method already handles {} requests; use a separate method for {} requests",
+                                kind.name(),
+                                flag_kind.name()
+                            ),
+                        ))
+                    }
+                    _ => kind = Some(flag_kind),
+                }
+                flags.push(flag);
+            }
+        }
+    }
+
+    let Some(uuid) = uuid else {
+        return Err(Error::new_spanned(&attr, "missing characteristic `uuid`"));
+    };
+    let Some(kind) = kind else {
+        return Err(Error::new_spanned(
+            &attr,
+            "missing characteristic flags, for example `read`, `write` or `notify`",
+        ));
+    };
+
+    let sig = &method.sig;
+    if sig.asyncness.is_none() {
+        return Err(Error::new(sig.fn_token.span(), "characteristic methods must be `async`"));
+    }
+    match sig.inputs.first() {
+        Some(FnArg::Receiver(recv)) if recv.reference.is_some() && recv.mutability.is_none() => (),
+        _ => return Err(Error::new(sig.ident.span(), "characteristic methods must take `&self`")),
+    }
+    if sig.inputs.len() != kind.arg_count() + 1 {
+        let args = match kind {
+            Kind::Read => "`&self` and a `CharacteristicReadRequest`",
+            Kind::Write => "`&self`, the value and a `CharacteristicWriteRequest`",
+            Kind::Notify => "`&self` and a `CharacteristicNotifier`",
+        };
+        return Err(Error::new(
+            sig.inputs.span(),
+            format!("characteristic {} methods must take {args}", kind.name()),
+        ));
+    }
+
+    Ok(Some((uuid, kind, flags)))
+}
+
+fn expand(args: Punctuated<Arg, Token![,]>, mut item: ItemImpl) -> Result<TokenStream2> {
+    let mut uuid = None;
+    let mut primary = LitBool::new(true, Span::call_site());
+    for arg in args {
+        match arg {
+            Arg::Value(name, value) if name == "uuid" => uuid = Some(UuidArg::parse(value)?),
+            Arg::Value(name, Expr::Lit(ExprLit { lit: Lit::Bool(value), .. })) if name == "primary" => {
+                primary = value
+            }
+            arg => {
+                let name = arg.name();
+                return Err(Error::new(name.span(), format!("unknown gatt_service argument `{name}`")));
+            }
+        }
+    }
+    let Some(uuid) = uuid else {
+        return Err(Error::new(Span::call_site(), "missing service `uuid`"));
+    };
+
+    if let Some((_, path, _)) = &item.trait_ {
+        return Err(Error::new(path.span(), "gatt_service must be applied to an inherent impl block"));
+    }
+
+    let mut characteristics: Vec<CharacteristicDef> = Vec::new();
+    for impl_item in &mut item.items {
+        let ImplItem::Fn(method) = impl_item else { continue };
+        let Some((char_uuid, kind, flags)) = take_characteristic_attr(method)? else { continue };
+
+        let pos = match characteristics.iter().position(|c| c.uuid.same(&char_uuid)) {
+            Some(pos) => pos,
+            None => {
+                characteristics.push(CharacteristicDef {
+                    uuid: char_uuid,
+                    read: None,
+                    write: None,
+                    notify: None,
+                });
+                characteristics.len() - 1
+            }
+        };
+        let handler = characteristics[pos].handler_mut(kind);
+        if handler.is_some() {
+            return Err(Error::new(
+                method.sig.ident.span(),
+                format!("characteristic already has a {} method", kind.name()),
+            ));
+        }
+        *handler = Some(Handler { method: method.sig.ident.clone(), flags });
+    }
+
+    if characteristics.is_empty() {
+        return Err(Error::new(
+            item.self_ty.span(),
+            "gatt_service requires at least one method marked with `#[characteristic(...)]`",
+        ));
+    }
+
+    let self_ty = &item.self_ty;
+    let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+    let characteristics = characteristics.iter().map(CharacteristicDef::to_tokens);
+
+    Ok(quote! {
+        #item
+
+        impl #impl_generics #self_ty #where_clause {
+            /// Builds the GATT service definition.
+            ///
+            /// Requests are handled by the methods marked with `#[characteristic(...)]`.
+            #[allow(dead_code)]
+            pub fn gatt_service(self: ::std::sync::Arc<Self>) -> ::bluer::gatt::local::Service {
+                ::bluer::gatt::local::Service {
+                    uuid: #uuid,
+                    primary: #primary,
+                    characteristics: ::std::vec![#(#characteristics),*],
+                    ..::std::default::Default::default()
+                }
+            }
+        }
+    })
+}
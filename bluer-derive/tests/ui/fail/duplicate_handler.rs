@@ -0,0 +1,14 @@
+use bluer::gatt::local::gatt_service;
+
+struct Service;
+
+#[gatt_service(uuid = "f7c8a3c6-1a4a-4c0e-9c6f-3f8b0c6b9a51")]
+impl Service {
+    #[characteristic(uuid = "f7c8a3c6-1a4a-4c0e-9c6f-3f8b0c6b9a52", notify)]
+    async fn value(&self, _notifier: bluer::gatt::local::CharacteristicNotifier) {}
+
+    #[characteristic(uuid = "F7C8A3C6-1A4A-4C0E-9C6F-3F8B0C6B9A52", indicate)]
+    async fn value2(&self, _notifier: bluer::gatt::local::CharacteristicNotifier) {}
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use bluer::gatt::local::gatt_service;
+
+struct Service;
+
+#[gatt_service(uuid = "f7c8a3c6-1a4a-4c0e-9c6f-3f8b0c6b9a51")]
+impl Service {
+    #[characteristic(read)]
+    async fn value(&self, _req: bluer::gatt::local::CharacteristicReadRequest) -> bluer::gatt::local::ReqResult<Vec<u8>> {
+        Ok(vec![])
+    }
+}
+
+fn main() {}
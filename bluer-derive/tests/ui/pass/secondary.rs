@@ -0,0 +1,17 @@
+use bluer::gatt::local::{gatt_service, CharacteristicReadRequest, ReqResult};
+use std::sync::Arc;
+
+struct Secondary;
+
+#[gatt_service(uuid = "0000180f-0000-1000-8000-00805f9b34fb", primary = false)]
+impl Secondary {
+    #[characteristic(uuid = "00002a19-0000-1000-8000-00805f9b34fb", read)]
+    async fn level(&self, _req: CharacteristicReadRequest) -> ReqResult<Vec<u8>> {
+        Ok(vec![100])
+    }
+}
+
+fn main() {
+    let service = Arc::new(Secondary).gatt_service();
+    assert!(!service.primary);
+}
@@ -0,0 +1,64 @@
+use bluer::{
+    gatt::local::{
+        gatt_service, CharacteristicNotifier, CharacteristicReadRequest, CharacteristicWriteRequest, ReqResult,
+    },
+    Uuid,
+};
+use std::sync::{Arc, Mutex};
+
+const VALUE_UUID: Uuid = Uuid::from_u128(0xf7c8a3c6_1a4a_4c0e_9c6f_3f8b0c6b9a52);
+
+struct Counter {
+    value: Mutex<u32>,
+}
+
+#[gatt_service(uuid = "f7c8a3c6-1a4a-4c0e-9c6f-3f8b0c6b9a51")]
+impl Counter {
+    #[characteristic(uuid = VALUE_UUID, read)]
+    async fn value(&self, _req: CharacteristicReadRequest) -> ReqResult<Vec<u8>> {
+        Ok(self.value.lock().unwrap().to_le_bytes().to_vec())
+    }
+
+    #[characteristic(uuid = VALUE_UUID, write, write_without_response)]
+    async fn set_value(&self, value: Vec<u8>, _req: CharacteristicWriteRequest) -> ReqResult<()> {
+        *self.value.lock().unwrap() = value.len() as u32;
+        Ok(())
+    }
+
+    #[characteristic(uuid = VALUE_UUID, notify)]
+    async fn notify_value(&self, mut notifier: CharacteristicNotifier) {
+        let value = *self.value.lock().unwrap();
+        let _ = notifier.notify(value.to_le_bytes().to_vec()).await;
+    }
+
+    #[characteristic(uuid = "f7c8a3c6-1a4a-4c0e-9c6f-3f8b0c6b9a53", secure_read)]
+    async fn secret(&self, _req: CharacteristicReadRequest) -> ReqResult<Vec<u8>> {
+        Ok(vec![1, 2, 3])
+    }
+
+    fn reset(&self) {
+        *self.value.lock().unwrap() = 0;
+    }
+}
+
+fn main() {
+    let counter = Arc::new(Counter { value: Mutex::new(0) });
+    counter.reset();
+
+    let service = counter.gatt_service();
+    assert!(service.primary);
+    assert_eq!(service.uuid, Uuid::from_u128(0xf7c8a3c6_1a4a_4c0e_9c6f_3f8b0c6b9a51));
+    assert_eq!(service.characteristics.len(), 2);
+
+    let value = &service.characteristics[0];
+    assert_eq!(value.uuid, VALUE_UUID);
+    assert!(value.read.as_ref().unwrap().read);
+    let write = value.write.as_ref().unwrap();
+    assert!(write.write && write.write_without_response);
+    assert!(value.notify.as_ref().unwrap().notify);
+
+    let secret = &service.characteristics[1];
+    let read = secret.read.as_ref().unwrap();
+    assert!(read.secure_read && !read.read);
+    assert!(secret.write.is_none() && secret.notify.is_none());
+}
@@ -0,0 +1,84 @@
+//! Captures HCI traffic into btsnoop and pcapng files.
+
+use bluer::hci::capture::{Capture, Format};
+use clap::{Parser, ValueEnum};
+use std::{path::PathBuf, process::exit, time::Duration};
+use tokio::{select, signal::ctrl_c, time::sleep};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum FileFormat {
+    /// btsnoop format as used by btmon.
+    Btsnoop,
+    /// pcapng format for Wireshark.
+    Pcapng,
+}
+
+impl From<FileFormat> for Format {
+    fn from(format: FileFormat) -> Self {
+        match format {
+            FileFormat::Btsnoop => Self::Btsnoop,
+            FileFormat::Pcapng => Self::Pcapng,
+        }
+    }
+}
+
+#[derive(Parser)]
+#[clap(
+    name = "blucap",
+    about = "Captures Bluetooth HCI traffic of all controllers into btsnoop or pcapng files.\n\
+             Files can be opened using btmon -r or Wireshark.\n\
+             Requires the CAP_NET_ADMIN capability.",
+    author = "Sebastian Urban <surban@surban.net>",
+    version = env!("CARGO_PKG_VERSION"),
+)]
+struct Opts {
+    /// Capture file to write.
+    /// An existing file is overwritten.
+    file: PathBuf,
+    /// File format.
+    /// If unspecified, pcapng is used for files ending in .pcapng or .pcap and btsnoop otherwise.
+    #[clap(long, short, value_enum)]
+    format: Option<FileFormat>,
+    /// Stop capturing after the specified number of seconds.
+    /// If unspecified, capture until interrupted by Ctrl+C.
+    #[clap(long, short)]
+    duration: Option<u64>,
+}
+
+impl Opts {
+    async fn perform(self) -> Result<()> {
+        let format = self.format.map(Format::from).unwrap_or_else(|| Format::from_path(&self.file));
+        let capture = Capture::start_with_format(&self.file, format).await?;
+        eprintln!("Capturing into {}, press Ctrl+C to stop", self.file.display());
+
+        let duration = async {
+            match self.duration {
+                Some(secs) => sleep(Duration::from_secs(secs)).await,
+                None => std::future::pending().await,
+            }
+        };
+        select! {
+            res = ctrl_c() => res?,
+            () = duration => (),
+        }
+
+        let count = capture.stop().await?;
+        eprintln!("Captured {count} packets");
+        Ok(())
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    env_logger::init();
+    let opts: Opts = Opts::parse();
+    match opts.perform().await {
+        Ok(()) => exit(0),
+        Err(err) => {
+            eprintln!("Error: {}", &err);
+            exit(2);
+        }
+    }
+}
@@ -8,9 +8,10 @@ use bluer::{
     },
     gatt::{
         local::{
-            self, characteristic_control, Application, ApplicationHandle, CharacteristicControlEvent,
-            CharacteristicNotify, CharacteristicWrite, Service,
+            self, characteristic_control, Application, ApplicationHandle, CharacteristicControl,
+            CharacteristicControlEvent, CharacteristicNotify, CharacteristicWrite, Service,
         },
+        nus::{NusServer, NusStream},
         remote, CharacteristicFlags, CharacteristicReader, CharacteristicWriter, WriteOp,
     },
     id, Adapter, AdapterEvent, Address, AddressType, Device, DeviceEvent, DeviceProperty, Session, SessionEvent,
@@ -19,11 +20,7 @@ use bluer::{
 use bytes::BytesMut;
 use clap::Parser;
 use crossterm::{terminal, tty::IsTty};
-use futures::{
-    future, pin_mut,
-    stream::{self, SelectAll},
-    FutureExt, Stream, StreamExt, TryFutureExt,
-};
+use futures::{future, pin_mut, stream::SelectAll, FutureExt, Stream, StreamExt, TryFutureExt};
 use libc::{STDIN_FILENO, STDOUT_FILENO};
 use pretty_hex::{hex_write, HexConfig};
 use std::{
@@ -1069,35 +1066,28 @@ impl ConnectOpts {
         let dev = find_device(&adapter, self.address).await?;
         connect(&dev).await?;
 
-        let rh;
-        let wh;
+        let rh: Option<Box<dyn AsyncRead + Unpin>>;
+        let wh: Option<Box<dyn AsyncWrite + Unpin>>;
+        let mtu;
         if self.nordic_uart {
-            let rx_char = find_characteristic(
-                &dev,
-                id::Service::ComNordicsemiServiceUart.into(),
-                id::Characteristic::ComNordicsemiCharacteristicUartRx.into(),
-            )
-            .await?
-            .ok_or("TX service or characteristic not found")?;
-            let tx_char = find_characteristic(
-                &dev,
-                id::Service::ComNordicsemiServiceUart.into(),
-                id::Characteristic::ComNordicsemiCharacteristicUartTx.into(),
-            )
-            .await?
-            .ok_or("TX service or characteristic not found")?;
-            rh = tx_char.notify_io().await.ok();
-            wh = rx_char.write_io().await.ok();
+            let stream = NusStream::connect(&dev).await?;
+            mtu = stream.mtu();
+            let (stream_rh, stream_wh) = tokio::io::split(stream);
+            rh = Some(Box::new(stream_rh));
+            wh = Some(Box::new(stream_wh));
         } else {
             let char = find_characteristic(&dev, self.service.into(), self.characteristic.into())
                 .await?
                 .ok_or("service or characteristic not found")?;
-            rh = char.notify_io().await.ok();
-            wh = char.write_io().await.ok();
-        };
-
-        if rh.is_none() && wh.is_none() {
-            return Err("neither writing nor notify are supported".into());
+            let char_rh = char.notify_io().await.ok();
+            let char_wh = char.write_io().await.ok();
+            mtu = match (&char_rh, &char_wh) {
+                (Some(rh), _) => rh.mtu(),
+                (_, Some(wh)) => wh.mtu(),
+                _ => return Err("neither writing nor notify are supported".into()),
+            };
+            rh = char_rh.map(|rh| Box::new(rh) as _);
+            wh = char_wh.map(|wh| Box::new(wh) as _);
         }
 
         let is_tty = std::io::stdin().is_tty();
@@ -1108,7 +1098,7 @@ impl ConnectOpts {
             false
         };
 
-        io_loop(rh, wh, tokio::io::stdin(), tokio::io::stdout(), true, is_tty, true).await?;
+        io_loop(rh, wh, mtu, tokio::io::stdin(), tokio::io::stdout(), true, is_tty, true).await?;
 
         if in_raw {
             terminal::disable_raw_mode()?;
@@ -1120,9 +1110,11 @@ impl ConnectOpts {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn io_loop(
-    mut rh: Option<CharacteristicReader>, mut wh: Option<CharacteristicWriter>, pin: impl AsyncRead + Unpin,
-    pout: impl AsyncWrite + Unpin, is_std: bool, rh_required: bool, pin_required: bool,
+    mut rh: Option<impl AsyncRead + Unpin>, mut wh: Option<impl AsyncWrite + Unpin>, mtu: usize,
+    pin: impl AsyncRead + Unpin, pout: impl AsyncWrite + Unpin, is_std: bool, rh_required: bool,
+    pin_required: bool,
 ) -> Result<()> {
     let mut pin = Some(pin);
     let mut pout = Some(pout);
@@ -1135,11 +1127,6 @@ async fn io_loop(
             break;
         }
 
-        let mtu = match (&rh, &wh) {
-            (Some(rh), _) => rh.mtu(),
-            (_, Some(wh)) => wh.mtu(),
-            _ => 100,
-        };
         let mut recv_buf = BytesMut::with_capacity(mtu);
         let mut pin_buf = BytesMut::with_capacity(mtu);
 
@@ -1225,26 +1212,35 @@ struct ListenOpts {
 impl ListenOpts {
     pub async fn perform(self) -> Result<()> {
         let (_session, adapter) = get_session_adapter(self.bind).await?;
-        let (_adv, _app, mut control) = make_app(
-            &adapter,
-            self.no_advertise,
-            self.nordic_uart,
-            self.service.into(),
-            self.characteristic.into(),
-        )
-        .await?;
+
+        if self.nordic_uart {
+            let mut server = make_nus_server(&adapter, self.no_advertise).await?;
+
+            if self.verbose {
+                println!("Serving on {}", adapter.address().await?);
+            }
+
+            let stream = server.accept().await?;
+            let mtu = stream.mtu();
+            let (rh, wh) = tokio::io::split(stream);
+
+            let in_raw = self.enable_raw_mode()?;
+            io_loop(Some(rh), Some(wh), mtu, tokio::io::stdin(), tokio::io::stdout(), true, true, true).await?;
+            if in_raw {
+                terminal::disable_raw_mode()?;
+            }
+
+            return Ok(());
+        }
+
+        let (_adv, _app, mut control) =
+            make_app(&adapter, self.no_advertise, self.service.into(), self.characteristic.into()).await?;
 
         if self.verbose {
             println!("Serving on {}", adapter.address().await?);
         }
 
-        let is_tty = std::io::stdin().is_tty();
-        let in_raw = if is_tty && self.raw {
-            terminal::enable_raw_mode()?;
-            true
-        } else {
-            false
-        };
+        let in_raw = self.enable_raw_mode()?;
 
         io_loop_serve(&mut control, None, None, tokio::io::stdin(), tokio::io::stdout(), true, true, true)
             .await?;
@@ -1255,6 +1251,15 @@ impl ListenOpts {
 
         Ok(())
     }
+
+    fn enable_raw_mode(&self) -> Result<bool> {
+        if std::io::stdin().is_tty() && self.raw {
+            terminal::enable_raw_mode()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
 }
 
 #[derive(Parser)]
@@ -1315,30 +1320,41 @@ impl ServeOpts {
             exit(3);
         });
 
-        loop {
-            let (_adv, _app, mut control) = make_app(
-                &adapter,
-                self.no_advertise,
-                self.nordic_uart,
-                self.service.into(),
-                self.characteristic.into(),
-            )
-            .await?;
+        let mut nus_server =
+            if self.nordic_uart { Some(make_nus_server(&adapter, self.no_advertise).await?) } else { None };
 
-            let mut rh = None;
-            let mut wh = None;
+        loop {
+            let mut _adv_app = None;
+            let conn;
             let mtu;
 
-            match control.next().await {
-                Some(CharacteristicControlEvent::Write(req)) => {
-                    mtu = req.mtu();
-                    rh = Some(req.accept()?);
+            match &mut nus_server {
+                Some(nus_server) => {
+                    let stream = nus_server.accept().await?;
+                    mtu = stream.mtu();
+                    conn = ServeConnection::Nus(stream);
                 }
-                Some(CharacteristicControlEvent::Notify(notifier)) => {
-                    mtu = notifier.mtu();
-                    wh = Some(notifier);
+                None => {
+                    let (adv, app, mut control) =
+                        make_app(&adapter, self.no_advertise, self.service.into(), self.characteristic.into())
+                            .await?;
+                    _adv_app = Some((adv, app));
+
+                    let mut rh = None;
+                    let mut wh = None;
+                    match control.next().await {
+                        Some(CharacteristicControlEvent::Write(req)) => {
+                            mtu = req.mtu();
+                            rh = Some(req.accept()?);
+                        }
+                        Some(CharacteristicControlEvent::Notify(notifier)) => {
+                            mtu = notifier.mtu();
+                            wh = Some(notifier);
+                        }
+                        None => break,
+                    }
+                    conn = ServeConnection::Gatt { control, rh, wh };
                 }
-                None => break,
             }
 
             if self.verbose {
@@ -1361,7 +1377,7 @@ impl ServeOpts {
                 let pin = IoCompat::new(pin);
                 let pout = IoCompat::new(pout);
                 select! {
-                    res = io_loop_serve(&mut control, rh, wh, pin, pout, false, true, false) => {
+                    res = conn.io_loop(pin, pout) => {
                         res?;
                         if self.verbose {
                             eprintln!("Connection terminated");
@@ -1390,7 +1406,7 @@ impl ServeOpts {
                 let pin = child.stdout.take().unwrap();
                 let pout = child.stdin.take().unwrap();
                 select! {
-                    res = io_loop_serve(&mut control, rh, wh, pin, pout, false, true, false) => {
+                    res = conn.io_loop(pin, pout) => {
                         res?;
                         if self.verbose {
                             eprintln!("Connection terminated");
@@ -1414,9 +1430,8 @@ impl ServeOpts {
 }
 
 async fn make_app(
-    adapter: &Adapter, no_advertise: bool, nordic_uart: bool, service: Uuid, characteristic: Uuid,
-) -> Result<(Option<AdvertisementHandle>, ApplicationHandle, impl Stream<Item = CharacteristicControlEvent>)> {
-    let service = if nordic_uart { id::Service::ComNordicsemiServiceUart.into() } else { service };
+    adapter: &Adapter, no_advertise: bool, service: Uuid, characteristic: Uuid,
+) -> Result<(Option<AdvertisementHandle>, ApplicationHandle, CharacteristicControl)> {
     let le_advertisement = Advertisement {
         service_uuids: vec![service].into_iter().collect(),
         discoverable: Some(true),
@@ -1424,72 +1439,62 @@ async fn make_app(
     };
     let adv = if !no_advertise { Some(adapter.advertise(le_advertisement).await?) } else { None };
 
-    #[allow(clippy::branches_sharing_code)]
-    let (app, events) = if nordic_uart {
-        let (control_rx, control_rx_handle) = characteristic_control();
-        let (control_tx, control_tx_handle) = characteristic_control();
-        let app = Application {
-            services: vec![Service {
-                uuid: id::Service::ComNordicsemiServiceUart.into(),
-                primary: true,
-                characteristics: vec![
-                    local::Characteristic {
-                        uuid: id::Characteristic::ComNordicsemiCharacteristicUartRx.into(),
-                        write: Some(CharacteristicWrite {
-                            write: true,
-                            write_without_response: true,
-                            method: bluer::gatt::local::CharacteristicWriteMethod::Io,
-                            ..Default::default()
-                        }),
-                        control_handle: control_rx_handle,
-                        ..Default::default()
-                    },
-                    local::Characteristic {
-                        uuid: id::Characteristic::ComNordicsemiCharacteristicUartTx.into(),
-                        notify: Some(CharacteristicNotify {
-                            notify: true,
-                            method: bluer::gatt::local::CharacteristicNotifyMethod::Io,
-                            ..Default::default()
-                        }),
-                        control_handle: control_tx_handle,
-                        ..Default::default()
-                    },
-                ],
-                ..Default::default()
-            }],
-            ..Default::default()
-        };
-        let control = stream::select(control_tx, control_rx).boxed();
-        (adapter.serve_gatt_application(app).await?, control)
-    } else {
-        let (control, control_handle) = characteristic_control();
-        let app = Application {
-            services: vec![Service {
-                uuid: service,
-                primary: true,
-                characteristics: vec![local::Characteristic {
-                    uuid: characteristic,
-                    write: Some(CharacteristicWrite {
-                        write_without_response: true,
-                        method: bluer::gatt::local::CharacteristicWriteMethod::Io,
-                        ..Default::default()
-                    }),
-                    notify: Some(CharacteristicNotify {
-                        notify: true,
-                        method: bluer::gatt::local::CharacteristicNotifyMethod::Io,
-                        ..Default::default()
-                    }),
-                    control_handle,
+    let (control, control_handle) = characteristic_control();
+    let app = Application {
+        services: vec![Service {
+            uuid: service,
+            primary: true,
+            characteristics: vec![local::Characteristic {
+                uuid: characteristic,
+                write: Some(CharacteristicWrite {
+                    write_without_response: true,
+                    method: bluer::gatt::local::CharacteristicWriteMethod::Io,
                     ..Default::default()
-                }],
+                }),
+                notify: Some(CharacteristicNotify {
+                    notify: true,
+                    method: bluer::gatt::local::CharacteristicNotifyMethod::Io,
+                    ..Default::default()
+                }),
+                control_handle,
                 ..Default::default()
             }],
             ..Default::default()
-        };
-        (adapter.serve_gatt_application(app).await?, control.boxed())
+        }],
+        ..Default::default()
     };
+    let app = adapter.serve_gatt_application(app).await?;
+
+    Ok((adv, app, control))
+}
+
+async fn make_nus_server(adapter: &Adapter, no_advertise: bool) -> Result<NusServer> {
+    let le_advertisement = Advertisement { discoverable: Some(true), ..Default::default() };
+    let server =
+        NusServer::serve_with_advertisement(adapter, if no_advertise { None } else { Some(le_advertisement) })
+            .await?;
+    Ok(server)
+}
+
+/// Connection accepted by [ServeOpts].
+enum ServeConnection {
+    Gatt { control: CharacteristicControl, rh: Option<CharacteristicReader>, wh: Option<CharacteristicWriter> },
+    Nus(NusStream),
+}
 
-    Ok((adv, app, events))
+impl ServeConnection {
+    async fn io_loop(self, pin: impl AsyncRead + Unpin, pout: impl AsyncWrite + Unpin) -> Result<()> {
+        match self {
+            Self::Gatt { mut control, rh, wh } => {
+                io_loop_serve(&mut control, rh, wh, pin, pout, false, true, false).await
+            }
+            Self::Nus(stream) => {
+                let mtu = stream.mtu();
+                let (rh, wh) = tokio::io::split(stream);
+                io_loop(Some(rh), Some(wh), mtu, pin, pout, false, true, false).await
+            }
+        }
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -0,0 +1,100 @@
+//! Join or attach a mesh network and receive sensor status messages.
+//!
+//! Example meshd
+//! [bluer/bluer]$ sudo /usr/libexec/bluetooth/bluetooth-meshd --config ${PWD}/examples/meshd/config --storage ${PWD}/examples/meshd/lib --debug
+//!
+//! Example receive
+//! [bluer/bluer]$ RUST_LOG=TRACE cargo run --features=mesh --example mesh_sensor_client -- --token 7eb48c91911361da
+//!
+//! Example send
+//! [bluer/bluer]$ RUST_LOG=TRACE cargo run --features=mesh --example mesh_sensor_server -- --token dae519a06e504bd3
+
+use bluer::mesh::{
+    access::Dispatcher,
+    application::Application,
+    element::{element_control, Element, ElementControlHandle, Model},
+    models::sensor::{PropertyId, SensorClient, SENSOR_CLIENT},
+};
+use clap::Parser;
+use tokio::{
+    signal,
+    time::{sleep, Duration},
+};
+use uuid::Uuid;
+
+#[derive(Parser)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Token of the node (hex), join the network if not specified.
+    #[clap(short, long, value_parser = parse_hex)]
+    token: Option<u64>,
+}
+
+fn parse_hex(s: &str) -> Result<u64, std::num::ParseIntError> {
+    u64::from_str_radix(s, 16)
+}
+
+fn application(device_id: Uuid, control_handle: ElementControlHandle) -> Application {
+    Application {
+        device_id,
+        elements: vec![Element { models: vec![Model::new(SENSOR_CLIENT)], control_handle, ..Default::default() }],
+        ..Default::default()
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+    let args = Args::parse();
+    let session = bluer::Session::new().await?;
+    let mesh = session.mesh().await?;
+
+    let (element_control, element_handle) = element_control();
+    let device_id = Uuid::new_v4();
+
+    let token = match args.token {
+        Some(token) => token,
+        None => {
+            println!("Joining device: {}", device_id.as_simple());
+            let app = mesh.join(application(device_id, element_handle.clone())).await?;
+            let token = app.token().expect("token after join");
+            println!("Joined with token {token:016x}");
+            drop(app);
+
+            // Wait a bit for the configuration to take effect.
+            sleep(Duration::from_secs(5)).await;
+            token
+        }
+    };
+
+    println!("Attaching with token {token:016x}");
+    let node = mesh.attach(application(device_id, element_handle), token).await?;
+
+    let (status_handler, mut status_rx) = SensorClient::status_handler();
+    let mut dispatcher = Dispatcher::new(node, element_control);
+    dispatcher.add_model(status_handler)?;
+    let handle = dispatcher.run();
+
+    println!("Sensor client ready. Press Ctrl+C to quit");
+
+    loop {
+        tokio::select! {
+            _ = signal::ctrl_c() => break,
+            Some(received) = status_rx.recv() => {
+                match received.message.value(PropertyId::PRESENT_AMBIENT_TEMPERATURE) {
+                    // The temperature is encoded in steps of 0.5 degrees Celsius.
+                    Some(&[value]) => {
+                        println!("Temperature from {:#06x}: {} °C", received.source, value as i8 as f32 / 2.0)
+                    }
+                    _ => println!("Sensor status from {:#06x}: {:?}", received.source, received.message),
+                }
+            }
+        }
+    }
+
+    println!("Shutting down");
+    drop(handle);
+    sleep(Duration::from_secs(1)).await;
+
+    Ok(())
+}
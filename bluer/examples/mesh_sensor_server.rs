@@ -0,0 +1,114 @@
+//! Join or attach a mesh network and send sensor status messages.
+//!
+//! Example meshd
+//! [bluer/bluer]$ sudo /usr/libexec/bluetooth/bluetooth-meshd --config ${PWD}/examples/meshd/config --storage ${PWD}/examples/meshd/lib --debug
+//!
+//! Example send
+//! [bluer/bluer]$ RUST_LOG=TRACE cargo run --features=mesh --example mesh_sensor_server -- --token dae519a06e504bd3
+//!
+//! Example receive
+//! [bluer/bluer]$ RUST_LOG=TRACE cargo run --features=mesh --example mesh_sensor_client -- --token 7eb48c91911361da
+
+use bluer::mesh::{
+    access::Dispatcher,
+    application::Application,
+    element::{element_control, Element, ElementControlHandle, Model},
+    models::sensor::{PropertyId, SensorDescriptor, SensorServer, SENSOR_SERVER},
+};
+use clap::Parser;
+use tokio::{
+    io::{stdin, AsyncBufReadExt, BufReader},
+    signal,
+    time::{interval, sleep, Duration},
+};
+use uuid::Uuid;
+
+#[derive(Parser)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Token of the node (hex), join the network if not specified.
+    #[clap(short, long, value_parser = parse_hex)]
+    token: Option<u64>,
+    /// Destination address of the sensor status messages (hex).
+    #[clap(short, long, value_parser = parse_hex_u16, default_value = "00bc")]
+    destination: u16,
+}
+
+fn parse_hex(s: &str) -> Result<u64, std::num::ParseIntError> {
+    u64::from_str_radix(s, 16)
+}
+
+fn parse_hex_u16(s: &str) -> Result<u16, std::num::ParseIntError> {
+    u16::from_str_radix(s, 16)
+}
+
+/// Present ambient temperature in degrees Celsius.
+const TEMPERATURE: f32 = 21.0;
+
+fn application(device_id: Uuid, control_handle: ElementControlHandle) -> Application {
+    Application {
+        device_id,
+        elements: vec![Element { models: vec![Model::new(SENSOR_SERVER)], control_handle, ..Default::default() }],
+        ..Default::default()
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+    let args = Args::parse();
+    let session = bluer::Session::new().await?;
+    let mesh = session.mesh().await?;
+
+    let (element_control, element_handle) = element_control();
+    let device_id = Uuid::new_v4();
+
+    let token = match args.token {
+        Some(token) => token,
+        None => {
+            println!("Joining device: {}", device_id.as_simple());
+            let app = mesh.join(application(device_id, element_handle.clone())).await?;
+            let token = app.token().expect("token after join");
+            println!("Joined with token {token:016x}");
+            drop(app);
+
+            // Wait a bit for the configuration to take effect.
+            sleep(Duration::from_secs(5)).await;
+            token
+        }
+    };
+
+    println!("Attaching with token {token:016x}");
+    let node = mesh.attach(application(device_id, element_handle), token).await?;
+
+    let server = SensorServer::new([SensorDescriptor::new(PropertyId::PRESENT_AMBIENT_TEMPERATURE)]);
+    // The temperature is encoded in steps of 0.5 degrees Celsius.
+    server.set_value(PropertyId::PRESENT_AMBIENT_TEMPERATURE, vec![(TEMPERATURE * 2.0) as i8 as u8])?;
+
+    let mut dispatcher = Dispatcher::new(node, element_control);
+    dispatcher.add_model(server.handler())?;
+    let handle = dispatcher.run();
+
+    println!("Sensor server ready. Press enter to send a message. Press Ctrl+C to quit");
+
+    let mut lines = BufReader::new(stdin()).lines();
+    let mut interval = interval(Duration::from_secs(16));
+    loop {
+        tokio::select! {
+            _ = signal::ctrl_c() => break,
+            _ = interval.tick() => (),
+            Ok(Some(_)) = lines.next_line() => (),
+        }
+
+        println!("Sending temperature {TEMPERATURE} °C to {:#06x}", args.destination);
+        if let Err(err) = handle.send(args.destination, 0, &server.status()).await {
+            println!("Sending failed: {err}");
+        }
+    }
+
+    println!("Shutting down");
+    drop(handle);
+    sleep(Duration::from_secs(1)).await;
+
+    Ok(())
+}
@@ -0,0 +1,600 @@
+//! Advertising data (AD) structures.
+//!
+//! Advertising and scan response data, as well as extended inquiry response (EIR) data,
+//! consist of a sequence of AD structures.
+//! Each AD structure is encoded as a length byte, followed by the AD type and the data.
+//!
+//! Use [AdStructure::parse] to decode raw advertising data into [AdStructure]s and
+//! [AdStructure::encode] to encode them, checking the length limit of the [AdvertisingPdu].
+//! The map representation used by
+//! [Device::advertising_data](crate::Device::advertising_data) and
+//! [Advertisement::advertising_data](crate::adv::Advertisement::advertising_data)
+//! is converted using [AdStructure::from_map] and [AdStructure::to_map].
+
+use std::{
+    collections::BTreeMap,
+    io::{Error, ErrorKind, Result},
+};
+use uuid::Uuid;
+
+use crate::{Address, UuidExt};
+
+/// AD type values assigned by the Bluetooth SIG.
+pub mod ad_type {
+    /// Flags.
+    pub const FLAGS: u8 = 0x01;
+    /// Incomplete list of 16-bit service class UUIDs.
+    pub const INCOMPLETE_UUIDS_16: u8 = 0x02;
+    /// Complete list of 16-bit service class UUIDs.
+    pub const COMPLETE_UUIDS_16: u8 = 0x03;
+    /// Incomplete list of 32-bit service class UUIDs.
+    pub const INCOMPLETE_UUIDS_32: u8 = 0x04;
+    /// Complete list of 32-bit service class UUIDs.
+    pub const COMPLETE_UUIDS_32: u8 = 0x05;
+    /// Incomplete list of 128-bit service class UUIDs.
+    pub const INCOMPLETE_UUIDS_128: u8 = 0x06;
+    /// Complete list of 128-bit service class UUIDs.
+    pub const COMPLETE_UUIDS_128: u8 = 0x07;
+    /// Shortened local name.
+    pub const SHORT_LOCAL_NAME: u8 = 0x08;
+    /// Complete local name.
+    pub const COMPLETE_LOCAL_NAME: u8 = 0x09;
+    /// TX power level.
+    pub const TX_POWER_LEVEL: u8 = 0x0a;
+    /// Class of device.
+    pub const CLASS_OF_DEVICE: u8 = 0x0d;
+    /// Peripheral connection interval range.
+    pub const PERIPHERAL_CONNECTION_INTERVAL_RANGE: u8 = 0x12;
+    /// List of 16-bit service solicitation UUIDs.
+    pub const SOLICITED_UUIDS_16: u8 = 0x14;
+    /// List of 128-bit service solicitation UUIDs.
+    pub const SOLICITED_UUIDS_128: u8 = 0x15;
+    /// Service data with 16-bit UUID.
+    pub const SERVICE_DATA_16: u8 = 0x16;
+    /// Public target address.
+    pub const PUBLIC_TARGET_ADDRESS: u8 = 0x17;
+    /// Random target address.
+    pub const RANDOM_TARGET_ADDRESS: u8 = 0x18;
+    /// Appearance.
+    pub const APPEARANCE: u8 = 0x19;
+    /// Advertising interval.
+    pub const ADVERTISING_INTERVAL: u8 = 0x1a;
+    /// LE Bluetooth device address.
+    pub const LE_DEVICE_ADDRESS: u8 = 0x1b;
+    /// LE role.
+    pub const LE_ROLE: u8 = 0x1c;
+    /// List of 32-bit service solicitation UUIDs.
+    pub const SOLICITED_UUIDS_32: u8 = 0x1f;
+    /// Service data with 32-bit UUID.
+    pub const SERVICE_DATA_32: u8 = 0x20;
+    /// Service data with 128-bit UUID.
+    pub const SERVICE_DATA_128: u8 = 0x21;
+    /// URI.
+    pub const URI: u8 = 0x24;
+    /// LE supported features.
+    pub const LE_SUPPORTED_FEATURES: u8 = 0x27;
+    /// Advertising interval, long.
+    pub const ADVERTISING_INTERVAL_LONG: u8 = 0x2f;
+    /// Broadcast name.
+    pub const BROADCAST_NAME: u8 = 0x30;
+    /// Manufacturer specific data.
+    pub const MANUFACTURER_DATA: u8 = 0xff;
+}
+
+/// Bits of the [flags AD structure](AdStructure::Flags).
+pub mod flags {
+    /// LE limited discoverable mode.
+    pub const LE_LIMITED_DISCOVERABLE: u8 = 0x01;
+    /// LE general discoverable mode.
+    pub const LE_GENERAL_DISCOVERABLE: u8 = 0x02;
+    /// BR/EDR not supported.
+    pub const BR_EDR_NOT_SUPPORTED: u8 = 0x04;
+    /// Simultaneous LE and BR/EDR to same device capable (controller).
+    pub const LE_BR_EDR_CONTROLLER: u8 = 0x08;
+}
+
+/// Kind of advertising PDU that determines the maximum length of advertising data.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum AdvertisingPdu {
+    /// Legacy advertising PDU.
+    Legacy,
+    /// Extended advertising PDU.
+    Extended,
+}
+
+impl AdvertisingPdu {
+    /// Maximum length of advertising data in bytes.
+    pub const fn max_data_len(&self) -> usize {
+        match self {
+            Self::Legacy => 31,
+            Self::Extended => 251,
+        }
+    }
+}
+
+/// Advertising data (AD) structure.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum AdStructure {
+    /// Flags, see [flags].
+    Flags(u8),
+    /// List of 16-bit service class UUIDs.
+    ServiceUuids16 {
+        /// Whether the list is complete.
+        complete: bool,
+        /// 16-bit UUIDs.
+        uuids: Vec<u16>,
+    },
+    /// List of 32-bit service class UUIDs.
+    ServiceUuids32 {
+        /// Whether the list is complete.
+        complete: bool,
+        /// 32-bit UUIDs.
+        uuids: Vec<u32>,
+    },
+    /// List of 128-bit service class UUIDs.
+    ServiceUuids128 {
+        /// Whether the list is complete.
+        complete: bool,
+        /// UUIDs.
+        uuids: Vec<Uuid>,
+    },
+    /// Local name of the device.
+    LocalName {
+        /// Whether the name is complete or shortened.
+        complete: bool,
+        /// Name.
+        name: String,
+    },
+    /// Transmit power level in dBm.
+    TxPowerLevel(i8),
+    /// Class of device.
+    ClassOfDevice(u32),
+    /// Preferred connection interval range in units of 1.25 ms.
+    PeripheralConnectionIntervalRange {
+        /// Minimum connection interval.
+        min: u16,
+        /// Maximum connection interval.
+        max: u16,
+    },
+    /// List of 16-bit service solicitation UUIDs.
+    SolicitedUuids16(Vec<u16>),
+    /// List of 32-bit service solicitation UUIDs.
+    SolicitedUuids32(Vec<u32>),
+    /// List of 128-bit service solicitation UUIDs.
+    SolicitedUuids128(Vec<Uuid>),
+    /// Service data with 16-bit UUID.
+    ServiceData16 {
+        /// 16-bit UUID.
+        uuid: u16,
+        /// Data.
+        data: Vec<u8>,
+    },
+    /// Service data with 32-bit UUID.
+    ServiceData32 {
+        /// 32-bit UUID.
+        uuid: u32,
+        /// Data.
+        data: Vec<u8>,
+    },
+    /// Service data with 128-bit UUID.
+    ServiceData128 {
+        /// UUID.
+        uuid: Uuid,
+        /// Data.
+        data: Vec<u8>,
+    },
+    /// Public target addresses.
+    PublicTargetAddresses(Vec<Address>),
+    /// Random target addresses.
+    RandomTargetAddresses(Vec<Address>),
+    /// Appearance.
+    Appearance(u16),
+    /// Advertising interval in units of 0.625 ms.
+    AdvertisingInterval(u16),
+    /// LE Bluetooth device address.
+    LeDeviceAddress {
+        /// Address.
+        address: Address,
+        /// Whether the address is random.
+        random: bool,
+    },
+    /// LE role.
+    LeRole(u8),
+    /// URI.
+    ///
+    /// The scheme is encoded as a single code point as specified
+    /// by the Bluetooth SIG, for example `\u{16}` for `http:`.
+    Uri(String),
+    /// LE supported features bit mask.
+    LeSupportedFeatures(Vec<u8>),
+    /// Advertising interval in units of 0.625 ms, long form.
+    AdvertisingIntervalLong(u32),
+    /// Broadcast name.
+    BroadcastName(String),
+    /// Manufacturer specific data.
+    ManufacturerData {
+        /// Company identifier.
+        company_id: u16,
+        /// Data.
+        data: Vec<u8>,
+    },
+    /// AD structure of unknown type or with malformed data.
+    Unknown {
+        /// AD type.
+        ad_type: u8,
+        /// Data.
+        data: Vec<u8>,
+    },
+}
+
+fn le_u16(b: &[u8]) -> u16 {
+    u16::from_le_bytes([b[0], b[1]])
+}
+
+fn le_u32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn le_uuid(b: &[u8]) -> Uuid {
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(b);
+    bytes.reverse();
+    Uuid::from_bytes(bytes)
+}
+
+fn le_address(b: &[u8]) -> Address {
+    let mut bytes = [0; 6];
+    bytes.copy_from_slice(b);
+    bytes.reverse();
+    Address::new(bytes)
+}
+
+fn chunks<T>(data: &[u8], size: usize, f: impl Fn(&[u8]) -> T) -> Option<Vec<T>> {
+    if data.len() % size != 0 {
+        return None;
+    }
+    Some(data.chunks_exact(size).map(f).collect())
+}
+
+impl AdStructure {
+    /// Decodes an AD structure from its type and data.
+    ///
+    /// If the AD type is unknown or the data is malformed for the AD type,
+    /// [AdStructure::Unknown] is returned.
+    pub fn decode(ad_type: u8, data: &[u8]) -> Self {
+        Self::decode_known(ad_type, data).unwrap_or_else(|| Self::Unknown { ad_type, data: data.to_vec() })
+    }
+
+    fn decode_known(ad_type: u8, data: &[u8]) -> Option<Self> {
+        use ad_type::*;
+        let string = |data: &[u8]| String::from_utf8(data.to_vec()).ok();
+        Some(match ad_type {
+            FLAGS if data.len() == 1 => Self::Flags(data[0]),
+            INCOMPLETE_UUIDS_16 | COMPLETE_UUIDS_16 => {
+                Self::ServiceUuids16 { complete: ad_type == COMPLETE_UUIDS_16, uuids: chunks(data, 2, le_u16)? }
+            }
+            INCOMPLETE_UUIDS_32 | COMPLETE_UUIDS_32 => {
+                Self::ServiceUuids32 { complete: ad_type == COMPLETE_UUIDS_32, uuids: chunks(data, 4, le_u32)? }
+            }
+            INCOMPLETE_UUIDS_128 | COMPLETE_UUIDS_128 => Self::ServiceUuids128 {
+                complete: ad_type == COMPLETE_UUIDS_128,
+                uuids: chunks(data, 16, le_uuid)?,
+            },
+            SHORT_LOCAL_NAME | COMPLETE_LOCAL_NAME => {
+                Self::LocalName { complete: ad_type == COMPLETE_LOCAL_NAME, name: string(data)? }
+            }
+            TX_POWER_LEVEL if data.len() == 1 => Self::TxPowerLevel(data[0] as i8),
+            CLASS_OF_DEVICE if data.len() == 3 => Self::ClassOfDevice(le_u32(&[data[0], data[1], data[2], 0])),
+            PERIPHERAL_CONNECTION_INTERVAL_RANGE if data.len() == 4 => {
+                Self::PeripheralConnectionIntervalRange { min: le_u16(&data[0..2]), max: le_u16(&data[2..4]) }
+            }
+            SOLICITED_UUIDS_16 => Self::SolicitedUuids16(chunks(data, 2, le_u16)?),
+            SOLICITED_UUIDS_32 => Self::SolicitedUuids32(chunks(data, 4, le_u32)?),
+            SOLICITED_UUIDS_128 => Self::SolicitedUuids128(chunks(data, 16, le_uuid)?),
+            SERVICE_DATA_16 if data.len() >= 2 => {
+                Self::ServiceData16 { uuid: le_u16(&data[0..2]), data: data[2..].to_vec() }
+            }
+            SERVICE_DATA_32 if data.len() >= 4 => {
+                Self::ServiceData32 { uuid: le_u32(&data[0..4]), data: data[4..].to_vec() }
+            }
+            SERVICE_DATA_128 if data.len() >= 16 => {
+                Self::ServiceData128 { uuid: le_uuid(&data[0..16]), data: data[16..].to_vec() }
+            }
+            PUBLIC_TARGET_ADDRESS => Self::PublicTargetAddresses(chunks(data, 6, le_address)?),
+            RANDOM_TARGET_ADDRESS => Self::RandomTargetAddresses(chunks(data, 6, le_address)?),
+            APPEARANCE if data.len() == 2 => Self::Appearance(le_u16(data)),
+            ADVERTISING_INTERVAL if data.len() == 2 => Self::AdvertisingInterval(le_u16(data)),
+            LE_DEVICE_ADDRESS if data.len() == 7 && data[6] <= 1 => {
+                Self::LeDeviceAddress { address: le_address(&data[0..6]), random: data[6] == 1 }
+            }
+            LE_ROLE if data.len() == 1 => Self::LeRole(data[0]),
+            URI => Self::Uri(string(data)?),
+            LE_SUPPORTED_FEATURES => Self::LeSupportedFeatures(data.to_vec()),
+            ADVERTISING_INTERVAL_LONG if data.len() == 3 => {
+                Self::AdvertisingIntervalLong(le_u32(&[data[0], data[1], data[2], 0]))
+            }
+            ADVERTISING_INTERVAL_LONG if data.len() == 4 => Self::AdvertisingIntervalLong(le_u32(data)),
+            BROADCAST_NAME => Self::BroadcastName(string(data)?),
+            MANUFACTURER_DATA if data.len() >= 2 => {
+                Self::ManufacturerData { company_id: le_u16(&data[0..2]), data: data[2..].to_vec() }
+            }
+            _ => return None,
+        })
+    }
+
+    /// AD type.
+    pub fn ad_type(&self) -> u8 {
+        use ad_type::*;
+        match self {
+            Self::Flags(_) => FLAGS,
+            Self::ServiceUuids16 { complete: false, .. } => INCOMPLETE_UUIDS_16,
+            Self::ServiceUuids16 { complete: true, .. } => COMPLETE_UUIDS_16,
+            Self::ServiceUuids32 { complete: false, .. } => INCOMPLETE_UUIDS_32,
+            Self::ServiceUuids32 { complete: true, .. } => COMPLETE_UUIDS_32,
+            Self::ServiceUuids128 { complete: false, .. } => INCOMPLETE_UUIDS_128,
+            Self::ServiceUuids128 { complete: true, .. } => COMPLETE_UUIDS_128,
+            Self::LocalName { complete: false, .. } => SHORT_LOCAL_NAME,
+            Self::LocalName { complete: true, .. } => COMPLETE_LOCAL_NAME,
+            Self::TxPowerLevel(_) => TX_POWER_LEVEL,
+            Self::ClassOfDevice(_) => CLASS_OF_DEVICE,
+            Self::PeripheralConnectionIntervalRange { .. } => PERIPHERAL_CONNECTION_INTERVAL_RANGE,
+            Self::SolicitedUuids16(_) => SOLICITED_UUIDS_16,
+            Self::SolicitedUuids32(_) => SOLICITED_UUIDS_32,
+            Self::SolicitedUuids128(_) => SOLICITED_UUIDS_128,
+            Self::ServiceData16 { .. } => SERVICE_DATA_16,
+            Self::ServiceData32 { .. } => SERVICE_DATA_32,
+            Self::ServiceData128 { .. } => SERVICE_DATA_128,
+            Self::PublicTargetAddresses(_) => PUBLIC_TARGET_ADDRESS,
+            Self::RandomTargetAddresses(_) => RANDOM_TARGET_ADDRESS,
+            Self::Appearance(_) => APPEARANCE,
+            Self::AdvertisingInterval(_) => ADVERTISING_INTERVAL,
+            Self::LeDeviceAddress { .. } => LE_DEVICE_ADDRESS,
+            Self::LeRole(_) => LE_ROLE,
+            Self::Uri(_) => URI,
+            Self::LeSupportedFeatures(_) => LE_SUPPORTED_FEATURES,
+            Self::AdvertisingIntervalLong(_) => ADVERTISING_INTERVAL_LONG,
+            Self::BroadcastName(_) => BROADCAST_NAME,
+            Self::ManufacturerData { .. } => MANUFACTURER_DATA,
+            Self::Unknown { ad_type, .. } => *ad_type,
+        }
+    }
+
+    /// Data of the AD structure, excluding length and AD type.
+    pub fn data(&self) -> Vec<u8> {
+        fn uuids_128(uuids: &[Uuid]) -> Vec<u8> {
+            uuids.iter().flat_map(|uuid| uuid.as_bytes().iter().rev().copied()).collect()
+        }
+        fn addresses(addrs: &[Address]) -> Vec<u8> {
+            addrs.iter().flat_map(|addr| addr.0.iter().rev().copied()).collect()
+        }
+        fn with_prefix(prefix: &[u8], data: &[u8]) -> Vec<u8> {
+            let mut buf = prefix.to_vec();
+            buf.extend_from_slice(data);
+            buf
+        }
+
+        match self {
+            Self::Flags(v) | Self::LeRole(v) => vec![*v],
+            Self::ServiceUuids16 { uuids, .. } | Self::SolicitedUuids16(uuids) => {
+                uuids.iter().flat_map(|uuid| uuid.to_le_bytes()).collect()
+            }
+            Self::ServiceUuids32 { uuids, .. } | Self::SolicitedUuids32(uuids) => {
+                uuids.iter().flat_map(|uuid| uuid.to_le_bytes()).collect()
+            }
+            Self::ServiceUuids128 { uuids, .. } | Self::SolicitedUuids128(uuids) => uuids_128(uuids),
+            Self::LocalName { name, .. } => name.as_bytes().to_vec(),
+            Self::TxPowerLevel(v) => vec![*v as u8],
+            Self::ClassOfDevice(v) => v.to_le_bytes()[0..3].to_vec(),
+            Self::PeripheralConnectionIntervalRange { min, max } => {
+                with_prefix(&min.to_le_bytes(), &max.to_le_bytes())
+            }
+            Self::ServiceData16 { uuid, data } => with_prefix(&uuid.to_le_bytes(), data),
+            Self::ServiceData32 { uuid, data } => with_prefix(&uuid.to_le_bytes(), data),
+            Self::ServiceData128 { uuid, data } => with_prefix(&uuids_128(&[*uuid]), data),
+            Self::PublicTargetAddresses(addrs) | Self::RandomTargetAddresses(addrs) => addresses(addrs),
+            Self::Appearance(v) | Self::AdvertisingInterval(v) => v.to_le_bytes().to_vec(),
+            Self::LeDeviceAddress { address, random } => {
+                with_prefix(&addresses(&[*address]), &[u8::from(*random)])
+            }
+            Self::Uri(s) | Self::BroadcastName(s) => s.as_bytes().to_vec(),
+            Self::LeSupportedFeatures(data) => data.clone(),
+            Self::AdvertisingIntervalLong(v) if *v <= 0xff_ffff => v.to_le_bytes()[0..3].to_vec(),
+            Self::AdvertisingIntervalLong(v) => v.to_le_bytes().to_vec(),
+            Self::ManufacturerData { company_id, data } => with_prefix(&company_id.to_le_bytes(), data),
+            Self::Unknown { data, .. } => data.clone(),
+        }
+    }
+
+    /// Service class UUIDs contained in this AD structure in their 128-bit form.
+    ///
+    /// Returns an empty list if this is not a list of service class UUIDs.
+    pub fn service_uuids(&self) -> Vec<Uuid> {
+        match self {
+            Self::ServiceUuids16 { uuids, .. } => uuids.iter().map(|uuid| Uuid::from_u16(*uuid)).collect(),
+            Self::ServiceUuids32 { uuids, .. } => uuids.iter().map(|uuid| Uuid::from_u32(*uuid)).collect(),
+            Self::ServiceUuids128 { uuids, .. } => uuids.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Parses advertising data consisting of a sequence of AD structures.
+    ///
+    /// Parsing stops at the first AD structure of length zero,
+    /// which marks the start of the non-significant part of the data.
+    pub fn parse(mut buf: &[u8]) -> Result<Vec<Self>> {
+        let mut structures = Vec::new();
+        while let Some((&len, rest)) = buf.split_first() {
+            if len == 0 {
+                break;
+            }
+            let len = usize::from(len);
+            if rest.len() < len {
+                return Err(Error::new(ErrorKind::InvalidData, "AD structure exceeds advertising data"));
+            }
+            structures.push(Self::decode(rest[0], &rest[1..len]));
+            buf = &rest[len..];
+        }
+        Ok(structures)
+    }
+
+    /// Encodes AD structures into advertising data.
+    ///
+    /// Fails if the encoded data exceeds the maximum length allowed by the
+    /// specified kind of advertising PDU.
+    pub fn encode(structures: &[Self], pdu: AdvertisingPdu) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        for structure in structures {
+            let data = structure.data();
+            let len = u8::try_from(data.len() + 1)
+                .map_err(|_| Error::new(ErrorKind::InvalidInput, "AD structure too long"))?;
+            buf.push(len);
+            buf.push(structure.ad_type());
+            buf.extend(data);
+        }
+        if buf.len() > pdu.max_data_len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("advertising data length {} exceeds maximum of {} bytes", buf.len(), pdu.max_data_len()),
+            ));
+        }
+        Ok(buf)
+    }
+
+    /// Decodes AD structures from a map of AD type to data.
+    ///
+    /// The AD structures are returned ordered by AD type.
+    pub fn from_map<'a>(map: impl IntoIterator<Item = (&'a u8, &'a Vec<u8>)>) -> Vec<Self> {
+        let mut structures: Vec<_> =
+            map.into_iter().map(|(ad_type, data)| Self::decode(*ad_type, data)).collect();
+        structures.sort_by_key(|structure| structure.ad_type());
+        structures
+    }
+
+    /// Encodes AD structures into a map of AD type to data.
+    ///
+    /// Fails if multiple AD structures have the same AD type.
+    pub fn to_map(structures: &[Self]) -> Result<BTreeMap<u8, Vec<u8>>> {
+        let mut map = BTreeMap::new();
+        for structure in structures {
+            if map.insert(structure.ad_type(), structure.data()).is_some() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("duplicate AD type 0x{:02x}", structure.ad_type()),
+                ));
+            }
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn all_structures() -> Vec<AdStructure> {
+        let uuid: Uuid = "6e400001-b5a3-f393-e0a9-e50e24dcca9e".parse().unwrap();
+        let address = Address::new([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]);
+        vec![
+            AdStructure::Flags(flags::LE_GENERAL_DISCOVERABLE | flags::BR_EDR_NOT_SUPPORTED),
+            AdStructure::ServiceUuids16 { complete: true, uuids: vec![0x180d, 0x180f] },
+            AdStructure::ServiceUuids32 { complete: false, uuids: vec![0x1234_5678] },
+            AdStructure::ServiceUuids128 { complete: true, uuids: vec![uuid] },
+            AdStructure::LocalName { complete: false, name: "BlueR".to_string() },
+            AdStructure::TxPowerLevel(-8),
+            AdStructure::ClassOfDevice(0x240404),
+            AdStructure::PeripheralConnectionIntervalRange { min: 0x0006, max: 0x0c80 },
+            AdStructure::SolicitedUuids16(vec![0x1812]),
+            AdStructure::SolicitedUuids32(vec![0xabcd_0001]),
+            AdStructure::SolicitedUuids128(vec![uuid]),
+            AdStructure::ServiceData16 { uuid: 0xfeaa, data: vec![0x10, 0x00] },
+            AdStructure::ServiceData32 { uuid: 0x0001_feaa, data: vec![] },
+            AdStructure::ServiceData128 { uuid, data: vec![0x01] },
+            AdStructure::PublicTargetAddresses(vec![address]),
+            AdStructure::RandomTargetAddresses(vec![address, address]),
+            AdStructure::Appearance(0x03c1),
+            AdStructure::AdvertisingInterval(0x0800),
+            AdStructure::LeDeviceAddress { address, random: true },
+            AdStructure::LeRole(0x02),
+            AdStructure::Uri("\u{17}//example.com".to_string()),
+            AdStructure::LeSupportedFeatures(vec![0x01, 0x02, 0x03]),
+            AdStructure::AdvertisingIntervalLong(0x01_0000),
+            AdStructure::AdvertisingIntervalLong(0x0100_0000),
+            AdStructure::BroadcastName("Broadcast".to_string()),
+            AdStructure::ManufacturerData { company_id: 0x004c, data: vec![0x02, 0x15] },
+            AdStructure::Unknown { ad_type: 0x3d, data: vec![0xde, 0xad] },
+        ]
+    }
+
+    #[test]
+    fn parse_advertising_data() {
+        let data = [
+            0x02, 0x01, 0x06, 0x03, 0x03, 0x0d, 0x18, 0x06, 0x09, b'B', b'l', b'u', b'e', b'R', 0x02, 0x0a, 0xf8,
+            0x05, 0xff, 0x4c, 0x00, 0x02, 0x15, 0x00, 0x00,
+        ];
+        assert_eq!(
+            AdStructure::parse(&data).unwrap(),
+            vec![
+                AdStructure::Flags(0x06),
+                AdStructure::ServiceUuids16 { complete: true, uuids: vec![0x180d] },
+                AdStructure::LocalName { complete: true, name: "BlueR".to_string() },
+                AdStructure::TxPowerLevel(-8),
+                AdStructure::ManufacturerData { company_id: 0x004c, data: vec![0x02, 0x15] },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_malformed() {
+        assert!(AdStructure::parse(&[0x03, 0x01, 0x06]).is_err());
+        assert_eq!(
+            AdStructure::parse(&[0x02, 0x03, 0x0d]).unwrap(),
+            vec![AdStructure::Unknown { ad_type: 0x03, data: vec![0x0d] }]
+        );
+        assert_eq!(
+            AdStructure::parse(&[0x03, 0x09, 0xff, 0xfe]).unwrap(),
+            vec![AdStructure::Unknown { ad_type: 0x09, data: vec![0xff, 0xfe] }]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        for structure in all_structures() {
+            let buf = AdStructure::encode(std::slice::from_ref(&structure), AdvertisingPdu::Extended).unwrap();
+            assert_eq!(AdStructure::parse(&buf).unwrap(), vec![structure]);
+        }
+
+        let structures = all_structures();
+        let buf = AdStructure::encode(&structures, AdvertisingPdu::Extended).unwrap();
+        assert_eq!(AdStructure::parse(&buf).unwrap(), structures);
+    }
+
+    #[test]
+    fn length_limits() {
+        let name = |len| vec![AdStructure::LocalName { complete: true, name: "x".repeat(len) }];
+        assert_eq!(AdStructure::encode(&name(29), AdvertisingPdu::Legacy).unwrap().len(), 31);
+        assert!(AdStructure::encode(&name(30), AdvertisingPdu::Legacy).is_err());
+        assert_eq!(AdStructure::encode(&name(249), AdvertisingPdu::Extended).unwrap().len(), 251);
+        assert!(AdStructure::encode(&name(250), AdvertisingPdu::Extended).is_err());
+        assert!(AdStructure::encode(&name(254), AdvertisingPdu::Extended).is_err());
+    }
+
+    #[test]
+    fn map_round_trip() {
+        let structures = vec![
+            AdStructure::Flags(0x06),
+            AdStructure::Appearance(0x03c1),
+            AdStructure::ManufacturerData { company_id: 0x004c, data: vec![0x02, 0x15] },
+        ];
+        let map = AdStructure::to_map(&structures).unwrap();
+        assert_eq!(map.get(&ad_type::APPEARANCE), Some(&vec![0xc1, 0x03]));
+        assert_eq!(AdStructure::from_map(&map), structures);
+
+        let map: HashMap<u8, Vec<u8>> = map.into_iter().collect();
+        assert_eq!(AdStructure::from_map(&map), structures);
+
+        assert!(AdStructure::to_map(&[AdStructure::Flags(0x06), AdStructure::Flags(0x02)]).is_err());
+    }
+}
@@ -20,20 +20,26 @@ use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use uuid::Uuid;
 
+#[cfg(feature = "media")]
+use crate::media;
 use crate::{
     adv,
     adv::{Advertisement, AdvertisementHandle, Capabilities, Feature, PlatformFeature, SecondaryChannel},
-    all_dbus_objects, device,
+    all_dbus_objects, battery, device,
     device::Device,
+    device_set,
+    device_set::DeviceSet,
     gatt,
     monitor::MonitorManager,
-    Address, AddressType, Error, ErrorKind, Event, InternalErrorKind, Modalias, Result, SessionInner,
+    network, Address, AddressType, Error, ErrorKind, Event, InternalErrorKind, Modalias, Result, SessionInner,
     SingleSessionToken, SERVICE_NAME, TIMEOUT,
 };
 
 pub(crate) const INTERFACE: &str = "org.bluez.Adapter1";
 pub(crate) const PATH: &str = "/org/bluez";
 pub(crate) const PREFIX: &str = "/org/bluez/";
+pub(crate) const ADMIN_POLICY_SET_INTERFACE: &str = "org.bluez.AdminPolicySet1";
+pub(crate) const ADMIN_POLICY_STATUS_INTERFACE: &str = "org.bluez.AdminPolicyStatus1";
 
 /// Default adapter name.
 pub(crate) const DEFAULT_NAME: &str = "hci0";
@@ -128,6 +134,27 @@ impl Adapter {
         Device::new(self.inner.clone(), self.name.clone(), address)
     }
 
+    /// Names of the coordinated device sets known to this adapter.
+    pub async fn device_set_names(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for (path, interfaces) in all_dbus_objects(&self.inner.connection).await? {
+            match DeviceSet::parse_dbus_path(&path) {
+                Some((adapter, name))
+                    if adapter == *self.name && interfaces.contains_key(device_set::INTERFACE) =>
+                {
+                    names.push(name.to_string())
+                }
+                _ => (),
+            }
+        }
+        Ok(names)
+    }
+
+    /// Get interface to coordinated device set with specified name.
+    pub fn device_set(&self, name: &str) -> Result<DeviceSet> {
+        DeviceSet::new(self.inner.clone(), self.name.clone(), name)
+    }
+
     /// Gets the filter used for device discovery.
     pub async fn discovery_filter(&self) -> DiscoveryFilter {
         self.inner.adapter_discovery_filter.lock().await.get(self.name()).cloned().unwrap_or_default()
@@ -241,8 +268,9 @@ impl Adapter {
             .single_session(
                 &self.dbus_path,
                 async move {
-                    self.call_method("SetDiscoveryFilter", (self.discovery_filter().await.into_dict(),)).await?;
-                    self.call_method("StartDiscovery", ()).await?;
+                    self.call_method::<_, ()>("SetDiscoveryFilter", (self.discovery_filter().await.into_dict(),))
+                        .await?;
+                    self.call_method::<_, ()>("StartDiscovery", ()).await?;
                     Ok(())
                 },
                 async move {
@@ -267,18 +295,34 @@ impl Adapter {
         let name = self.name.clone();
         let events = self.inner.events(self.dbus_path.clone(), true).await?;
         let stream = events.flat_map(move |event| match event {
-            Event::ObjectAdded { object, .. } => match Device::parse_dbus_path(&object) {
-                Some((adapter, address)) if adapter == *name => {
-                    stream::once(async move { AdapterEvent::DeviceAdded(address) }).boxed()
+            Event::ObjectAdded { object, interfaces } => {
+                match (Device::parse_dbus_path(&object), DeviceSet::parse_dbus_path(&object)) {
+                    (Some((adapter, address)), _) if adapter == *name => {
+                        stream::once(async move { AdapterEvent::DeviceAdded(address) }).boxed()
+                    }
+                    (_, Some((adapter, set_name)))
+                        if adapter == *name && interfaces.contains(device_set::INTERFACE) =>
+                    {
+                        let set_name = set_name.to_string();
+                        stream::once(async move { AdapterEvent::DeviceSetAdded(set_name) }).boxed()
+                    }
+                    _ => stream::empty().boxed(),
                 }
-                _ => stream::empty().boxed(),
-            },
-            Event::ObjectRemoved { object, .. } => match Device::parse_dbus_path(&object) {
-                Some((adapter, address)) if adapter == *name => {
-                    stream::once(async move { AdapterEvent::DeviceRemoved(address) }).boxed()
+            }
+            Event::ObjectRemoved { object, interfaces } => {
+                match (Device::parse_dbus_path(&object), DeviceSet::parse_dbus_path(&object)) {
+                    (Some((adapter, address)), _) if adapter == *name => {
+                        stream::once(async move { AdapterEvent::DeviceRemoved(address) }).boxed()
+                    }
+                    (_, Some((adapter, set_name)))
+                        if adapter == *name && interfaces.contains(device_set::INTERFACE) =>
+                    {
+                        let set_name = set_name.to_string();
+                        stream::once(async move { AdapterEvent::DeviceSetRemoved(set_name) }).boxed()
+                    }
+                    _ => stream::empty().boxed(),
                 }
-                _ => stream::empty().boxed(),
-            },
+            }
             Event::PropertiesChanged { changed, .. } => stream::iter(
                 AdapterProperty::from_prop_map(changed).into_iter().map(AdapterEvent::PropertyChanged),
             )
@@ -336,6 +380,48 @@ impl Adapter {
         gatt_profile.register(self.inner.clone(), self.name.clone()).await
     }
 
+    /// Registers a local network server for the specified role.
+    ///
+    /// Remote devices connecting to the network service are
+    /// added to the specified network bridge, which must exist.
+    ///
+    /// Drop the returned [NetworkServerHandle](network::NetworkServerHandle) to unregister the server.
+    pub async fn register_network_server(
+        &self, role: network::Role, bridge: &str,
+    ) -> Result<network::NetworkServerHandle> {
+        network::NetworkServerHandle::register(self.inner.clone(), self.name.clone(), role, bridge).await
+    }
+
+    /// Registers a local battery provider.
+    ///
+    /// This allows feeding battery levels of remote devices,
+    /// obtained by the application through other means, to BlueZ.
+    /// The provided batteries then appear as battery information of
+    /// the corresponding devices.
+    ///
+    /// This requires the Bluetooth daemon to run in experimental mode.
+    ///
+    /// Drop the returned [BatteryProviderHandle](battery::BatteryProviderHandle) to unregister the provider.
+    pub async fn serve_battery_provider(
+        &self, battery_provider: battery::BatteryProvider,
+    ) -> Result<battery::BatteryProviderHandle> {
+        battery_provider.register(self.inner.clone(), self.name.clone()).await
+    }
+
+    /// Registers a local media endpoint.
+    ///
+    /// The endpoint is used by BlueZ to negotiate the codec configuration of
+    /// media connections with remote devices, for example to implement an A2DP sink.
+    ///
+    /// Drop the returned [EndpointHandle](media::endpoint::EndpointHandle) to unregister the endpoint.
+    #[cfg(feature = "media")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "media")))]
+    pub async fn register_media_endpoint(
+        &self, endpoint: media::endpoint::Endpoint,
+    ) -> Result<media::endpoint::EndpointHandle> {
+        media::endpoint::RegisteredEndpoint::register(endpoint, self.inner.clone(), &self.name).await
+    }
+
     // ===========================================================================================
     // Methods
     // ===========================================================================================
@@ -346,7 +432,7 @@ impl Adapter {
     /// It will remove also the pairing information.
     pub async fn remove_device(&self, address: Address) -> Result<()> {
         let path = Device::dbus_path(self.name(), address)?;
-        self.call_method("RemoveDevice", ((path),)).await?;
+        self.call_method::<_, ()>("RemoveDevice", ((path),)).await?;
         Ok(())
     }
 
@@ -387,6 +473,18 @@ impl Adapter {
 
         self.device(address)
     }
+
+    /// Sets the service allow list of the admin policy.
+    ///
+    /// Only connections of services with the specified UUIDs are accepted,
+    /// both incoming and outgoing.
+    /// All other services are blocked and their existing connections are terminated.
+    ///
+    /// An empty list allows all services.
+    pub async fn set_service_allow_list(&self, uuids: HashSet<Uuid>) -> Result<()> {
+        let uuids: Vec<String> = uuids.into_iter().map(|uuid| uuid.to_string()).collect();
+        self.call_method_with_interface("SetServiceAllowList", (uuids,), ADMIN_POLICY_SET_INTERFACE).await
+    }
 }
 
 define_properties!(
@@ -584,6 +682,49 @@ define_properties!(
             get: (modalias, v => { v.parse()? }),
         );
 
+        /// List of supported roles.
+        property(
+            Roles, BTreeSet<AdapterRole>,
+            dbus: (INTERFACE, "Roles", Vec<String>, OPTIONAL),
+            get: (roles, v => {
+                v.iter().filter_map(|s| s.parse().ok()).collect()
+            }),
+        );
+
+        /// List of experimental features that have been enabled.
+        property(
+            ExperimentalFeatures, BTreeSet<ExperimentalFeature>,
+            dbus: (INTERFACE, "ExperimentalFeatures", Vec<String>, OPTIONAL),
+            get: (experimental_features, v => {
+                v
+                .iter()
+                .map(|uuid| {
+                    uuid.parse::<Uuid>()
+                        .map(ExperimentalFeature::from)
+                        .map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidUuid(uuid.to_string()))))
+                })
+                .collect::<Result<BTreeSet<ExperimentalFeature>>>()?
+            }),
+        );
+
+        /// The manufacturer of the controller,
+        /// as a company identifier assigned by the Bluetooth SIG.
+        property(
+            Manufacturer, u16,
+            dbus: (INTERFACE, "Manufacturer", u16, OPTIONAL),
+            get: (manufacturer, v => {v.to_owned()}),
+        );
+
+        /// The Bluetooth version supported by the controller,
+        /// as the HCI version number assigned by the Bluetooth SIG.
+        ///
+        /// For example, `0x09` is Bluetooth 5.0 and `0x0d` is Bluetooth 5.4.
+        property(
+            Version, u8,
+            dbus: (INTERFACE, "Version", u8, OPTIONAL),
+            get: (version, v => {v.to_owned()}),
+        );
+
         // ===========================================================================================
         // LE advertising manager properties
         // ===========================================================================================
@@ -646,6 +787,24 @@ define_properties!(
                 v.iter().filter_map(|s| s.parse().ok()).collect()
             }),
         );
+
+        /// Service allow list of the admin policy.
+        ///
+        /// An empty list means that all services are allowed.
+        /// Use [set_service_allow_list](Self::set_service_allow_list) to change it.
+        property(
+            ServiceAllowList, HashSet<Uuid>,
+            dbus: (ADMIN_POLICY_STATUS_INTERFACE, "ServiceAllowList", Vec<String>, OPTIONAL),
+            get: (service_allow_list, v => {
+                v
+                .iter()
+                .map(|uuid| {
+                    uuid.parse()
+                        .map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidUuid(uuid.to_string()))))
+                })
+                .collect::<Result<HashSet<Uuid>>>()?
+            }),
+        );
     }
 );
 
@@ -653,15 +812,97 @@ define_properties!(
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum AdapterEvent {
     /// Bluetooth device with specified address was added.
     DeviceAdded(Address),
     /// Bluetooth device with specified address was removed.
     DeviceRemoved(Address),
+    /// Coordinated device set with specified name was added.
+    DeviceSetAdded(String),
+    /// Coordinated device set with specified name was removed.
+    DeviceSetRemoved(String),
     /// Bluetooth adapter property changed.
     PropertyChanged(AdapterProperty),
 }
 
+/// Role supported by a Bluetooth adapter.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum AdapterRole {
+    /// Supports the central role.
+    #[strum(serialize = "central")]
+    Central,
+    /// Supports the peripheral role.
+    #[strum(serialize = "peripheral")]
+    Peripheral,
+    /// Supports both roles concurrently.
+    #[strum(serialize = "central-peripheral")]
+    CentralPeripheral,
+}
+
+/// Experimental feature of the Bluetooth daemon.
+///
+/// Experimental features are enabled using the `KernelExperimental` setting
+/// of the Bluetooth daemon configuration.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ExperimentalFeature {
+    /// Debug feature.
+    Debug,
+    /// Simultaneous central and peripheral roles.
+    CentralPeripheral,
+    /// LL privacy.
+    LlPrivacy,
+    /// Bluetooth quality report.
+    QualityReport,
+    /// Offload codecs.
+    OffloadCodecs,
+    /// ISO sockets.
+    IsoSocket,
+    /// Other feature identified by its UUID.
+    Other(Uuid),
+}
+
+impl ExperimentalFeature {
+    /// UUID identifying the experimental feature.
+    pub fn uuid(&self) -> Uuid {
+        Uuid::from_u128(match self {
+            Self::Debug => 0xd4992530_b9ec_469f_ab01_6c481c47da1c,
+            Self::CentralPeripheral => 0x671b10b5_42c0_4696_9227_eb28d1b049d6,
+            Self::LlPrivacy => 0x15c0a148_c273_11ea_b3de_0242ac130004,
+            Self::QualityReport => 0x330859bc_7506_492d_9370_9a6f0614037f,
+            Self::OffloadCodecs => 0xa6695ace_ee7f_4fb9_881a_5fac66c629af,
+            Self::IsoSocket => 0x6fbaf188_05e0_496a_9885_d6ddfdb4e03e,
+            Self::Other(uuid) => return *uuid,
+        })
+    }
+}
+
+impl From<Uuid> for ExperimentalFeature {
+    fn from(uuid: Uuid) -> Self {
+        [
+            Self::Debug,
+            Self::CentralPeripheral,
+            Self::LlPrivacy,
+            Self::QualityReport,
+            Self::OffloadCodecs,
+            Self::IsoSocket,
+        ]
+        .into_iter()
+        .find(|feature| feature.uuid() == uuid)
+        .unwrap_or(Self::Other(uuid))
+    }
+}
+
+impl From<ExperimentalFeature> for Uuid {
+    fn from(feature: ExperimentalFeature) -> Self {
+        feature.uuid()
+    }
+}
+
 /// Transport parameter determines the type of scan.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Display, EnumString)]
 #[non_exhaustive]
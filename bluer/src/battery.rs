@@ -0,0 +1,245 @@
+//! Battery level reporting.
+//!
+//! Battery levels of remote devices known to BlueZ are available through
+//! [Device::battery_percentage](crate::Device::battery_percentage) and are reported
+//! as [device property changes](crate::DeviceEvent).
+//!
+//! Applications that obtain battery levels of devices through other means,
+//! for example using a vendor-specific protocol, can feed them to BlueZ by
+//! publishing a [BatteryProvider] using [Adapter::serve_battery_provider](crate::Adapter::serve_battery_provider).
+//! The provided batteries then appear as battery information of the corresponding devices,
+//! making them available to the desktop environment.
+//!
+//! The battery provider API is experimental in BlueZ and requires the Bluetooth daemon
+//! to be started with the `--experimental` option.
+
+use dbus::{
+    arg::{PropMap, Variant},
+    channel::Sender,
+    message::SignalArgs,
+    nonblock::{stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged, Proxy, SyncConnection},
+    Path,
+};
+use dbus_crossroads::{Crossroads, IfaceBuilder, IfaceToken};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex as SyncMutex},
+};
+use tokio::sync::{oneshot, Mutex};
+use uuid::Uuid;
+
+use crate::{
+    Adapter, Address, Device, Error, ErrorKind, InternalErrorKind, Result, SessionInner, SERVICE_NAME, TIMEOUT,
+};
+
+pub(crate) const INTERFACE: &str = "org.bluez.Battery1";
+pub(crate) const PROVIDER_INTERFACE: &str = "org.bluez.BatteryProvider1";
+pub(crate) const PROVIDER_MANAGER_INTERFACE: &str = "org.bluez.BatteryProviderManager1";
+pub(crate) const PROVIDER_PREFIX: &str = publish_path!("battery/");
+
+/// Definition of a battery of a remote device provided to BlueZ.
+#[derive(Debug, Clone, Default)]
+pub struct Battery {
+    /// Address of the device the battery belongs to.
+    pub device: Address,
+    /// The percentage of battery left as an unsigned 8-bit integer.
+    ///
+    /// Values greater than 100 are clamped to 100.
+    pub percentage: u8,
+    /// Describes where the battery information comes from.
+    ///
+    /// This is useful when there are several battery providers for a device,
+    /// for example "HFP 1.7" or "HID".
+    pub source: Option<String>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Definition of a local battery provider to publish to BlueZ.
+#[derive(Debug, Clone, Default)]
+pub struct BatteryProvider {
+    /// Initially provided batteries.
+    ///
+    /// Batteries can be added and removed later using the [BatteryProviderHandle].
+    pub batteries: Vec<Battery>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// A battery published on D-Bus.
+pub(crate) struct RegisteredBattery {
+    device_path: Path<'static>,
+    battery: SyncMutex<Battery>,
+}
+
+impl RegisteredBattery {
+    pub(crate) fn register_interface(cr: &mut Crossroads) -> IfaceToken<Arc<Self>> {
+        cr.register(PROVIDER_INTERFACE, |ib: &mut IfaceBuilder<Arc<Self>>| {
+            cr_property!(ib, "Device", reg => {
+                Some(reg.device_path.clone())
+            });
+            cr_property!(ib, "Percentage", reg => {
+                Some(reg.battery.lock().unwrap().percentage.min(100))
+            });
+            cr_property!(ib, "Source", reg => {
+                reg.battery.lock().unwrap().source.clone()
+            });
+        })
+    }
+}
+
+struct ProviderState {
+    batteries: HashMap<Address, (Path<'static>, Arc<RegisteredBattery>)>,
+}
+
+impl BatteryProvider {
+    pub(crate) async fn register(
+        self, inner: Arc<SessionInner>, adapter_name: Arc<String>,
+    ) -> Result<BatteryProviderHandle> {
+        let root = Path::new(format!("{}{}", PROVIDER_PREFIX, Uuid::new_v4().as_simple())).unwrap();
+        log::trace!("Publishing battery provider at {}", &root);
+
+        {
+            let mut cr = inner.crossroads.lock().await;
+            let object_manager_token = cr.object_manager();
+            let introspectable_token = cr.introspectable();
+            let properties_token = cr.properties();
+            cr.insert(root.clone(), [&object_manager_token, &introspectable_token, &properties_token], ());
+        }
+
+        let proxy =
+            Proxy::new(SERVICE_NAME, Adapter::dbus_path(&adapter_name)?, TIMEOUT, inner.connection.clone());
+        let state = Arc::new(Mutex::new(ProviderState { batteries: HashMap::new() }));
+
+        let (_drop_tx, drop_rx) = oneshot::channel();
+        let unreg_root = root.clone();
+        let unreg_inner = inner.clone();
+        let unreg_state = state.clone();
+        let unreg_proxy = proxy.clone();
+        tokio::spawn(async move {
+            let _ = drop_rx.await;
+
+            log::trace!("Unregistering battery provider at {}", &unreg_root);
+            let _: std::result::Result<(), dbus::Error> = unreg_proxy
+                .method_call(PROVIDER_MANAGER_INTERFACE, "UnregisterBatteryProvider", (unreg_root.clone(),))
+                .await;
+
+            log::trace!("Unpublishing battery provider at {}", &unreg_root);
+            let state = unreg_state.lock().await;
+            let mut cr = unreg_inner.crossroads.lock().await;
+            for (path, _) in state.batteries.values() {
+                cr.remove::<Arc<RegisteredBattery>>(path);
+            }
+            cr.remove::<()>(&unreg_root);
+        });
+
+        let handle = BatteryProviderHandle { inner, adapter_name, root: root.clone(), state, _drop_tx };
+        for battery in self.batteries {
+            handle.add(battery).await?;
+        }
+
+        log::trace!("Registering battery provider at {}", &root);
+        proxy.method_call::<(), _, _, _>(PROVIDER_MANAGER_INTERFACE, "RegisterBatteryProvider", (root,)).await?;
+
+        Ok(handle)
+    }
+}
+
+/// Handle to a local battery provider published to BlueZ.
+///
+/// Use it to add, update and remove provided batteries.
+///
+/// Drop this handle to unpublish.
+pub struct BatteryProviderHandle {
+    inner: Arc<SessionInner>,
+    adapter_name: Arc<String>,
+    root: Path<'static>,
+    state: Arc<Mutex<ProviderState>>,
+    _drop_tx: oneshot::Sender<()>,
+}
+
+impl fmt::Debug for BatteryProviderHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BatteryProviderHandle {{ {} }}", &self.root)
+    }
+}
+
+impl BatteryProviderHandle {
+    /// Adds a battery.
+    ///
+    /// Fails if a battery for the same device is already provided.
+    pub async fn add(&self, battery: Battery) -> Result<()> {
+        let mut state = self.state.lock().await;
+        if state.batteries.contains_key(&battery.device) {
+            return Err(Error::new(ErrorKind::AlreadyExists));
+        }
+
+        let device_path = Device::dbus_path(&self.adapter_name, battery.device)?;
+        let path =
+            Path::new(format!("{}/dev_{}", &self.root, battery.device.to_string().replace(':', "_"))).unwrap();
+        log::trace!("Publishing battery of {} at {}", battery.device, &path);
+
+        let address = battery.device;
+        let reg = Arc::new(RegisteredBattery { device_path, battery: SyncMutex::new(battery) });
+        {
+            let mut cr = self.inner.crossroads.lock().await;
+            cr.insert(path.clone(), &[self.inner.battery_provider_token], reg.clone());
+        }
+        state.batteries.insert(address, (path, reg));
+        Ok(())
+    }
+
+    /// Removes the battery of the specified device.
+    pub async fn remove(&self, device: Address) -> Result<()> {
+        let mut state = self.state.lock().await;
+        let (path, _) = state.batteries.remove(&device).ok_or_else(|| Error::new(ErrorKind::NotFound))?;
+        log::trace!("Unpublishing battery of {} at {}", device, &path);
+        let mut cr = self.inner.crossroads.lock().await;
+        cr.remove::<Arc<RegisteredBattery>>(&path);
+        Ok(())
+    }
+
+    /// Updates the percentage of battery left of the specified device.
+    ///
+    /// Values greater than 100 are clamped to 100.
+    pub async fn set_percentage(&self, device: Address, percentage: u8) -> Result<()> {
+        let state = self.state.lock().await;
+        let (path, reg) = state.batteries.get(&device).ok_or_else(|| Error::new(ErrorKind::NotFound))?;
+        let percentage = percentage.min(100);
+        {
+            let mut battery = reg.battery.lock().unwrap();
+            if battery.percentage == percentage {
+                return Ok(());
+            }
+            battery.percentage = percentage;
+        }
+        log::trace!("{}: {}.Percentage <- {}", path, PROVIDER_INTERFACE, percentage);
+        emit_percentage_changed(&self.inner.connection, path, percentage)
+    }
+
+    /// Addresses of devices with provided batteries.
+    pub async fn devices(&self) -> Vec<Address> {
+        self.state.lock().await.batteries.keys().copied().collect()
+    }
+}
+
+fn emit_percentage_changed(connection: &SyncConnection, path: &Path<'static>, percentage: u8) -> Result<()> {
+    let mut changed_properties = PropMap::new();
+    changed_properties.insert("Percentage".to_string(), Variant(Box::new(percentage)));
+    let ppc = PropertiesPropertiesChanged {
+        interface_name: PROVIDER_INTERFACE.to_string(),
+        changed_properties,
+        invalidated_properties: Vec::new(),
+    };
+    connection
+        .send(ppc.to_emit_message(path))
+        .map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::DBusConnectionLost)))?;
+    Ok(())
+}
+
+impl Drop for BatteryProviderHandle {
+    fn drop(&mut self) {
+        // required for drop order
+    }
+}
@@ -0,0 +1,489 @@
+//! Beacon formats.
+//!
+//! This module decodes and encodes the advertising payloads of
+//! [iBeacon](IBeacon), [AltBeacon](AltBeacon) and [Eddystone](Eddystone) beacons.
+//!
+//! iBeacon and AltBeacon payloads are contained in the manufacturer specific data
+//! and can be decoded from [Device::manufacturer_data](crate::Device::manufacturer_data).
+//! Eddystone frames are contained in the service data and can be decoded from
+//! [Device::service_data](crate::Device::service_data).
+//!
+//! With the `bluetoothd` feature enabled each beacon can be converted into an
+//! [Advertisement](crate::adv::Advertisement) for use with
+//! [Adapter::advertise](crate::Adapter::advertise).
+
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind, Result},
+    time::Duration,
+};
+use uuid::Uuid;
+
+#[cfg(feature = "bluetoothd")]
+use crate::adv::{Advertisement, Type};
+use crate::UuidExt;
+
+/// Company identifier of Apple, Inc. used by iBeacon.
+pub const APPLE_COMPANY_ID: u16 = 0x004c;
+
+/// 16-bit UUID of the Eddystone service.
+pub const EDDYSTONE_UUID_16: u16 = 0xfeaa;
+
+/// UUID of the Eddystone service.
+pub fn eddystone_uuid() -> Uuid {
+    Uuid::from_u16(EDDYSTONE_UUID_16)
+}
+
+/// iBeacon.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IBeacon {
+    /// Proximity UUID.
+    pub uuid: Uuid,
+    /// Major value.
+    pub major: u16,
+    /// Minor value.
+    pub minor: u16,
+    /// Measured power at 1 m distance in dBm.
+    pub measured_power: i8,
+}
+
+impl IBeacon {
+    const TYPE: u8 = 0x02;
+    const LEN: u8 = 0x15;
+
+    /// Decodes an iBeacon from the manufacturer specific data of Apple, Inc.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        match data {
+            [Self::TYPE, Self::LEN, uuid @ .., m1, m2, n1, n2, power] if uuid.len() == 16 => Some(Self {
+                uuid: Uuid::from_slice(uuid).ok()?,
+                major: u16::from_be_bytes([*m1, *m2]),
+                minor: u16::from_be_bytes([*n1, *n2]),
+                measured_power: *power as i8,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Decodes an iBeacon from manufacturer specific data keyed by company identifier.
+    pub fn from_manufacturer_data(manufacturer_data: &HashMap<u16, Vec<u8>>) -> Option<Self> {
+        manufacturer_data.get(&APPLE_COMPANY_ID).and_then(|data| Self::from_bytes(data))
+    }
+
+    /// Encodes the iBeacon into manufacturer specific data of Apple, Inc.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![Self::TYPE, Self::LEN];
+        buf.extend_from_slice(self.uuid.as_bytes());
+        buf.extend_from_slice(&self.major.to_be_bytes());
+        buf.extend_from_slice(&self.minor.to_be_bytes());
+        buf.push(self.measured_power as u8);
+        buf
+    }
+
+    /// Advertisement broadcasting this iBeacon.
+    #[cfg(feature = "bluetoothd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+    pub fn advertisement(&self) -> Advertisement {
+        Advertisement {
+            advertisement_type: Type::Broadcast,
+            manufacturer_data: [(APPLE_COMPANY_ID, self.to_bytes())].into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// AltBeacon.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AltBeacon {
+    /// Company identifier of the beacon manufacturer.
+    pub company_id: u16,
+    /// Beacon identifier.
+    ///
+    /// Usually the first 16 bytes contain an organizational unit UUID
+    /// and the remaining bytes are used for further subdivision.
+    pub beacon_id: [u8; 20],
+    /// Average received signal strength at 1 m distance in dBm.
+    pub reference_rssi: i8,
+    /// Value reserved for use by the manufacturer.
+    pub manufacturer_reserved: u8,
+}
+
+impl AltBeacon {
+    const BEACON_CODE: [u8; 2] = [0xbe, 0xac];
+
+    /// Decodes an AltBeacon from the manufacturer specific data of the specified company.
+    pub fn from_bytes(company_id: u16, data: &[u8]) -> Option<Self> {
+        match data {
+            [0xbe, 0xac, beacon_id @ .., rssi, reserved] => Some(Self {
+                company_id,
+                beacon_id: beacon_id.try_into().ok()?,
+                reference_rssi: *rssi as i8,
+                manufacturer_reserved: *reserved,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Decodes an AltBeacon from manufacturer specific data keyed by company identifier.
+    pub fn from_manufacturer_data(manufacturer_data: &HashMap<u16, Vec<u8>>) -> Option<Self> {
+        manufacturer_data.iter().find_map(|(company_id, data)| Self::from_bytes(*company_id, data))
+    }
+
+    /// Encodes the AltBeacon into manufacturer specific data.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Self::BEACON_CODE.to_vec();
+        buf.extend_from_slice(&self.beacon_id);
+        buf.push(self.reference_rssi as u8);
+        buf.push(self.manufacturer_reserved);
+        buf
+    }
+
+    /// Advertisement broadcasting this AltBeacon.
+    #[cfg(feature = "bluetoothd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+    pub fn advertisement(&self) -> Advertisement {
+        Advertisement {
+            advertisement_type: Type::Broadcast,
+            manufacturer_data: [(self.company_id, self.to_bytes())].into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Eddystone-UID frame.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EddystoneUid {
+    /// Calibrated TX power at 0 m distance in dBm.
+    pub tx_power: i8,
+    /// Namespace identifier.
+    pub namespace: [u8; 10],
+    /// Instance identifier.
+    pub instance: [u8; 6],
+}
+
+/// Eddystone-URL frame.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EddystoneUrl {
+    /// Calibrated TX power at 0 m distance in dBm.
+    pub tx_power: i8,
+    /// URL.
+    pub url: String,
+}
+
+/// Eddystone-TLM frame containing unencrypted telemetry.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EddystoneTlm {
+    /// Battery voltage in mV.
+    ///
+    /// Zero if the beacon is not battery powered.
+    pub battery_voltage: u16,
+    /// Beacon temperature in degrees Celsius, if supported.
+    pub temperature: Option<f32>,
+    /// Number of advertising frames sent since power-on or reboot.
+    pub advertising_count: u32,
+    /// Time since power-on or reboot with a resolution of 0.1 s.
+    pub uptime: Duration,
+}
+
+/// Eddystone-EID frame.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EddystoneEid {
+    /// Calibrated TX power at 0 m distance in dBm.
+    pub tx_power: i8,
+    /// Ephemeral identifier.
+    pub eid: [u8; 8],
+}
+
+/// Eddystone frame.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Eddystone {
+    /// Eddystone-UID frame.
+    Uid(EddystoneUid),
+    /// Eddystone-URL frame.
+    Url(EddystoneUrl),
+    /// Eddystone-TLM frame.
+    Tlm(EddystoneTlm),
+    /// Encrypted Eddystone-TLM frame.
+    ///
+    /// Contains the encrypted telemetry, salt and message integrity check.
+    EncryptedTlm(Vec<u8>),
+    /// Eddystone-EID frame.
+    Eid(EddystoneEid),
+}
+
+const URL_SCHEMES: [&str; 4] = ["http://www.", "https://www.", "http://", "https://"];
+
+const URL_EXPANSIONS: [&str; 14] = [
+    ".com/", ".org/", ".edu/", ".net/", ".info/", ".biz/", ".gov/", ".com", ".org", ".edu", ".net", ".info",
+    ".biz", ".gov",
+];
+
+const URL_MAX_LEN: usize = 17;
+
+impl Eddystone {
+    const FRAME_UID: u8 = 0x00;
+    const FRAME_URL: u8 = 0x10;
+    const FRAME_TLM: u8 = 0x20;
+    const FRAME_EID: u8 = 0x30;
+
+    const TLM_UNENCRYPTED: u8 = 0x00;
+    const TLM_ENCRYPTED: u8 = 0x01;
+    const TEMPERATURE_UNSUPPORTED: i16 = -0x8000;
+
+    /// Decodes an Eddystone frame from the service data of the Eddystone service.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        match data {
+            // The two reserved bytes are optional in practice.
+            [Self::FRAME_UID, tx_power, ids @ ..] if ids.len() == 16 || ids.len() == 18 => {
+                Some(Self::Uid(EddystoneUid {
+                    tx_power: *tx_power as i8,
+                    namespace: ids[0..10].try_into().ok()?,
+                    instance: ids[10..16].try_into().ok()?,
+                }))
+            }
+            [Self::FRAME_URL, tx_power, scheme, url @ ..] if url.len() <= URL_MAX_LEN => {
+                let mut decoded = URL_SCHEMES.get(usize::from(*scheme))?.to_string();
+                for &b in url {
+                    match URL_EXPANSIONS.get(usize::from(b)) {
+                        Some(expansion) => decoded.push_str(expansion),
+                        None if (0x21..0x7f).contains(&b) => decoded.push(char::from(b)),
+                        None => return None,
+                    }
+                }
+                Some(Self::Url(EddystoneUrl { tx_power: *tx_power as i8, url: decoded }))
+            }
+            [Self::FRAME_TLM, Self::TLM_UNENCRYPTED, tlm @ ..] if tlm.len() == 12 => {
+                let temperature = i16::from_be_bytes([tlm[2], tlm[3]]);
+                Some(Self::Tlm(EddystoneTlm {
+                    battery_voltage: u16::from_be_bytes([tlm[0], tlm[1]]),
+                    temperature: (temperature != Self::TEMPERATURE_UNSUPPORTED)
+                        .then(|| f32::from(temperature) / 256.),
+                    advertising_count: u32::from_be_bytes(tlm[4..8].try_into().ok()?),
+                    uptime: Duration::from_millis(
+                        u64::from(u32::from_be_bytes(tlm[8..12].try_into().ok()?)) * 100,
+                    ),
+                }))
+            }
+            [Self::FRAME_TLM, Self::TLM_ENCRYPTED, etlm @ ..] => Some(Self::EncryptedTlm(etlm.to_vec())),
+            [Self::FRAME_EID, tx_power, eid @ ..] => {
+                Some(Self::Eid(EddystoneEid { tx_power: *tx_power as i8, eid: eid.try_into().ok()? }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Decodes an Eddystone frame from service data keyed by service UUID.
+    pub fn from_service_data(service_data: &HashMap<Uuid, Vec<u8>>) -> Option<Self> {
+        service_data.get(&eddystone_uuid()).and_then(|data| Self::from_bytes(data))
+    }
+
+    /// Encodes the Eddystone frame into service data of the Eddystone service.
+    ///
+    /// Fails if the URL of an Eddystone-URL frame cannot be encoded or is too long.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        match self {
+            Self::Uid(uid) => {
+                buf.extend_from_slice(&[Self::FRAME_UID, uid.tx_power as u8]);
+                buf.extend_from_slice(&uid.namespace);
+                buf.extend_from_slice(&uid.instance);
+                buf.extend_from_slice(&[0, 0]);
+            }
+            Self::Url(url) => {
+                buf.extend_from_slice(&[Self::FRAME_URL, url.tx_power as u8]);
+                buf.extend(encode_url(&url.url)?);
+            }
+            Self::Tlm(tlm) => {
+                let temperature = match tlm.temperature {
+                    Some(temperature) => (temperature * 256.).round() as i16,
+                    None => Self::TEMPERATURE_UNSUPPORTED,
+                };
+                let uptime = u32::try_from(tlm.uptime.as_millis() / 100)
+                    .map_err(|_| Error::new(ErrorKind::InvalidInput, "uptime too long"))?;
+                buf.extend_from_slice(&[Self::FRAME_TLM, Self::TLM_UNENCRYPTED]);
+                buf.extend_from_slice(&tlm.battery_voltage.to_be_bytes());
+                buf.extend_from_slice(&temperature.to_be_bytes());
+                buf.extend_from_slice(&tlm.advertising_count.to_be_bytes());
+                buf.extend_from_slice(&uptime.to_be_bytes());
+            }
+            Self::EncryptedTlm(etlm) => {
+                buf.extend_from_slice(&[Self::FRAME_TLM, Self::TLM_ENCRYPTED]);
+                buf.extend_from_slice(etlm);
+            }
+            Self::Eid(eid) => {
+                buf.extend_from_slice(&[Self::FRAME_EID, eid.tx_power as u8]);
+                buf.extend_from_slice(&eid.eid);
+            }
+        }
+        Ok(buf)
+    }
+
+    /// Advertisement broadcasting this Eddystone frame.
+    ///
+    /// Fails if the frame cannot be encoded.
+    #[cfg(feature = "bluetoothd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+    pub fn advertisement(&self) -> Result<Advertisement> {
+        Ok(Advertisement {
+            advertisement_type: Type::Broadcast,
+            service_uuids: [eddystone_uuid()].into(),
+            service_data: [(eddystone_uuid(), self.to_bytes()?)].into(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Encodes an URL including its scheme using the Eddystone-URL compression.
+fn encode_url(url: &str) -> Result<Vec<u8>> {
+    let invalid = |msg| Error::new(ErrorKind::InvalidInput, msg);
+
+    let (scheme, mut rest) = URL_SCHEMES
+        .iter()
+        .enumerate()
+        .find_map(|(code, scheme)| url.strip_prefix(scheme).map(|rest| (code as u8, rest)))
+        .ok_or_else(|| invalid("URL scheme not supported by Eddystone"))?;
+
+    let mut buf = vec![scheme];
+    while !rest.is_empty() {
+        match URL_EXPANSIONS.iter().position(|expansion| rest.starts_with(expansion)) {
+            Some(code) => {
+                buf.push(code as u8);
+                rest = &rest[URL_EXPANSIONS[code].len()..];
+            }
+            None => {
+                let b = rest.as_bytes()[0];
+                if !(0x21..0x7f).contains(&b) {
+                    return Err(invalid("URL contains characters not supported by Eddystone"));
+                }
+                buf.push(b);
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    if buf.len() > URL_MAX_LEN + 1 {
+        return Err(invalid("encoded URL too long for Eddystone"));
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ibeacon() {
+        let data = [
+            0x02, 0x15, 0xe2, 0xc5, 0x6d, 0xb5, 0xdf, 0xfb, 0x48, 0xd2, 0xb0, 0x60, 0xd0, 0xf5, 0xa7, 0x10, 0x96,
+            0xe0, 0x00, 0x01, 0x00, 0x02, 0xc5,
+        ];
+        let beacon = IBeacon {
+            uuid: "e2c56db5-dffb-48d2-b060-d0f5a71096e0".parse().unwrap(),
+            major: 1,
+            minor: 2,
+            measured_power: -59,
+        };
+        assert_eq!(IBeacon::from_bytes(&data), Some(beacon));
+        assert_eq!(beacon.to_bytes(), data);
+
+        let manufacturer_data = [(APPLE_COMPANY_ID, data.to_vec())].into();
+        assert_eq!(IBeacon::from_manufacturer_data(&manufacturer_data), Some(beacon));
+        assert_eq!(IBeacon::from_bytes(&data[..22]), None);
+    }
+
+    #[test]
+    fn altbeacon() {
+        let data = [
+            0xbe, 0xac, 0x2f, 0x23, 0x44, 0x54, 0xcf, 0x6d, 0x4a, 0x0f, 0xad, 0xf2, 0xf4, 0x91, 0x1b, 0xa9, 0xff,
+            0xa6, 0x00, 0x01, 0x00, 0x02, 0xc5, 0x00,
+        ];
+        let beacon = AltBeacon {
+            company_id: 0x0118,
+            beacon_id: data[2..22].try_into().unwrap(),
+            reference_rssi: -59,
+            manufacturer_reserved: 0,
+        };
+        assert_eq!(AltBeacon::from_bytes(0x0118, &data), Some(beacon));
+        assert_eq!(beacon.to_bytes(), data);
+
+        let manufacturer_data = [(0x0118, data.to_vec())].into();
+        assert_eq!(AltBeacon::from_manufacturer_data(&manufacturer_data), Some(beacon));
+        assert_eq!(AltBeacon::from_bytes(0x0118, &data[..23]), None);
+    }
+
+    #[test]
+    fn eddystone_uid() {
+        let data = [
+            0x00, 0xe7, 0xed, 0xd7, 0x0a, 0x2a, 0x5c, 0x09, 0x94, 0x25, 0xc2, 0x5e, 0x01, 0x02, 0x03, 0x04, 0x05,
+            0x06, 0x00, 0x00,
+        ];
+        let frame = Eddystone::Uid(EddystoneUid {
+            tx_power: -25,
+            namespace: [0xed, 0xd7, 0x0a, 0x2a, 0x5c, 0x09, 0x94, 0x25, 0xc2, 0x5e],
+            instance: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+        });
+        assert_eq!(Eddystone::from_bytes(&data), Some(frame.clone()));
+        assert_eq!(Eddystone::from_bytes(&data[..18]), Some(frame.clone()));
+        assert_eq!(frame.to_bytes().unwrap(), data);
+    }
+
+    #[test]
+    fn eddystone_url() {
+        let data = [0x10, 0xeb, 0x03, b'g', b'o', b'o', b'g', b'l', b'e', 0x07];
+        let frame = Eddystone::Url(EddystoneUrl { tx_power: -21, url: "https://google.com".to_string() });
+        assert_eq!(Eddystone::from_bytes(&data), Some(frame.clone()));
+        assert_eq!(frame.to_bytes().unwrap(), data);
+
+        let data = [0x10, 0xf4, 0x00, b'b', b'l', b'u', b'e', b'r', 0x00, b'd', b'o', b'c', b's'];
+        let frame = Eddystone::Url(EddystoneUrl { tx_power: -12, url: "http://www.bluer.com/docs".to_string() });
+        assert_eq!(Eddystone::from_bytes(&data), Some(frame.clone()));
+        assert_eq!(frame.to_bytes().unwrap(), data);
+
+        let invalid = |url: &str| Eddystone::Url(EddystoneUrl { tx_power: 0, url: url.to_string() }).to_bytes();
+        assert!(invalid("ftp://example.com").is_err());
+        assert!(invalid("https://example.com/with space").is_err());
+        assert!(invalid("https://an-example-that-is-too-long.com").is_err());
+    }
+
+    #[test]
+    fn eddystone_tlm() {
+        let data = [0x20, 0x00, 0x0b, 0xb8, 0x17, 0x80, 0x00, 0x00, 0x03, 0xe8, 0x00, 0x00, 0x27, 0x10];
+        let frame = Eddystone::Tlm(EddystoneTlm {
+            battery_voltage: 3000,
+            temperature: Some(23.5),
+            advertising_count: 1000,
+            uptime: Duration::from_secs(1000),
+        });
+        assert_eq!(Eddystone::from_bytes(&data), Some(frame.clone()));
+        assert_eq!(frame.to_bytes().unwrap(), data);
+
+        let data = [0x20, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a];
+        let frame = Eddystone::Tlm(EddystoneTlm {
+            battery_voltage: 0,
+            temperature: None,
+            advertising_count: 1,
+            uptime: Duration::from_secs(1),
+        });
+        assert_eq!(Eddystone::from_bytes(&data), Some(frame.clone()));
+        assert_eq!(frame.to_bytes().unwrap(), data);
+    }
+
+    #[test]
+    fn eddystone_eid() {
+        let data = [0x30, 0xf0, 0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0];
+        let frame = Eddystone::Eid(EddystoneEid { tx_power: -16, eid: data[2..].try_into().unwrap() });
+        assert_eq!(Eddystone::from_bytes(&data), Some(frame.clone()));
+        assert_eq!(frame.to_bytes().unwrap(), data);
+
+        let service_data = [(eddystone_uuid(), data.to_vec())].into();
+        assert_eq!(Eddystone::from_service_data(&service_data), Some(frame));
+        assert_eq!(Eddystone::from_bytes(&data[..9]), None);
+    }
+}
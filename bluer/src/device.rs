@@ -1,7 +1,7 @@
 //! Remote Bluetooth device.
 
 use dbus::{
-    arg::{RefArg, Variant},
+    arg::{PropMap, RefArg, Variant},
     nonblock::{Proxy, SyncConnection},
     Path,
 };
@@ -11,17 +11,26 @@ use std::{
     fmt,
     sync::Arc,
 };
+use strum::{Display, EnumString};
 use tokio::{sync::oneshot, time::sleep};
 use uuid::Uuid;
 
+#[cfg(feature = "hid")]
+use crate::hid;
+#[cfg(feature = "media")]
+use crate::media;
 use crate::{
-    all_dbus_objects,
+    adapter::ADMIN_POLICY_STATUS_INTERFACE,
+    all_dbus_objects, battery,
+    device_set::{DeviceSet, DeviceSetMembership},
     gatt::{self, remote::Service, SERVICE_INTERFACE},
-    Adapter, Address, AddressType, Error, ErrorKind, Event, InternalErrorKind, Modalias, Result, SessionInner,
-    SERVICE_NAME, TIMEOUT,
+    network, Adapter, Address, AddressType, Error, ErrorKind, Event, InternalErrorKind, Modalias, Result,
+    SessionInner, SERVICE_NAME, TIMEOUT,
 };
 
 pub(crate) const INTERFACE: &str = "org.bluez.Device1";
+pub(crate) const BEARER_LE_INTERFACE: &str = "org.bluez.Bearer.LE1";
+pub(crate) const BEARER_BREDR_INTERFACE: &str = "org.bluez.Bearer.BREDR1";
 
 /// Interface to a Bluetooth device.
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
@@ -93,7 +102,11 @@ impl Device {
     pub async fn events(&self) -> Result<impl Stream<Item = DeviceEvent>> {
         let events = self.inner.events(self.dbus_path.clone(), false).await?;
         let stream = events.flat_map(move |event| match event {
-            Event::PropertiesChanged { changed, .. } => {
+            Event::PropertiesChanged { interface, changed, .. }
+                if interface == INTERFACE
+                    || interface == battery::INTERFACE
+                    || interface == ADMIN_POLICY_STATUS_INTERFACE =>
+            {
                 stream::iter(DeviceProperty::from_prop_map(changed).into_iter().map(DeviceEvent::PropertyChanged))
                     .boxed()
             }
@@ -163,6 +176,50 @@ impl Device {
         gatt::remote::Service::new(self.inner.clone(), self.adapter_name.clone(), self.address, service_id)
     }
 
+    /// Coordinated device sets this device is a member of.
+    pub async fn sets(&self) -> Result<Vec<DeviceSet>> {
+        self.set_memberships()
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|membership| DeviceSet::new(self.inner.clone(), self.adapter_name.clone(), &membership.name))
+            .collect()
+    }
+
+    /// Media transports of this device.
+    #[cfg(feature = "media")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "media")))]
+    pub async fn media_transports(&self) -> Result<Vec<media::transport::Transport>> {
+        self.media_objects(media::transport::INTERFACE, media::transport::Transport::new).await
+    }
+
+    /// Media players of this device.
+    #[cfg(feature = "media")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "media")))]
+    pub async fn media_players(&self) -> Result<Vec<media::player::Player>> {
+        self.media_objects(media::player::INTERFACE, media::player::Player::new).await
+    }
+
+    #[cfg(feature = "media")]
+    async fn media_objects<T>(
+        &self, interface: &str, new: impl Fn(Arc<SessionInner>, Arc<String>, Address, &str) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        let mut objects = Vec::new();
+        for (path, interfaces) in all_dbus_objects(&self.inner.connection).await? {
+            match media::parse_device_object_path(&path) {
+                Some((adapter, device_address, name))
+                    if adapter == *self.adapter_name
+                        && device_address == self.address
+                        && interfaces.contains_key(interface) =>
+                {
+                    objects.push(new(self.inner.clone(), self.adapter_name.clone(), self.address, name)?);
+                }
+                _ => (),
+            }
+        }
+        Ok(objects)
+    }
+
     dbus_interface!();
     dbus_default_interface!(INTERFACE);
 
@@ -216,6 +273,35 @@ impl Device {
         self.call_method("Disconnect", ()).await
     }
 
+    fn bearer_interface(bearer: AddressType) -> &'static str {
+        match bearer {
+            AddressType::BrEdr => BEARER_BREDR_INTERFACE,
+            AddressType::LePublic | AddressType::LeRandom => BEARER_LE_INTERFACE,
+        }
+    }
+
+    /// Connects this dual-mode device using the specified bearer only.
+    ///
+    /// Both [AddressType::LePublic] and [AddressType::LeRandom] select the LE bearer.
+    /// Services of the device are connected over the specified bearer,
+    /// regardless of the [preferred bearer](Self::preferred_bearer).
+    pub async fn connect_bearer(&self, bearer: AddressType) -> Result<()> {
+        self.call_method_with_interface("Connect", (), Self::bearer_interface(bearer)).await
+    }
+
+    /// Disconnects the specified bearer of this dual-mode device,
+    /// leaving the other bearer connected.
+    pub async fn disconnect_bearer(&self, bearer: AddressType) -> Result<()> {
+        self.call_method_with_interface("Disconnect", (), Self::bearer_interface(bearer)).await
+    }
+
+    /// Indicates whether the specified bearer of this device is connected.
+    pub async fn is_bearer_connected(&self, bearer: AddressType) -> Result<bool> {
+        let connected: Option<bool> =
+            self.get_opt_property_with_interface("Connected", Self::bearer_interface(bearer)).await?;
+        Ok(connected.unwrap_or_default())
+    }
+
     /// This method connects a specific profile of this
     /// device. The UUID provided is the remote service
     /// UUID for the profile.
@@ -236,6 +322,47 @@ impl Device {
         self.call_method("DisconnectProfile", (uuid.to_string(),)).await
     }
 
+    /// Connects to the network service of this device
+    /// using the specified role of the remote device.
+    ///
+    /// Returns the name of the created network interface,
+    /// for example `bnep0`.
+    pub async fn network_connect(&self, role: network::Role) -> Result<String> {
+        let (interface,): (String,) =
+            self.call_method_with_interface("Connect", (role.to_string(),), network::INTERFACE).await?;
+        Ok(interface)
+    }
+
+    /// Disconnects from the network service of this device.
+    ///
+    /// The network interface is removed.
+    pub async fn network_disconnect(&self) -> Result<()> {
+        self.call_method_with_interface("Disconnect", (), network::INTERFACE).await
+    }
+
+    /// Name of the network interface connected to the network service of this device.
+    ///
+    /// Returns [None] if not connected.
+    pub async fn network_interface(&self) -> Result<Option<String>> {
+        let connected: Option<bool> =
+            self.get_opt_property_with_interface("Connected", network::INTERFACE).await?;
+        if !connected.unwrap_or_default() {
+            return Ok(None);
+        }
+        self.get_opt_property_with_interface("Interface", network::INTERFACE).await
+    }
+
+    /// Reconnection behavior of this device, if it is an input device.
+    ///
+    /// Returns [None] if the device does not provide the HID profile.
+    #[cfg(feature = "hid")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "hid")))]
+    pub async fn input_reconnect_mode(&self) -> Result<Option<hid::ReconnectMode>> {
+        let mode: Option<String> =
+            self.get_opt_property_with_interface("ReconnectMode", hid::INPUT_INTERFACE).await?;
+        Ok(mode.map(|mode| mode.parse()).transpose()?)
+    }
+
     /// This method will connect to the remote device,
     /// initiate pairing and then retrieve all SDP records
     /// (or GATT primary services).
@@ -380,6 +507,16 @@ define_properties!(
             set: (set_blocked, v => {v}),
         );
 
+        /// Preferred bearer for connections of this dual-mode device.
+        ///
+        /// This property is experimental.
+        property(
+            PreferredBearer, PreferredBearer,
+            dbus: (INTERFACE, "PreferredBearer", String, OPTIONAL),
+            get: (preferred_bearer, v => {v.parse()?}),
+            set: (set_preferred_bearer, v => {v.to_string()}),
+        );
+
         /// If set to true this device will be allowed to wake the
         /// host from system suspend.
         property(
@@ -518,9 +655,76 @@ define_properties!(
                 mt
             }),
         );
+
+        /// The percentage of battery left as an unsigned 8-bit integer.
+        ///
+        /// Only available if the battery level of the device is known,
+        /// either by the Battery Service of the device or through a
+        /// [battery provider](crate::battery::BatteryProvider).
+        property(
+            BatteryPercentage, u8,
+            dbus: (battery::INTERFACE, "Percentage", u8, OPTIONAL),
+            get: (battery_percentage, v => {v.to_owned()}),
+        );
+
+        /// Describes where the battery information comes from.
+        ///
+        /// Only available if the battery information is provided
+        /// by a [battery provider](crate::battery::BatteryProvider)
+        /// that specified its source.
+        property(
+            BatterySource, String,
+            dbus: (battery::INTERFACE, "Source", String, OPTIONAL),
+            get: (battery_source, v => {v.to_owned()}),
+        );
+
+        /// Indicates whether the device is affected by the admin policy,
+        /// i.e. whether at least one of its services is blocked by the
+        /// [service allow list](crate::Adapter::service_allow_list).
+        property(
+            AffectedByPolicy, bool,
+            dbus: (ADMIN_POLICY_STATUS_INTERFACE, "AffectedByPolicy", bool, OPTIONAL),
+            get: (affected_by_policy, v => {v.to_owned()}),
+        );
+
+        /// Coordinated device sets this device is a member of,
+        /// together with the rank of the device within each set.
+        ///
+        /// Use [sets](Self::sets) to obtain interfaces to the device sets.
+        property(
+            Sets, Vec<DeviceSetMembership>,
+            dbus: (INTERFACE, "Sets", HashMap<Path<'static>, PropMap>, OPTIONAL),
+            get: (set_memberships, v => {
+                v.iter().filter_map(|(path, props)| {
+                    DeviceSet::parse_dbus_path(path).map(|(_, name)| DeviceSetMembership {
+                        name: name.to_string(),
+                        rank: read_opt_prop!(props, "Rank", u8),
+                    })
+                }).collect()
+            }),
+        );
     }
 );
 
+/// Preferred bearer of a dual-mode Bluetooth device.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum PreferredBearer {
+    /// Connect to the last used bearer.
+    #[strum(serialize = "last-used")]
+    LastUsed,
+    /// Connect to the bearer the device was last seen on.
+    #[strum(serialize = "last-seen")]
+    LastSeen,
+    /// Connect to LE first.
+    #[strum(serialize = "le")]
+    Le,
+    /// Connect to BR/EDR first.
+    #[strum(serialize = "bredr")]
+    BrEdr,
+}
+
 /// Bluetooth device event.
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
 #[derive(Debug, Clone)]
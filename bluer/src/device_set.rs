@@ -0,0 +1,167 @@
+//! Bluetooth coordinated device set.
+
+use dbus::{
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use futures::{stream, Stream, StreamExt};
+use std::{fmt, sync::Arc};
+
+use crate::{
+    Adapter, Address, Device, Error, ErrorKind, Event, InternalErrorKind, Result, SessionInner, SERVICE_NAME,
+    TIMEOUT,
+};
+
+pub(crate) const INTERFACE: &str = "org.bluez.DeviceSet1";
+
+/// Interface to a coordinated set of Bluetooth devices.
+///
+/// Devices supporting the Coordinated Set Identification Profile,
+/// such as a pair of hearing aids or earbuds, are grouped into sets by the Bluetooth daemon.
+/// Use [Adapter::device_set] or [Device::sets] to obtain a device set.
+#[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+#[derive(Clone)]
+pub struct DeviceSet {
+    inner: Arc<SessionInner>,
+    dbus_path: Path<'static>,
+    adapter_name: Arc<String>,
+    name: String,
+}
+
+impl fmt::Debug for DeviceSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DeviceSet {{ adapter_name: {}, name: {} }}", self.adapter_name(), self.name())
+    }
+}
+
+impl DeviceSet {
+    /// Create interface to device set with specified name on specified adapter.
+    pub(crate) fn new(inner: Arc<SessionInner>, adapter_name: Arc<String>, name: &str) -> Result<Self> {
+        Ok(Self { inner, dbus_path: Self::dbus_path(&adapter_name, name)?, adapter_name, name: name.to_string() })
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(SERVICE_NAME, &self.dbus_path, TIMEOUT, &*self.inner.connection)
+    }
+
+    pub(crate) fn dbus_path(adapter_name: &str, name: &str) -> Result<Path<'static>> {
+        let adapter_path = Adapter::dbus_path(adapter_name)?;
+        Path::new(format!("{adapter_path}/{name}"))
+            .map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))
+    }
+
+    pub(crate) fn parse_dbus_path<'a>(path: &'a Path) -> Option<(&'a str, &'a str)> {
+        match Adapter::parse_dbus_path_prefix(path) {
+            Some((adapter_name, p)) => match p.strip_prefix('/') {
+                Some(name) if name.starts_with("set_") && !name.contains('/') => Some((adapter_name, name)),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
+    /// The Bluetooth adapter name.
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    /// The name of the device set.
+    ///
+    /// For example `set_0123456789abcdef0123456789abcdef`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Interfaces to the devices that are members of this set.
+    pub async fn devices(&self) -> Result<Vec<Device>> {
+        self.device_addresses()
+            .await?
+            .into_iter()
+            .map(|address| Device::new(self.inner.clone(), self.adapter_name.clone(), address))
+            .collect()
+    }
+
+    /// Streams device set property changes.
+    ///
+    /// The stream ends when the device set is removed.
+    pub async fn events(&self) -> Result<impl Stream<Item = DeviceSetEvent>> {
+        let events = self.inner.events(self.dbus_path.clone(), false).await?;
+        let stream = events.flat_map(move |event| match event {
+            Event::PropertiesChanged { interface, changed, .. } if interface == INTERFACE => stream::iter(
+                DeviceSetProperty::from_prop_map(changed).into_iter().map(DeviceSetEvent::PropertyChanged),
+            )
+            .boxed(),
+            _ => stream::empty().boxed(),
+        });
+        Ok(stream)
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(INTERFACE);
+
+    // ===========================================================================================
+    // Methods
+    // ===========================================================================================
+
+    /// Connects all devices of the set.
+    pub async fn connect(&self) -> Result<()> {
+        self.call_method("Connect", ()).await
+    }
+
+    /// Disconnects all devices of the set.
+    pub async fn disconnect(&self) -> Result<()> {
+        self.call_method("Disconnect", ()).await
+    }
+}
+
+define_properties!(
+    DeviceSet,
+    /// Bluetooth device set property.
+    pub DeviceSetProperty => {
+        /// Indicates whether the devices of the set are connected
+        /// automatically when one of them is connected.
+        property(
+            AutoConnect, bool,
+            dbus: (INTERFACE, "AutoConnect", bool, MANDATORY),
+            get: (auto_connect, v => {v.to_owned()}),
+            set: (set_auto_connect, v => {v}),
+        );
+
+        /// Addresses of the devices that are members of the set.
+        property(
+            Devices, Vec<Address>,
+            dbus: (INTERFACE, "Devices", Vec<Path<'static>>, MANDATORY),
+            get: (device_addresses, v => {
+                v.iter().filter_map(|path| Device::parse_dbus_path(path).map(|(_, address)| address)).collect()
+            }),
+        );
+
+        /// Number of devices in the set.
+        property(
+            Size, u8,
+            dbus: (INTERFACE, "Size", u8, MANDATORY),
+            get: (size, v => {v.to_owned()}),
+        );
+    }
+);
+
+/// Bluetooth device set event.
+#[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeviceSetEvent {
+    /// Property changed.
+    PropertyChanged(DeviceSetProperty),
+}
+
+/// Membership of a device in a [DeviceSet].
+#[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct DeviceSetMembership {
+    /// Name of the device set.
+    pub name: String,
+    /// Rank of the device within the set.
+    pub rank: Option<u8>,
+}
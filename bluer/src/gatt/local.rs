@@ -33,6 +33,10 @@ use crate::{
     ERR_PREFIX, SERVICE_NAME, TIMEOUT,
 };
 
+#[cfg(feature = "derive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
+pub use bluer_derive::gatt_service;
+
 pub(crate) const MANAGER_INTERFACE: &str = "org.bluez.GattManager1";
 
 /// Link type.
@@ -1351,7 +1355,13 @@ impl Application {
         log::trace!("Registering application at {}", &app_path);
         let proxy =
             Proxy::new(SERVICE_NAME, Adapter::dbus_path(&adapter_name)?, TIMEOUT, inner.connection.clone());
-        proxy.method_call(MANAGER_INTERFACE, "RegisterApplication", (app_path.clone(), PropMap::new())).await?;
+        proxy
+            .method_call::<(), _, _, _>(
+                MANAGER_INTERFACE,
+                "RegisterApplication",
+                (app_path.clone(), PropMap::new()),
+            )
+            .await?;
 
         let (drop_tx, drop_rx) = oneshot::channel();
         let app_path_unreg = app_path.clone();
@@ -1439,7 +1449,11 @@ impl Profile {
         let proxy =
             Proxy::new(SERVICE_NAME, Adapter::dbus_path(&adapter_name)?, TIMEOUT, inner.connection.clone());
         proxy
-            .method_call(MANAGER_INTERFACE, "RegisterApplication", (profile_path.clone(), PropMap::new()))
+            .method_call::<(), _, _, _>(
+                MANAGER_INTERFACE,
+                "RegisterApplication",
+                (profile_path.clone(), PropMap::new()),
+            )
             .await?;
 
         let (drop_tx, drop_rx) = oneshot::channel();
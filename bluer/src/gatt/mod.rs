@@ -19,7 +19,10 @@ use tokio::{
 use crate::Address;
 
 pub mod local;
+pub mod nus;
 pub mod remote;
+pub mod services;
+pub mod value;
 
 pub(crate) const SERVICE_INTERFACE: &str = "org.bluez.GattService1";
 pub(crate) const CHARACTERISTIC_INTERFACE: &str = "org.bluez.GattCharacteristic1";
@@ -117,6 +120,16 @@ pub struct CharacteristicReader {
 }
 
 impl CharacteristicReader {
+    /// Creates a reader for the socket received from the other side of the notification session.
+    pub(crate) fn from_fd(
+        adapter_name: String, device_address: Address, mtu: usize, fd: OwnedFd,
+    ) -> std::io::Result<Self> {
+        let stream = unsafe { std::os::unix::net::UnixStream::from_raw_fd(fd.into_fd()) };
+        stream.set_nonblocking(true)?;
+        let stream = UnixStream::from_std(stream)?;
+        Ok(Self { adapter_name, device_address, mtu, stream, buf: Vec::new() })
+    }
+
     /// Name of adapter.
     pub fn adapter_name(&self) -> &str {
         &self.adapter_name
@@ -230,6 +243,16 @@ pub struct CharacteristicWriter {
 }
 
 impl CharacteristicWriter {
+    /// Creates a writer for the socket received from the other side of the write session.
+    pub(crate) fn from_fd(
+        adapter_name: String, device_address: Address, mtu: usize, fd: OwnedFd,
+    ) -> std::io::Result<Self> {
+        let stream = unsafe { std::os::unix::net::UnixStream::from_raw_fd(fd.into_fd()) };
+        stream.set_nonblocking(true)?;
+        let stream = UnixStream::from_std(stream)?;
+        Ok(Self { adapter_name, device_address, mtu, stream })
+    }
+
     /// Name of adapter.
     pub fn adapter_name(&self) -> &str {
         &self.adapter_name
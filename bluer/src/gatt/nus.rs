@@ -0,0 +1,431 @@
+//! Nordic UART Service (NUS).
+//!
+//! The Nordic UART Service emulates a serial port over GATT.
+//! Data is sent to the peripheral by writing to its RX characteristic and
+//! received from the peripheral via notifications of its TX characteristic.
+//!
+//! [NusStream] combines both characteristics into a single [AsyncRead] and [AsyncWrite] stream.
+//! Use [NusStream::connect] to connect to a remote peripheral and [NusServer]
+//! to provide the service from a local adapter.
+
+use futures::{ready, StreamExt};
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use uuid::Uuid;
+
+use super::{
+    local::{
+        characteristic_control, Application, ApplicationHandle, Characteristic, CharacteristicControl,
+        CharacteristicControlEvent, CharacteristicNotify, CharacteristicNotifyMethod, CharacteristicWrite,
+        CharacteristicWriteMethod, ReqError, Service,
+    },
+    CharacteristicReader, CharacteristicWriter,
+};
+use crate::{
+    adv::{Advertisement, AdvertisementHandle},
+    Adapter, Address, Device, Error, ErrorKind, Result,
+};
+
+/// UUID of the Nordic UART service.
+pub const SERVICE_UUID: Uuid = Uuid::from_u128(0x6e400001_b5a3_f393_e0a9_e50e24dcca9e);
+/// UUID of the RX characteristic, which is written by the client.
+pub const RX_CHARACTERISTIC_UUID: Uuid = Uuid::from_u128(0x6e400002_b5a3_f393_e0a9_e50e24dcca9e);
+/// UUID of the TX characteristic, which is notified by the server.
+pub const TX_CHARACTERISTIC_UUID: Uuid = Uuid::from_u128(0x6e400003_b5a3_f393_e0a9_e50e24dcca9e);
+
+type ControlSlot = Arc<Mutex<Option<Controls>>>;
+
+/// Controls of the local RX and TX characteristics of a [NusServer].
+#[derive(Debug)]
+struct Controls {
+    rx: CharacteristicControl,
+    tx: CharacteristicControl,
+}
+
+/// Controls lent from a [NusServer], which are returned to it when dropped.
+#[derive(Debug)]
+struct LentControls {
+    controls: Option<Controls>,
+    slot: ControlSlot,
+}
+
+impl LentControls {
+    fn take(slot: &ControlSlot) -> Result<Self> {
+        let controls = slot.lock().unwrap().take().ok_or_else(|| Error::new(ErrorKind::InProgress))?;
+        Ok(Self { controls: Some(controls), slot: slot.clone() })
+    }
+
+    fn get(&mut self) -> &mut Controls {
+        self.controls.as_mut().unwrap()
+    }
+}
+
+impl Drop for LentControls {
+    fn drop(&mut self) {
+        if let Some(controls) = self.controls.take() {
+            *self.slot.lock().unwrap() = Some(controls);
+        }
+    }
+}
+
+/// Nordic UART Service connection.
+///
+/// Writes are split into chunks of at most [mtu](Self::mtu) bytes,
+/// each of which is sent using a single write or notify operation.
+#[derive(Debug)]
+pub struct NusStream {
+    device_address: Address,
+    reader: Option<CharacteristicReader>,
+    writer: Option<CharacteristicWriter>,
+    /// Controls of the local characteristics, while serving a client.
+    controls: Option<LentControls>,
+}
+
+impl NusStream {
+    /// Connects to the Nordic UART service of the remote device.
+    ///
+    /// The device must be connected.
+    /// Fails with [ErrorKind::NotFound] if the device does not provide the service.
+    pub async fn connect(device: &Device) -> Result<Self> {
+        let mut rx = None;
+        let mut tx = None;
+        for service in device.services().await? {
+            if service.uuid().await? != SERVICE_UUID {
+                continue;
+            }
+            for characteristic in service.characteristics().await? {
+                match characteristic.uuid().await? {
+                    RX_CHARACTERISTIC_UUID => rx = Some(characteristic),
+                    TX_CHARACTERISTIC_UUID => tx = Some(characteristic),
+                    _ => (),
+                }
+            }
+        }
+        let (Some(rx), Some(tx)) = (rx, tx) else {
+            return Err(Error::new(ErrorKind::NotFound));
+        };
+
+        Ok(Self {
+            device_address: device.address(),
+            reader: Some(tx.notify_io().await?),
+            writer: Some(rx.write_io().await?),
+            controls: None,
+        })
+    }
+
+    /// Address of the remote device.
+    pub fn device_address(&self) -> Address {
+        self.device_address
+    }
+
+    /// Maximum transmission unit.
+    pub fn mtu(&self) -> usize {
+        match (&self.writer, &self.reader) {
+            (Some(writer), _) => writer.mtu(),
+            (None, Some(reader)) => reader.mtu(),
+            (None, None) => 0,
+        }
+    }
+
+    /// Waits for the client's first write to the local RX characteristic.
+    ///
+    /// Returns [None] once the connection has been closed.
+    fn poll_accept_reader(&mut self, cx: &mut Context) -> Poll<Option<std::io::Result<()>>> {
+        let Some(controls) = &mut self.controls else { return Poll::Ready(None) };
+        loop {
+            match controls.get().rx.poll_next_unpin(cx) {
+                Poll::Ready(Some(CharacteristicControlEvent::Write(req))) => {
+                    if req.device_address() == self.device_address {
+                        self.reader = Some(req.accept()?);
+                        return Poll::Ready(Some(Ok(())));
+                    }
+                    req.reject(ReqError::NotPermitted);
+                }
+                Poll::Ready(Some(CharacteristicControlEvent::Notify(_))) => (),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => {
+                    // The notification session socket becomes readable when it is closed.
+                    return match self.writer.as_ref().map(|writer| writer.stream.poll_read_ready(cx)) {
+                        Some(Poll::Ready(_)) => Poll::Ready(None),
+                        _ => Poll::Pending,
+                    };
+                }
+            }
+        }
+    }
+
+    /// Waits for the client to subscribe to the local TX characteristic.
+    ///
+    /// Returns [None] if the service has been unregistered.
+    fn poll_accept_writer(&mut self, cx: &mut Context) -> Poll<Option<()>> {
+        let Some(controls) = &mut self.controls else { return Poll::Ready(None) };
+        loop {
+            match ready!(controls.get().tx.poll_next_unpin(cx)) {
+                Some(CharacteristicControlEvent::Notify(writer)) => {
+                    if writer.device_address() == self.device_address {
+                        self.writer = Some(writer);
+                        return Poll::Ready(Some(()));
+                    }
+                }
+                Some(CharacteristicControlEvent::Write(req)) => req.reject(ReqError::NotSupported),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+
+    fn poll_writer(&mut self, cx: &mut Context) -> Poll<std::io::Result<Pin<&mut CharacteristicWriter>>> {
+        if self.writer.is_none() && ready!(self.poll_accept_writer(cx)).is_none() {
+            return Poll::Ready(Err(std::io::ErrorKind::NotConnected.into()));
+        }
+        Poll::Ready(Ok(Pin::new(self.writer.as_mut().unwrap())))
+    }
+}
+
+impl AsyncRead for NusStream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut ReadBuf) -> Poll<std::io::Result<()>> {
+        if self.reader.is_none() {
+            match ready!(self.poll_accept_reader(cx)) {
+                Some(res) => res?,
+                None => return Poll::Ready(Ok(())),
+            }
+        }
+        Pin::new(self.reader.as_mut().unwrap()).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for NusStream {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        ready!(self.poll_writer(cx))?.poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<std::io::Result<()>> {
+        match &mut self.writer {
+            Some(writer) => Pin::new(writer).poll_flush(cx),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<std::io::Result<()>> {
+        match &mut self.writer {
+            Some(writer) => Pin::new(writer).poll_shutdown(cx),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+/// Nordic UART Service provided by a local adapter.
+///
+/// Clients are served one at a time.
+/// The service is unregistered when this is dropped.
+pub struct NusServer {
+    controls: ControlSlot,
+    _app: ApplicationHandle,
+    _adv: Option<AdvertisementHandle>,
+}
+
+impl std::fmt::Debug for NusServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "NusServer")
+    }
+}
+
+impl NusServer {
+    /// Publishes the Nordic UART service on the specified adapter and advertises it.
+    pub async fn serve(adapter: &Adapter) -> Result<Self> {
+        let advertisement = Advertisement { discoverable: Some(true), ..Default::default() };
+        Self::serve_with_advertisement(adapter, Some(advertisement)).await
+    }
+
+    /// Publishes the Nordic UART service on the specified adapter.
+    ///
+    /// If an advertisement is specified, the service UUID is added to it and it is
+    /// advertised while the server exists.
+    pub async fn serve_with_advertisement(
+        adapter: &Adapter, advertisement: Option<Advertisement>,
+    ) -> Result<Self> {
+        let (rx_control, rx_control_handle) = characteristic_control();
+        let (tx_control, tx_control_handle) = characteristic_control();
+        let app = Application {
+            services: vec![Service {
+                uuid: SERVICE_UUID,
+                primary: true,
+                characteristics: vec![
+                    Characteristic {
+                        uuid: RX_CHARACTERISTIC_UUID,
+                        write: Some(CharacteristicWrite {
+                            write: true,
+                            write_without_response: true,
+                            method: CharacteristicWriteMethod::Io,
+                            ..Default::default()
+                        }),
+                        control_handle: rx_control_handle,
+                        ..Default::default()
+                    },
+                    Characteristic {
+                        uuid: TX_CHARACTERISTIC_UUID,
+                        notify: Some(CharacteristicNotify {
+                            notify: true,
+                            method: CharacteristicNotifyMethod::Io,
+                            ..Default::default()
+                        }),
+                        control_handle: tx_control_handle,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let app = adapter.serve_gatt_application(app).await?;
+
+        let adv = match advertisement {
+            Some(mut advertisement) => {
+                advertisement.service_uuids.insert(SERVICE_UUID);
+                Some(adapter.advertise(advertisement).await?)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            controls: Arc::new(Mutex::new(Some(Controls { rx: rx_control, tx: tx_control }))),
+            _app: app,
+            _adv: adv,
+        })
+    }
+
+    /// Waits for a client to write to the RX characteristic or subscribe to the TX
+    /// characteristic and returns the connection.
+    ///
+    /// Clients may do either first.
+    /// If the client wrote first, its data is readable immediately and writes to the
+    /// returned stream wait until it has subscribed to the TX characteristic.
+    /// If the client subscribed first, data written by it becomes readable once it has
+    /// performed its first write.
+    ///
+    /// Fails with [ErrorKind::InProgress] if the connection to the previous client has not been dropped.
+    pub async fn accept(&mut self) -> Result<NusStream> {
+        let mut controls = LentControls::take(&self.controls)?;
+        let (device_address, reader, writer) = loop {
+            let Controls { rx, tx } = controls.get();
+            tokio::select! {
+                evt = rx.next() => match evt {
+                    Some(CharacteristicControlEvent::Write(req)) => {
+                        break (req.device_address(), Some(req.accept()?), None);
+                    }
+                    Some(CharacteristicControlEvent::Notify(_)) => (),
+                    None => return Err(Error::new(ErrorKind::NotRegistered)),
+                },
+                evt = tx.next() => match evt {
+                    Some(CharacteristicControlEvent::Notify(writer)) => {
+                        break (writer.device_address(), None, Some(writer));
+                    }
+                    Some(CharacteristicControlEvent::Write(req)) => req.reject(ReqError::NotSupported),
+                    None => return Err(Error::new(ErrorKind::NotRegistered)),
+                },
+            }
+        };
+        Ok(NusStream { device_address, reader, writer, controls: Some(controls) })
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use std::{future::Future, time::Duration};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        time::timeout,
+    };
+
+    use super::*;
+    use crate::mock::{Bluetoothd, MockDevice, VirtualDevice};
+
+    async fn within<T>(fut: impl Future<Output = T>) -> T {
+        timeout(Duration::from_secs(5), fut).await.expect("timed out")
+    }
+
+    /// Starts the mock daemon with a client device and serves the Nordic UART service.
+    async fn start() -> (Bluetoothd, MockDevice, NusServer) {
+        let bluetoothd = Bluetoothd::new().await.expect("dbus-daemon is required");
+        let mock_adapter = bluetoothd.add_adapter("hci0", "00:11:22:33:44:55".parse().unwrap()).unwrap();
+        let client = mock_adapter
+            .add_device(VirtualDevice { address: "66:77:88:99:aa:bb".parse().unwrap(), ..Default::default() })
+            .unwrap();
+
+        let session = bluetoothd.session().await.unwrap();
+        let adapter = session.default_adapter().await.unwrap();
+        let server = NusServer::serve_with_advertisement(&adapter, None).await.unwrap();
+
+        (bluetoothd, client, server)
+    }
+
+    async fn read(reader: &mut (impl AsyncRead + Unpin)) -> Vec<u8> {
+        let mut buf = [0; 64];
+        let n = within(reader.read(&mut buf)).await.unwrap();
+        buf[..n].to_vec()
+    }
+
+    #[tokio::test]
+    async fn accept_on_write() {
+        let (_bluetoothd, client, mut server) = start().await;
+
+        let (stream, rx) =
+            within(async { tokio::join!(server.accept(), client.acquire_write(RX_CHARACTERISTIC_UUID)) }).await;
+        let (mut stream, mut rx) = (stream.unwrap(), rx.unwrap());
+        assert_eq!(stream.device_address(), client.address());
+
+        rx.write_all(b"hello").await.unwrap();
+        assert_eq!(read(&mut stream).await, b"hello");
+
+        // Writes wait until the client has subscribed to the TX characteristic.
+        let (res, tx) = within(async {
+            tokio::join!(stream.write_all(b"world"), client.acquire_notify(TX_CHARACTERISTIC_UUID))
+        })
+        .await;
+        res.unwrap();
+        assert_eq!(read(&mut tx.unwrap()).await, b"world");
+    }
+
+    #[tokio::test]
+    async fn accept_on_subscribe() {
+        let (_bluetoothd, client, mut server) = start().await;
+
+        let (stream, tx) =
+            within(async { tokio::join!(server.accept(), client.acquire_notify(TX_CHARACTERISTIC_UUID)) }).await;
+        let (mut stream, mut tx) = (stream.unwrap(), tx.unwrap());
+        assert_eq!(stream.device_address(), client.address());
+
+        stream.write_all(b"hello").await.unwrap();
+        assert_eq!(read(&mut tx).await, b"hello");
+
+        // Data becomes readable once the client has performed its first write.
+        let mut buf = [0; 64];
+        let (n, _rx) = within(async {
+            tokio::join!(stream.read(&mut buf), async {
+                let mut rx = client.acquire_write(RX_CHARACTERISTIC_UUID).await.unwrap();
+                rx.write_all(b"world").await.unwrap();
+                rx
+            })
+        })
+        .await;
+        assert_eq!(&buf[..n.unwrap()], b"world");
+    }
+
+    #[tokio::test]
+    async fn controls_returned_on_drop() {
+        let (_bluetoothd, client, mut server) = start().await;
+
+        let (stream, _tx) =
+            within(async { tokio::join!(server.accept(), client.acquire_notify(TX_CHARACTERISTIC_UUID)) }).await;
+        let stream = stream.unwrap();
+        assert_eq!(server.accept().await.unwrap_err().kind, ErrorKind::InProgress);
+
+        drop(stream);
+        let (stream, _tx) =
+            within(async { tokio::join!(server.accept(), client.acquire_notify(TX_CHARACTERISTIC_UUID)) }).await;
+        assert_eq!(stream.unwrap().device_address(), client.address());
+    }
+}
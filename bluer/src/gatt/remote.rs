@@ -6,11 +6,11 @@ use dbus::{
     Path,
 };
 use futures::{Stream, StreamExt};
-use std::{fmt, os::unix::prelude::FromRawFd, sync::Arc};
-use tokio::net::UnixStream;
+use std::{fmt, sync::Arc};
 use uuid::Uuid;
 
 use super::{
+    value::{PresentationFormat, Value, PRESENTATION_FORMAT_UUID},
     CharacteristicFlags, CharacteristicReader, CharacteristicWriter, WriteOp, CHARACTERISTIC_INTERFACE,
     DESCRIPTOR_INTERFACE, SERVICE_INTERFACE,
 };
@@ -330,10 +330,33 @@ impl Characteristic {
     ///
     /// Takes extended options for the write operation.
     pub async fn write_ext(&self, value: &[u8], req: &CharacteristicWriteRequest) -> Result<()> {
-        self.call_method("WriteValue", (value, req.to_dict())).await?;
+        self.call_method::<_, ()>("WriteValue", (value, req.to_dict())).await?;
         Ok(())
     }
 
+    /// Reads the value of the characteristic and decodes it as the specified type.
+    pub async fn read_as<T: Value>(&self) -> Result<T> {
+        T::from_bytes(&self.read().await?)
+    }
+
+    /// Encodes the specified value and writes it to the characteristic.
+    pub async fn write_as<T: Value>(&self, value: &T) -> Result<()> {
+        self.write(&value.to_bytes()).await
+    }
+
+    /// Reads the Characteristic Presentation Format descriptor of the characteristic.
+    ///
+    /// Returns [None] if the characteristic has no such descriptor.
+    /// If the characteristic has multiple presentation formats, the first is returned.
+    pub async fn presentation_format(&self) -> Result<Option<PresentationFormat>> {
+        for descriptor in self.descriptors().await? {
+            if descriptor.uuid().await? == PRESENTATION_FORMAT_UUID {
+                return Ok(Some(PresentationFormat::from_bytes(&descriptor.read().await?)?));
+            }
+        }
+        Ok(None)
+    }
+
     /// Acquire writer for writing with low overhead.
     ///
     /// It only works with characteristic that has
@@ -351,17 +374,9 @@ impl Characteristic {
     pub async fn write_io(&self) -> Result<CharacteristicWriter> {
         let options = PropMap::new();
         let (fd, mtu): (OwnedFd, u16) = self.call_method("AcquireWrite", (options,)).await?;
-        let stream = unsafe { std::os::unix::net::UnixStream::from_raw_fd(fd.into_fd()) };
-        stream.set_nonblocking(true)?;
-        let stream = UnixStream::from_std(stream)?;
         // WORKAROUND: BlueZ drops data at end of packet if full MTU is used.
         let mtu = mtu.saturating_sub(5).into();
-        Ok(CharacteristicWriter {
-            adapter_name: self.adapter_name().to_string(),
-            device_address: self.device_address,
-            mtu,
-            stream,
-        })
+        Ok(CharacteristicWriter::from_fd(self.adapter_name().to_string(), self.device_address, mtu, fd)?)
     }
 
     /// Starts a notification or indication session from this characteristic
@@ -387,6 +402,14 @@ impl Characteristic {
         Ok(values)
     }
 
+    /// Starts a notification or indication session from this characteristic
+    /// and decodes the received values as the specified type.
+    ///
+    /// Values that cannot be decoded are returned as errors.
+    pub async fn notify_as<T: Value>(&self) -> Result<impl Stream<Item = Result<T>>> {
+        Ok(self.notify().await?.map(|value| T::from_bytes(&value)))
+    }
+
     async fn notify_session(&self) -> Result<SingleSessionToken> {
         let dbus_path = self.dbus_path.clone();
         let connection = self.inner.connection.clone();
@@ -394,7 +417,7 @@ impl Characteristic {
             .single_session(
                 &self.dbus_path,
                 async move {
-                    self.call_method("StartNotify", ()).await?;
+                    self.call_method::<_, ()>("StartNotify", ()).await?;
                     Ok(())
                 },
                 async move {
@@ -432,16 +455,7 @@ impl Characteristic {
     pub async fn notify_io(&self) -> Result<CharacteristicReader> {
         let options = PropMap::new();
         let (fd, mtu): (OwnedFd, u16) = self.call_method("AcquireNotify", (options,)).await?;
-        let stream = unsafe { std::os::unix::net::UnixStream::from_raw_fd(fd.into_fd()) };
-        stream.set_nonblocking(true)?;
-        let stream = UnixStream::from_std(stream)?;
-        Ok(CharacteristicReader {
-            adapter_name: self.adapter_name().to_string(),
-            device_address: self.device_address,
-            mtu: mtu.into(),
-            stream,
-            buf: Vec::new(),
-        })
+        Ok(CharacteristicReader::from_fd(self.adapter_name().to_string(), self.device_address, mtu.into(), fd)?)
     }
 
     dbus_interface!();
@@ -667,7 +681,7 @@ impl Descriptor {
     ///
     /// Takes extended options for the write operation.
     pub async fn write_ext(&self, value: &[u8], req: &DescriptorWriteRequest) -> Result<()> {
-        self.call_method("WriteValue", (value, req.to_dict())).await?;
+        self.call_method::<_, ()>("WriteValue", (value, req.to_dict())).await?;
         Ok(())
     }
 }
@@ -0,0 +1,461 @@
+//! Standard GATT services.
+//!
+//! This module provides ready-made local services for publishing the
+//! Device Information, Battery and Current Time services using
+//! [Adapter::serve_gatt_application](crate::Adapter::serve_gatt_application),
+//! as well as functions for reading these services from a remote [Device].
+
+use futures::{FutureExt, Stream, StreamExt};
+use std::sync::Arc;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use super::{
+    local::{
+        Characteristic, CharacteristicNotify, CharacteristicNotifyMethod, CharacteristicRead, ReqError, Service,
+    },
+    remote,
+    value::{BatteryLevel, CurrentTime, PnpId, Value},
+};
+use crate::{Device, Error, ErrorKind, Result};
+
+/// UUID of the Device Information service.
+pub const DEVICE_INFORMATION_UUID: Uuid = Uuid::from_u128(0x0000180a_0000_1000_8000_00805f9b34fb);
+/// UUID of the Battery service.
+pub const BATTERY_UUID: Uuid = Uuid::from_u128(0x0000180f_0000_1000_8000_00805f9b34fb);
+/// UUID of the Current Time service.
+pub const CURRENT_TIME_UUID: Uuid = Uuid::from_u128(0x00001805_0000_1000_8000_00805f9b34fb);
+
+const SYSTEM_ID_UUID: Uuid = Uuid::from_u128(0x00002a23_0000_1000_8000_00805f9b34fb);
+const MODEL_NUMBER_UUID: Uuid = Uuid::from_u128(0x00002a24_0000_1000_8000_00805f9b34fb);
+const SERIAL_NUMBER_UUID: Uuid = Uuid::from_u128(0x00002a25_0000_1000_8000_00805f9b34fb);
+const FIRMWARE_REVISION_UUID: Uuid = Uuid::from_u128(0x00002a26_0000_1000_8000_00805f9b34fb);
+const HARDWARE_REVISION_UUID: Uuid = Uuid::from_u128(0x00002a27_0000_1000_8000_00805f9b34fb);
+const SOFTWARE_REVISION_UUID: Uuid = Uuid::from_u128(0x00002a28_0000_1000_8000_00805f9b34fb);
+const MANUFACTURER_NAME_UUID: Uuid = Uuid::from_u128(0x00002a29_0000_1000_8000_00805f9b34fb);
+
+/// Contents of the Device Information service.
+///
+/// Characteristics that are [None] are not included in the service.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DeviceInformation {
+    /// Manufacturer name.
+    pub manufacturer_name: Option<String>,
+    /// Model number.
+    pub model_number: Option<String>,
+    /// Serial number.
+    pub serial_number: Option<String>,
+    /// Hardware revision.
+    pub hardware_revision: Option<String>,
+    /// Firmware revision.
+    pub firmware_revision: Option<String>,
+    /// Software revision.
+    pub software_revision: Option<String>,
+    /// System id consisting of a 40-bit manufacturer defined identifier
+    /// followed by a 24-bit organizationally unique identifier.
+    pub system_id: Option<u64>,
+    /// PnP id.
+    pub pnp_id: Option<PnpId>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl DeviceInformation {
+    fn fields(&self) -> Vec<(Uuid, Vec<u8>)> {
+        let strings = [
+            (MANUFACTURER_NAME_UUID, &self.manufacturer_name),
+            (MODEL_NUMBER_UUID, &self.model_number),
+            (SERIAL_NUMBER_UUID, &self.serial_number),
+            (HARDWARE_REVISION_UUID, &self.hardware_revision),
+            (FIRMWARE_REVISION_UUID, &self.firmware_revision),
+            (SOFTWARE_REVISION_UUID, &self.software_revision),
+        ];
+        let mut fields: Vec<_> =
+            strings.into_iter().filter_map(|(uuid, v)| v.as_ref().map(|v| (uuid, v.to_bytes()))).collect();
+        if let Some(system_id) = self.system_id {
+            fields.push((SYSTEM_ID_UUID, system_id.to_bytes()));
+        }
+        if let Some(pnp_id) = &self.pnp_id {
+            fields.push((PnpId::UUID, pnp_id.to_bytes()));
+        }
+        fields
+    }
+
+    fn set_field(&mut self, uuid: Uuid, value: &[u8]) -> Result<()> {
+        match uuid {
+            MANUFACTURER_NAME_UUID => self.manufacturer_name = Some(String::from_bytes(value)?),
+            MODEL_NUMBER_UUID => self.model_number = Some(String::from_bytes(value)?),
+            SERIAL_NUMBER_UUID => self.serial_number = Some(String::from_bytes(value)?),
+            HARDWARE_REVISION_UUID => self.hardware_revision = Some(String::from_bytes(value)?),
+            FIRMWARE_REVISION_UUID => self.firmware_revision = Some(String::from_bytes(value)?),
+            SOFTWARE_REVISION_UUID => self.software_revision = Some(String::from_bytes(value)?),
+            SYSTEM_ID_UUID => self.system_id = Some(u64::from_bytes(value)?),
+            PnpId::UUID => self.pnp_id = Some(PnpId::from_bytes(value)?),
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+/// Read-only characteristic with a constant value.
+fn constant_characteristic(uuid: Uuid, value: Vec<u8>) -> Characteristic {
+    Characteristic {
+        uuid,
+        read: Some(CharacteristicRead {
+            read: true,
+            fun: Box::new(move |req| {
+                let value = value.get(usize::from(req.offset)..).map(|v| v.to_vec());
+                async move { value.ok_or(ReqError::InvalidOffset) }.boxed()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Readable and notifying characteristic whose value is derived from the watched state.
+///
+/// Notifications are sent whenever the state changes.
+/// `read_value` provides the value for read requests and `notify_value` the value
+/// of the notification sent for a state change.
+fn watched_characteristic<T: Send + Sync + 'static>(
+    uuid: Uuid, rx: watch::Receiver<T>, read_value: impl Fn(&T) -> Vec<u8> + Send + Sync + 'static,
+    notify_value: impl Fn(&T) -> Vec<u8> + Send + Sync + 'static,
+) -> Characteristic {
+    let value = Arc::new(notify_value);
+    let read_rx = rx.clone();
+    Characteristic {
+        uuid,
+        read: Some(CharacteristicRead {
+            read: true,
+            fun: Box::new(move |req| {
+                let value = read_value(&read_rx.borrow());
+                let value = value.get(usize::from(req.offset)..).map(|v| v.to_vec());
+                async move { value.ok_or(ReqError::InvalidOffset) }.boxed()
+            }),
+            ..Default::default()
+        }),
+        notify: Some(CharacteristicNotify {
+            notify: true,
+            method: CharacteristicNotifyMethod::Fun(Box::new(move |mut notifier| {
+                let mut rx = rx.clone();
+                let value = value.clone();
+                async move {
+                    rx.borrow_and_update();
+                    tokio::spawn(async move {
+                        loop {
+                            tokio::select! {
+                                res = rx.changed() => {
+                                    if res.is_err() {
+                                        break;
+                                    }
+                                }
+                                () = notifier.stopped() => break,
+                            }
+                            let data = value(&rx.borrow_and_update());
+                            if notifier.notify(data).await.is_err() {
+                                break;
+                            }
+                        }
+                    });
+                }
+                .boxed()
+            })),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Creates a local Device Information service.
+pub fn device_information_service(info: &DeviceInformation) -> Service {
+    Service {
+        uuid: DEVICE_INFORMATION_UUID,
+        primary: true,
+        characteristics: info
+            .fields()
+            .into_iter()
+            .map(|(uuid, value)| constant_characteristic(uuid, value))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Handle for updating the battery level of a local [battery service](battery_service).
+///
+/// Dropping all clones of the handle ends notification sessions.
+#[derive(Clone)]
+pub struct BatteryLevelHandle {
+    tx: Arc<watch::Sender<u8>>,
+}
+
+impl std::fmt::Debug for BatteryLevelHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "BatteryLevelHandle {{ level: {} }}", self.level())
+    }
+}
+
+impl BatteryLevelHandle {
+    /// Current battery level in percent.
+    pub fn level(&self) -> u8 {
+        *self.tx.borrow()
+    }
+
+    /// Sets the battery level in percent.
+    ///
+    /// Values above 100 are clamped.
+    /// Subscribed clients are notified if the level has changed.
+    pub fn set_level(&self, level: u8) {
+        let level = level.min(100);
+        self.tx.send_if_modified(|v| {
+            let changed = *v != level;
+            *v = level;
+            changed
+        });
+    }
+}
+
+fn battery_level(level: &u8) -> Vec<u8> {
+    BatteryLevel(*level).to_bytes()
+}
+
+/// Creates a local Battery service with the specified initial battery level in percent.
+///
+/// The returned handle is used to update the battery level.
+pub fn battery_service(level: u8) -> (Service, BatteryLevelHandle) {
+    let (tx, rx) = watch::channel(level.min(100));
+    let service = Service {
+        uuid: BATTERY_UUID,
+        primary: true,
+        characteristics: vec![watched_characteristic(BatteryLevel::UUID, rx, battery_level, battery_level)],
+        ..Default::default()
+    };
+    (service, BatteryLevelHandle { tx: Arc::new(tx) })
+}
+
+/// Handle for signalling time adjustments of a local [current time service](current_time_service).
+///
+/// Dropping all clones of the handle ends notification sessions.
+#[derive(Clone, Debug)]
+pub struct CurrentTimeHandle {
+    tx: Arc<watch::Sender<u8>>,
+}
+
+impl CurrentTimeHandle {
+    /// Notifies subscribed clients that the system time has been adjusted.
+    ///
+    /// The reason is a bitwise combination of the `ADJUST_*` constants of [CurrentTime].
+    /// It is only included in the resulting notification; read requests
+    /// always report an adjust reason of zero.
+    pub fn adjusted(&self, adjust_reason: u8) {
+        self.tx.send_replace(adjust_reason);
+    }
+}
+
+/// Creates a local Current Time service providing the local time of the system.
+///
+/// The returned handle is used to notify clients when the system time is adjusted.
+pub fn current_time_service() -> (Service, CurrentTimeHandle) {
+    let (tx, rx) = watch::channel(0);
+    let service = Service {
+        uuid: CURRENT_TIME_UUID,
+        primary: true,
+        characteristics: vec![watched_characteristic(
+            CurrentTime::UUID,
+            rx,
+            |_| CurrentTime::now().to_bytes(),
+            |adjust_reason| CurrentTime { adjust_reason: *adjust_reason, ..CurrentTime::now() }.to_bytes(),
+        )],
+        ..Default::default()
+    };
+    (service, CurrentTimeHandle { tx: Arc::new(tx) })
+}
+
+/// Finds the remote service with the specified UUID.
+async fn find_service(device: &Device, uuid: Uuid) -> Result<remote::Service> {
+    for service in device.services().await? {
+        if service.uuid().await? == uuid {
+            return Ok(service);
+        }
+    }
+    Err(Error::new(ErrorKind::NotFound))
+}
+
+/// Finds the remote characteristic with the specified UUID within the service with the specified UUID.
+async fn find_characteristic(device: &Device, service_uuid: Uuid, uuid: Uuid) -> Result<remote::Characteristic> {
+    for characteristic in find_service(device, service_uuid).await?.characteristics().await? {
+        if characteristic.uuid().await? == uuid {
+            return Ok(characteristic);
+        }
+    }
+    Err(Error::new(ErrorKind::NotFound))
+}
+
+/// Reads the Device Information service of a remote device.
+///
+/// Characteristics that cannot be read or decoded are skipped and left as [None].
+///
+/// Fails with [ErrorKind::NotFound] if the device does not provide the service.
+pub async fn read_device_information(device: &Device) -> Result<DeviceInformation> {
+    let mut info = DeviceInformation::default();
+    for characteristic in find_service(device, DEVICE_INFORMATION_UUID).await?.characteristics().await? {
+        let uuid = characteristic.uuid().await?;
+        if let Err(err) = characteristic.read().await.and_then(|value| info.set_field(uuid, &value)) {
+            log::warn!("Skipping device information characteristic {uuid}: {err}");
+        }
+    }
+    Ok(info)
+}
+
+/// Reads the battery level in percent from the Battery service of a remote device.
+///
+/// The Bluetooth daemon usually handles the Battery service itself and hides it from
+/// GATT clients.
+/// In this case this fails with [ErrorKind::NotFound] and
+/// [Device::battery_percentage] should be used instead.
+pub async fn read_battery_level(device: &Device) -> Result<u8> {
+    let characteristic = find_characteristic(device, BATTERY_UUID, BatteryLevel::UUID).await?;
+    Ok(characteristic.read_as::<BatteryLevel>().await?.0)
+}
+
+/// Streams battery level changes in percent from the Battery service of a remote device.
+///
+/// See [read_battery_level] for availability of the service.
+pub async fn notify_battery_level(device: &Device) -> Result<impl Stream<Item = Result<u8>>> {
+    let characteristic = find_characteristic(device, BATTERY_UUID, BatteryLevel::UUID).await?;
+    Ok(characteristic.notify_as::<BatteryLevel>().await?.map(|level| level.map(|level| level.0)))
+}
+
+/// Reads the current time from the Current Time service of a remote device.
+///
+/// Fails with [ErrorKind::NotFound] if the device does not provide the service.
+pub async fn read_current_time(device: &Device) -> Result<CurrentTime> {
+    find_characteristic(device, CURRENT_TIME_UUID, CurrentTime::UUID).await?.read_as().await
+}
+
+/// Streams time adjustments from the Current Time service of a remote device.
+///
+/// Fails with [ErrorKind::NotFound] if the device does not provide the service.
+pub async fn notify_current_time(device: &Device) -> Result<impl Stream<Item = Result<CurrentTime>>> {
+    find_characteristic(device, CURRENT_TIME_UUID, CurrentTime::UUID).await?.notify_as().await
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::{gatt::local::CharacteristicReadRequest, Address};
+
+    fn read(characteristic: &Characteristic, offset: u16) -> std::result::Result<Vec<u8>, ReqError> {
+        let req = CharacteristicReadRequest {
+            adapter_name: "hci0".to_string(),
+            device_address: Address::any(),
+            offset,
+            mtu: 23,
+            link: None,
+        };
+        block_on((characteristic.read.as_ref().unwrap().fun)(req))
+    }
+
+    #[test]
+    fn device_information() {
+        let info = DeviceInformation {
+            manufacturer_name: Some("BlueR".to_string()),
+            serial_number: Some("0001".to_string()),
+            system_id: Some(0x0011_2233_4455_6677),
+            pnp_id: Some(PnpId {
+                vendor_id_source: 2,
+                vendor_id: 0x1d6b,
+                product_id: 0x0246,
+                product_version: 1,
+            }),
+            ..Default::default()
+        };
+        let service = device_information_service(&info);
+        assert_eq!(service.uuid, DEVICE_INFORMATION_UUID);
+        assert!(service.primary);
+
+        let uuids: Vec<_> = service.characteristics.iter().map(|c| c.uuid).collect();
+        assert_eq!(uuids, [MANUFACTURER_NAME_UUID, SERIAL_NUMBER_UUID, SYSTEM_ID_UUID, PnpId::UUID]);
+        for characteristic in &service.characteristics {
+            assert!(characteristic.read.as_ref().unwrap().read);
+            assert!(characteristic.write.is_none());
+            assert!(characteristic.notify.is_none());
+        }
+
+        assert_eq!(read(&service.characteristics[0], 0).unwrap(), b"BlueR");
+        assert_eq!(read(&service.characteristics[0], 3).unwrap(), b"eR");
+        assert_eq!(read(&service.characteristics[0], 5).unwrap(), b"");
+        assert!(matches!(read(&service.characteristics[0], 6), Err(ReqError::InvalidOffset)));
+        assert_eq!(
+            read(&service.characteristics[2], 0).unwrap(),
+            [0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00]
+        );
+        assert_eq!(read(&service.characteristics[3], 0).unwrap(), [0x02, 0x6b, 0x1d, 0x46, 0x02, 0x01, 0x00]);
+
+        let mut decoded = DeviceInformation::default();
+        for (uuid, value) in info.fields() {
+            decoded.set_field(uuid, &value).unwrap();
+        }
+        assert_eq!(decoded, info);
+    }
+
+    #[test]
+    fn device_information_fields() {
+        let mut info = DeviceInformation::default();
+        info.set_field(SYSTEM_ID_UUID, &[0xef, 0xcd, 0xab, 0x89, 0x67, 0x45, 0x23, 0x01]).unwrap();
+        assert_eq!(info.system_id, Some(0x0123_4567_89ab_cdef));
+        info.set_field(PnpId::UUID, &[0x01, 0x4c, 0x00, 0x34, 0x12, 0x00, 0x02]).unwrap();
+        assert_eq!(
+            info.pnp_id,
+            Some(PnpId { vendor_id_source: 1, vendor_id: 0x004c, product_id: 0x1234, product_version: 0x0200 })
+        );
+        info.set_field(FIRMWARE_REVISION_UUID, b"1.2.3").unwrap();
+        assert_eq!(info.firmware_revision.as_deref(), Some("1.2.3"));
+
+        // Unknown characteristics are ignored.
+        info.set_field(BatteryLevel::UUID, &[0x64]).unwrap();
+
+        assert_eq!(info.set_field(SYSTEM_ID_UUID, &[0x01, 0x02]).unwrap_err().kind, ErrorKind::InvalidLength);
+        assert!(info.set_field(PnpId::UUID, &[0x01; 6]).is_err());
+        assert!(info.set_field(MODEL_NUMBER_UUID, &[0xff, 0xfe]).is_err());
+        assert_eq!(info.system_id, Some(0x0123_4567_89ab_cdef));
+        assert_eq!(info.model_number, None);
+    }
+
+    #[test]
+    fn battery() {
+        let (service, handle) = battery_service(150);
+        assert_eq!(service.uuid, BATTERY_UUID);
+        assert!(service.primary);
+        assert_eq!(service.characteristics.len(), 1);
+        let characteristic = &service.characteristics[0];
+        assert_eq!(characteristic.uuid, BatteryLevel::UUID);
+        assert!(characteristic.notify.as_ref().unwrap().notify);
+
+        assert_eq!(handle.level(), 100);
+        assert_eq!(read(characteristic, 0).unwrap(), [100]);
+        handle.set_level(42);
+        assert_eq!(read(characteristic, 0).unwrap(), [42]);
+        assert!(matches!(read(characteristic, 2), Err(ReqError::InvalidOffset)));
+    }
+
+    #[test]
+    fn current_time() {
+        let (service, handle) = current_time_service();
+        assert_eq!(service.uuid, CURRENT_TIME_UUID);
+        assert!(service.primary);
+        assert_eq!(service.characteristics.len(), 1);
+        let characteristic = &service.characteristics[0];
+        assert_eq!(characteristic.uuid, CurrentTime::UUID);
+        assert!(characteristic.notify.as_ref().unwrap().notify);
+
+        handle.adjusted(CurrentTime::ADJUST_MANUAL);
+        let current_time = CurrentTime::from_bytes(&read(characteristic, 0).unwrap()).unwrap();
+        assert_eq!(current_time.adjust_reason, 0);
+        assert!((1..=7).contains(&current_time.day_of_week));
+    }
+}
@@ -0,0 +1,1196 @@
+//! Typed GATT characteristic values.
+//!
+//! This module implements the formats and units of the
+//! [Characteristic Presentation Format descriptor](PresentationFormat)
+//! and the [Value] trait for encoding and decoding typed characteristic values,
+//! including decoders for common characteristics defined by the Bluetooth SIG.
+//!
+//! Use [Characteristic::read_as](super::remote::Characteristic::read_as) and
+//! [Characteristic::write_as](super::remote::Characteristic::write_as) to
+//! access a remote characteristic using a typed value.
+
+use std::{
+    fmt,
+    ops::RangeInclusive,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use uuid::Uuid;
+
+use crate::{Error, ErrorKind, InternalErrorKind, Result, UuidExt};
+
+/// UUID of the Characteristic Presentation Format descriptor.
+pub const PRESENTATION_FORMAT_UUID: Uuid = Uuid::from_u128(0x00002904_0000_1000_8000_00805f9b34fb);
+
+fn invalid_length() -> Error {
+    Error::new(ErrorKind::InvalidLength)
+}
+
+fn invalid_value() -> Error {
+    Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))
+}
+
+fn fixed<const N: usize>(data: &[u8]) -> Result<[u8; N]> {
+    data.try_into().map_err(|_| invalid_length())
+}
+
+/// Format of a characteristic value.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Format {
+    /// Unsigned 1-bit; 0 = false, 1 = true.
+    Boolean,
+    /// Unsigned 2-bit integer.
+    UInt2,
+    /// Unsigned 4-bit integer.
+    UInt4,
+    /// Unsigned 8-bit integer.
+    UInt8,
+    /// Unsigned 12-bit integer.
+    UInt12,
+    /// Unsigned 16-bit integer.
+    UInt16,
+    /// Unsigned 24-bit integer.
+    UInt24,
+    /// Unsigned 32-bit integer.
+    UInt32,
+    /// Unsigned 48-bit integer.
+    UInt48,
+    /// Unsigned 64-bit integer.
+    UInt64,
+    /// Unsigned 128-bit integer.
+    UInt128,
+    /// Signed 8-bit integer.
+    SInt8,
+    /// Signed 12-bit integer.
+    SInt12,
+    /// Signed 16-bit integer.
+    SInt16,
+    /// Signed 24-bit integer.
+    SInt24,
+    /// Signed 32-bit integer.
+    SInt32,
+    /// Signed 48-bit integer.
+    SInt48,
+    /// Signed 64-bit integer.
+    SInt64,
+    /// Signed 128-bit integer.
+    SInt128,
+    /// IEEE-754 32-bit floating point.
+    Float32,
+    /// IEEE-754 64-bit floating point.
+    Float64,
+    /// IEEE-11073 16-bit SFLOAT.
+    MedFloat16,
+    /// IEEE-11073 32-bit FLOAT.
+    MedFloat32,
+    /// IEEE-20601 format, two unsigned 16-bit integers.
+    DUInt16,
+    /// UTF-8 string.
+    Utf8,
+    /// UTF-16 string.
+    Utf16,
+    /// Opaque structure.
+    Struct,
+    /// IEEE-11073 ASN.1 encoded medical data.
+    MedAsn1,
+    /// Unknown format.
+    Unknown(u8),
+}
+
+impl From<u8> for Format {
+    fn from(value: u8) -> Self {
+        match value {
+            0x01 => Self::Boolean,
+            0x02 => Self::UInt2,
+            0x03 => Self::UInt4,
+            0x04 => Self::UInt8,
+            0x05 => Self::UInt12,
+            0x06 => Self::UInt16,
+            0x07 => Self::UInt24,
+            0x08 => Self::UInt32,
+            0x09 => Self::UInt48,
+            0x0a => Self::UInt64,
+            0x0b => Self::UInt128,
+            0x0c => Self::SInt8,
+            0x0d => Self::SInt12,
+            0x0e => Self::SInt16,
+            0x0f => Self::SInt24,
+            0x10 => Self::SInt32,
+            0x11 => Self::SInt48,
+            0x12 => Self::SInt64,
+            0x13 => Self::SInt128,
+            0x14 => Self::Float32,
+            0x15 => Self::Float64,
+            0x16 => Self::MedFloat16,
+            0x17 => Self::MedFloat32,
+            0x18 => Self::DUInt16,
+            0x19 => Self::Utf8,
+            0x1a => Self::Utf16,
+            0x1b => Self::Struct,
+            0x1c => Self::MedAsn1,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<Format> for u8 {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Boolean => 0x01,
+            Format::UInt2 => 0x02,
+            Format::UInt4 => 0x03,
+            Format::UInt8 => 0x04,
+            Format::UInt12 => 0x05,
+            Format::UInt16 => 0x06,
+            Format::UInt24 => 0x07,
+            Format::UInt32 => 0x08,
+            Format::UInt48 => 0x09,
+            Format::UInt64 => 0x0a,
+            Format::UInt128 => 0x0b,
+            Format::SInt8 => 0x0c,
+            Format::SInt12 => 0x0d,
+            Format::SInt16 => 0x0e,
+            Format::SInt24 => 0x0f,
+            Format::SInt32 => 0x10,
+            Format::SInt48 => 0x11,
+            Format::SInt64 => 0x12,
+            Format::SInt128 => 0x13,
+            Format::Float32 => 0x14,
+            Format::Float64 => 0x15,
+            Format::MedFloat16 => 0x16,
+            Format::MedFloat32 => 0x17,
+            Format::DUInt16 => 0x18,
+            Format::Utf8 => 0x19,
+            Format::Utf16 => 0x1a,
+            Format::Struct => 0x1b,
+            Format::MedAsn1 => 0x1c,
+            Format::Unknown(other) => other,
+        }
+    }
+}
+
+impl Format {
+    /// Size of an unsigned integer format in bytes and its bit width.
+    fn unsigned(&self) -> Option<(usize, u32)> {
+        Some(match self {
+            Self::UInt2 => (1, 2),
+            Self::UInt4 => (1, 4),
+            Self::UInt8 => (1, 8),
+            Self::UInt12 => (2, 12),
+            Self::UInt16 => (2, 16),
+            Self::UInt24 => (3, 24),
+            Self::UInt32 => (4, 32),
+            Self::UInt48 => (6, 48),
+            Self::UInt64 => (8, 64),
+            Self::UInt128 => (16, 128),
+            _ => return None,
+        })
+    }
+
+    /// Size of a signed integer format in bytes and its bit width.
+    fn signed(&self) -> Option<(usize, u32)> {
+        Some(match self {
+            Self::SInt8 => (1, 8),
+            Self::SInt12 => (2, 12),
+            Self::SInt16 => (2, 16),
+            Self::SInt24 => (3, 24),
+            Self::SInt32 => (4, 32),
+            Self::SInt48 => (6, 48),
+            Self::SInt64 => (8, 64),
+            Self::SInt128 => (16, 128),
+            _ => return None,
+        })
+    }
+}
+
+/// Unit of a characteristic value.
+///
+/// Units are 16-bit UUIDs assigned by the Bluetooth SIG.
+/// Commonly used units are provided as associated constants.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Unit(pub u16);
+
+impl Unit {
+    /// Unitless.
+    pub const UNITLESS: Self = Self(0x2700);
+    /// Length in metres.
+    pub const METRE: Self = Self(0x2701);
+    /// Mass in kilograms.
+    pub const KILOGRAM: Self = Self(0x2702);
+    /// Time in seconds.
+    pub const SECOND: Self = Self(0x2703);
+    /// Electric current in amperes.
+    pub const AMPERE: Self = Self(0x2704);
+    /// Thermodynamic temperature in kelvin.
+    pub const KELVIN: Self = Self(0x2705);
+    /// Luminous intensity in candelas.
+    pub const CANDELA: Self = Self(0x2707);
+    /// Velocity in metres per second.
+    pub const METRES_PER_SECOND: Self = Self(0x2711);
+    /// Frequency in hertz.
+    pub const HERTZ: Self = Self(0x2722);
+    /// Force in newtons.
+    pub const NEWTON: Self = Self(0x2723);
+    /// Pressure in pascals.
+    pub const PASCAL: Self = Self(0x2724);
+    /// Energy in joules.
+    pub const JOULE: Self = Self(0x2725);
+    /// Power in watts.
+    pub const WATT: Self = Self(0x2726);
+    /// Electric potential difference in volts.
+    pub const VOLT: Self = Self(0x2728);
+    /// Celsius temperature in degrees Celsius.
+    pub const DEGREE_CELSIUS: Self = Self(0x272f);
+    /// Illuminance in lux.
+    pub const LUX: Self = Self(0x2731);
+    /// Time in minutes.
+    pub const MINUTE: Self = Self(0x2760);
+    /// Time in hours.
+    pub const HOUR: Self = Self(0x2761);
+    /// Time in days.
+    pub const DAY: Self = Self(0x2762);
+    /// Plane angle in degrees.
+    pub const DEGREE: Self = Self(0x2763);
+    /// Pressure in bars.
+    pub const BAR: Self = Self(0x2780);
+    /// Pressure in millimetres of mercury.
+    pub const MILLIMETRE_OF_MERCURY: Self = Self(0x2781);
+    /// Beats per minute.
+    pub const BEATS_PER_MINUTE: Self = Self(0x27a7);
+    /// Thermodynamic temperature in degrees Fahrenheit.
+    pub const DEGREE_FAHRENHEIT: Self = Self(0x27ac);
+    /// Percentage.
+    pub const PERCENTAGE: Self = Self(0x27ad);
+    /// Electric charge in ampere hours.
+    pub const AMPERE_HOURS: Self = Self(0x27b0);
+    /// Concentration in parts per million.
+    pub const PARTS_PER_MILLION: Self = Self(0x27c4);
+    /// Concentration in parts per billion.
+    pub const PARTS_PER_BILLION: Self = Self(0x27c5);
+
+    /// UUID of the unit.
+    pub fn uuid(&self) -> Uuid {
+        Uuid::from_u16(self.0)
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04x}", self.0)
+    }
+}
+
+/// Contents of a Characteristic Presentation Format descriptor.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct PresentationFormat {
+    /// Format of the value.
+    pub format: Format,
+    /// Base 10 exponent of integer values.
+    ///
+    /// The actual value is the integer value multiplied by `10^exponent`.
+    pub exponent: i8,
+    /// Unit of the value.
+    pub unit: Unit,
+    /// Name space of the description.
+    pub namespace: u8,
+    /// Description.
+    pub description: u16,
+}
+
+impl PresentationFormat {
+    /// Name space of descriptions assigned by the Bluetooth SIG.
+    pub const NAMESPACE_BLUETOOTH_SIG: u8 = 0x01;
+
+    /// Creates a presentation format with the specified format, exponent and unit.
+    pub fn new(format: Format, exponent: i8, unit: Unit) -> Self {
+        Self { format, exponent, unit, namespace: Self::NAMESPACE_BLUETOOTH_SIG, description: 0 }
+    }
+
+    /// Decodes the contents of a Characteristic Presentation Format descriptor.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let data: [u8; 7] = fixed(data)?;
+        Ok(Self {
+            format: data[0].into(),
+            exponent: data[1] as i8,
+            unit: Unit(u16::from_le_bytes([data[2], data[3]])),
+            namespace: data[4],
+            description: u16::from_le_bytes([data[5], data[6]]),
+        })
+    }
+
+    /// Encodes the contents of a Characteristic Presentation Format descriptor.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![self.format.into(), self.exponent as u8];
+        buf.extend_from_slice(&self.unit.0.to_le_bytes());
+        buf.push(self.namespace);
+        buf.extend_from_slice(&self.description.to_le_bytes());
+        buf
+    }
+
+    /// Decodes a characteristic value according to this presentation format.
+    ///
+    /// The exponent is not applied, use [scaled](Self::scaled) for that.
+    pub fn decode(&self, data: &[u8]) -> Result<FormattedValue> {
+        if let Some((len, bits)) = self.format.unsigned() {
+            if data.len() != len {
+                return Err(invalid_length());
+            }
+            let mut buf = [0; 16];
+            buf[..len].copy_from_slice(data);
+            let value = u128::from_le_bytes(buf);
+            return Ok(FormattedValue::Unsigned(if bits < 128 { value & ((1 << bits) - 1) } else { value }));
+        }
+        if let Some((len, bits)) = self.format.signed() {
+            if data.len() != len {
+                return Err(invalid_length());
+            }
+            let mut buf = [0; 16];
+            buf[..len].copy_from_slice(data);
+            let shift = 128 - bits;
+            return Ok(FormattedValue::Signed((i128::from_le_bytes(buf) << shift) >> shift));
+        }
+        Ok(match self.format {
+            Format::Boolean => match data {
+                [v] => FormattedValue::Boolean(*v & 0x01 != 0),
+                _ => return Err(invalid_length()),
+            },
+            Format::Float32 => FormattedValue::Float(f32::from_le_bytes(fixed(data)?).into()),
+            Format::Float64 => FormattedValue::Float(f64::from_le_bytes(fixed(data)?)),
+            Format::MedFloat16 => FormattedValue::Float(MedFloat16::from_bytes(data)?.0),
+            Format::MedFloat32 => FormattedValue::Float(MedFloat32::from_bytes(data)?.0),
+            Format::DUInt16 => {
+                let data: [u8; 4] = fixed(data)?;
+                FormattedValue::DUInt16(
+                    u16::from_le_bytes([data[0], data[1]]),
+                    u16::from_le_bytes([data[2], data[3]]),
+                )
+            }
+            Format::Utf8 => FormattedValue::Text(String::from_bytes(data)?),
+            Format::Utf16 => {
+                if data.len() % 2 != 0 {
+                    return Err(invalid_length());
+                }
+                let units: Vec<u16> = data.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+                FormattedValue::Text(String::from_utf16(&units).map_err(|_| invalid_value())?)
+            }
+            _ => FormattedValue::Opaque(data.to_vec()),
+        })
+    }
+
+    /// Encodes a characteristic value according to this presentation format.
+    ///
+    /// Fails if the value does not match the format or is out of range.
+    pub fn encode(&self, value: &FormattedValue) -> Result<Vec<u8>> {
+        match (self.format, value) {
+            (format, FormattedValue::Unsigned(v)) if format.unsigned().is_some() => {
+                let (len, bits) = format.unsigned().unwrap();
+                if bits < 128 && *v >> bits != 0 {
+                    return Err(invalid_value());
+                }
+                Ok(v.to_le_bytes()[..len].to_vec())
+            }
+            (format, FormattedValue::Signed(v)) if format.signed().is_some() => {
+                let (len, bits) = format.signed().unwrap();
+                let range: RangeInclusive<i128> =
+                    if bits < 128 { -(1 << (bits - 1))..=(1 << (bits - 1)) - 1 } else { i128::MIN..=i128::MAX };
+                if !range.contains(v) {
+                    return Err(invalid_value());
+                }
+                Ok(v.to_le_bytes()[..len].to_vec())
+            }
+            (Format::Boolean, FormattedValue::Boolean(v)) => Ok(vec![u8::from(*v)]),
+            (Format::Float32, FormattedValue::Float(v)) => Ok((*v as f32).to_le_bytes().to_vec()),
+            (Format::Float64, FormattedValue::Float(v)) => Ok(v.to_le_bytes().to_vec()),
+            (Format::MedFloat16, FormattedValue::Float(v)) => Ok(MedFloat16(*v).to_bytes()),
+            (Format::MedFloat32, FormattedValue::Float(v)) => Ok(MedFloat32(*v).to_bytes()),
+            (Format::DUInt16, FormattedValue::DUInt16(a, b)) => {
+                Ok(a.to_le_bytes().into_iter().chain(b.to_le_bytes()).collect())
+            }
+            (Format::Utf8, FormattedValue::Text(s)) => Ok(s.as_bytes().to_vec()),
+            (Format::Utf16, FormattedValue::Text(s)) => Ok(s.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+            (Format::Struct | Format::MedAsn1 | Format::Unknown(_), FormattedValue::Opaque(data)) => {
+                Ok(data.clone())
+            }
+            _ => Err(invalid_value()),
+        }
+    }
+
+    /// Decodes a numeric characteristic value and applies the exponent.
+    ///
+    /// Fails if the format is not numeric.
+    pub fn scaled(&self, data: &[u8]) -> Result<f64> {
+        let value = match self.decode(data)? {
+            FormattedValue::Unsigned(v) => v as f64,
+            FormattedValue::Signed(v) => v as f64,
+            FormattedValue::Float(v) => return Ok(v),
+            _ => return Err(invalid_value()),
+        };
+        Ok(value * 10f64.powi(self.exponent.into()))
+    }
+}
+
+/// Characteristic value decoded according to a [PresentationFormat].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FormattedValue {
+    /// Boolean value.
+    Boolean(bool),
+    /// Unsigned integer value.
+    Unsigned(u128),
+    /// Signed integer value.
+    Signed(i128),
+    /// Floating point value.
+    Float(f64),
+    /// Two unsigned 16-bit integers.
+    DUInt16(u16, u16),
+    /// String value.
+    Text(String),
+    /// Opaque data.
+    Opaque(Vec<u8>),
+}
+
+/// A typed characteristic value.
+pub trait Value: Sized {
+    /// Decodes the value from the characteristic value.
+    fn from_bytes(data: &[u8]) -> Result<Self>;
+
+    /// Encodes the value into the characteristic value.
+    fn to_bytes(&self) -> Vec<u8>;
+}
+
+macro_rules! impl_value_le {
+    ($($t:ty),*) => {
+        $(
+            impl Value for $t {
+                fn from_bytes(data: &[u8]) -> Result<Self> {
+                    Ok(Self::from_le_bytes(fixed(data)?))
+                }
+
+                fn to_bytes(&self) -> Vec<u8> {
+                    self.to_le_bytes().to_vec()
+                }
+            }
+        )*
+    };
+}
+
+impl_value_le!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl Value for bool {
+    fn from_bytes(data: &[u8]) -> Result<Self> {
+        Ok(u8::from_bytes(data)? & 0x01 != 0)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        vec![u8::from(*self)]
+    }
+}
+
+impl Value for String {
+    fn from_bytes(data: &[u8]) -> Result<Self> {
+        String::from_utf8(data.to_vec()).map_err(|_| invalid_value())
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+}
+
+impl Value for Vec<u8> {
+    fn from_bytes(data: &[u8]) -> Result<Self> {
+        Ok(data.to_vec())
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.clone()
+    }
+}
+
+macro_rules! impl_value_int {
+    ($name:ident, $t:ty, $len:expr, $doc:literal) => {
+        #[doc = $doc]
+        ///
+        /// Encoding truncates the value to the size of the format.
+        #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct $name(pub $t);
+
+        impl Value for $name {
+            fn from_bytes(data: &[u8]) -> Result<Self> {
+                const SHIFT: u32 = <$t>::BITS - $len * 8;
+                let data: [u8; $len] = fixed(data)?;
+                let mut buf = [0; std::mem::size_of::<$t>()];
+                buf[..$len].copy_from_slice(&data);
+                Ok(Self((<$t>::from_le_bytes(buf) << SHIFT) >> SHIFT))
+            }
+
+            fn to_bytes(&self) -> Vec<u8> {
+                self.0.to_le_bytes()[..$len].to_vec()
+            }
+        }
+    };
+}
+
+impl_value_int!(U24, u32, 3, "Unsigned 24-bit integer.");
+impl_value_int!(I24, i32, 3, "Signed 24-bit integer.");
+impl_value_int!(U48, u64, 6, "Unsigned 48-bit integer.");
+impl_value_int!(I48, i64, 6, "Signed 48-bit integer.");
+
+/// Decodes an IEEE-11073 floating point number.
+fn decode_medfloat(mantissa: i32, exponent: i32, max: i32) -> f64 {
+    match mantissa {
+        _ if exponent != 0 => {}
+        m if m == max + 2 => return f64::NAN,
+        m if m == -(max + 3) => return f64::NAN,
+        m if m == max + 1 => return f64::INFINITY,
+        m if m == -(max + 1) => return f64::NEG_INFINITY,
+        m if m == -(max + 2) => return f64::NAN,
+        _ => {}
+    }
+    f64::from(mantissa) * 10f64.powi(exponent)
+}
+
+/// Encodes an IEEE-11073 floating point number into mantissa and exponent,
+/// using the smallest exponent that can represent the value.
+fn encode_medfloat(value: f64, max: i32, exponents: RangeInclusive<i32>) -> (i32, i32) {
+    if value.is_nan() {
+        return (max + 2, 0);
+    }
+    let max_exponent = *exponents.end();
+    for exponent in exponents {
+        let mantissa = (value / 10f64.powi(exponent)).round();
+        if mantissa.abs() <= f64::from(max) {
+            let (mut mantissa, mut exponent) = (mantissa as i32, exponent);
+            if mantissa == 0 {
+                return (0, 0);
+            }
+            while mantissa % 10 == 0 && exponent < max_exponent {
+                mantissa /= 10;
+                exponent += 1;
+            }
+            return (mantissa, exponent);
+        }
+    }
+    if value > 0. {
+        (max + 1, 0)
+    } else {
+        (-(max + 1), 0)
+    }
+}
+
+/// IEEE-11073 16-bit SFLOAT.
+///
+/// Special values are mapped to NaN and infinity.
+/// Values that are out of range are encoded as infinity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MedFloat16(pub f64);
+
+impl MedFloat16 {
+    const MAX: i32 = 0x07fd;
+}
+
+impl Value for MedFloat16 {
+    fn from_bytes(data: &[u8]) -> Result<Self> {
+        let raw = u16::from_bytes(data)?;
+        let mantissa = i32::from(((raw << 4) as i16) >> 4);
+        let exponent = i32::from((raw as i16) >> 12);
+        Ok(Self(decode_medfloat(mantissa, exponent, Self::MAX)))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let (mantissa, exponent) = encode_medfloat(self.0, Self::MAX, -8..=7);
+        let raw = ((exponent as u16) << 12) | (mantissa as u16 & 0x0fff);
+        raw.to_le_bytes().to_vec()
+    }
+}
+
+/// IEEE-11073 32-bit FLOAT.
+///
+/// Special values are mapped to NaN and infinity.
+/// Values that are out of range are encoded as infinity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MedFloat32(pub f64);
+
+impl MedFloat32 {
+    const MAX: i32 = 0x007f_fffd;
+}
+
+impl Value for MedFloat32 {
+    fn from_bytes(data: &[u8]) -> Result<Self> {
+        let raw = u32::from_bytes(data)?;
+        let mantissa = ((raw << 8) as i32) >> 8;
+        let exponent = (raw as i32) >> 24;
+        Ok(Self(decode_medfloat(mantissa, exponent, Self::MAX)))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let (mantissa, exponent) = encode_medfloat(self.0, Self::MAX, -128..=127);
+        let raw = ((exponent as u32) << 24) | (mantissa as u32 & 0x00ff_ffff);
+        raw.to_le_bytes().to_vec()
+    }
+}
+
+/// Battery Level characteristic.
+///
+/// Contains the battery charge in percent.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatteryLevel(pub u8);
+
+impl BatteryLevel {
+    /// UUID of the characteristic.
+    pub const UUID: Uuid = Uuid::from_u128(0x00002a19_0000_1000_8000_00805f9b34fb);
+}
+
+impl Value for BatteryLevel {
+    fn from_bytes(data: &[u8]) -> Result<Self> {
+        match u8::from_bytes(data)? {
+            level @ 0..=100 => Ok(Self(level)),
+            _ => Err(invalid_value()),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        vec![self.0]
+    }
+}
+
+/// Temperature characteristic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Temperature {
+    /// Temperature in degrees Celsius with a resolution of 0.01 degrees.
+    ///
+    /// [None] if the temperature is not known.
+    pub celsius: Option<f64>,
+}
+
+impl Temperature {
+    /// UUID of the characteristic.
+    pub const UUID: Uuid = Uuid::from_u128(0x00002a6e_0000_1000_8000_00805f9b34fb);
+
+    const UNKNOWN: i16 = i16::MIN;
+}
+
+impl Value for Temperature {
+    fn from_bytes(data: &[u8]) -> Result<Self> {
+        let raw = i16::from_bytes(data)?;
+        Ok(Self { celsius: (raw != Self::UNKNOWN).then(|| f64::from(raw) / 100.) })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let raw = match self.celsius {
+            Some(celsius) => (celsius * 100.).round().clamp(-27315., f64::from(i16::MAX)) as i16,
+            None => Self::UNKNOWN,
+        };
+        raw.to_le_bytes().to_vec()
+    }
+}
+
+/// Heart Rate Measurement characteristic.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeartRateMeasurement {
+    /// Heart rate in beats per minute.
+    pub heart_rate: u16,
+    /// Whether skin contact is detected.
+    ///
+    /// [None] if the sensor does not support contact detection.
+    pub sensor_contact: Option<bool>,
+    /// Accumulated energy expended in kilojoules.
+    pub energy_expended: Option<u16>,
+    /// RR-intervals in units of 1/1024 seconds, oldest first.
+    pub rr_intervals: Vec<u16>,
+}
+
+impl HeartRateMeasurement {
+    /// UUID of the characteristic.
+    pub const UUID: Uuid = Uuid::from_u128(0x00002a37_0000_1000_8000_00805f9b34fb);
+
+    const HEART_RATE_U16: u8 = 0x01;
+    const SENSOR_CONTACT_DETECTED: u8 = 0x02;
+    const SENSOR_CONTACT_SUPPORTED: u8 = 0x04;
+    const ENERGY_EXPENDED: u8 = 0x08;
+    const RR_INTERVALS: u8 = 0x10;
+}
+
+impl Value for HeartRateMeasurement {
+    fn from_bytes(data: &[u8]) -> Result<Self> {
+        let (&flags, mut data) = data.split_first().ok_or_else(invalid_length)?;
+        let heart_rate = if flags & Self::HEART_RATE_U16 != 0 {
+            u16::from_bytes(take(&mut data, 2)?)?
+        } else {
+            u8::from_bytes(take(&mut data, 1)?)?.into()
+        };
+        let sensor_contact =
+            (flags & Self::SENSOR_CONTACT_SUPPORTED != 0).then_some(flags & Self::SENSOR_CONTACT_DETECTED != 0);
+        let energy_expended =
+            if flags & Self::ENERGY_EXPENDED != 0 { Some(u16::from_bytes(take(&mut data, 2)?)?) } else { None };
+        let rr_intervals = if flags & Self::RR_INTERVALS != 0 {
+            if data.len() % 2 != 0 {
+                return Err(invalid_length());
+            }
+            data.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self { heart_rate, sensor_contact, energy_expended, rr_intervals })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut flags = 0;
+        let mut buf = vec![0];
+        match u8::try_from(self.heart_rate) {
+            Ok(heart_rate) => buf.push(heart_rate),
+            Err(_) => {
+                flags |= Self::HEART_RATE_U16;
+                buf.extend_from_slice(&self.heart_rate.to_le_bytes());
+            }
+        }
+        match self.sensor_contact {
+            Some(true) => flags |= Self::SENSOR_CONTACT_SUPPORTED | Self::SENSOR_CONTACT_DETECTED,
+            Some(false) => flags |= Self::SENSOR_CONTACT_SUPPORTED,
+            None => (),
+        }
+        if let Some(energy_expended) = self.energy_expended {
+            flags |= Self::ENERGY_EXPENDED;
+            buf.extend_from_slice(&energy_expended.to_le_bytes());
+        }
+        if !self.rr_intervals.is_empty() {
+            flags |= Self::RR_INTERVALS;
+            for rr in &self.rr_intervals {
+                buf.extend_from_slice(&rr.to_le_bytes());
+            }
+        }
+        buf[0] = flags;
+        buf
+    }
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if data.len() < len {
+        return Err(invalid_length());
+    }
+    let (head, tail) = data.split_at(len);
+    *data = tail;
+    Ok(head)
+}
+
+/// Date Time characteristic.
+///
+/// Fields that are zero are not known.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DateTime {
+    /// Year as defined by the Gregorian calendar, 1582 to 9999.
+    pub year: u16,
+    /// Month of the year, 1 to 12.
+    pub month: u8,
+    /// Day of the month, 1 to 31.
+    pub day: u8,
+    /// Hours past midnight, 0 to 23.
+    pub hours: u8,
+    /// Minutes since the start of the hour, 0 to 59.
+    pub minutes: u8,
+    /// Seconds since the start of the minute, 0 to 59.
+    pub seconds: u8,
+}
+
+impl DateTime {
+    /// UUID of the characteristic.
+    pub const UUID: Uuid = Uuid::from_u128(0x00002a08_0000_1000_8000_00805f9b34fb);
+}
+
+impl Value for DateTime {
+    fn from_bytes(data: &[u8]) -> Result<Self> {
+        let data: [u8; 7] = fixed(data)?;
+        let dt = Self {
+            year: u16::from_le_bytes([data[0], data[1]]),
+            month: data[2],
+            day: data[3],
+            hours: data[4],
+            minutes: data[5],
+            seconds: data[6],
+        };
+        if dt.month > 12 || dt.day > 31 || dt.hours > 23 || dt.minutes > 59 || dt.seconds > 59 {
+            return Err(invalid_value());
+        }
+        Ok(dt)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = self.year.to_le_bytes().to_vec();
+        buf.extend_from_slice(&[self.month, self.day, self.hours, self.minutes, self.seconds]);
+        buf
+    }
+}
+
+/// Current Time characteristic.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CurrentTime {
+    /// Local date and time.
+    pub date_time: DateTime,
+    /// Day of the week, 1 (Monday) to 7 (Sunday) or 0 if not known.
+    pub day_of_week: u8,
+    /// Fractions of a second in units of 1/256 seconds.
+    pub fractions256: u8,
+    /// Reason for the last adjustment of the time.
+    ///
+    /// Bitwise combination of the `ADJUST_*` constants.
+    pub adjust_reason: u8,
+}
+
+impl CurrentTime {
+    /// UUID of the characteristic.
+    pub const UUID: Uuid = Uuid::from_u128(0x00002a2b_0000_1000_8000_00805f9b34fb);
+
+    /// Time was set manually.
+    pub const ADJUST_MANUAL: u8 = 0x01;
+    /// Time was updated from an external reference.
+    pub const ADJUST_EXTERNAL_REFERENCE: u8 = 0x02;
+    /// Time zone has changed.
+    pub const ADJUST_TIME_ZONE: u8 = 0x04;
+    /// Daylight saving time has changed.
+    pub const ADJUST_DST: u8 = 0x08;
+
+    /// Current local time of the system.
+    pub fn now() -> Self {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let secs = now.as_secs() as libc::time_t;
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        if unsafe { libc::localtime_r(&secs, &mut tm) }.is_null() {
+            return Self::default();
+        }
+        Self {
+            date_time: DateTime {
+                year: (tm.tm_year + 1900) as u16,
+                month: (tm.tm_mon + 1) as u8,
+                day: tm.tm_mday as u8,
+                hours: tm.tm_hour as u8,
+                minutes: tm.tm_min as u8,
+                seconds: tm.tm_sec.min(59) as u8,
+            },
+            day_of_week: ((tm.tm_wday + 6) % 7 + 1) as u8,
+            fractions256: (u64::from(now.subsec_nanos()) * 256 / 1_000_000_000) as u8,
+            adjust_reason: 0,
+        }
+    }
+}
+
+impl Value for CurrentTime {
+    fn from_bytes(data: &[u8]) -> Result<Self> {
+        let data: [u8; 10] = fixed(data)?;
+        let day_of_week = data[7];
+        if day_of_week > 7 {
+            return Err(invalid_value());
+        }
+        Ok(Self {
+            date_time: DateTime::from_bytes(&data[..7])?,
+            day_of_week,
+            fractions256: data[8],
+            adjust_reason: data[9],
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = self.date_time.to_bytes();
+        buf.extend_from_slice(&[self.day_of_week, self.fractions256, self.adjust_reason]);
+        buf
+    }
+}
+
+/// PnP ID characteristic.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PnpId {
+    /// Source of the vendor id.
+    ///
+    /// 1 for a Bluetooth SIG assigned company identifier,
+    /// 2 for a USB Implementer's Forum assigned vendor id.
+    pub vendor_id_source: u8,
+    /// Vendor id.
+    pub vendor_id: u16,
+    /// Product id.
+    pub product_id: u16,
+    /// Product version.
+    pub product_version: u16,
+}
+
+impl PnpId {
+    /// UUID of the characteristic.
+    pub const UUID: Uuid = Uuid::from_u128(0x00002a50_0000_1000_8000_00805f9b34fb);
+}
+
+impl Value for PnpId {
+    fn from_bytes(data: &[u8]) -> Result<Self> {
+        let data: [u8; 7] = fixed(data)?;
+        Ok(Self {
+            vendor_id_source: data[0],
+            vendor_id: u16::from_le_bytes([data[1], data[2]]),
+            product_id: u16::from_le_bytes([data[3], data[4]]),
+            product_version: u16::from_le_bytes([data[5], data[6]]),
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![self.vendor_id_source];
+        buf.extend_from_slice(&self.vendor_id.to_le_bytes());
+        buf.extend_from_slice(&self.product_id.to_le_bytes());
+        buf.extend_from_slice(&self.product_version.to_le_bytes());
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(value: f64, expected: f64) {
+        assert!((value - expected).abs() < 1e-9, "{value} != {expected}");
+    }
+
+    fn kind<T: fmt::Debug>(result: Result<T>) -> ErrorKind {
+        result.unwrap_err().kind
+    }
+
+    #[test]
+    fn presentation_format() {
+        // Temperature in degrees Celsius as a signed 16-bit integer with exponent -2.
+        let data = [0x0e, 0xfe, 0x2f, 0x27, 0x01, 0x00, 0x00];
+        let pf = PresentationFormat::from_bytes(&data).unwrap();
+        assert_eq!(pf, PresentationFormat::new(Format::SInt16, -2, Unit::DEGREE_CELSIUS));
+        assert_eq!(pf.to_bytes(), data);
+        assert_eq!(pf.decode(&[0x3a, 0x09]).unwrap(), FormattedValue::Signed(2362));
+        assert_close(pf.scaled(&[0x3a, 0x09]).unwrap(), 23.62);
+        assert_eq!(pf.encode(&FormattedValue::Signed(-1)).unwrap(), [0xff, 0xff]);
+        assert_eq!(kind(pf.decode(&[0x3a])), ErrorKind::InvalidLength);
+
+        let pf = PresentationFormat::from_bytes(&[0x1d, 0x00, 0x00, 0x27, 0x02, 0x34, 0x12]).unwrap();
+        assert_eq!(pf.format, Format::Unknown(0x1d));
+        assert_eq!(pf.namespace, 0x02);
+        assert_eq!(pf.description, 0x1234);
+        assert_eq!(pf.decode(&[0x01, 0x02]).unwrap(), FormattedValue::Opaque(vec![0x01, 0x02]));
+
+        assert_eq!(kind(PresentationFormat::from_bytes(&data[..6])), ErrorKind::InvalidLength);
+        assert_eq!(kind(PresentationFormat::from_bytes(&[0; 8])), ErrorKind::InvalidLength);
+
+        for format in 0..=0xffu8 {
+            assert_eq!(u8::from(Format::from(format)), format);
+        }
+    }
+
+    #[test]
+    fn presentation_format_integers() {
+        let pf = |format| PresentationFormat::new(format, 0, Unit::UNITLESS);
+
+        assert_eq!(pf(Format::UInt12).decode(&[0xff, 0xff]).unwrap(), FormattedValue::Unsigned(0x0fff));
+        assert_eq!(pf(Format::SInt12).decode(&[0xff, 0x0f]).unwrap(), FormattedValue::Signed(-1));
+        assert_eq!(pf(Format::SInt12).decode(&[0xff, 0x07]).unwrap(), FormattedValue::Signed(2047));
+        assert_eq!(pf(Format::SInt24).decode(&[0x00, 0x00, 0x80]).unwrap(), FormattedValue::Signed(-0x80_0000));
+        assert_eq!(pf(Format::UInt24).decode(&[0x01, 0x02, 0x03]).unwrap(), FormattedValue::Unsigned(0x03_0201));
+        assert_eq!(pf(Format::UInt48).decode(&[0xff; 6]).unwrap(), FormattedValue::Unsigned(0xffff_ffff_ffff));
+        assert_eq!(pf(Format::SInt128).decode(&[0xff; 16]).unwrap(), FormattedValue::Signed(-1));
+        assert_eq!(kind(pf(Format::UInt24).decode(&[0x01, 0x02])), ErrorKind::InvalidLength);
+
+        assert_eq!(pf(Format::UInt12).encode(&FormattedValue::Unsigned(0x0fff)).unwrap(), [0xff, 0x0f]);
+        assert!(pf(Format::UInt12).encode(&FormattedValue::Unsigned(0x1000)).is_err());
+        assert_eq!(pf(Format::SInt8).encode(&FormattedValue::Signed(-128)).unwrap(), [0x80]);
+        assert!(pf(Format::SInt8).encode(&FormattedValue::Signed(128)).is_err());
+        assert!(pf(Format::SInt8).encode(&FormattedValue::Unsigned(1)).is_err());
+        assert_eq!(pf(Format::UInt128).encode(&FormattedValue::Unsigned(u128::MAX)).unwrap(), [0xff; 16]);
+    }
+
+    #[test]
+    fn presentation_format_other() {
+        let pf = |format| PresentationFormat::new(format, 0, Unit::UNITLESS);
+
+        assert_eq!(pf(Format::Boolean).decode(&[0x01]).unwrap(), FormattedValue::Boolean(true));
+        assert_eq!(pf(Format::Boolean).encode(&FormattedValue::Boolean(false)).unwrap(), [0x00]);
+        assert_eq!(kind(pf(Format::Boolean).decode(&[])), ErrorKind::InvalidLength);
+        assert_eq!(
+            pf(Format::DUInt16).decode(&[0x78, 0x00, 0x50, 0x00]).unwrap(),
+            FormattedValue::DUInt16(120, 80)
+        );
+        assert_eq!(pf(Format::Float32).decode(&[0x00, 0x00, 0xc0, 0x3f]).unwrap(), FormattedValue::Float(1.5));
+        assert_close(pf(Format::MedFloat16).scaled(&[0x6c, 0xf1]).unwrap(), 36.4);
+
+        let text = FormattedValue::Text("Hé".to_string());
+        assert_eq!(pf(Format::Utf8).encode(&text).unwrap(), [0x48, 0xc3, 0xa9]);
+        assert_eq!(pf(Format::Utf16).encode(&text).unwrap(), [0x48, 0x00, 0xe9, 0x00]);
+        assert_eq!(pf(Format::Utf16).decode(&[0x48, 0x00, 0xe9, 0x00]).unwrap(), text);
+        assert_eq!(kind(pf(Format::Utf16).decode(&[0x48, 0x00, 0xe9])), ErrorKind::InvalidLength);
+        assert!(pf(Format::Utf16).decode(&[0x00, 0xd8]).is_err());
+        assert!(pf(Format::Utf8).decode(&[0xc3]).is_err());
+        assert!(pf(Format::Utf8).scaled(b"1").is_err());
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(U24::from_bytes(&[0xff, 0xff, 0xff]).unwrap(), U24(0xff_ffff));
+        assert_eq!(I24::from_bytes(&[0xff, 0xff, 0xff]).unwrap(), I24(-1));
+        assert_eq!(I24::from_bytes(&[0x00, 0x00, 0x80]).unwrap(), I24(-0x80_0000));
+        assert_eq!(I24::from_bytes(&[0xff, 0xff, 0x7f]).unwrap(), I24(0x7f_ffff));
+        assert_eq!(I48::from_bytes(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x80]).unwrap(), I48(-(1 << 47)));
+        assert_eq!(U48::from_bytes(&[0x01, 0x02, 0x03, 0x04, 0x05, 0x06]).unwrap(), U48(0x0605_0403_0201));
+
+        assert_eq!(I24(-2).to_bytes(), [0xfe, 0xff, 0xff]);
+        assert_eq!(U24(0x0102_0304).to_bytes(), [0x04, 0x03, 0x02]);
+        assert_eq!(I48(-1).to_bytes(), [0xff; 6]);
+
+        assert_eq!(kind(U24::from_bytes(&[0x01, 0x02])), ErrorKind::InvalidLength);
+        assert_eq!(kind(I24::from_bytes(&[0x01, 0x02, 0x03, 0x04])), ErrorKind::InvalidLength);
+        assert_eq!(kind(u16::from_bytes(&[0x01])), ErrorKind::InvalidLength);
+        assert!(bool::from_bytes(&[0x01]).unwrap());
+    }
+
+    #[test]
+    fn medfloat16() {
+        // 36.4 = 364 * 10^-1.
+        assert_close(MedFloat16::from_bytes(&[0x6c, 0xf1]).unwrap().0, 36.4);
+        assert_eq!(MedFloat16(36.4).to_bytes(), [0x6c, 0xf1]);
+        // -12.5 = -125 * 10^-1.
+        assert_close(MedFloat16::from_bytes(&[0x83, 0xff]).unwrap().0, -12.5);
+        assert_eq!(MedFloat16(-12.5).to_bytes(), [0x83, 0xff]);
+        // 2045 * 10^2 is the largest value; 2.0e5 is stored as 2 * 10^5.
+        assert_close(MedFloat16::from_bytes(&[0xfd, 0x27]).unwrap().0, 204_500.);
+        assert_eq!(MedFloat16(200_000.).to_bytes(), [0x02, 0x50]);
+        assert_eq!(MedFloat16(0.).to_bytes(), [0x00, 0x00]);
+
+        // Special values: NaN, NRes, +INF, -INF and the reserved value.
+        assert!(MedFloat16::from_bytes(&[0xff, 0x07]).unwrap().0.is_nan());
+        assert!(MedFloat16::from_bytes(&[0x00, 0x08]).unwrap().0.is_nan());
+        assert_eq!(MedFloat16::from_bytes(&[0xfe, 0x07]).unwrap().0, f64::INFINITY);
+        assert_eq!(MedFloat16::from_bytes(&[0x02, 0x08]).unwrap().0, f64::NEG_INFINITY);
+        assert!(MedFloat16::from_bytes(&[0x01, 0x08]).unwrap().0.is_nan());
+        // The special mantissas only have a special meaning with exponent zero.
+        assert_close(MedFloat16::from_bytes(&[0xff, 0x17]).unwrap().0, 20_470.);
+
+        assert_eq!(MedFloat16(f64::NAN).to_bytes(), [0xff, 0x07]);
+        assert_eq!(MedFloat16(f64::INFINITY).to_bytes(), [0xfe, 0x07]);
+        assert_eq!(MedFloat16(-1e12).to_bytes(), [0x02, 0x08]);
+
+        assert_eq!(kind(MedFloat16::from_bytes(&[0x6c])), ErrorKind::InvalidLength);
+    }
+
+    #[test]
+    fn medfloat32() {
+        // 36.4 = 364 * 10^-1.
+        assert_close(MedFloat32::from_bytes(&[0x6c, 0x01, 0x00, 0xff]).unwrap().0, 36.4);
+        assert_eq!(MedFloat32(36.4).to_bytes(), [0x6c, 0x01, 0x00, 0xff]);
+        assert_close(MedFloat32::from_bytes(&[0x83, 0xff, 0xff, 0xfe]).unwrap().0, -1.25);
+        assert_eq!(MedFloat32(-1.25).to_bytes(), [0x83, 0xff, 0xff, 0xfe]);
+
+        // Special values: NaN, NRes, +INF, -INF and the reserved value.
+        assert!(MedFloat32::from_bytes(&[0xff, 0xff, 0x7f, 0x00]).unwrap().0.is_nan());
+        assert!(MedFloat32::from_bytes(&[0x00, 0x00, 0x80, 0x00]).unwrap().0.is_nan());
+        assert_eq!(MedFloat32::from_bytes(&[0xfe, 0xff, 0x7f, 0x00]).unwrap().0, f64::INFINITY);
+        assert_eq!(MedFloat32::from_bytes(&[0x02, 0x00, 0x80, 0x00]).unwrap().0, f64::NEG_INFINITY);
+        assert!(MedFloat32::from_bytes(&[0x01, 0x00, 0x80, 0x00]).unwrap().0.is_nan());
+
+        assert_eq!(MedFloat32(f64::NAN).to_bytes(), [0xff, 0xff, 0x7f, 0x00]);
+        assert_eq!(MedFloat32(f64::NEG_INFINITY).to_bytes(), [0x02, 0x00, 0x80, 0x00]);
+
+        assert_eq!(kind(MedFloat32::from_bytes(&[0x6c, 0x01, 0x00])), ErrorKind::InvalidLength);
+    }
+
+    #[test]
+    fn battery_level() {
+        assert_eq!(BatteryLevel::from_bytes(&[0x64]).unwrap(), BatteryLevel(100));
+        assert_eq!(BatteryLevel(42).to_bytes(), [0x2a]);
+        assert_eq!(kind(BatteryLevel::from_bytes(&[0x65])), ErrorKind::Internal(InternalErrorKind::InvalidValue));
+        assert_eq!(kind(BatteryLevel::from_bytes(&[])), ErrorKind::InvalidLength);
+        assert_eq!(kind(BatteryLevel::from_bytes(&[0x50, 0x00])), ErrorKind::InvalidLength);
+    }
+
+    #[test]
+    fn temperature() {
+        assert_eq!(Temperature::from_bytes(&[0x3a, 0x09]).unwrap(), Temperature { celsius: Some(23.62) });
+        assert_eq!(Temperature { celsius: Some(23.62) }.to_bytes(), [0x3a, 0x09]);
+        assert_eq!(Temperature::from_bytes(&[0x00, 0x80]).unwrap(), Temperature { celsius: None });
+        assert_eq!(Temperature { celsius: None }.to_bytes(), [0x00, 0x80]);
+        // Values below absolute zero are clamped.
+        assert_eq!(Temperature { celsius: Some(-273.15) }.to_bytes(), [0x4d, 0x95]);
+        assert_eq!(Temperature { celsius: Some(-300.) }.to_bytes(), [0x4d, 0x95]);
+        assert_eq!(kind(Temperature::from_bytes(&[0x3a])), ErrorKind::InvalidLength);
+    }
+
+    #[test]
+    fn heart_rate_measurement() {
+        let hrm = HeartRateMeasurement::from_bytes(&[0x00, 0x48]).unwrap();
+        assert_eq!(hrm, HeartRateMeasurement { heart_rate: 72, ..Default::default() });
+        assert_eq!(hrm.to_bytes(), [0x00, 0x48]);
+
+        // Contact supported but not detected.
+        let hrm = HeartRateMeasurement::from_bytes(&[0x04, 0x48]).unwrap();
+        assert_eq!(hrm.sensor_contact, Some(false));
+        assert_eq!(hrm.to_bytes(), [0x04, 0x48]);
+
+        // Contact detected with two RR-intervals.
+        let data = [0x16, 0x48, 0x00, 0x04, 0x10, 0x04];
+        let hrm = HeartRateMeasurement::from_bytes(&data).unwrap();
+        assert_eq!(
+            hrm,
+            HeartRateMeasurement {
+                heart_rate: 72,
+                sensor_contact: Some(true),
+                energy_expended: None,
+                rr_intervals: vec![0x0400, 0x0410]
+            }
+        );
+        assert_eq!(hrm.to_bytes(), data);
+
+        // 16-bit heart rate with energy expended.
+        let data = [0x09, 0x2c, 0x01, 0x10, 0x00];
+        let hrm = HeartRateMeasurement::from_bytes(&data).unwrap();
+        assert_eq!(
+            hrm,
+            HeartRateMeasurement { heart_rate: 300, energy_expended: Some(16), ..Default::default() }
+        );
+        assert_eq!(hrm.to_bytes(), data);
+
+        assert_eq!(kind(HeartRateMeasurement::from_bytes(&[])), ErrorKind::InvalidLength);
+        assert_eq!(kind(HeartRateMeasurement::from_bytes(&[0x00])), ErrorKind::InvalidLength);
+        assert_eq!(kind(HeartRateMeasurement::from_bytes(&[0x01, 0x2c])), ErrorKind::InvalidLength);
+        assert_eq!(kind(HeartRateMeasurement::from_bytes(&[0x08, 0x48, 0x10])), ErrorKind::InvalidLength);
+        assert_eq!(kind(HeartRateMeasurement::from_bytes(&[0x10, 0x48, 0x00])), ErrorKind::InvalidLength);
+    }
+
+    #[test]
+    fn date_time() {
+        let data = [0xe7, 0x07, 0x07, 0x13, 0x0d, 0x2d, 0x1e];
+        let dt = DateTime { year: 2023, month: 7, day: 19, hours: 13, minutes: 45, seconds: 30 };
+        assert_eq!(DateTime::from_bytes(&data).unwrap(), dt);
+        assert_eq!(dt.to_bytes(), data);
+        assert_eq!(DateTime::from_bytes(&[0; 7]).unwrap(), DateTime::default());
+
+        assert!(DateTime::from_bytes(&[0xe7, 0x07, 0x0d, 0x13, 0x0d, 0x2d, 0x1e]).is_err());
+        assert!(DateTime::from_bytes(&[0xe7, 0x07, 0x07, 0x13, 0x18, 0x2d, 0x1e]).is_err());
+        assert_eq!(kind(DateTime::from_bytes(&data[..6])), ErrorKind::InvalidLength);
+
+        let mut data = data.to_vec();
+        data.extend_from_slice(&[0x03, 0x80, CurrentTime::ADJUST_MANUAL | CurrentTime::ADJUST_TIME_ZONE]);
+        let ct = CurrentTime { date_time: dt, day_of_week: 3, fractions256: 0x80, adjust_reason: 0x05 };
+        assert_eq!(CurrentTime::from_bytes(&data).unwrap(), ct);
+        assert_eq!(ct.to_bytes(), data);
+        data[7] = 8;
+        assert!(CurrentTime::from_bytes(&data).is_err());
+        assert_eq!(kind(CurrentTime::from_bytes(&data[..9])), ErrorKind::InvalidLength);
+    }
+
+    #[test]
+    fn pnp_id() {
+        let data = [0x02, 0x6b, 0x1d, 0x46, 0x02, 0x00, 0x01];
+        let pnp = PnpId { vendor_id_source: 2, vendor_id: 0x1d6b, product_id: 0x0246, product_version: 0x0100 };
+        assert_eq!(PnpId::from_bytes(&data).unwrap(), pnp);
+        assert_eq!(pnp.to_bytes(), data);
+        assert_eq!(kind(PnpId::from_bytes(&data[..6])), ErrorKind::InvalidLength);
+    }
+}
@@ -0,0 +1,633 @@
+//! Capture of HCI traffic from the monitor channel into btsnoop and pcapng files.
+//!
+//! Files written by this module can be opened using `btmon -r` and Wireshark.
+//!
+//! Use [Capture::start] to capture all HCI traffic into a file in the background.
+//! The [BtsnoopWriter] and [PcapngWriter] can also be used directly for writing
+//! [MonitorPacket]s obtained by other means, and the corresponding readers
+//! allow to read the files back.
+
+use futures::{
+    channel::oneshot,
+    future::{self, Either},
+};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Error, ErrorKind, Read, Result, Write},
+    path::Path,
+    pin::pin,
+    sync::mpsc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::task::{spawn_blocking, JoinHandle};
+
+use super::{Monitor, MonitorPacket};
+
+/// A captured monitor packet with its timestamp.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// Time of reception.
+    pub timestamp: SystemTime,
+    /// Monitor packet.
+    pub packet: MonitorPacket,
+}
+
+/// Capture file format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// btsnoop format as used by `btmon`.
+    #[default]
+    Btsnoop,
+    /// pcapng format with link type `LINKTYPE_BLUETOOTH_LINUX_MONITOR`.
+    Pcapng,
+}
+
+impl Format {
+    /// Guesses the file format from the extension of the specified path.
+    ///
+    /// Files ending in `.pcapng` or `.pcap` use the [pcapng format](Self::Pcapng),
+    /// all other files use the [btsnoop format](Self::Btsnoop).
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("pcapng") || ext.eq_ignore_ascii_case("pcap") => Self::Pcapng,
+            _ => Self::Btsnoop,
+        }
+    }
+}
+
+fn invalid_data(msg: &'static str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+/// Reads exactly `N` bytes, returning `None` on end of file before the first byte.
+fn read_array_or_eof<const N: usize>(r: &mut impl Read) -> Result<Option<[u8; N]>> {
+    let mut buf = [0; N];
+    let mut pos = 0;
+    while pos < N {
+        match r.read(&mut buf[pos..]) {
+            Ok(0) if pos == 0 => return Ok(None),
+            Ok(0) => return Err(Error::new(ErrorKind::UnexpectedEof, "truncated capture file")),
+            Ok(n) => pos += n,
+            Err(err) if err.kind() == ErrorKind::Interrupted => (),
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(Some(buf))
+}
+
+/// btsnoop file identification pattern.
+const BTSNOOP_MAGIC: &[u8; 8] = b"btsnoop\0";
+
+/// btsnoop version.
+const BTSNOOP_VERSION: u32 = 1;
+
+/// btsnoop datalink type of Linux monitor channel.
+const BTSNOOP_TYPE_MONITOR: u32 = 2001;
+
+/// Microseconds between 0000-01-01 and the UNIX epoch, as used by btsnoop timestamps.
+const BTSNOOP_EPOCH_OFFSET: u64 = 0x00dc_ddb3_0f2f_8000;
+
+/// Writer for btsnoop files containing monitor packets.
+#[derive(Debug)]
+pub struct BtsnoopWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> BtsnoopWriter<W> {
+    /// Creates a new writer and writes the file header.
+    pub fn new(mut inner: W) -> Result<Self> {
+        inner.write_all(BTSNOOP_MAGIC)?;
+        inner.write_all(&BTSNOOP_VERSION.to_be_bytes())?;
+        inner.write_all(&BTSNOOP_TYPE_MONITOR.to_be_bytes())?;
+        Ok(Self { inner })
+    }
+
+    /// Writes a record.
+    pub fn write(&mut self, record: &Record) -> Result<()> {
+        let len: u32 = record
+            .packet
+            .data
+            .len()
+            .try_into()
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, "packet too long"))?;
+        let flags = (u32::from(record.packet.index) << 16) | u32::from(u16::from(record.packet.opcode));
+        let micros = record.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_micros() as u64;
+
+        self.inner.write_all(&len.to_be_bytes())?;
+        self.inner.write_all(&len.to_be_bytes())?;
+        self.inner.write_all(&flags.to_be_bytes())?;
+        self.inner.write_all(&0u32.to_be_bytes())?;
+        self.inner.write_all(&(micros + BTSNOOP_EPOCH_OFFSET).to_be_bytes())?;
+        self.inner.write_all(&record.packet.data)
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Reader for btsnoop files containing monitor packets.
+#[derive(Debug)]
+pub struct BtsnoopReader<R: Read> {
+    inner: R,
+}
+
+impl<R: Read> BtsnoopReader<R> {
+    /// Creates a new reader and verifies the file header.
+    pub fn new(mut inner: R) -> Result<Self> {
+        let header: [u8; 16] =
+            read_array_or_eof(&mut inner)?.ok_or_else(|| invalid_data("missing btsnoop header"))?;
+        if &header[..8] != BTSNOOP_MAGIC {
+            return Err(invalid_data("not a btsnoop file"));
+        }
+        if u32::from_be_bytes(header[8..12].try_into().unwrap()) != BTSNOOP_VERSION {
+            return Err(invalid_data("unsupported btsnoop version"));
+        }
+        if u32::from_be_bytes(header[12..16].try_into().unwrap()) != BTSNOOP_TYPE_MONITOR {
+            return Err(invalid_data("btsnoop file does not contain monitor packets"));
+        }
+        Ok(Self { inner })
+    }
+
+    /// Reads the next record.
+    ///
+    /// Returns `None` at the end of the file.
+    pub fn read(&mut self) -> Result<Option<Record>> {
+        let Some(header) = read_array_or_eof::<24>(&mut self.inner)? else { return Ok(None) };
+        let field = |i: usize| u32::from_be_bytes(header[i..i + 4].try_into().unwrap());
+        let included_len = field(4);
+        let flags = field(8);
+        let timestamp = u64::from_be_bytes(header[16..24].try_into().unwrap());
+
+        let mut data = vec![0; included_len as usize];
+        self.inner.read_exact(&mut data)?;
+
+        let micros =
+            timestamp.checked_sub(BTSNOOP_EPOCH_OFFSET).ok_or_else(|| invalid_data("invalid timestamp"))?;
+        Ok(Some(Record {
+            timestamp: UNIX_EPOCH + Duration::from_micros(micros),
+            packet: MonitorPacket { opcode: ((flags & 0xffff) as u16).into(), index: (flags >> 16) as u16, data },
+        }))
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// pcapng section header block type.
+const PCAPNG_SHB: u32 = 0x0a0d_0d0a;
+
+/// pcapng interface description block type.
+const PCAPNG_IDB: u32 = 0x0000_0001;
+
+/// pcapng enhanced packet block type.
+const PCAPNG_EPB: u32 = 0x0000_0006;
+
+/// pcapng byte-order magic.
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+
+/// Link type for Linux Bluetooth monitor packets.
+pub const LINKTYPE_BLUETOOTH_LINUX_MONITOR: u16 = 254;
+
+/// Writer for pcapng files containing monitor packets.
+///
+/// Timestamps are stored with microsecond resolution.
+#[derive(Debug)]
+pub struct PcapngWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> PcapngWriter<W> {
+    /// Creates a new writer and writes the section header and interface description.
+    pub fn new(inner: W) -> Result<Self> {
+        let mut this = Self { inner };
+
+        let mut shb = Vec::new();
+        shb.extend_from_slice(&PCAPNG_BYTE_ORDER_MAGIC.to_le_bytes());
+        shb.extend_from_slice(&1u16.to_le_bytes());
+        shb.extend_from_slice(&0u16.to_le_bytes());
+        shb.extend_from_slice(&(-1i64).to_le_bytes());
+        this.write_block(PCAPNG_SHB, &shb)?;
+
+        let mut idb = Vec::new();
+        idb.extend_from_slice(&LINKTYPE_BLUETOOTH_LINUX_MONITOR.to_le_bytes());
+        idb.extend_from_slice(&0u16.to_le_bytes());
+        idb.extend_from_slice(&0u32.to_le_bytes());
+        this.write_block(PCAPNG_IDB, &idb)?;
+
+        Ok(this)
+    }
+
+    fn write_block(&mut self, block_type: u32, body: &[u8]) -> Result<()> {
+        let padding = (4 - body.len() % 4) % 4;
+        let total_len: u32 = (12 + body.len() + padding)
+            .try_into()
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, "packet too long"))?;
+        self.inner.write_all(&block_type.to_le_bytes())?;
+        self.inner.write_all(&total_len.to_le_bytes())?;
+        self.inner.write_all(body)?;
+        self.inner.write_all(&[0; 3][..padding])?;
+        self.inner.write_all(&total_len.to_le_bytes())
+    }
+
+    /// Writes a record.
+    pub fn write(&mut self, record: &Record) -> Result<()> {
+        let micros = record.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_micros() as u64;
+        let len: u32 = (4 + record.packet.data.len())
+            .try_into()
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, "packet too long"))?;
+
+        let mut epb = Vec::with_capacity(24 + len as usize);
+        epb.extend_from_slice(&0u32.to_le_bytes());
+        epb.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+        epb.extend_from_slice(&(micros as u32).to_le_bytes());
+        epb.extend_from_slice(&len.to_le_bytes());
+        epb.extend_from_slice(&len.to_le_bytes());
+        epb.extend_from_slice(&record.packet.index.to_be_bytes());
+        epb.extend_from_slice(&u16::from(record.packet.opcode).to_be_bytes());
+        epb.extend_from_slice(&record.packet.data);
+        self.write_block(PCAPNG_EPB, &epb)
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Reader for pcapng files containing monitor packets.
+///
+/// Only little-endian files with microsecond timestamp resolution are supported.
+/// Packets of interfaces with other link types are skipped.
+#[derive(Debug)]
+pub struct PcapngReader<R: Read> {
+    inner: R,
+    monitor_interfaces: Vec<bool>,
+}
+
+impl<R: Read> PcapngReader<R> {
+    /// Creates a new reader and verifies the section header.
+    pub fn new(inner: R) -> Result<Self> {
+        let mut this = Self { inner, monitor_interfaces: Vec::new() };
+        match this.read_block()? {
+            Some((PCAPNG_SHB, body)) if body.len() >= 16 => {
+                if u32::from_le_bytes(body[..4].try_into().unwrap()) != PCAPNG_BYTE_ORDER_MAGIC {
+                    return Err(invalid_data("unsupported pcapng byte order"));
+                }
+            }
+            _ => return Err(invalid_data("not a pcapng file")),
+        }
+        Ok(this)
+    }
+
+    fn read_block(&mut self) -> Result<Option<(u32, Vec<u8>)>> {
+        let Some(header) = read_array_or_eof::<8>(&mut self.inner)? else { return Ok(None) };
+        let block_type = u32::from_le_bytes(header[..4].try_into().unwrap());
+        let total_len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+        if total_len < 12 || total_len % 4 != 0 {
+            return Err(invalid_data("invalid pcapng block length"));
+        }
+        let mut body = vec![0; total_len - 8];
+        self.inner.read_exact(&mut body)?;
+        body.truncate(total_len - 12);
+        Ok(Some((block_type, body)))
+    }
+
+    /// Reads the next record.
+    ///
+    /// Returns `None` at the end of the file.
+    pub fn read(&mut self) -> Result<Option<Record>> {
+        loop {
+            let Some((block_type, body)) = self.read_block()? else { return Ok(None) };
+            let field = |i: usize| -> Result<u32> {
+                body.get(i..i + 4)
+                    .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+                    .ok_or_else(|| invalid_data("pcapng block too short"))
+            };
+
+            match block_type {
+                PCAPNG_SHB => self.monitor_interfaces.clear(),
+                PCAPNG_IDB => {
+                    let link_type = field(0)? as u16;
+                    self.monitor_interfaces.push(link_type == LINKTYPE_BLUETOOTH_LINUX_MONITOR);
+                }
+                PCAPNG_EPB => {
+                    let interface = field(0)? as usize;
+                    if !self.monitor_interfaces.get(interface).copied().unwrap_or_default() {
+                        continue;
+                    }
+                    let micros = (u64::from(field(4)?) << 32) | u64::from(field(8)?);
+                    let captured_len = field(12)? as usize;
+                    let packet = body
+                        .get(20..20 + captured_len)
+                        .filter(|packet| packet.len() >= 4)
+                        .ok_or_else(|| invalid_data("invalid pcapng packet length"))?;
+                    return Ok(Some(Record {
+                        timestamp: UNIX_EPOCH + Duration::from_micros(micros),
+                        packet: MonitorPacket {
+                            index: u16::from_be_bytes([packet[0], packet[1]]),
+                            opcode: u16::from_be_bytes([packet[2], packet[3]]).into(),
+                            data: packet[4..].to_vec(),
+                        },
+                    }));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// Writer for either capture file format.
+enum CaptureWriter<W: Write> {
+    Btsnoop(BtsnoopWriter<W>),
+    Pcapng(PcapngWriter<W>),
+}
+
+impl<W: Write> CaptureWriter<W> {
+    fn new(inner: W, format: Format) -> Result<Self> {
+        Ok(match format {
+            Format::Btsnoop => Self::Btsnoop(BtsnoopWriter::new(inner)?),
+            Format::Pcapng => Self::Pcapng(PcapngWriter::new(inner)?),
+        })
+    }
+
+    fn write(&mut self, record: &Record) -> Result<()> {
+        match self {
+            Self::Btsnoop(w) => w.write(record),
+            Self::Pcapng(w) => w.write(record),
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        match self {
+            Self::Btsnoop(w) => w.flush(),
+            Self::Pcapng(w) => w.flush(),
+        }
+    }
+}
+
+/// Reads a capture file in either format.
+pub fn read_file(path: impl AsRef<Path>) -> Result<Vec<Record>> {
+    let mut file = BufReader::new(File::open(path)?);
+    let mut magic = [0; 4];
+    file.read_exact(&mut magic)?;
+    let file = (&magic[..]).chain(file);
+
+    let mut records = Vec::new();
+    if magic == PCAPNG_SHB.to_le_bytes() {
+        let mut reader = PcapngReader::new(file)?;
+        while let Some(record) = reader.read()? {
+            records.push(record);
+        }
+    } else {
+        let mut reader = BtsnoopReader::new(file)?;
+        while let Some(record) = reader.read()? {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+/// Background capture of all HCI traffic into a file.
+///
+/// Packets are written on a blocking thread, which flushes the file whenever
+/// no further packets are queued.
+/// The capture is stopped and the file is flushed when [stop](Self::stop) is called.
+/// Dropping the capture stops it without reporting errors.
+///
+/// Capturing requires the `CAP_NET_ADMIN` capability.
+#[derive(Debug)]
+pub struct Capture {
+    stop_tx: Option<oneshot::Sender<()>>,
+    task: JoinHandle<Result<u64>>,
+}
+
+impl Capture {
+    /// Starts capturing into the specified file.
+    ///
+    /// The file format is guessed from the file extension using [Format::from_path].
+    /// An existing file is overwritten.
+    pub async fn start(path: impl AsRef<Path>) -> Result<Self> {
+        let format = Format::from_path(&path);
+        Self::start_with_format(path, format).await
+    }
+
+    /// Starts capturing into the specified file using the specified format.
+    ///
+    /// An existing file is overwritten.
+    pub async fn start_with_format(path: impl AsRef<Path>, format: Format) -> Result<Self> {
+        let monitor = Monitor::new()?;
+        let file = BufWriter::new(File::create(path)?);
+        Self::start_with_writer(monitor, file, format)
+    }
+
+    /// Starts capturing packets received by the specified monitor socket into the specified writer.
+    pub fn start_with_writer<W>(monitor: Monitor, writer: W, format: Format) -> Result<Self>
+    where
+        W: Write + Send + 'static,
+    {
+        let writer = CaptureWriter::new(writer, format)?;
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let task = tokio::spawn(Self::task(monitor, writer, stop_rx));
+        Ok(Self { stop_tx: Some(stop_tx), task })
+    }
+
+    async fn task<W: Write + Send + 'static>(
+        monitor: Monitor, writer: CaptureWriter<W>, mut stop_rx: oneshot::Receiver<()>,
+    ) -> Result<u64> {
+        let (record_tx, record_rx) = mpsc::channel();
+        let write_task = spawn_blocking(move || Self::write_records(writer, record_rx));
+
+        let res = loop {
+            let packet = match future::select(&mut stop_rx, pin!(monitor.recv())).await {
+                Either::Left(_) => break Ok(()),
+                Either::Right((Ok(packet), _)) => packet,
+                Either::Right((Err(err), _)) => break Err(err),
+            };
+            if record_tx.send(Record { timestamp: SystemTime::now(), packet }).is_err() {
+                // Writing failed; the error is returned by the write task.
+                break Ok(());
+            }
+        };
+
+        drop(record_tx);
+        let count = write_task.await.map_err(|err| Error::new(ErrorKind::Other, err))??;
+        res.map(|()| count)
+    }
+
+    /// Writes records until the channel is closed.
+    ///
+    /// The writer is flushed whenever no further records are queued.
+    fn write_records<W: Write>(mut writer: CaptureWriter<W>, record_rx: mpsc::Receiver<Record>) -> Result<u64> {
+        let mut count = 0;
+        let mut next = record_rx.recv().ok();
+        while let Some(record) = next {
+            writer.write(&record)?;
+            count += 1;
+            next = match record_rx.try_recv() {
+                Ok(record) => Some(record),
+                Err(_) => {
+                    writer.flush()?;
+                    record_rx.recv().ok()
+                }
+            };
+        }
+        writer.flush()?;
+        Ok(count)
+    }
+
+    /// Stops capturing and returns the number of captured packets.
+    pub async fn stop(mut self) -> Result<u64> {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+        (&mut self.task).await.map_err(|err| Error::new(ErrorKind::Other, err))?
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hci::MonitorOpcode;
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record {
+                timestamp: UNIX_EPOCH + Duration::from_micros(1_700_000_000_123_456),
+                packet: MonitorPacket {
+                    opcode: MonitorOpcode::CommandPkt,
+                    index: 0,
+                    data: vec![0x03, 0x0c, 0x00],
+                },
+            },
+            Record {
+                timestamp: UNIX_EPOCH + Duration::from_micros(1_700_000_000_234_567),
+                packet: MonitorPacket {
+                    opcode: MonitorOpcode::EventPkt,
+                    index: 1,
+                    data: vec![0x0e, 0x04, 0x01, 0x03, 0x0c, 0x00],
+                },
+            },
+            Record {
+                timestamp: UNIX_EPOCH + Duration::from_micros(1_700_000_001_000_000),
+                packet: MonitorPacket { opcode: MonitorOpcode::Other(0x1234), index: 0xffff, data: vec![] },
+            },
+        ]
+    }
+
+    /// Writer counting flushes.
+    #[derive(Clone, Default)]
+    struct SharedBuf(std::sync::Arc<std::sync::Mutex<(Vec<u8>, usize)>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.0.lock().unwrap().0.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            self.0.lock().unwrap().1 += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_queued_records() {
+        let buf = SharedBuf::default();
+        let writer = CaptureWriter::new(buf.clone(), Format::Btsnoop).unwrap();
+        let (record_tx, record_rx) = mpsc::channel();
+        for record in records() {
+            record_tx.send(record).unwrap();
+        }
+        drop(record_tx);
+
+        assert_eq!(Capture::write_records(writer, record_rx).unwrap(), 3);
+
+        // Queued records are written without flushing in between.
+        let (data, flushes) = &*buf.0.lock().unwrap();
+        assert!(*flushes <= 2);
+        let mut reader = BtsnoopReader::new(&data[..]).unwrap();
+        for record in records() {
+            assert_eq!(reader.read().unwrap(), Some(record));
+        }
+        assert_eq!(reader.read().unwrap(), None);
+    }
+
+    #[test]
+    fn btsnoop_round_trip() {
+        let mut writer = BtsnoopWriter::new(Vec::new()).unwrap();
+        for record in records() {
+            writer.write(&record).unwrap();
+        }
+        let buf = writer.into_inner();
+        assert_eq!(&buf[..16], b"btsnoop\0\x00\x00\x00\x01\x00\x00\x07\xd1");
+
+        let mut reader = BtsnoopReader::new(&buf[..]).unwrap();
+        for record in records() {
+            assert_eq!(reader.read().unwrap(), Some(record));
+        }
+        assert_eq!(reader.read().unwrap(), None);
+    }
+
+    #[test]
+    fn pcapng_round_trip() {
+        let mut writer = PcapngWriter::new(Vec::new()).unwrap();
+        for record in records() {
+            writer.write(&record).unwrap();
+        }
+        let buf = writer.into_inner();
+        assert_eq!(buf.len() % 4, 0);
+        assert_eq!(&buf[..4], &PCAPNG_SHB.to_le_bytes());
+
+        let mut reader = PcapngReader::new(&buf[..]).unwrap();
+        for record in records() {
+            assert_eq!(reader.read().unwrap(), Some(record));
+        }
+        assert_eq!(reader.read().unwrap(), None);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut writer = BtsnoopWriter::new(Vec::new()).unwrap();
+        writer.write(&records()[1]).unwrap();
+        let buf = writer.into_inner();
+        let mut reader = BtsnoopReader::new(&buf[..buf.len() - 1]).unwrap();
+        assert!(reader.read().is_err());
+
+        assert!(PcapngReader::new(&buf[..]).is_err());
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(Format::from_path("trace.pcapng"), Format::Pcapng);
+        assert_eq!(Format::from_path("trace.PCAP"), Format::Pcapng);
+        assert_eq!(Format::from_path("trace.log"), Format::Btsnoop);
+        assert_eq!(Format::from_path("trace"), Format::Btsnoop);
+    }
+}
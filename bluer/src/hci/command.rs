@@ -0,0 +1,351 @@
+//! HCI commands and their return parameters.
+
+use std::io::{Error, ErrorKind, Result};
+
+use super::{Packet, PacketType, Status};
+use crate::{hci_util::Reader, Address};
+
+/// HCI command opcodes.
+///
+/// An opcode consists of the opcode group field (OGF) in the upper 6 bits
+/// and the opcode command field (OCF) in the lower 10 bits.
+pub mod opcode {
+    /// Builds an opcode from opcode group field (OGF) and opcode command field (OCF).
+    pub const fn new(ogf: u8, ocf: u16) -> u16 {
+        ((ogf as u16) << 10) | (ocf & 0x03ff)
+    }
+
+    /// Opcode group field (OGF) of opcode.
+    pub const fn ogf(opcode: u16) -> u8 {
+        (opcode >> 10) as u8
+    }
+
+    /// Opcode command field (OCF) of opcode.
+    pub const fn ocf(opcode: u16) -> u16 {
+        opcode & 0x03ff
+    }
+
+    /// Link control commands.
+    pub const OGF_LINK_CTL: u8 = 0x01;
+    /// Controller and baseband commands.
+    pub const OGF_HOST_CTL: u8 = 0x03;
+    /// Informational parameters.
+    pub const OGF_INFO_PARAM: u8 = 0x04;
+    /// Status parameters.
+    pub const OGF_STATUS_PARAM: u8 = 0x05;
+    /// LE controller commands.
+    pub const OGF_LE_CTL: u8 = 0x08;
+    /// Vendor-specific commands.
+    pub const OGF_VENDOR: u8 = 0x3f;
+
+    /// Disconnect.
+    pub const DISCONNECT: u16 = new(OGF_LINK_CTL, 0x0006);
+    /// Reset.
+    pub const RESET: u16 = new(OGF_HOST_CTL, 0x0003);
+    /// Write local name.
+    pub const WRITE_LOCAL_NAME: u16 = new(OGF_HOST_CTL, 0x0013);
+    /// Read local name.
+    pub const READ_LOCAL_NAME: u16 = new(OGF_HOST_CTL, 0x0014);
+    /// Read transmit power level.
+    pub const READ_TRANSMIT_POWER_LEVEL: u16 = new(OGF_HOST_CTL, 0x002d);
+    /// Read local version information.
+    pub const READ_LOCAL_VERSION: u16 = new(OGF_INFO_PARAM, 0x0001);
+    /// Read BD_ADDR.
+    pub const READ_BD_ADDR: u16 = new(OGF_INFO_PARAM, 0x0009);
+    /// Read RSSI.
+    pub const READ_RSSI: u16 = new(OGF_STATUS_PARAM, 0x0005);
+    /// LE read advertising channel TX power.
+    pub const LE_READ_ADVERTISING_CHANNEL_TX_POWER: u16 = new(OGF_LE_CTL, 0x0007);
+    /// LE set scan parameters.
+    pub const LE_SET_SCAN_PARAMETERS: u16 = new(OGF_LE_CTL, 0x000b);
+    /// LE set scan enable.
+    pub const LE_SET_SCAN_ENABLE: u16 = new(OGF_LE_CTL, 0x000c);
+}
+
+/// Length of local name field.
+const LOCAL_NAME_LEN: usize = 248;
+
+/// HCI command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Command {
+    /// Terminate a connection.
+    Disconnect {
+        /// Connection handle.
+        handle: u16,
+        /// Reason for termination.
+        reason: Status,
+    },
+    /// Reset the controller.
+    Reset,
+    /// Write local name.
+    WriteLocalName(String),
+    /// Read local name.
+    ReadLocalName,
+    /// Read transmit power level of a connection.
+    ReadTransmitPowerLevel {
+        /// Connection handle.
+        handle: u16,
+        /// Read maximum instead of current transmit power level.
+        maximum: bool,
+    },
+    /// Read local version information.
+    ReadLocalVersion,
+    /// Read public address of controller.
+    ReadBdAddr,
+    /// Read RSSI of a connection.
+    ReadRssi {
+        /// Connection handle.
+        handle: u16,
+    },
+    /// Read transmit power level used for LE advertising.
+    LeReadAdvertisingChannelTxPower,
+    /// Set LE scan parameters.
+    LeSetScanParameters {
+        /// Active scanning, i.e. send scan requests.
+        active: bool,
+        /// Scan interval in units of 0.625 ms.
+        interval: u16,
+        /// Scan window in units of 0.625 ms.
+        window: u16,
+        /// Own address type.
+        own_address_type: u8,
+        /// Scanning filter policy.
+        filter_policy: u8,
+    },
+    /// Enable or disable LE scanning.
+    LeSetScanEnable {
+        /// Enable scanning.
+        enable: bool,
+        /// Filter duplicate advertising reports.
+        filter_duplicates: bool,
+    },
+    /// Vendor-specific command.
+    Vendor {
+        /// Opcode command field.
+        ocf: u16,
+        /// Parameters.
+        params: Vec<u8>,
+    },
+    /// Command not encoded by this library.
+    Raw {
+        /// Opcode.
+        opcode: u16,
+        /// Parameters.
+        params: Vec<u8>,
+    },
+}
+
+impl Command {
+    /// Command opcode.
+    pub fn opcode(&self) -> u16 {
+        match self {
+            Self::Disconnect { .. } => opcode::DISCONNECT,
+            Self::Reset => opcode::RESET,
+            Self::WriteLocalName(_) => opcode::WRITE_LOCAL_NAME,
+            Self::ReadLocalName => opcode::READ_LOCAL_NAME,
+            Self::ReadTransmitPowerLevel { .. } => opcode::READ_TRANSMIT_POWER_LEVEL,
+            Self::ReadLocalVersion => opcode::READ_LOCAL_VERSION,
+            Self::ReadBdAddr => opcode::READ_BD_ADDR,
+            Self::ReadRssi { .. } => opcode::READ_RSSI,
+            Self::LeReadAdvertisingChannelTxPower => opcode::LE_READ_ADVERTISING_CHANNEL_TX_POWER,
+            Self::LeSetScanParameters { .. } => opcode::LE_SET_SCAN_PARAMETERS,
+            Self::LeSetScanEnable { .. } => opcode::LE_SET_SCAN_ENABLE,
+            Self::Vendor { ocf, .. } => opcode::new(opcode::OGF_VENDOR, *ocf),
+            Self::Raw { opcode, .. } => *opcode,
+        }
+    }
+
+    /// Encodes the command parameters.
+    pub fn encode_params(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        match self {
+            Self::Reset
+            | Self::ReadLocalName
+            | Self::ReadLocalVersion
+            | Self::ReadBdAddr
+            | Self::LeReadAdvertisingChannelTxPower => (),
+            Self::Disconnect { handle, reason } => {
+                buf.extend_from_slice(&handle.to_le_bytes());
+                buf.push(reason.0);
+            }
+            Self::WriteLocalName(name) => {
+                if name.len() > LOCAL_NAME_LEN {
+                    return Err(Error::new(ErrorKind::InvalidInput, "local name too long"));
+                }
+                buf.extend_from_slice(name.as_bytes());
+                buf.resize(LOCAL_NAME_LEN, 0);
+            }
+            Self::ReadTransmitPowerLevel { handle, maximum } => {
+                buf.extend_from_slice(&handle.to_le_bytes());
+                buf.push(*maximum as u8);
+            }
+            Self::ReadRssi { handle } => buf.extend_from_slice(&handle.to_le_bytes()),
+            Self::LeSetScanParameters { active, interval, window, own_address_type, filter_policy } => {
+                buf.push(*active as u8);
+                buf.extend_from_slice(&interval.to_le_bytes());
+                buf.extend_from_slice(&window.to_le_bytes());
+                buf.push(*own_address_type);
+                buf.push(*filter_policy);
+            }
+            Self::LeSetScanEnable { enable, filter_duplicates } => {
+                buf.push(*enable as u8);
+                buf.push(*filter_duplicates as u8);
+            }
+            Self::Vendor { params, .. } | Self::Raw { params, .. } => buf.extend_from_slice(params),
+        }
+        if buf.len() > u8::MAX as usize {
+            return Err(Error::new(ErrorKind::InvalidInput, "HCI command parameters too long"));
+        }
+        Ok(buf)
+    }
+
+    /// Encodes the command as an HCI command packet.
+    pub fn to_packet(&self) -> Result<Packet> {
+        let params = self.encode_params()?;
+        let mut data = Vec::with_capacity(3 + params.len());
+        data.extend_from_slice(&self.opcode().to_le_bytes());
+        data.push(params.len() as u8);
+        data.extend_from_slice(&params);
+        Ok(Packet { packet_type: PacketType::Command, data })
+    }
+}
+
+/// Local version information of a controller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LocalVersion {
+    /// HCI version.
+    pub hci_version: u8,
+    /// HCI revision.
+    pub hci_revision: u16,
+    /// Link manager protocol (LMP) version.
+    pub lmp_version: u8,
+    /// Manufacturer id.
+    pub manufacturer: u16,
+    /// LMP subversion.
+    pub lmp_subversion: u16,
+}
+
+impl LocalVersion {
+    pub(crate) fn decode(params: &[u8]) -> Result<Self> {
+        let mut r = Reader::new(params);
+        let this = Self {
+            hci_version: r.u8()?,
+            hci_revision: r.u16()?,
+            lmp_version: r.u8()?,
+            manufacturer: r.u16()?,
+            lmp_subversion: r.u16()?,
+        };
+        r.finish()?;
+        Ok(this)
+    }
+}
+
+pub(crate) fn decode_bd_addr(params: &[u8]) -> Result<Address> {
+    let mut r = Reader::new(params);
+    let addr = r.address()?;
+    r.finish()?;
+    Ok(addr)
+}
+
+pub(crate) fn decode_local_name(params: &[u8]) -> Result<String> {
+    let mut r = Reader::new(params);
+    let name = r.string(LOCAL_NAME_LEN)?;
+    r.finish()?;
+    Ok(name)
+}
+
+pub(crate) fn decode_handle_i8(params: &[u8]) -> Result<i8> {
+    let mut r = Reader::new(params);
+    let _handle = r.u16()?;
+    let value = r.i8()?;
+    r.finish()?;
+    Ok(value)
+}
+
+pub(crate) fn decode_i8(params: &[u8]) -> Result<i8> {
+    let mut r = Reader::new(params);
+    let value = r.i8()?;
+    r.finish()?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(command: &Command) -> Vec<u8> {
+        command.to_packet().unwrap().encode()
+    }
+
+    #[test]
+    fn opcodes() {
+        assert_eq!(opcode::RESET, 0x0c03);
+        assert_eq!(opcode::DISCONNECT, 0x0406);
+        assert_eq!(opcode::LE_SET_SCAN_ENABLE, 0x200c);
+        assert_eq!(opcode::ogf(opcode::LE_SET_SCAN_ENABLE), opcode::OGF_LE_CTL);
+        assert_eq!(opcode::ocf(opcode::LE_SET_SCAN_ENABLE), 0x000c);
+    }
+
+    #[test]
+    fn encode_commands() {
+        assert_eq!(encode(&Command::Reset), [0x01, 0x03, 0x0c, 0x00]);
+        assert_eq!(
+            encode(&Command::Disconnect { handle: 0x0040, reason: Status(0x13) }),
+            [0x01, 0x06, 0x04, 0x03, 0x40, 0x00, 0x13]
+        );
+        assert_eq!(
+            encode(&Command::LeSetScanParameters {
+                active: true,
+                interval: 0x0010,
+                window: 0x0010,
+                own_address_type: 0x00,
+                filter_policy: 0x00
+            }),
+            [0x01, 0x0b, 0x20, 0x07, 0x01, 0x10, 0x00, 0x10, 0x00, 0x00, 0x00]
+        );
+        assert_eq!(
+            encode(&Command::LeSetScanEnable { enable: true, filter_duplicates: false }),
+            [0x01, 0x0c, 0x20, 0x02, 0x01, 0x00]
+        );
+
+        let name = Command::WriteLocalName("BlueR".to_string()).encode_params().unwrap();
+        assert_eq!(name.len(), LOCAL_NAME_LEN);
+        assert_eq!(&name[..6], b"BlueR\0");
+    }
+
+    #[test]
+    fn encode_vendor_command() {
+        let command = Command::Vendor { ocf: 0x0001, params: vec![0xaa, 0xbb] };
+        assert_eq!(command.opcode(), opcode::new(opcode::OGF_VENDOR, 0x0001));
+        assert_eq!(command.opcode(), 0xfc01);
+        assert_eq!(encode(&command), [0x01, 0x01, 0xfc, 0x02, 0xaa, 0xbb]);
+    }
+
+    #[test]
+    fn encode_too_long() {
+        assert!(Command::WriteLocalName("x".repeat(LOCAL_NAME_LEN + 1)).to_packet().is_err());
+        assert!(Command::Raw { opcode: 0x0c13, params: vec![0; 256] }.to_packet().is_err());
+    }
+
+    #[test]
+    fn decode_return_parameters() {
+        assert_eq!(
+            LocalVersion::decode(&[0x0c, 0x0e, 0x00, 0x0c, 0x02, 0x00, 0x34, 0x12]).unwrap(),
+            LocalVersion {
+                hci_version: 0x0c,
+                hci_revision: 0x000e,
+                lmp_version: 0x0c,
+                manufacturer: 0x0002,
+                lmp_subversion: 0x1234
+            }
+        );
+        assert!(LocalVersion::decode(&[0x0c, 0x0e, 0x00]).is_err());
+        assert_eq!(
+            decode_bd_addr(&[0x55, 0x44, 0x33, 0x22, 0x11, 0x00]).unwrap(),
+            Address([0x00, 0x11, 0x22, 0x33, 0x44, 0x55])
+        );
+        assert_eq!(decode_handle_i8(&[0x40, 0x00, 0xf6]).unwrap(), -10);
+    }
+}
@@ -0,0 +1,330 @@
+//! HCI events.
+
+use std::io::{Error, ErrorKind, Result};
+
+use super::Status;
+use crate::{hci_util::Reader, Address};
+
+/// HCI event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    /// Inquiry has completed.
+    InquiryComplete {
+        /// Status.
+        status: Status,
+    },
+    /// A BR/EDR connection has been established.
+    ConnectionComplete {
+        /// Status.
+        status: Status,
+        /// Connection handle.
+        handle: u16,
+        /// Address of remote device.
+        address: Address,
+        /// Link type.
+        link_type: u8,
+        /// Whether encryption is enabled.
+        encryption_enabled: bool,
+    },
+    /// A connection has been terminated.
+    DisconnectionComplete {
+        /// Status.
+        status: Status,
+        /// Connection handle.
+        handle: u16,
+        /// Reason for disconnection.
+        reason: Status,
+    },
+    /// Encryption of a connection has changed.
+    EncryptionChange {
+        /// Status.
+        status: Status,
+        /// Connection handle.
+        handle: u16,
+        /// Encryption mode; zero if encryption is off.
+        encryption: u8,
+    },
+    /// A command has completed.
+    CommandComplete {
+        /// Number of command packets the host may send.
+        num_packets: u8,
+        /// Command opcode.
+        opcode: u16,
+        /// Return parameters, usually starting with a status.
+        params: Vec<u8>,
+    },
+    /// A command has been received and is being processed, or has failed.
+    CommandStatus {
+        /// Status.
+        status: Status,
+        /// Number of command packets the host may send.
+        num_packets: u8,
+        /// Command opcode.
+        opcode: u16,
+    },
+    /// A hardware failure has occurred.
+    HardwareError {
+        /// Hardware error code.
+        code: u8,
+    },
+    /// Number of completed data packets per connection handle.
+    NumberOfCompletedPackets(Vec<(u16, u16)>),
+    /// LE meta event.
+    Le(LeEvent),
+    /// Vendor-specific event.
+    Vendor(Vec<u8>),
+    /// Event not decoded by this library.
+    Unknown {
+        /// Event code.
+        code: u8,
+        /// Raw parameters.
+        params: Vec<u8>,
+    },
+}
+
+impl Event {
+    /// Decodes an event packet, excluding the packet type indicator.
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let mut r = Reader::new(data);
+        let code = r.u8()?;
+        let len = r.u8()?;
+        let mut r = Reader::new(r.bytes(len.into())?);
+        let event = match code {
+            0x01 => Self::InquiryComplete { status: Status(r.u8()?) },
+            0x03 => Self::ConnectionComplete {
+                status: Status(r.u8()?),
+                handle: r.u16()?,
+                address: r.address()?,
+                link_type: r.u8()?,
+                encryption_enabled: r.u8()? != 0,
+            },
+            0x05 => {
+                Self::DisconnectionComplete { status: Status(r.u8()?), handle: r.u16()?, reason: Status(r.u8()?) }
+            }
+            0x08 => Self::EncryptionChange { status: Status(r.u8()?), handle: r.u16()?, encryption: r.u8()? },
+            0x0e => Self::CommandComplete { num_packets: r.u8()?, opcode: r.u16()?, params: r.rest().to_vec() },
+            0x0f => Self::CommandStatus { status: Status(r.u8()?), num_packets: r.u8()?, opcode: r.u16()? },
+            0x10 => Self::HardwareError { code: r.u8()? },
+            0x13 => {
+                let count = r.u8()?;
+                let handles = (0..count).map(|_| Ok((r.u16()?, r.u16()?))).collect::<Result<_>>()?;
+                Self::NumberOfCompletedPackets(handles)
+            }
+            0x3e => Self::Le(LeEvent::decode(&mut r)?),
+            0xff => Self::Vendor(r.rest().to_vec()),
+            code => Self::Unknown { code, params: r.rest().to_vec() },
+        };
+        r.finish()?;
+        Ok(event)
+    }
+}
+
+/// LE meta event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LeEvent {
+    /// An LE connection has been established.
+    ConnectionComplete {
+        /// Status.
+        status: Status,
+        /// Connection handle.
+        handle: u16,
+        /// Role; zero if central and one if peripheral.
+        role: u8,
+        /// Address type of remote device; zero if public and one if random.
+        address_type: u8,
+        /// Address of remote device.
+        address: Address,
+        /// Connection interval in units of 1.25 ms.
+        interval: u16,
+        /// Peripheral latency in number of connection events.
+        latency: u16,
+        /// Supervision timeout in units of 10 ms.
+        timeout: u16,
+        /// Central clock accuracy.
+        clock_accuracy: u8,
+    },
+    /// Advertising reports have been received.
+    AdvertisingReport(Vec<AdvertisingReport>),
+    /// Connection parameters have been updated.
+    ConnectionUpdateComplete {
+        /// Status.
+        status: Status,
+        /// Connection handle.
+        handle: u16,
+        /// Connection interval in units of 1.25 ms.
+        interval: u16,
+        /// Peripheral latency in number of connection events.
+        latency: u16,
+        /// Supervision timeout in units of 10 ms.
+        timeout: u16,
+    },
+    /// LE meta event not decoded by this library.
+    Unknown {
+        /// Subevent code.
+        subevent: u8,
+        /// Raw parameters.
+        params: Vec<u8>,
+    },
+}
+
+impl LeEvent {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        let subevent = r.u8()?;
+        let event = match subevent {
+            0x01 => Self::ConnectionComplete {
+                status: Status(r.u8()?),
+                handle: r.u16()?,
+                role: r.u8()?,
+                address_type: r.u8()?,
+                address: r.address()?,
+                interval: r.u16()?,
+                latency: r.u16()?,
+                timeout: r.u16()?,
+                clock_accuracy: r.u8()?,
+            },
+            0x02 => {
+                let count = r.u8()?;
+                let reports = (0..count).map(|_| AdvertisingReport::decode(r)).collect::<Result<_>>()?;
+                Self::AdvertisingReport(reports)
+            }
+            0x03 => Self::ConnectionUpdateComplete {
+                status: Status(r.u8()?),
+                handle: r.u16()?,
+                interval: r.u16()?,
+                latency: r.u16()?,
+                timeout: r.u16()?,
+            },
+            subevent => Self::Unknown { subevent, params: r.rest().to_vec() },
+        };
+        Ok(event)
+    }
+}
+
+/// LE advertising report.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdvertisingReport {
+    /// Advertising event type.
+    pub event_type: u8,
+    /// Address type of advertiser; zero if public and one if random.
+    pub address_type: u8,
+    /// Address of advertiser.
+    pub address: Address,
+    /// Advertising or scan response data.
+    pub data: Vec<u8>,
+    /// Received signal strength in dBm.
+    ///
+    /// 127 indicates that the RSSI is not available.
+    pub rssi: i8,
+}
+
+impl AdvertisingReport {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        let event_type = r.u8()?;
+        let address_type = r.u8()?;
+        let address = r.address()?;
+        let len = r.u8()?;
+        if len > 31 {
+            return Err(Error::new(ErrorKind::InvalidData, "advertising data too long"));
+        }
+        let data = r.bytes(len.into())?.to_vec();
+        let rssi = r.i8()?;
+        Ok(Self { event_type, address_type, address, data, rssi })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_command_complete() {
+        assert_eq!(
+            Event::decode(&[0x0e, 0x04, 0x01, 0x03, 0x0c, 0x00]).unwrap(),
+            Event::CommandComplete { num_packets: 1, opcode: 0x0c03, params: vec![0x00] }
+        );
+    }
+
+    #[test]
+    fn decode_command_status() {
+        assert_eq!(
+            Event::decode(&[0x0f, 0x04, 0x0c, 0x01, 0x06, 0x04]).unwrap(),
+            Event::CommandStatus { status: Status::COMMAND_DISALLOWED, num_packets: 1, opcode: 0x0406 }
+        );
+    }
+
+    #[test]
+    fn decode_disconnection_complete() {
+        assert_eq!(
+            Event::decode(&[0x05, 0x04, 0x00, 0x40, 0x00, 0x13]).unwrap(),
+            Event::DisconnectionComplete {
+                status: Status::SUCCESS,
+                handle: 0x0040,
+                reason: Status::REMOTE_USER_TERMINATED
+            }
+        );
+    }
+
+    #[test]
+    fn decode_advertising_report() {
+        let data = [
+            0x3e, 0x19, 0x02, 0x02, // LE advertising report with two reports
+            0x00, 0x00, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x03, 0x02, 0x01, 0x06, 0xc8, // first report
+            0x04, 0x01, 0xbb, 0xaa, 0x99, 0x88, 0x77, 0xc6, 0x00, 0x7f, // second report
+        ];
+        assert_eq!(
+            Event::decode(&data).unwrap(),
+            Event::Le(LeEvent::AdvertisingReport(vec![
+                AdvertisingReport {
+                    event_type: 0x00,
+                    address_type: 0x00,
+                    address: Address([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]),
+                    data: vec![0x02, 0x01, 0x06],
+                    rssi: -56,
+                },
+                AdvertisingReport {
+                    event_type: 0x04,
+                    address_type: 0x01,
+                    address: Address([0xc6, 0x77, 0x88, 0x99, 0xaa, 0xbb]),
+                    data: vec![],
+                    rssi: 127,
+                },
+            ]))
+        );
+    }
+
+    #[test]
+    fn decode_advertising_data_too_long() {
+        let mut data = vec![0x3e, 0x2c, 0x02, 0x01, 0x00, 0x00, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x20];
+        data.extend_from_slice(&[0; 32]);
+        data.push(0xc8);
+        assert_eq!(Event::decode(&data).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn decode_truncated() {
+        // Parameter length exceeds packet.
+        assert!(Event::decode(&[0x05, 0x04, 0x00, 0x40, 0x00]).is_err());
+        // Parameters too short for event.
+        assert!(Event::decode(&[0x05, 0x03, 0x00, 0x40, 0x00]).is_err());
+        // Parameters too long for event.
+        assert!(Event::decode(&[0x05, 0x05, 0x00, 0x40, 0x00, 0x13, 0x00]).is_err());
+        // Fewer advertising reports than announced.
+        assert!(Event::decode(&[0x3e, 0x02, 0x02, 0x01]).is_err());
+        assert!(Event::decode(&[]).is_err());
+    }
+
+    #[test]
+    fn decode_unknown() {
+        assert_eq!(
+            Event::decode(&[0x57, 0x02, 0xaa, 0xbb]).unwrap(),
+            Event::Unknown { code: 0x57, params: vec![0xaa, 0xbb] }
+        );
+        assert_eq!(
+            Event::decode(&[0x3e, 0x03, 0x14, 0x01, 0x02]).unwrap(),
+            Event::Le(LeEvent::Unknown { subevent: 0x14, params: vec![0x01, 0x02] })
+        );
+        assert_eq!(Event::decode(&[0xff, 0x02, 0x01, 0x02]).unwrap(), Event::Vendor(vec![0x01, 0x02]));
+    }
+}
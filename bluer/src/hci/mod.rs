@@ -0,0 +1,681 @@
+//! Host controller interface (HCI) sockets.
+//!
+//! HCI sockets provide direct access to Bluetooth controllers.
+//! Three channels are supported:
+//!
+//!   * The raw channel ([Socket::raw]) allows sending HCI commands to a controller
+//!     and receiving HCI events, while the controller remains under control of the kernel.
+//!     This is useful for sending vendor-specific commands or reading link information,
+//!     such as the received signal strength of a connection.
+//!   * The user channel ([Socket::user]) provides exclusive access to a controller.
+//!     The kernel and Bluetooth daemon stop using the controller while the socket is open.
+//!     The controller must be powered off before opening the user channel.
+//!   * The monitor channel ([Monitor]) receives a copy of all HCI traffic of all controllers
+//!     as well as kernel log messages.
+//!
+//! Use a [Client] to send [commands](Command) and receive [events](Event) over a raw or user channel socket.
+//! Traffic received over the monitor channel can be saved to btsnoop and pcapng files
+//! using the [capture] module.
+//!
+//! Opening HCI sockets requires the `CAP_NET_RAW` capability;
+//! the user and monitor channels require the `CAP_NET_ADMIN` capability.
+//!
+//! No [Session](crate::Session) and therefore no running Bluetooth daemon is required.
+//!
+
+use futures::Stream;
+use libc::{AF_BLUETOOTH, SOCK_RAW};
+use num_derive::FromPrimitive;
+use std::{
+    fmt,
+    io::{Error, ErrorKind, Result},
+    os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
+    sync::Arc,
+};
+use tokio::{io::unix::AsyncFd, task::JoinHandle};
+
+use crate::{
+    hci_util::{self, CommandDispatcher, HciSocketAddr, Reader},
+    sock::{self, OwnedFd},
+    sys::{
+        hci_filter, BTPROTO_HCI, HCI_CHANNEL_MONITOR, HCI_CHANNEL_RAW, HCI_CHANNEL_USER, HCI_DEV_NONE,
+        HCI_FILTER, SOL_HCI,
+    },
+    Address,
+};
+
+pub mod capture;
+mod command;
+mod event;
+
+pub use command::{opcode, Command, LocalVersion};
+pub use event::{AdvertisingReport, Event, LeEvent};
+
+/// Controller index used by monitor packets not related to a controller.
+pub const INDEX_NONE: u16 = HCI_DEV_NONE;
+
+/// Maximum size of an HCI packet including packet indicator.
+const MAX_PACKET_LEN: usize = 1 + 4 + u16::MAX as usize;
+
+/// HCI status code.
+///
+/// A non-zero status code indicates an error.
+/// Refer to the Bluetooth Core Specification, Volume 1, Part F for a list of possible values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Status(pub u8);
+
+impl Status {
+    /// Success.
+    pub const SUCCESS: Self = Self(0x00);
+    /// Unknown HCI command.
+    pub const UNKNOWN_COMMAND: Self = Self(0x01);
+    /// Unknown connection identifier.
+    pub const UNKNOWN_CONNECTION_ID: Self = Self(0x02);
+    /// Hardware failure.
+    pub const HARDWARE_FAILURE: Self = Self(0x03);
+    /// Authentication failure.
+    pub const AUTHENTICATION_FAILURE: Self = Self(0x05);
+    /// Connection timeout.
+    pub const CONNECTION_TIMEOUT: Self = Self(0x08);
+    /// Command disallowed.
+    pub const COMMAND_DISALLOWED: Self = Self(0x0c);
+    /// Unsupported feature or parameter value.
+    pub const UNSUPPORTED_FEATURE: Self = Self(0x11);
+    /// Invalid HCI command parameters.
+    pub const INVALID_PARAMETERS: Self = Self(0x12);
+    /// Remote user terminated connection.
+    pub const REMOTE_USER_TERMINATED: Self = Self(0x13);
+    /// Connection terminated by local host.
+    pub const LOCAL_HOST_TERMINATED: Self = Self(0x16);
+
+    /// Whether the status indicates success.
+    pub const fn is_success(&self) -> bool {
+        self.0 == Self::SUCCESS.0
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HCI command failed with status 0x{:02x}", self.0)
+    }
+}
+
+impl std::error::Error for Status {}
+
+impl From<Status> for Error {
+    fn from(status: Status) -> Self {
+        let kind = match status {
+            Status::UNKNOWN_COMMAND | Status::UNSUPPORTED_FEATURE => ErrorKind::Unsupported,
+            Status::INVALID_PARAMETERS => ErrorKind::InvalidInput,
+            Status::CONNECTION_TIMEOUT => ErrorKind::TimedOut,
+            Status::UNKNOWN_CONNECTION_ID => ErrorKind::NotConnected,
+            _ => ErrorKind::Other,
+        };
+        Error::new(kind, status)
+    }
+}
+
+/// HCI packet type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, FromPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum PacketType {
+    /// Command packet.
+    Command = 0x01,
+    /// ACL data packet.
+    Acl = 0x02,
+    /// Synchronous (SCO) data packet.
+    Sco = 0x03,
+    /// Event packet.
+    Event = 0x04,
+    /// Isochronous (ISO) data packet.
+    Iso = 0x05,
+    /// Vendor diagnostic packet.
+    Vendor = 0xff,
+}
+
+/// HCI packet as exchanged over raw and user channel sockets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Packet {
+    /// Packet type.
+    pub packet_type: PacketType,
+    /// Packet data, excluding the packet type indicator.
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Encodes the packet including its packet type indicator.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(1 + self.data.len());
+        buf.push(self.packet_type as u8);
+        buf.extend_from_slice(&self.data);
+        buf
+    }
+
+    /// Decodes a packet including its packet type indicator.
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        let mut r = Reader::new(buf);
+        let packet_type = num_traits::FromPrimitive::from_u8(r.u8()?)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid HCI packet type"))?;
+        Ok(Self { packet_type, data: r.rest().to_vec() })
+    }
+}
+
+/// An HCI socket bound to the raw or user channel of a controller.
+///
+/// Each sent or received datagram contains one [Packet].
+pub struct Socket {
+    fd: AsyncFd<OwnedFd>,
+}
+
+impl fmt::Debug for Socket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Socket").field("fd", &self.fd.as_raw_fd()).finish()
+    }
+}
+
+impl Socket {
+    /// Opens the raw channel of the controller with the specified index.
+    ///
+    /// The socket receives all HCI events of the controller.
+    /// The controller must be powered on for sending commands.
+    pub fn raw(dev_id: u16) -> Result<Self> {
+        let fd = sock::socket(AF_BLUETOOTH, SOCK_RAW, BTPROTO_HCI)?;
+        let filter = hci_filter { type_mask: 1 << PacketType::Event as u8, event_mask: [u32::MAX; 2], opcode: 0 };
+        sock::setsockopt(&fd, SOL_HCI, HCI_FILTER, &filter)?;
+        sock::bind(&fd, HciSocketAddr { dev: dev_id, channel: HCI_CHANNEL_RAW })?;
+        Ok(Self { fd: AsyncFd::new(fd)? })
+    }
+
+    /// Opens the user channel of the controller with the specified index,
+    /// gaining exclusive access to it.
+    ///
+    /// The controller must be powered off.
+    /// The socket receives all packets sent by the controller.
+    pub fn user(dev_id: u16) -> Result<Self> {
+        let fd = sock::socket(AF_BLUETOOTH, SOCK_RAW, BTPROTO_HCI)?;
+        sock::bind(&fd, HciSocketAddr { dev: dev_id, channel: HCI_CHANNEL_USER })?;
+        Ok(Self { fd: AsyncFd::new(fd)? })
+    }
+
+    /// Sends a packet.
+    pub async fn send(&self, packet: &Packet) -> Result<()> {
+        hci_util::send_datagram(&self.fd, &packet.encode()).await
+    }
+
+    /// Receives a packet.
+    pub async fn recv(&self) -> Result<Packet> {
+        let buf = hci_util::recv_datagram(&self.fd, MAX_PACKET_LEN).await?;
+        Packet::decode(&buf)
+    }
+
+    /// Constructs a new [Socket] from the given raw file descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// This function *consumes ownership* of the specified file descriptor.
+    /// The returned object will take responsibility for closing it when the object goes out of scope.
+    ///
+    /// # Safety
+    /// If the passed file descriptor is invalid, undefined behavior may occur.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(OwnedFd::new(fd))? })
+    }
+}
+
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for Socket {
+    fn into_raw_fd(self) -> RawFd {
+        self.fd.into_inner().into_raw_fd()
+    }
+}
+
+impl FromRawFd for Socket {
+    /// Constructs a new instance of `Self` from the given raw file
+    /// descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// # Panics
+    /// Panics when the conversion fails.
+    /// Use [Socket::from_raw_fd] for a non-panicking variant.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_raw_fd(fd).expect("from_raw_fd failed")
+    }
+}
+
+/// Opcode of a packet received over the monitor channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum MonitorOpcode {
+    /// A controller was added.
+    NewIndex,
+    /// A controller was removed.
+    DelIndex,
+    /// HCI command sent to controller.
+    CommandPkt,
+    /// HCI event received from controller.
+    EventPkt,
+    /// ACL data sent to controller.
+    AclTxPkt,
+    /// ACL data received from controller.
+    AclRxPkt,
+    /// SCO data sent to controller.
+    ScoTxPkt,
+    /// SCO data received from controller.
+    ScoRxPkt,
+    /// A controller was opened.
+    OpenIndex,
+    /// A controller was closed.
+    CloseIndex,
+    /// Controller information.
+    IndexInfo,
+    /// Vendor diagnostic message.
+    VendorDiag,
+    /// System note.
+    SystemNote,
+    /// User logging message.
+    UserLogging,
+    /// Management control socket was opened.
+    CtrlOpen,
+    /// Management control socket was closed.
+    CtrlClose,
+    /// Management command.
+    CtrlCommand,
+    /// Management event.
+    CtrlEvent,
+    /// ISO data sent to controller.
+    IsoTxPkt,
+    /// ISO data received from controller.
+    IsoRxPkt,
+    /// Other opcode.
+    Other(u16),
+}
+
+impl From<u16> for MonitorOpcode {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => Self::NewIndex,
+            1 => Self::DelIndex,
+            2 => Self::CommandPkt,
+            3 => Self::EventPkt,
+            4 => Self::AclTxPkt,
+            5 => Self::AclRxPkt,
+            6 => Self::ScoTxPkt,
+            7 => Self::ScoRxPkt,
+            8 => Self::OpenIndex,
+            9 => Self::CloseIndex,
+            10 => Self::IndexInfo,
+            11 => Self::VendorDiag,
+            12 => Self::SystemNote,
+            13 => Self::UserLogging,
+            14 => Self::CtrlOpen,
+            15 => Self::CtrlClose,
+            16 => Self::CtrlCommand,
+            17 => Self::CtrlEvent,
+            18 => Self::IsoTxPkt,
+            19 => Self::IsoRxPkt,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl From<MonitorOpcode> for u16 {
+    fn from(opcode: MonitorOpcode) -> Self {
+        match opcode {
+            MonitorOpcode::NewIndex => 0,
+            MonitorOpcode::DelIndex => 1,
+            MonitorOpcode::CommandPkt => 2,
+            MonitorOpcode::EventPkt => 3,
+            MonitorOpcode::AclTxPkt => 4,
+            MonitorOpcode::AclRxPkt => 5,
+            MonitorOpcode::ScoTxPkt => 6,
+            MonitorOpcode::ScoRxPkt => 7,
+            MonitorOpcode::OpenIndex => 8,
+            MonitorOpcode::CloseIndex => 9,
+            MonitorOpcode::IndexInfo => 10,
+            MonitorOpcode::VendorDiag => 11,
+            MonitorOpcode::SystemNote => 12,
+            MonitorOpcode::UserLogging => 13,
+            MonitorOpcode::CtrlOpen => 14,
+            MonitorOpcode::CtrlClose => 15,
+            MonitorOpcode::CtrlCommand => 16,
+            MonitorOpcode::CtrlEvent => 17,
+            MonitorOpcode::IsoTxPkt => 18,
+            MonitorOpcode::IsoRxPkt => 19,
+            MonitorOpcode::Other(other) => other,
+        }
+    }
+}
+
+/// Packet received over the monitor channel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MonitorPacket {
+    /// Opcode.
+    pub opcode: MonitorOpcode,
+    /// Controller index or [INDEX_NONE].
+    pub index: u16,
+    /// Packet data.
+    ///
+    /// For HCI packets this excludes the packet type indicator,
+    /// since the packet type is given by the opcode.
+    pub data: Vec<u8>,
+}
+
+impl MonitorPacket {
+    /// Length of monitor header.
+    pub const HEADER_LEN: usize = 6;
+
+    /// Encodes the packet including its monitor header.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let len: u16 =
+            self.data.len().try_into().map_err(|_| Error::new(ErrorKind::InvalidInput, "packet too long"))?;
+        let mut buf = Vec::with_capacity(Self::HEADER_LEN + self.data.len());
+        buf.extend_from_slice(&u16::from(self.opcode).to_le_bytes());
+        buf.extend_from_slice(&self.index.to_le_bytes());
+        buf.extend_from_slice(&len.to_le_bytes());
+        buf.extend_from_slice(&self.data);
+        Ok(buf)
+    }
+
+    /// Decodes a packet including its monitor header.
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        let mut r = Reader::new(buf);
+        let opcode = r.u16()?.into();
+        let index = r.u16()?;
+        let len = r.u16()?;
+        let data = r.bytes(len.into())?.to_vec();
+        r.finish()?;
+        Ok(Self { opcode, index, data })
+    }
+}
+
+/// An HCI socket bound to the monitor channel.
+///
+/// It receives a copy of all HCI traffic of all controllers.
+pub struct Monitor {
+    fd: AsyncFd<OwnedFd>,
+}
+
+impl fmt::Debug for Monitor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Monitor").field("fd", &self.fd.as_raw_fd()).finish()
+    }
+}
+
+impl Monitor {
+    /// Opens the monitor channel.
+    ///
+    /// The kernel first reports all existing controllers using
+    /// [MonitorOpcode::NewIndex] and related packets.
+    pub fn new() -> Result<Self> {
+        let fd = sock::socket(AF_BLUETOOTH, SOCK_RAW, BTPROTO_HCI)?;
+        sock::bind(&fd, HciSocketAddr { dev: HCI_DEV_NONE, channel: HCI_CHANNEL_MONITOR })?;
+        Ok(Self { fd: AsyncFd::new(fd)? })
+    }
+
+    /// Receives a packet.
+    pub async fn recv(&self) -> Result<MonitorPacket> {
+        let buf = hci_util::recv_datagram(&self.fd, MonitorPacket::HEADER_LEN + MAX_PACKET_LEN).await?;
+        MonitorPacket::decode(&buf)
+    }
+
+    /// Constructs a new [Monitor] from the given raw file descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// This function *consumes ownership* of the specified file descriptor.
+    /// The returned object will take responsibility for closing it when the object goes out of scope.
+    ///
+    /// # Safety
+    /// If the passed file descriptor is invalid, undefined behavior may occur.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(OwnedFd::new(fd))? })
+    }
+}
+
+impl AsRawFd for Monitor {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for Monitor {
+    fn into_raw_fd(self) -> RawFd {
+        self.fd.into_inner().into_raw_fd()
+    }
+}
+
+impl FromRawFd for Monitor {
+    /// Constructs a new instance of `Self` from the given raw file
+    /// descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// # Panics
+    /// Panics when the conversion fails.
+    /// Use [Monitor::from_raw_fd] for a non-panicking variant.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_raw_fd(fd).expect("from_raw_fd failed")
+    }
+}
+
+struct ClientInner {
+    socket: Socket,
+    /// Commands are identified by their opcode.
+    commands: CommandDispatcher<u16, Event>,
+}
+
+impl ClientInner {
+    async fn recv_task(self: Arc<Self>) {
+        loop {
+            let packet = match self.socket.recv().await {
+                Ok(packet) => packet,
+                Err(err) => {
+                    log::warn!("Receiving HCI packet failed: {}", &err);
+                    self.commands.fail(err);
+                    break;
+                }
+            };
+            if packet.packet_type != PacketType::Event {
+                continue;
+            }
+
+            let event = match Event::decode(&packet.data) {
+                Ok(event) => event,
+                Err(err) => {
+                    log::warn!("Invalid HCI event: {}", &err);
+                    continue;
+                }
+            };
+            log::trace!("HCI event: {:?}", &event);
+
+            match &event {
+                Event::CommandComplete { opcode, params, .. } => {
+                    let result = match params.split_first() {
+                        Some((&status, rest)) if Status(status).is_success() => Ok(rest.to_vec()),
+                        Some((&status, _)) => Err(Status(status).into()),
+                        None => Ok(Vec::new()),
+                    };
+                    self.commands.complete(*opcode, result);
+                }
+                Event::CommandStatus { status, opcode, .. } => {
+                    let result = match status {
+                        status if status.is_success() => Ok(Vec::new()),
+                        status => Err((*status).into()),
+                    };
+                    self.commands.complete(*opcode, result);
+                }
+                _ => (),
+            }
+
+            self.commands.publish(event);
+        }
+    }
+}
+
+/// HCI client sending commands and receiving events over a raw or user channel socket.
+///
+/// Commands are sent one at a time; concurrent calls are queued.
+pub struct Client {
+    inner: Arc<ClientInner>,
+    recv_task: JoinHandle<()>,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Client").field("socket", &self.inner.socket).finish()
+    }
+}
+
+impl Client {
+    /// Opens the raw channel of the controller with the specified index
+    /// and starts receiving events.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn raw(dev_id: u16) -> Result<Self> {
+        Ok(Self::from_socket(Socket::raw(dev_id)?))
+    }
+
+    /// Opens the user channel of the controller with the specified index
+    /// and starts receiving events.
+    ///
+    /// The controller must be powered off.
+    /// Must be called from within a Tokio runtime.
+    pub fn user(dev_id: u16) -> Result<Self> {
+        Ok(Self::from_socket(Socket::user(dev_id)?))
+    }
+
+    /// Creates a client using the specified HCI socket.
+    pub fn from_socket(socket: Socket) -> Self {
+        let inner = Arc::new(ClientInner { socket, commands: CommandDispatcher::new() });
+        let recv_task = tokio::spawn(inner.clone().recv_task());
+        Self { inner, recv_task }
+    }
+
+    /// Sends a command and waits for its completion.
+    ///
+    /// If the controller completes the command using a command complete event,
+    /// its return parameters excluding the status are returned.
+    /// If the controller acknowledges the command using a command status event,
+    /// empty return parameters are returned; the outcome of the command is then
+    /// reported by a command-specific event, which can be received using [events](Self::events).
+    ///
+    /// A non-zero status is returned as an error containing the [Status].
+    pub async fn send_command(&self, command: &Command) -> Result<Vec<u8>> {
+        let packet = command.to_packet()?;
+        let pending = self.inner.commands.begin(command.opcode()).await;
+
+        log::trace!("HCI command: {:?}", command);
+        self.inner.socket.send(&packet).await?;
+
+        pending.reply("HCI socket closed").await
+    }
+
+    /// Stream of HCI events.
+    ///
+    /// This includes events caused by commands sent by other sockets.
+    pub fn events(&self) -> impl Stream<Item = Event> {
+        self.inner.commands.subscribe()
+    }
+
+    /// Resets the controller.
+    pub async fn reset(&self) -> Result<()> {
+        self.send_command(&Command::Reset).await?;
+        Ok(())
+    }
+
+    /// Reads the version information of the controller.
+    pub async fn read_local_version(&self) -> Result<LocalVersion> {
+        let params = self.send_command(&Command::ReadLocalVersion).await?;
+        LocalVersion::decode(&params)
+    }
+
+    /// Reads the public address of the controller.
+    pub async fn read_bd_addr(&self) -> Result<Address> {
+        let params = self.send_command(&Command::ReadBdAddr).await?;
+        command::decode_bd_addr(&params)
+    }
+
+    /// Reads the local name of the controller.
+    pub async fn read_local_name(&self) -> Result<String> {
+        let params = self.send_command(&Command::ReadLocalName).await?;
+        command::decode_local_name(&params)
+    }
+
+    /// Sets the local name of the controller.
+    pub async fn write_local_name(&self, name: &str) -> Result<()> {
+        self.send_command(&Command::WriteLocalName(name.to_string())).await?;
+        Ok(())
+    }
+
+    /// Reads the received signal strength indication (RSSI) in dBm of the connection
+    /// with the specified handle.
+    pub async fn read_rssi(&self, handle: u16) -> Result<i8> {
+        let params = self.send_command(&Command::ReadRssi { handle }).await?;
+        command::decode_handle_i8(&params)
+    }
+
+    /// Reads the current or maximum transmit power level in dBm of the connection
+    /// with the specified handle.
+    pub async fn read_transmit_power_level(&self, handle: u16, maximum: bool) -> Result<i8> {
+        let params = self.send_command(&Command::ReadTransmitPowerLevel { handle, maximum }).await?;
+        command::decode_handle_i8(&params)
+    }
+
+    /// Reads the transmit power level in dBm used for LE advertising.
+    pub async fn le_read_advertising_channel_tx_power(&self) -> Result<i8> {
+        let params = self.send_command(&Command::LeReadAdvertisingChannelTxPower).await?;
+        command::decode_i8(&params)
+    }
+
+    /// Sets the LE scan parameters.
+    ///
+    /// Interval and window are specified in units of 0.625 ms.
+    pub async fn le_set_scan_parameters(&self, active: bool, interval: u16, window: u16) -> Result<()> {
+        self.send_command(&Command::LeSetScanParameters {
+            active,
+            interval,
+            window,
+            own_address_type: 0,
+            filter_policy: 0,
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Enables or disables LE scanning.
+    ///
+    /// Advertising reports are delivered as [LeEvent::AdvertisingReport] through [events](Self::events).
+    pub async fn le_set_scan_enable(&self, enable: bool, filter_duplicates: bool) -> Result<()> {
+        self.send_command(&Command::LeSetScanEnable { enable, filter_duplicates }).await?;
+        Ok(())
+    }
+
+    /// Terminates the connection with the specified handle.
+    ///
+    /// The termination is reported by an [Event::DisconnectionComplete] event.
+    pub async fn disconnect(&self, handle: u16, reason: Status) -> Result<()> {
+        self.send_command(&Command::Disconnect { handle, reason }).await?;
+        Ok(())
+    }
+
+    /// Sends a vendor-specific command with the specified opcode command field (OCF)
+    /// and returns its return parameters.
+    pub async fn vendor_command(&self, ocf: u16, params: &[u8]) -> Result<Vec<u8>> {
+        self.send_command(&Command::Vendor { ocf, params: params.to_vec() }).await
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.recv_task.abort();
+    }
+}
@@ -0,0 +1,291 @@
+//! Utilities shared by HCI and management sockets.
+
+use futures::{
+    channel::{mpsc, oneshot},
+    lock::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard},
+};
+use libc::AF_BLUETOOTH;
+use std::{
+    io::{Error, ErrorKind, Result},
+    sync::Mutex,
+};
+use tokio::io::{unix::AsyncFd, ReadBuf};
+
+use crate::{
+    sock::{self, OwnedFd},
+    sys::sockaddr_hci,
+    Address, AddressType,
+};
+
+/// Sends a datagram on a non-blocking socket.
+pub(crate) async fn send_datagram(fd: &AsyncFd<OwnedFd>, buf: &[u8]) -> Result<()> {
+    loop {
+        let mut guard = fd.writable().await?;
+        match guard.try_io(|inner| sock::send(inner.get_ref(), buf, 0)) {
+            Ok(result) => {
+                if result? != buf.len() {
+                    return Err(Error::new(ErrorKind::WriteZero, "incomplete datagram sent"));
+                }
+                return Ok(());
+            }
+            Err(_would_block) => continue,
+        }
+    }
+}
+
+/// Receives a datagram of at most the specified size from a non-blocking socket.
+pub(crate) async fn recv_datagram(fd: &AsyncFd<OwnedFd>, max_len: usize) -> Result<Vec<u8>> {
+    let mut buf = vec![0; max_len];
+    let mut read_buf = ReadBuf::new(&mut buf);
+    loop {
+        let mut guard = fd.readable().await?;
+        match guard.try_io(|inner| sock::recv(inner.get_ref(), &mut read_buf, 0)) {
+            Ok(result) => {
+                let n = result?;
+                buf.truncate(n);
+                return Ok(buf);
+            }
+            Err(_would_block) => continue,
+        }
+    }
+}
+
+/// Reader for little-endian encoded HCI and management data.
+pub(crate) struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.buf.len() < len {
+            return Err(Error::new(ErrorKind::InvalidData, "message too short"));
+        }
+        let (data, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(data)
+    }
+
+    pub fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    pub fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn i8(&mut self) -> Result<i8> {
+        Ok(self.u8()? as i8)
+    }
+
+    pub fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub fn u24(&mut self) -> Result<u32> {
+        let [a, b, c] = self.array()?;
+        Ok(u32::from_le_bytes([a, b, c, 0]))
+    }
+
+    pub fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    pub fn address(&mut self) -> Result<Address> {
+        let mut addr: [u8; 6] = self.array()?;
+        addr.reverse();
+        Ok(Address(addr))
+    }
+
+    pub fn address_type(&mut self) -> Result<AddressType> {
+        let value = self.u8()?;
+        num_traits::FromPrimitive::from_u8(value)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid address type"))
+    }
+
+    pub fn string(&mut self, len: usize) -> Result<String> {
+        let data = self.bytes(len)?;
+        let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+        Ok(String::from_utf8_lossy(&data[..end]).into_owned())
+    }
+
+    pub fn rest(&mut self) -> &'a [u8] {
+        let rest = self.buf;
+        self.buf = &[];
+        rest
+    }
+
+    pub fn finish(self) -> Result<()> {
+        if !self.buf.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData, "message too long"));
+        }
+        Ok(())
+    }
+}
+
+/// Writes a Bluetooth address in HCI byte order.
+pub(crate) fn put_address(buf: &mut Vec<u8>, addr: Address) {
+    let mut addr = addr.0;
+    addr.reverse();
+    buf.extend_from_slice(&addr);
+}
+
+/// HCI socket address.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct HciSocketAddr {
+    pub dev: u16,
+    pub channel: u16,
+}
+
+impl sock::SysSockAddr for HciSocketAddr {
+    type SysSockAddr = sockaddr_hci;
+
+    fn into_sys_sock_addr(self) -> Self::SysSockAddr {
+        sockaddr_hci { hci_family: AF_BLUETOOTH as _, hci_dev: self.dev, hci_channel: self.channel }
+    }
+
+    fn try_from_sys_sock_addr(saddr: Self::SysSockAddr) -> Result<Self> {
+        if saddr.hci_family != AF_BLUETOOTH as _ {
+            return Err(Error::new(ErrorKind::InvalidInput, "sockaddr_hci::hci_family is not AF_BLUETOOTH"));
+        }
+        Ok(Self { dev: saddr.hci_dev, channel: saddr.hci_channel })
+    }
+}
+
+/// Sender of the reply to a command.
+type ReplySender = oneshot::Sender<Result<Vec<u8>>>;
+
+/// Command reply and event dispatching shared by the HCI and management clients.
+///
+/// Commands are sent one at a time.
+/// The reply to the command in flight is identified by a key of type `K`.
+pub(crate) struct CommandDispatcher<K, E> {
+    command_lock: AsyncMutex<()>,
+    pending: Mutex<Option<(K, ReplySender)>>,
+    event_txs: Mutex<Vec<mpsc::UnboundedSender<E>>>,
+}
+
+impl<K: PartialEq, E: Clone> CommandDispatcher<K, E> {
+    pub fn new() -> Self {
+        Self { command_lock: AsyncMutex::new(()), pending: Mutex::new(None), event_txs: Mutex::new(Vec::new()) }
+    }
+
+    /// Waits until no other command is in flight and registers for the reply with the specified key.
+    ///
+    /// The registration is removed when the returned [PendingReply] is dropped,
+    /// even if no reply has been received.
+    pub async fn begin(&self, key: K) -> PendingReply<'_, K, E> {
+        let lock = self.command_lock.lock().await;
+        let (tx, rx) = oneshot::channel();
+        *self.pending.lock().unwrap() = Some((key, tx));
+        PendingReply { dispatcher: self, rx, _lock: lock }
+    }
+
+    /// Delivers the reply with the specified key to the command in flight, if it matches.
+    pub fn complete(&self, key: K, result: Result<Vec<u8>>) {
+        let mut pending = self.pending.lock().unwrap();
+        match pending.take() {
+            Some((pending_key, tx)) if pending_key == key => {
+                let _ = tx.send(result);
+            }
+            other => *pending = other,
+        }
+    }
+
+    /// Fails the command in flight and ends all event streams.
+    pub fn fail(&self, err: Error) {
+        if let Some((_, tx)) = self.pending.lock().unwrap().take() {
+            let _ = tx.send(Err(err));
+        }
+        self.event_txs.lock().unwrap().clear();
+    }
+
+    /// Sends an event to all subscribers.
+    pub fn publish(&self, event: E) {
+        self.event_txs.lock().unwrap().retain(|tx| tx.unbounded_send(event.clone()).is_ok());
+    }
+
+    /// Subscribes to events.
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<E> {
+        let (tx, rx) = mpsc::unbounded();
+        self.event_txs.lock().unwrap().push(tx);
+        rx
+    }
+}
+
+/// Registration for the reply to the command in flight.
+pub(crate) struct PendingReply<'a, K, E> {
+    dispatcher: &'a CommandDispatcher<K, E>,
+    rx: oneshot::Receiver<Result<Vec<u8>>>,
+    _lock: AsyncMutexGuard<'a, ()>,
+}
+
+impl<'a, K, E> PendingReply<'a, K, E> {
+    /// Waits for the reply.
+    ///
+    /// `closed` describes the error returned when the socket is closed before the reply arrives.
+    pub async fn reply(mut self, closed: &str) -> Result<Vec<u8>> {
+        (&mut self.rx).await.map_err(|_| Error::new(ErrorKind::ConnectionAborted, closed))?
+    }
+}
+
+impl<'a, K, E> Drop for PendingReply<'a, K, E> {
+    fn drop(&mut self) {
+        // The command lock is still held, thus the pending entry belongs to this command.
+        self.dispatcher.pending.lock().unwrap().take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, FutureExt, StreamExt};
+
+    use super::*;
+
+    #[test]
+    fn reply() {
+        let dispatcher = CommandDispatcher::<u16, u8>::new();
+        block_on(async {
+            let pending = dispatcher.begin(0x0c03).await;
+            dispatcher.complete(0x1001, Ok(vec![1]));
+            dispatcher.complete(0x0c03, Ok(vec![2]));
+            assert_eq!(pending.reply("closed").await.unwrap(), [2]);
+        });
+    }
+
+    #[test]
+    fn dropped_command() {
+        let dispatcher = CommandDispatcher::<u16, u8>::new();
+        block_on(async {
+            let pending = dispatcher.begin(0x0c03).await;
+            assert!(pending.reply("closed").now_or_never().is_none());
+            assert!(dispatcher.pending.lock().unwrap().is_none());
+
+            // The next command can be sent and receives its own reply.
+            let pending = dispatcher.begin(0x0c03).await;
+            dispatcher.complete(0x0c03, Ok(vec![3]));
+            assert_eq!(pending.reply("closed").await.unwrap(), [3]);
+        });
+    }
+
+    #[test]
+    fn fail() {
+        let dispatcher = CommandDispatcher::<u16, u8>::new();
+        let mut events = dispatcher.subscribe();
+        dispatcher.publish(1);
+        block_on(async {
+            assert_eq!(events.next().await, Some(1));
+            let pending = dispatcher.begin(0x0c03).await;
+            dispatcher.fail(Error::new(ErrorKind::BrokenPipe, "broken"));
+            assert_eq!(pending.reply("closed").await.unwrap_err().kind(), ErrorKind::BrokenPipe);
+            assert_eq!(events.next().await, None);
+        });
+    }
+}
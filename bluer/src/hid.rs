@@ -0,0 +1,694 @@
+//! Human Interface Device (HID) over classic Bluetooth.
+//!
+//! The HID profile of remote devices, such as keyboards and mice, is handled by the
+//! Bluetooth daemon. Use [Device::input_reconnect_mode](crate::Device::input_reconnect_mode)
+//! to query the reconnection behavior of a connected remote input device.
+//!
+//! To make the local system act as a HID device, describe it using a [HidDevice]
+//! and register it using [Session::register_hid_device](crate::Session::register_hid_device).
+//! This registers the HID SDP record and listens on the HIDP control and interrupt
+//! L2CAP channels. Accepted [connections](HidConnection) are then used to send
+//! [input reports](InputReport) to the host.
+//!
+//! The input plugin of the Bluetooth daemon also listens on the HIDP channels.
+//! It must be disabled, for example by starting the daemon with `--noplugin=input`,
+//! for the HID device role to work.
+//! Listening on the HIDP channels requires the `CAP_NET_BIND_SERVICE` capability.
+
+use std::{
+    fmt,
+    io::{Error, ErrorKind, Result},
+};
+use strum::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::{
+    l2cap::{SeqPacket, SeqPacketListener, SocketAddr},
+    rfcomm::{Profile, ProfileHandle, Role},
+    Address, AddressType, Session, UuidExt,
+};
+
+pub(crate) const INPUT_INTERFACE: &str = "org.bluez.Input1";
+
+/// PSM of the HIDP control channel.
+pub const PSM_CONTROL: u16 = 0x11;
+
+/// PSM of the HIDP interrupt channel.
+pub const PSM_INTERRUPT: u16 = 0x13;
+
+/// Service class UUID of the HID profile.
+pub fn service_uuid() -> Uuid {
+    Uuid::from_u16(0x1124)
+}
+
+/// Reconnection behavior of a remote input device.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReconnectMode {
+    /// Device and host are not required to automatically restore the connection.
+    #[strum(serialize = "none")]
+    None,
+    /// Bluetooth HID host restores the connection.
+    #[strum(serialize = "host")]
+    Host,
+    /// Bluetooth HID device restores the connection.
+    #[strum(serialize = "device")]
+    Device,
+    /// Bluetooth HID device shall attempt to restore the lost connection,
+    /// but Bluetooth HID host may also restore the connection.
+    #[strum(serialize = "any")]
+    Any,
+}
+
+/// HID device subclass, as used in the SDP record and class of device.
+pub mod subclass {
+    /// Keyboard.
+    pub const KEYBOARD: u8 = 0x40;
+    /// Pointing device, such as a mouse.
+    pub const POINTING: u8 = 0x80;
+    /// Combined keyboard and pointing device.
+    pub const COMBO: u8 = 0xc0;
+    /// Remote control.
+    pub const REMOTE_CONTROL: u8 = 0x0c;
+}
+
+/// Report descriptor of a standard keyboard using report id 1, matching [KeyboardReport].
+pub const KEYBOARD_REPORT_DESCRIPTOR: &[u8] = &[
+    0x05, 0x01, // Usage Page (Generic Desktop)
+    0x09, 0x06, // Usage (Keyboard)
+    0xa1, 0x01, // Collection (Application)
+    0x85, 0x01, //   Report ID (1)
+    0x05, 0x07, //   Usage Page (Key Codes)
+    0x19, 0xe0, //   Usage Minimum (224)
+    0x29, 0xe7, //   Usage Maximum (231)
+    0x15, 0x00, //   Logical Minimum (0)
+    0x25, 0x01, //   Logical Maximum (1)
+    0x75, 0x01, //   Report Size (1)
+    0x95, 0x08, //   Report Count (8)
+    0x81, 0x02, //   Input (Data, Variable, Absolute)
+    0x95, 0x01, //   Report Count (1)
+    0x75, 0x08, //   Report Size (8)
+    0x81, 0x01, //   Input (Constant)
+    0x95, 0x05, //   Report Count (5)
+    0x75, 0x01, //   Report Size (1)
+    0x05, 0x08, //   Usage Page (LEDs)
+    0x19, 0x01, //   Usage Minimum (1)
+    0x29, 0x05, //   Usage Maximum (5)
+    0x91, 0x02, //   Output (Data, Variable, Absolute)
+    0x95, 0x01, //   Report Count (1)
+    0x75, 0x03, //   Report Size (3)
+    0x91, 0x01, //   Output (Constant)
+    0x95, 0x06, //   Report Count (6)
+    0x75, 0x08, //   Report Size (8)
+    0x15, 0x00, //   Logical Minimum (0)
+    0x25, 0x65, //   Logical Maximum (101)
+    0x05, 0x07, //   Usage Page (Key Codes)
+    0x19, 0x00, //   Usage Minimum (0)
+    0x29, 0x65, //   Usage Maximum (101)
+    0x81, 0x00, //   Input (Data, Array)
+    0xc0, // End Collection
+];
+
+/// Report descriptor of a three-button mouse with wheel using report id 2, matching [MouseReport].
+pub const MOUSE_REPORT_DESCRIPTOR: &[u8] = &[
+    0x05, 0x01, // Usage Page (Generic Desktop)
+    0x09, 0x02, // Usage (Mouse)
+    0xa1, 0x01, // Collection (Application)
+    0x85, 0x02, //   Report ID (2)
+    0x09, 0x01, //   Usage (Pointer)
+    0xa1, 0x00, //   Collection (Physical)
+    0x05, 0x09, //     Usage Page (Buttons)
+    0x19, 0x01, //     Usage Minimum (1)
+    0x29, 0x03, //     Usage Maximum (3)
+    0x15, 0x00, //     Logical Minimum (0)
+    0x25, 0x01, //     Logical Maximum (1)
+    0x95, 0x03, //     Report Count (3)
+    0x75, 0x01, //     Report Size (1)
+    0x81, 0x02, //     Input (Data, Variable, Absolute)
+    0x95, 0x01, //     Report Count (1)
+    0x75, 0x05, //     Report Size (5)
+    0x81, 0x01, //     Input (Constant)
+    0x05, 0x01, //     Usage Page (Generic Desktop)
+    0x09, 0x30, //     Usage (X)
+    0x09, 0x31, //     Usage (Y)
+    0x09, 0x38, //     Usage (Wheel)
+    0x15, 0x81, //     Logical Minimum (-127)
+    0x25, 0x7f, //     Logical Maximum (127)
+    0x75, 0x08, //     Report Size (8)
+    0x95, 0x03, //     Report Count (3)
+    0x81, 0x06, //     Input (Data, Variable, Relative)
+    0xc0, //   End Collection
+    0xc0, // End Collection
+];
+
+/// An input report that can be sent to the host.
+pub trait InputReport {
+    /// Report id, if the report descriptor uses report ids.
+    fn report_id(&self) -> Option<u8>;
+
+    /// Report data, excluding the report id.
+    fn data(&self) -> Vec<u8>;
+}
+
+/// Input report of a keyboard described by [KEYBOARD_REPORT_DESCRIPTOR].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct KeyboardReport {
+    /// Modifier keys bit mask.
+    ///
+    /// Bit 0 is left control, bit 7 is right GUI.
+    pub modifiers: u8,
+    /// Usage ids of up to six pressed keys; unused entries are zero.
+    pub keys: [u8; 6],
+}
+
+impl InputReport for KeyboardReport {
+    fn report_id(&self) -> Option<u8> {
+        Some(1)
+    }
+
+    fn data(&self) -> Vec<u8> {
+        let mut data = vec![self.modifiers, 0];
+        data.extend_from_slice(&self.keys);
+        data
+    }
+}
+
+/// Input report of a mouse described by [MOUSE_REPORT_DESCRIPTOR].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct MouseReport {
+    /// Pressed buttons bit mask.
+    ///
+    /// Bit 0 is the left, bit 1 the right and bit 2 the middle button.
+    pub buttons: u8,
+    /// Relative horizontal movement.
+    pub x: i8,
+    /// Relative vertical movement.
+    pub y: i8,
+    /// Relative wheel movement.
+    pub wheel: i8,
+}
+
+impl InputReport for MouseReport {
+    fn report_id(&self) -> Option<u8> {
+        Some(2)
+    }
+
+    fn data(&self) -> Vec<u8> {
+        vec![self.buttons, self.x as u8, self.y as u8, self.wheel as u8]
+    }
+}
+
+/// Raw input report with arbitrary data.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct RawReport {
+    /// Report id.
+    pub id: Option<u8>,
+    /// Report data.
+    pub data: Vec<u8>,
+}
+
+impl InputReport for RawReport {
+    fn report_id(&self) -> Option<u8> {
+        self.id
+    }
+
+    fn data(&self) -> Vec<u8> {
+        self.data.clone()
+    }
+}
+
+/// HIDP message type, contained in the upper nibble of the transaction header.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum MessageType {
+    /// Handshake.
+    Handshake,
+    /// HID control.
+    HidControl,
+    /// Get report request.
+    GetReport,
+    /// Set report request.
+    SetReport,
+    /// Get protocol request.
+    GetProtocol,
+    /// Set protocol request.
+    SetProtocol,
+    /// Data.
+    Data,
+    /// Other message type.
+    Other(u8),
+}
+
+impl From<u8> for MessageType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x0 => Self::Handshake,
+            0x1 => Self::HidControl,
+            0x4 => Self::GetReport,
+            0x5 => Self::SetReport,
+            0x6 => Self::GetProtocol,
+            0x7 => Self::SetProtocol,
+            0xa => Self::Data,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl From<MessageType> for u8 {
+    fn from(value: MessageType) -> Self {
+        match value {
+            MessageType::Handshake => 0x0,
+            MessageType::HidControl => 0x1,
+            MessageType::GetReport => 0x4,
+            MessageType::SetReport => 0x5,
+            MessageType::GetProtocol => 0x6,
+            MessageType::SetProtocol => 0x7,
+            MessageType::Data => 0xa,
+            MessageType::Other(other) => other,
+        }
+    }
+}
+
+/// HIDP report type, contained in the parameter of report-related messages.
+pub mod report_type {
+    /// Other report.
+    pub const OTHER: u8 = 0x0;
+    /// Input report.
+    pub const INPUT: u8 = 0x1;
+    /// Output report.
+    pub const OUTPUT: u8 = 0x2;
+    /// Feature report.
+    pub const FEATURE: u8 = 0x3;
+}
+
+/// HIDP handshake result code.
+pub mod handshake {
+    /// Successful.
+    pub const SUCCESSFUL: u8 = 0x0;
+    /// Device not ready.
+    pub const NOT_READY: u8 = 0x1;
+    /// Invalid report id.
+    pub const ERR_INVALID_REPORT_ID: u8 = 0x2;
+    /// Unsupported request.
+    pub const ERR_UNSUPPORTED_REQUEST: u8 = 0x3;
+    /// Invalid parameter.
+    pub const ERR_INVALID_PARAMETER: u8 = 0x4;
+    /// Unknown error.
+    pub const ERR_UNKNOWN: u8 = 0xe;
+    /// Fatal error.
+    pub const ERR_FATAL: u8 = 0xf;
+}
+
+/// HIDP message.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Message {
+    /// Message type.
+    pub message_type: MessageType,
+    /// Message type specific parameter, contained in the lower nibble of the transaction header.
+    pub parameter: u8,
+    /// Payload following the transaction header.
+    pub payload: Vec<u8>,
+}
+
+impl Message {
+    /// Creates a message.
+    pub fn new(message_type: MessageType, parameter: u8, payload: Vec<u8>) -> Self {
+        Self { message_type, parameter, payload }
+    }
+
+    /// Parses a message from a received packet.
+    pub fn from_bytes(buf: &[u8]) -> Result<Self> {
+        let (&header, payload) =
+            buf.split_first().ok_or_else(|| Error::new(ErrorKind::InvalidData, "empty HIDP message"))?;
+        Ok(Self { message_type: (header >> 4).into(), parameter: header & 0x0f, payload: payload.to_vec() })
+    }
+
+    /// Encodes the message into a packet.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.payload.len() + 1);
+        buf.push((u8::from(self.message_type) << 4) | (self.parameter & 0x0f));
+        buf.extend_from_slice(&self.payload);
+        buf
+    }
+}
+
+/// Definition of a local HID device.
+///
+/// Use [Session::register_hid_device](crate::Session::register_hid_device) to register it.
+#[derive(Clone, Debug)]
+pub struct HidDevice {
+    /// Service name.
+    pub name: String,
+    /// Service description.
+    pub description: String,
+    /// Service provider name.
+    pub provider: String,
+    /// HID report descriptor.
+    pub report_descriptor: Vec<u8>,
+    /// Device subclass, see [subclass].
+    pub subclass: u8,
+    /// Country code of localized hardware, 0 if not localized.
+    pub country_code: u8,
+    /// Whether the device supports virtual cables.
+    pub virtual_cable: bool,
+    /// Whether the device initiates reconnections to the host.
+    pub reconnect_initiate: bool,
+    /// Whether the device accepts connections from the host when not connected.
+    pub normally_connectable: bool,
+    /// Whether the device supports the boot protocol.
+    pub boot_device: bool,
+    /// Pairing is required before connections will be established.
+    pub require_authentication: bool,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Default for HidDevice {
+    fn default() -> Self {
+        Self {
+            name: "BlueR HID device".to_string(),
+            description: String::new(),
+            provider: "BlueR".to_string(),
+            report_descriptor: Vec::new(),
+            subclass: 0,
+            country_code: 0,
+            virtual_cable: true,
+            reconnect_initiate: true,
+            normally_connectable: false,
+            boot_device: false,
+            require_authentication: true,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl HidDevice {
+    /// SDP record of the HID service in the XML format used by the Bluetooth daemon.
+    pub fn service_record(&self) -> String {
+        let bool_str = |b: bool| if b { "true" } else { "false" };
+        let descriptor: String = self.report_descriptor.iter().map(|b| format!("{b:02x}")).collect();
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+<record>
+  <attribute id="0x0001">
+    <sequence><uuid value="0x1124" /></sequence>
+  </attribute>
+  <attribute id="0x0004">
+    <sequence>
+      <sequence><uuid value="0x0100" /><uint16 value="0x{PSM_CONTROL:04x}" /></sequence>
+      <sequence><uuid value="0x0011" /></sequence>
+    </sequence>
+  </attribute>
+  <attribute id="0x0005">
+    <sequence><uuid value="0x1002" /></sequence>
+  </attribute>
+  <attribute id="0x0006">
+    <sequence><uint16 value="0x656e" /><uint16 value="0x006a" /><uint16 value="0x0100" /></sequence>
+  </attribute>
+  <attribute id="0x0009">
+    <sequence><sequence><uuid value="0x1124" /><uint16 value="0x0101" /></sequence></sequence>
+  </attribute>
+  <attribute id="0x000d">
+    <sequence>
+      <sequence>
+        <sequence><uuid value="0x0100" /><uint16 value="0x{PSM_INTERRUPT:04x}" /></sequence>
+        <sequence><uuid value="0x0011" /></sequence>
+      </sequence>
+    </sequence>
+  </attribute>
+  <attribute id="0x0100"><text value="{name}" /></attribute>
+  <attribute id="0x0101"><text value="{description}" /></attribute>
+  <attribute id="0x0102"><text value="{provider}" /></attribute>
+  <attribute id="0x0201"><uint16 value="0x0111" /></attribute>
+  <attribute id="0x0202"><uint8 value="0x{subclass:02x}" /></attribute>
+  <attribute id="0x0203"><uint8 value="0x{country_code:02x}" /></attribute>
+  <attribute id="0x0204"><boolean value="{virtual_cable}" /></attribute>
+  <attribute id="0x0205"><boolean value="{reconnect_initiate}" /></attribute>
+  <attribute id="0x0206">
+    <sequence><sequence><uint8 value="0x22" /><text encoding="hex" value="{descriptor}" /></sequence></sequence>
+  </attribute>
+  <attribute id="0x0207">
+    <sequence><sequence><uint16 value="0x0409" /><uint16 value="0x0100" /></sequence></sequence>
+  </attribute>
+  <attribute id="0x020b"><uint16 value="0x0100" /></attribute>
+  <attribute id="0x020c"><uint16 value="0x0c80" /></attribute>
+  <attribute id="0x020d"><boolean value="{normally_connectable}" /></attribute>
+  <attribute id="0x020e"><boolean value="{boot_device}" /></attribute>
+</record>
+"#,
+            name = xml_escape(&self.name),
+            description = xml_escape(&self.description),
+            provider = xml_escape(&self.provider),
+            subclass = self.subclass,
+            country_code = self.country_code,
+            virtual_cable = bool_str(self.virtual_cable),
+            reconnect_initiate = bool_str(self.reconnect_initiate),
+            normally_connectable = bool_str(self.normally_connectable),
+            boot_device = bool_str(self.boot_device),
+        )
+    }
+
+    fn profile(&self) -> Profile {
+        Profile {
+            uuid: service_uuid(),
+            name: Some(self.name.clone()),
+            role: Some(Role::Server),
+            require_authentication: Some(self.require_authentication),
+            require_authorization: Some(false),
+            auto_connect: Some(false),
+            service_record: Some(self.service_record()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Handle to a registered local HID device.
+///
+/// Use [accept](Self::accept) to accept connections from hosts.
+/// Drop to unregister the SDP record and stop listening.
+pub struct HidDeviceHandle {
+    profile: ProfileHandle,
+    control: SeqPacketListener,
+    interrupt: SeqPacketListener,
+}
+
+impl fmt::Debug for HidDeviceHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HidDeviceHandle {{ {:?} }}", &self.profile)
+    }
+}
+
+impl HidDeviceHandle {
+    pub(crate) async fn register(session: &Session, device: HidDevice) -> crate::Result<Self> {
+        let sa = |psm| SocketAddr::new(Address::any(), AddressType::BrEdr, psm);
+        let control = SeqPacketListener::bind(sa(PSM_CONTROL)).await?;
+        let interrupt = SeqPacketListener::bind(sa(PSM_INTERRUPT)).await?;
+        let profile = session.register_profile(device.profile()).await?;
+        Ok(Self { profile, control, interrupt })
+    }
+
+    /// Accepts a connection from a host.
+    ///
+    /// The host first connects the control channel and then the interrupt channel.
+    pub async fn accept(&self) -> Result<HidConnection> {
+        loop {
+            let (control, control_sa) = self.control.accept().await?;
+            let (interrupt, interrupt_sa) = self.interrupt.accept().await?;
+            if control_sa.addr == interrupt_sa.addr {
+                return Ok(HidConnection { control, interrupt, peer: control_sa.addr });
+            }
+            log::warn!(
+                "HIDP control channel from {} and interrupt channel from {} do not match",
+                control_sa.addr,
+                interrupt_sa.addr
+            );
+        }
+    }
+}
+
+/// Connection of a local HID device to a host.
+#[derive(Debug)]
+pub struct HidConnection {
+    control: SeqPacket,
+    interrupt: SeqPacket,
+    peer: Address,
+}
+
+impl HidConnection {
+    /// Connects to a host that the local HID device has been paired with.
+    ///
+    /// This is used to reconnect when [HidDevice::reconnect_initiate] is set.
+    pub async fn connect(host: Address) -> Result<Self> {
+        let control = SeqPacket::connect(SocketAddr::new(host, AddressType::BrEdr, PSM_CONTROL)).await?;
+        let interrupt = SeqPacket::connect(SocketAddr::new(host, AddressType::BrEdr, PSM_INTERRUPT)).await?;
+        Ok(Self { control, interrupt, peer: host })
+    }
+
+    /// Address of the connected host.
+    pub fn peer(&self) -> Address {
+        self.peer
+    }
+
+    /// The control channel socket.
+    pub fn control(&self) -> &SeqPacket {
+        &self.control
+    }
+
+    /// The interrupt channel socket.
+    pub fn interrupt(&self) -> &SeqPacket {
+        &self.interrupt
+    }
+
+    /// Sends an input report over the interrupt channel.
+    pub async fn send_input_report(&self, report: &impl InputReport) -> Result<()> {
+        let mut payload = Vec::new();
+        payload.extend(report.report_id());
+        payload.extend(report.data());
+        let msg = Message::new(MessageType::Data, report_type::INPUT, payload);
+        self.interrupt.send(&msg.to_bytes()).await?;
+        Ok(())
+    }
+
+    /// Receives a message from the interrupt channel, such as an output report.
+    pub async fn recv_interrupt(&self) -> Result<Message> {
+        let mut buf = vec![0; self.interrupt.recv_mtu()?];
+        let n = self.interrupt.recv(&mut buf).await?;
+        Message::from_bytes(&buf[..n])
+    }
+
+    /// Receives a request from the control channel.
+    pub async fn recv_control(&self) -> Result<Message> {
+        let mut buf = vec![0; self.control.recv_mtu()?];
+        let n = self.control.recv(&mut buf).await?;
+        Message::from_bytes(&buf[..n])
+    }
+
+    /// Sends a message over the control channel, for example a reply to a request.
+    pub async fn send_control(&self, msg: &Message) -> Result<()> {
+        self.control.send(&msg.to_bytes()).await?;
+        Ok(())
+    }
+
+    /// Sends a handshake with the specified [result code](handshake) over the control channel.
+    pub async fn send_handshake(&self, result: u8) -> Result<()> {
+        self.send_control(&Message::new(MessageType::Handshake, result, Vec::new())).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_messages() {
+        let msg = Message::from_bytes(&[0x00]).unwrap();
+        assert_eq!(msg, Message::new(MessageType::Handshake, handshake::SUCCESSFUL, Vec::new()));
+        let msg = Message::from_bytes(&[0x03]).unwrap();
+        assert_eq!(msg, Message::new(MessageType::Handshake, handshake::ERR_UNSUPPORTED_REQUEST, Vec::new()));
+
+        // GET_REPORT for input report 1 with buffer size 0x0040 (size flag is bit 3).
+        let msg = Message::from_bytes(&[0x49, 0x01, 0x40, 0x00]).unwrap();
+        assert_eq!(msg.message_type, MessageType::GetReport);
+        assert_eq!(msg.parameter & 0x03, report_type::INPUT);
+        assert_eq!(msg.parameter & 0x08, 0x08);
+        assert_eq!(msg.payload, [0x01, 0x40, 0x00]);
+
+        // SET_REPORT of output report 1 turning on the caps lock LED.
+        let msg = Message::from_bytes(&[0x52, 0x01, 0x02]).unwrap();
+        assert_eq!(msg, Message::new(MessageType::SetReport, report_type::OUTPUT, vec![0x01, 0x02]));
+
+        // DATA on the interrupt channel carrying an output report.
+        let msg = Message::from_bytes(&[0xa2, 0x01, 0x01]).unwrap();
+        assert_eq!(msg, Message::new(MessageType::Data, report_type::OUTPUT, vec![0x01, 0x01]));
+
+        let msg = Message::from_bytes(&[0x31]).unwrap();
+        assert_eq!(msg.message_type, MessageType::Other(0x3));
+        assert_eq!(msg.parameter, 0x1);
+
+        assert_eq!(Message::from_bytes(&[]).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn encode_messages() {
+        assert_eq!(Message::new(MessageType::Handshake, handshake::SUCCESSFUL, Vec::new()).to_bytes(), [0x00]);
+        assert_eq!(
+            Message::new(MessageType::Handshake, handshake::ERR_INVALID_REPORT_ID, Vec::new()).to_bytes(),
+            [0x02]
+        );
+        assert_eq!(
+            Message::new(MessageType::GetReport, report_type::FEATURE, vec![0x03]).to_bytes(),
+            [0x43, 0x03]
+        );
+        assert_eq!(
+            Message::new(MessageType::SetReport, report_type::OUTPUT, vec![0x01, 0x02]).to_bytes(),
+            [0x52, 0x01, 0x02]
+        );
+        assert_eq!(
+            Message::new(MessageType::Data, report_type::INPUT, vec![0x02, 0x01]).to_bytes(),
+            [0xa1, 0x02, 0x01]
+        );
+        // Only the lower nibble of the parameter is encoded.
+        assert_eq!(Message::new(MessageType::HidControl, 0x15, Vec::new()).to_bytes(), [0x15]);
+        assert_eq!(Message::new(MessageType::Other(0x3), 0x0, Vec::new()).to_bytes(), [0x30]);
+
+        for header in 0..=0xffu8 {
+            assert_eq!(Message::from_bytes(&[header, 0xaa]).unwrap().to_bytes(), [header, 0xaa]);
+        }
+    }
+
+    #[test]
+    fn report_data() {
+        // Left shift with the keys 'a' and 'b' pressed.
+        let report = KeyboardReport { modifiers: 0x02, keys: [0x04, 0x05, 0, 0, 0, 0] };
+        assert_eq!(report.report_id(), Some(1));
+        assert_eq!(report.data(), [0x02, 0x00, 0x04, 0x05, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(KeyboardReport::default().data(), [0; 8]);
+
+        let report = MouseReport { buttons: 0x01, x: -1, y: 5, wheel: -128 };
+        assert_eq!(report.report_id(), Some(2));
+        assert_eq!(report.data(), [0x01, 0xff, 0x05, 0x80]);
+
+        let report = RawReport { id: None, data: vec![0x10, 0x20] };
+        assert_eq!(report.report_id(), None);
+        assert_eq!(report.data(), [0x10, 0x20]);
+    }
+
+    #[test]
+    fn service_record() {
+        let device = HidDevice {
+            name: "Keys & <Mice>".to_string(),
+            report_descriptor: vec![0x05, 0x01, 0x09, 0x06, 0xa1, 0x01, 0xc0],
+            subclass: subclass::COMBO,
+            boot_device: true,
+            ..Default::default()
+        };
+        let record = device.service_record();
+
+        assert!(record.contains(r#"<uint8 value="0x22" /><text encoding="hex" value="05010906a101c0" />"#));
+        assert!(record.contains(r#"<uuid value="0x0100" /><uint16 value="0x0011" />"#));
+        assert!(record.contains(r#"<uuid value="0x0100" /><uint16 value="0x0013" />"#));
+        assert!(record.contains(r#"<attribute id="0x0100"><text value="Keys &amp; &lt;Mice&gt;" /></attribute>"#));
+        assert!(record.contains(r#"<attribute id="0x0202"><uint8 value="0xc0" /></attribute>"#));
+        assert!(record.contains(r#"<attribute id="0x020d"><boolean value="false" /></attribute>"#));
+        assert!(record.contains(r#"<attribute id="0x020e"><boolean value="true" /></attribute>"#));
+
+        let record = HidDevice { report_descriptor: KEYBOARD_REPORT_DESCRIPTOR.to_vec(), ..Default::default() }
+            .service_record();
+        let descriptor: String = KEYBOARD_REPORT_DESCRIPTOR.iter().map(|b| format!("{b:02x}")).collect();
+        assert!(record.contains(&format!(r#"<text encoding="hex" value="{descriptor}" />"#)));
+    }
+}
@@ -0,0 +1,666 @@
+//! Isochronous channel (ISO) sockets for LE Audio.
+//!
+//! ISO sockets transfer time-bounded data, such as audio, over Bluetooth Low Energy.
+//! Two kinds of isochronous streams are supported:
+//!
+//!   1. Connected isochronous streams (CIS) between two connected devices.
+//!      Use [Stream::connect] to establish a CIS as central and a [Listener] to
+//!      accept a CIS as peripheral.
+//!   2. Broadcast isochronous streams (BIS) from one broadcast source to any number of sinks.
+//!      Use [Stream::broadcast] to start a broadcast source and a [Listener] bound
+//!      to a [broadcast address](BroadcastAddr) to synchronize to a broadcast as sink.
+//!
+//! The parameters of an isochronous stream are configured using [UnicastQos] for
+//! CIS and [BroadcastQos] for BIS; they must be set before connecting or listening.
+//! Each packet sent or received corresponds to one service data unit (SDU),
+//! which must not exceed the maximum SDU size configured in the QoS.
+//!
+//! ISO socket support in the Linux kernel is currently an experimental feature.
+//! It must be enabled by setting
+//! `KernelExperimental = 6fbaf188-05e0-496a-9885-d6ddfdb4e03e`
+//! in the configuration file of the Bluetooth daemon (`/etc/bluetooth/main.conf`).
+//!
+
+use crate::{
+    sock::{self, OwnedFd},
+    sys::{
+        bt_iso_qos, sockaddr_iso, sockaddr_iso_bc, BASE_MAX_LENGTH, BTPROTO_ISO, BT_DEFER_SETUP, BT_ISO_BASE,
+        BT_ISO_QOS, ISO_MAX_NUM_BIS, SOCKADDR_ISO_SIZE,
+    },
+    Address, AddressType,
+};
+use futures::ready;
+use libc::{
+    AF_BLUETOOTH, EAGAIN, EINPROGRESS, MSG_PEEK, SHUT_RD, SHUT_RDWR, SHUT_WR, SOCK_SEQPACKET, SOL_BLUETOOTH,
+    SOL_SOCKET, SO_ERROR, SO_RCVBUF, TIOCINQ, TIOCOUTQ,
+};
+use num_traits::FromPrimitive;
+use std::{
+    convert::TryInto,
+    fmt,
+    io::{Error, ErrorKind, Result},
+    mem::{size_of, zeroed, ManuallyDrop},
+    net::Shutdown,
+    os::{
+        raw::c_int,
+        unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
+    },
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::io::{unix::AsyncFd, AsyncRead, AsyncWrite, ReadBuf};
+
+pub use crate::sys::{
+    bt_iso_bcast_qos as BroadcastQos, bt_iso_io_qos as IoQos, bt_iso_ucast_qos as UnicastQos,
+    BT_ISO_QOS_BIG_UNSET as BIG_UNSET, BT_ISO_QOS_BIS_UNSET as BIS_UNSET, BT_ISO_QOS_CIG_UNSET as CIG_UNSET,
+    BT_ISO_QOS_CIS_UNSET as CIS_UNSET,
+};
+
+/// Possible bit values for [IoQos::phy].
+pub mod phy {
+    pub use crate::sys::{BT_ISO_PHY_1M as LE_1M, BT_ISO_PHY_2M as LE_2M, BT_ISO_PHY_CODED as LE_CODED};
+}
+
+/// Maximum number of broadcast isochronous streams (BIS) in a [BroadcastAddr].
+pub const MAX_NUM_BIS: usize = ISO_MAX_NUM_BIS;
+
+/// Maximum length of the broadcast audio source endpoint (BASE) data.
+pub const MAX_BASE_LEN: usize = BASE_MAX_LENGTH;
+
+/// Address of a broadcast source, specifying the broadcast isochronous streams (BIS)
+/// to synchronize to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BroadcastAddr {
+    /// Address of the broadcast source.
+    pub addr: Address,
+    /// Address type of the broadcast source.
+    pub addr_type: AddressType,
+    /// Advertising set identifier (SID) of the periodic advertising of the broadcast source.
+    pub sid: u8,
+    num_bis: u8,
+    bis: [u8; MAX_NUM_BIS],
+}
+
+impl BroadcastAddr {
+    /// Creates a new broadcast address.
+    ///
+    /// `bis` specifies the indices of the broadcast isochronous streams to synchronize to.
+    /// At most [MAX_NUM_BIS] indices may be specified.
+    pub fn new(addr: Address, addr_type: AddressType, sid: u8, bis: &[u8]) -> Result<Self> {
+        if bis.len() > MAX_NUM_BIS {
+            return Err(Error::new(ErrorKind::InvalidInput, "too many BIS indices"));
+        }
+        let mut bis_array = [0; MAX_NUM_BIS];
+        bis_array[..bis.len()].copy_from_slice(bis);
+        Ok(Self { addr, addr_type, sid, num_bis: bis.len() as _, bis: bis_array })
+    }
+
+    /// Indices of the broadcast isochronous streams.
+    pub fn bis(&self) -> &[u8] {
+        &self.bis[..self.num_bis.into()]
+    }
+}
+
+/// An ISO socket address.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SocketAddr {
+    /// Device address.
+    ///
+    /// When listening or binding, specify [Address::any] for any local adapter address.
+    ///
+    /// When connecting, specify [Address::any] to start a broadcast source.
+    pub addr: Address,
+    /// Device address type.
+    pub addr_type: AddressType,
+    /// Broadcast source address.
+    ///
+    /// When listening, specify the broadcast source to synchronize to.
+    pub broadcast: Option<BroadcastAddr>,
+}
+
+impl SocketAddr {
+    /// Creates a new ISO socket address.
+    pub const fn new(addr: Address, addr_type: AddressType) -> Self {
+        Self { addr, addr_type, broadcast: None }
+    }
+
+    /// Creates a new ISO socket address for synchronizing to the specified broadcast source.
+    ///
+    /// `addr` and `addr_type` specify the local address.
+    pub const fn new_broadcast(addr: Address, addr_type: AddressType, broadcast: BroadcastAddr) -> Self {
+        Self { addr, addr_type, broadcast: Some(broadcast) }
+    }
+
+    /// When specified to [Socket::bind] binds to any public, local adapter address.
+    ///
+    /// When specified to [Socket::connect] starts a broadcast source.
+    pub const fn any_le() -> Self {
+        Self { addr: Address::any(), addr_type: AddressType::LePublic, broadcast: None }
+    }
+}
+
+impl sock::SysSockAddr for SocketAddr {
+    type SysSockAddr = sockaddr_iso;
+
+    const MIN_SYS_SOCK_ADDR_LEN: usize = SOCKADDR_ISO_SIZE;
+
+    fn sys_sock_addr_len(&self) -> usize {
+        match self.broadcast {
+            Some(_) => size_of::<sockaddr_iso>(),
+            None => SOCKADDR_ISO_SIZE,
+        }
+    }
+
+    fn into_sys_sock_addr(self) -> Self::SysSockAddr {
+        let iso_bc = match self.broadcast {
+            Some(bc) => sockaddr_iso_bc {
+                bc_bdaddr: bc.addr.into(),
+                bc_bdaddr_type: bc.addr_type as _,
+                bc_sid: bc.sid,
+                bc_num_bis: bc.num_bis,
+                bc_bis: bc.bis,
+            },
+            None => sockaddr_iso_bc {
+                bc_bdaddr: Default::default(),
+                bc_bdaddr_type: 0,
+                bc_sid: 0,
+                bc_num_bis: 0,
+                bc_bis: [0; MAX_NUM_BIS],
+            },
+        };
+        sockaddr_iso {
+            iso_family: AF_BLUETOOTH as _,
+            iso_bdaddr: self.addr.into(),
+            iso_bdaddr_type: self.addr_type as _,
+            iso_bc,
+        }
+    }
+
+    fn try_from_sys_sock_addr(saddr: Self::SysSockAddr) -> Result<Self> {
+        if saddr.iso_family != AF_BLUETOOTH as _ {
+            return Err(Error::new(ErrorKind::InvalidInput, "sockaddr_iso::iso_family is not AF_BLUETOOTH"));
+        }
+        let broadcast = if saddr.iso_bc.bc_num_bis != 0 {
+            if usize::from(saddr.iso_bc.bc_num_bis) > MAX_NUM_BIS {
+                return Err(Error::new(ErrorKind::InvalidInput, "invalid sockaddr_iso_bc::bc_num_bis"));
+            }
+            Some(BroadcastAddr {
+                addr: Address::from(saddr.iso_bc.bc_bdaddr),
+                addr_type: AddressType::from_u8(saddr.iso_bc.bc_bdaddr_type).ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidInput, "invalid sockaddr_iso_bc::bc_bdaddr_type")
+                })?,
+                sid: saddr.iso_bc.bc_sid,
+                num_bis: saddr.iso_bc.bc_num_bis,
+                bis: saddr.iso_bc.bc_bis,
+            })
+        } else {
+            None
+        };
+        Ok(Self {
+            addr: Address::from(saddr.iso_bdaddr),
+            addr_type: AddressType::from_u8(saddr.iso_bdaddr_type)
+                .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "invalid sockaddr_iso::iso_bdaddr_type"))?,
+            broadcast,
+        })
+    }
+}
+
+/// An ISO socket that has not yet been converted to a [Listener] or [Stream].
+///
+/// The primary use of this is to configure the socket before connecting or listening.
+pub struct Socket {
+    fd: AsyncFd<OwnedFd>,
+}
+
+impl fmt::Debug for Socket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Socket").field("fd", &self.fd.as_raw_fd()).finish()
+    }
+}
+
+impl Socket {
+    /// Creates a new socket of sequential packet type.
+    pub fn new() -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(sock::socket(AF_BLUETOOTH, SOCK_SEQPACKET, BTPROTO_ISO)?)? })
+    }
+
+    /// Convert the socket into a [Listener].
+    ///
+    /// `backlog` defines the maximum number of pending connections are queued by the operating system
+    /// at any given time.
+    pub fn listen(self, backlog: u32) -> Result<Listener> {
+        sock::listen(
+            self.fd.get_ref(),
+            backlog.try_into().map_err(|_| Error::new(ErrorKind::InvalidInput, "invalid backlog"))?,
+        )?;
+        Ok(Listener { socket: self })
+    }
+
+    /// Establish an isochronous stream with a peer at the specified socket address.
+    ///
+    /// If the address is [Address::any] a broadcast source is started.
+    pub async fn connect(self, sa: SocketAddr) -> Result<Stream> {
+        self.connect_priv(sa).await?;
+        Stream::from_socket(self)
+    }
+
+    /// Bind the socket to the given address.
+    pub fn bind(&self, sa: SocketAddr) -> Result<()> {
+        sock::bind(self.fd.get_ref(), sa)
+    }
+
+    /// Get the local address of this socket.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        sock::getsockname(self.fd.get_ref())
+    }
+
+    /// Get the peer address of this socket.
+    fn peer_addr_priv(&self) -> Result<SocketAddr> {
+        sock::getpeername(self.fd.get_ref())
+    }
+
+    /// Get QoS of connected isochronous stream (CIS).
+    ///
+    /// This corresponds to the `BT_ISO_QOS` socket option.
+    pub fn unicast_qos(&self) -> Result<UnicastQos> {
+        let qos: bt_iso_qos = sock::getsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_ISO_QOS)?;
+        Ok(unsafe { qos.ucast })
+    }
+
+    /// Set QoS of connected isochronous stream (CIS).
+    ///
+    /// This must be set before connecting or listening.
+    ///
+    /// This corresponds to the `BT_ISO_QOS` socket option.
+    pub fn set_unicast_qos(&self, unicast_qos: &UnicastQos) -> Result<()> {
+        let mut qos: bt_iso_qos = unsafe { zeroed() };
+        qos.ucast = *unicast_qos;
+        sock::setsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_ISO_QOS, &qos)
+    }
+
+    /// Get QoS of broadcast isochronous stream (BIS).
+    ///
+    /// This corresponds to the `BT_ISO_QOS` socket option.
+    pub fn broadcast_qos(&self) -> Result<BroadcastQos> {
+        let qos: bt_iso_qos = sock::getsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_ISO_QOS)?;
+        Ok(unsafe { qos.bcast })
+    }
+
+    /// Set QoS of broadcast isochronous stream (BIS).
+    ///
+    /// This must be set before connecting or listening.
+    ///
+    /// This corresponds to the `BT_ISO_QOS` socket option.
+    pub fn set_broadcast_qos(&self, broadcast_qos: &BroadcastQos) -> Result<()> {
+        let mut qos: bt_iso_qos = unsafe { zeroed() };
+        qos.bcast = *broadcast_qos;
+        sock::setsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_ISO_QOS, &qos)
+    }
+
+    /// Set broadcast audio source endpoint (BASE) data of broadcast source.
+    ///
+    /// The data is included in the periodic advertising of the broadcast source
+    /// and must not exceed [MAX_BASE_LEN] bytes.
+    ///
+    /// This corresponds to the `BT_ISO_BASE` socket option.
+    pub fn set_base(&self, base: &[u8]) -> Result<()> {
+        if base.len() > MAX_BASE_LEN {
+            return Err(Error::new(ErrorKind::InvalidInput, "BASE data too long"));
+        }
+        sock::setsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_ISO_BASE, base)
+    }
+
+    /// Get whether connection setup is deferred.
+    ///
+    /// This corresponds to the `BT_DEFER_SETUP` socket option.
+    pub fn is_defer_setup(&self) -> Result<bool> {
+        let value: u32 = sock::getsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_DEFER_SETUP)?;
+        Ok(value != 0)
+    }
+
+    /// Set whether connection setup is deferred.
+    ///
+    /// When enabled on a listening socket, incoming connections are accepted
+    /// before they are confirmed to the remote device.
+    /// The connection is confirmed when data is first received from the accepted socket,
+    /// which allows to configure the QoS before.
+    ///
+    /// This corresponds to the `BT_DEFER_SETUP` socket option.
+    pub fn set_defer_setup(&self, defer_setup: bool) -> Result<()> {
+        let value: u32 = defer_setup.into();
+        sock::setsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_DEFER_SETUP, &value)
+    }
+
+    /// Gets the maximum socket receive buffer in bytes.
+    ///
+    /// This corresponds to the `SO_RCVBUF` socket option.
+    pub fn recv_buffer(&self) -> Result<i32> {
+        sock::getsockopt(self.fd.get_ref(), SOL_SOCKET, SO_RCVBUF)
+    }
+
+    /// Sets the maximum socket receive buffer in bytes.
+    ///
+    /// This corresponds to the `SO_RCVBUF` socket option.
+    pub fn set_recv_buffer(&self, recv_buffer: i32) -> Result<()> {
+        sock::setsockopt(self.fd.get_ref(), SOL_SOCKET, SO_RCVBUF, &recv_buffer)
+    }
+
+    /// Get the number of bytes in the input buffer.
+    ///
+    /// This corresponds to the `TIOCINQ` IOCTL.
+    pub fn input_buffer(&self) -> Result<u32> {
+        let value: c_int = sock::ioctl_read(self.fd.get_ref(), TIOCINQ)?;
+        Ok(value as _)
+    }
+
+    /// Get the number of bytes in the output buffer.
+    ///
+    /// This corresponds to the `TIOCOUTQ` IOCTL.
+    pub fn output_buffer(&self) -> Result<u32> {
+        let value: c_int = sock::ioctl_read(self.fd.get_ref(), TIOCOUTQ)?;
+        Ok(value as _)
+    }
+
+    /// Constructs a new [Socket] from the given raw file descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// This function *consumes ownership* of the specified file descriptor.
+    /// The returned object will take responsibility for closing it when the object goes out of scope.
+    ///
+    /// # Safety
+    /// If the passed file descriptor is invalid, undefined behavior may occur.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(OwnedFd::new(fd))? })
+    }
+
+    fn from_owned_fd(fd: OwnedFd) -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(fd)? })
+    }
+
+    sock_priv!();
+}
+
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for Socket {
+    fn into_raw_fd(self) -> RawFd {
+        self.fd.into_inner().into_raw_fd()
+    }
+}
+
+impl FromRawFd for Socket {
+    /// Constructs a new instance of `Self` from the given raw file
+    /// descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// # Panics
+    /// Panics when the conversion fails.
+    /// Use [Socket::from_raw_fd] for a non-panicking variant.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_raw_fd(fd).expect("from_raw_fd failed")
+    }
+}
+
+/// An ISO socket server, listening for connected isochronous streams (CIS) or
+/// synchronizing to broadcast isochronous streams (BIS).
+#[derive(Debug)]
+pub struct Listener {
+    socket: Socket,
+}
+
+impl Listener {
+    /// Creates a new Listener, which will be bound to the specified socket address.
+    ///
+    /// Specify [SocketAddr::any_le] for any local adapter address to accept
+    /// connected isochronous streams.
+    /// Specify a socket address created by [SocketAddr::new_broadcast] to synchronize to
+    /// a broadcast source.
+    pub async fn bind(sa: SocketAddr) -> Result<Self> {
+        let socket = Socket::new()?;
+        socket.bind(sa)?;
+        socket.listen(1)
+    }
+
+    /// Accepts a new incoming isochronous stream from this listener.
+    pub async fn accept(&self) -> Result<(Stream, SocketAddr)> {
+        let (socket, sa) = self.socket.accept_priv().await?;
+        Ok((Stream::from_socket(socket)?, sa))
+    }
+
+    /// Polls to accept a new incoming isochronous stream to this listener.
+    pub fn poll_accept(&self, cx: &mut Context) -> Poll<Result<(Stream, SocketAddr)>> {
+        let (socket, sa) = ready!(self.socket.poll_accept_priv(cx))?;
+        Poll::Ready(Ok((Stream::from_socket(socket)?, sa)))
+    }
+
+    /// Constructs a new [Listener] from the given raw file descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// This function *consumes ownership* of the specified file descriptor.
+    /// The returned object will take responsibility for closing it when the object goes out of scope.
+    ///
+    /// # Safety
+    /// If the passed file descriptor is invalid, undefined behavior may occur.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        Ok(Self { socket: Socket::from_raw_fd(fd)? })
+    }
+}
+
+impl AsRef<Socket> for Listener {
+    fn as_ref(&self) -> &Socket {
+        &self.socket
+    }
+}
+
+impl AsRawFd for Listener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl FromRawFd for Listener {
+    /// Constructs a new instance of `Self` from the given raw file
+    /// descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// # Panics
+    /// Panics when the conversion fails.
+    /// Use [Listener::from_raw_fd] for a non-panicking variant.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_raw_fd(fd).expect("from_raw_fd failed")
+    }
+}
+
+/// An isochronous stream (sequenced, time-bounded transmission of service data units
+/// of fixed maximum length).
+///
+/// Service data units (SDUs) can be sent and received using [send](Self::send) and [recv](Self::recv).
+/// Alternatively, [AsyncRead] and [AsyncWrite] are implemented, where each read
+/// receives one SDU and each write sends one SDU.
+#[derive(Debug)]
+pub struct Stream {
+    socket: Socket,
+}
+
+impl Stream {
+    /// Create Stream from Socket.
+    fn from_socket(socket: Socket) -> Result<Self> {
+        Ok(Self { socket })
+    }
+
+    /// Establish a connected isochronous stream (CIS) with a peer at the specified socket address
+    /// using the specified QoS.
+    ///
+    /// Uses any local Bluetooth adapter.
+    pub async fn connect(addr: SocketAddr, qos: &UnicastQos) -> Result<Self> {
+        let socket = Socket::new()?;
+        socket.bind(SocketAddr::any_le())?;
+        socket.set_unicast_qos(qos)?;
+        socket.connect(addr).await
+    }
+
+    /// Start a broadcast source with a broadcast isochronous stream (BIS) using the specified QoS
+    /// and broadcast audio source endpoint (BASE) data.
+    ///
+    /// Uses any local Bluetooth adapter.
+    pub async fn broadcast(qos: &BroadcastQos, base: &[u8]) -> Result<Self> {
+        let socket = Socket::new()?;
+        socket.bind(SocketAddr::any_le())?;
+        socket.set_broadcast_qos(qos)?;
+        if !base.is_empty() {
+            socket.set_base(base)?;
+        }
+        socket.connect(SocketAddr::any_le()).await
+    }
+
+    /// Gets the peer address of this stream.
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        self.socket.peer_addr_priv()
+    }
+
+    /// Sends a service data unit (SDU).
+    ///
+    /// The length must not exceed the maximum SDU size for sending configured in the QoS.
+    pub async fn send(&self, buf: &[u8]) -> Result<usize> {
+        self.socket.send_priv(buf).await
+    }
+
+    /// Attempts to send a service data unit (SDU).
+    ///
+    /// The length must not exceed the maximum SDU size for sending configured in the QoS.
+    pub fn poll_send(&self, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        self.socket.poll_send_priv(cx, buf)
+    }
+
+    /// Receives a service data unit (SDU).
+    ///
+    /// The provided buffer must be at least of the maximum SDU size for receiving
+    /// configured in the QoS, otherwise the SDU may be truncated.
+    pub async fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+        self.socket.recv_priv(buf).await
+    }
+
+    /// Attempts to receive a service data unit (SDU).
+    ///
+    /// The provided buffer must be at least of the maximum SDU size for receiving
+    /// configured in the QoS, otherwise the SDU may be truncated.
+    pub fn poll_recv(&self, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<()>> {
+        self.socket.poll_recv_priv(cx, buf)
+    }
+
+    /// Receives data on the socket from the remote address to which it is connected,
+    /// without removing that data from the queue.
+    /// On success, returns the number of bytes peeked.
+    pub async fn peek(&self, buf: &mut [u8]) -> Result<usize> {
+        self.socket.peek_priv(buf).await
+    }
+
+    /// Attempts to receive data on the socket, without removing that data from
+    /// the queue, registering the current task for wakeup if data is not yet available.
+    pub fn poll_peek(&self, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<usize>> {
+        self.socket.poll_peek_priv(cx, buf)
+    }
+
+    /// Shuts down the read, write, or both halves of this connection.
+    pub fn shutdown(&self, how: Shutdown) -> Result<()> {
+        self.socket.shutdown_priv(how)
+    }
+
+    /// Splits the stream into a borrowed read half and a borrowed write half, which can be used
+    /// to read and write the stream concurrently.
+    #[allow(clippy::needless_lifetimes)]
+    pub fn split<'a>(&'a mut self) -> (stream::ReadHalf<'a>, stream::WriteHalf<'a>) {
+        (stream::ReadHalf(self), stream::WriteHalf(self))
+    }
+
+    /// Splits the into an owned read half and an owned write half, which can be used to read
+    /// and write the stream concurrently.
+    pub fn into_split(self) -> (stream::OwnedReadHalf, stream::OwnedWriteHalf) {
+        let stream = Arc::new(self);
+        let r = stream::OwnedReadHalf {
+            stream: ManuallyDrop::new(stream.clone()),
+            shutdown_on_drop: true,
+            drop: true,
+        };
+        let w = stream::OwnedWriteHalf { stream, shutdown_on_drop: true };
+        (r, w)
+    }
+
+    fn poll_write_priv(&self, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        self.socket.poll_send_priv(cx, buf)
+    }
+
+    /// Constructs a new [Stream] from the given raw file descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// This function *consumes ownership* of the specified file descriptor.
+    /// The returned object will take responsibility for closing it when the object goes out of scope.
+    ///
+    /// # Safety
+    /// If the passed file descriptor is invalid, undefined behavior may occur.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        Self::from_socket(Socket::from_raw_fd(fd)?)
+    }
+}
+
+impl AsRef<Socket> for Stream {
+    fn as_ref(&self) -> &Socket {
+        &self.socket
+    }
+}
+
+impl AsRawFd for Stream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl FromRawFd for Stream {
+    /// Constructs a new instance of `Self` from the given raw file
+    /// descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// # Panics
+    /// Panics when the conversion fails.
+    /// Use [Stream::from_raw_fd] for a non-panicking variant.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_raw_fd(fd).expect("from_raw_fd failed")
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<()>> {
+        self.socket.poll_recv_priv(cx, buf)
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        self.poll_write_priv(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+        self.socket.poll_flush_priv(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+        self.socket.poll_shutdown_priv(cx, Shutdown::Write)
+    }
+}
+
+#[allow(clippy::duplicate_mod)]
+#[path = "stream_util.rs"]
+pub mod stream;
@@ -8,7 +8,7 @@
 //!
 //! This library provides the official Rust interface to the [Linux Bluetooth protocol stack (BlueZ)].
 //! Both publishing local and consuming remote [GATT services] using *idiomatic* Rust code is supported.
-//! L2CAP and RFCOMM sockets are presented using an API similar to Tokio networking.
+//! L2CAP, RFCOMM, SCO and ISO sockets are presented using an API similar to Tokio networking.
 //!
 //! This library depends on the [tokio] asynchronous runtime.
 //!
@@ -18,26 +18,35 @@
 //!     * [enumeration](Session::adapter_names)
 //!     * configuration of power, discoverability, name, etc.
 //!     * hot-plug support through change events stream
+//!     * admin policy restricting the allowed services
 //! * [Bluetooth devices](Device)
 //!     * [discovery](Adapter::discover_devices) with custom filters
 //!     * querying of address, name, class, signal strength (RSSI), etc.
+//!     * battery level
 //!     * Bluetooth Low Energy advertisements
 //!     * [change events stream](Adapter::events)
 //!     * connecting and pairing
+//!     * coordinated device sets, such as hearing aid pairs
 //!     * [passive LE advertisement monitoring](Adapter::monitor)
 //! * [consumption of remote GATT services](Device::services)
 //!     * GATT service discovery
 //!     * read, write and notify operations on characteristics
 //!     * read and write operations on characteristic descriptors
 //!     * optional use of low-overhead [AsyncRead] and [AsyncWrite] streams for notify and write operations
+//!     * [typed characteristic values](gatt::value) and presentation formats
 //! * [publishing local GATT services](Adapter::serve_gatt_application)
 //!     * read, write and notify operations on characteristics
 //!     * read and write operations on characteristic descriptors
 //!     * two programming models supported
 //!         * callback-based interface
 //!         * low-overhead [AsyncRead] and [AsyncWrite] streams
+//!     * [standard services](gatt::services): Device Information, Battery and Current Time
+//!     * [attribute macro](gatt::local::gatt_service) for declaring services
+//! * [Nordic UART service](gatt::nus) client and server as a single [AsyncRead] and [AsyncWrite] stream
 //! * [sending Bluetooth Low Energy advertisements](Adapter::advertise)
 //! * [Bluetooth authorization agent](agent::Agent)
+//! * [providing battery levels of remote devices](battery::BatteryProvider)
+//! * [Personal Area Network (PAN)](network) client and server
 //! * efficient event dispatching
 //!     * not affected by D-Bus match rule count
 //!     * O(1) in number of subscriptions
@@ -51,9 +60,44 @@
 //!     * support for classic Bluetooth (BR/EDR)
 //!     * stream oriented
 //!     * async IO interface with [AsyncRead] and [AsyncWrite] support
+//! * [SCO sockets](sco)
+//!     * support for classic Bluetooth (BR/EDR) voice links
+//!     * sequential packet oriented
+//!     * async IO interface with [AsyncRead] and [AsyncWrite] support
+//! * [ISO sockets](iso)
+//!     * connected and broadcast isochronous streams for LE Audio
+//!     * sequential packet oriented
+//!     * async IO interface with [AsyncRead] and [AsyncWrite] support
+//! * [HCI sockets](hci)
+//!     * raw, user and monitor channels
+//!     * sending of HCI commands, including vendor-specific commands
+//!     * typed decoding of common HCI events
+//!     * capture of HCI traffic into btsnoop and pcapng files
+//! * [Bluetooth management interface](mgmt)
+//!     * controller configuration not available through the Bluetooth daemon
+//!     * loading of keys and connection parameters
+//!     * event stream
+//! * [Human Interface Device (HID) profile](hid)
+//!     * reconnection mode of remote input devices
+//!     * acting as a HID device, such as a keyboard or remote control
+//!     * sending of typed input reports
+//! * [media endpoints, transports and players](media)
+//!     * audio profiles such as A2DP and LE Audio
+//!     * async IO interface for media transports
+//!     * remote media player control and track metadata
+//! * [OBEX client](obex)
+//!     * object push, file transfer and phonebook access
+//!     * transfer progress monitoring
 //! * [Bluetooth Mesh](mesh)
-//!     * provision and join networks
+//!     * provision, join, create and import networks
+//!     * manage subnets, application keys and remote nodes
+//!     * configure remote nodes using the Configuration Client model
 //!     * send and receive messages
+//!     * typed access layer messages dispatched to model handlers
+//!     * Generic OnOff, Generic Level, Light Lightness and Sensor models
+//! * [parsing and building advertising data](ad)
+//! * [iBeacon, AltBeacon and Eddystone beacons](beacon)
+//! * [mock Bluetooth daemon](mock) for testing applications without Bluetooth hardware
 //! * [database of assigned numbers](id)
 //!     * manufacturer ids
 //!     * services classes, GATT services, characteristics and descriptors
@@ -65,11 +109,20 @@
 //! The following crate features are available.
 //!
 //! * `bluetoothd`: Enables all functions requiring a running Bluetooth daemon.
+//! * `derive`: Enables the [macro for declaring local GATT services](gatt::local::gatt_service).
 //! * `id`: Enables database of assigned numbers.
 //! * `l2cap`: Enables L2CAP sockets.
 //! * `rfcomm`: Enables RFCOMM sockets.
+//! * `sco`: Enables SCO sockets.
+//! * `iso`: Enables ISO sockets.
+//! * `hci`: Enables HCI sockets.
+//! * `mgmt`: Enables the Bluetooth management interface.
+//! * `hid`: Enables the Human Interface Device (HID) profile.
 //! * `mesh`: Enables Bluetooth mesh functionality.
+//! * `media`: Enables media endpoints, transports and players.
+//! * `obex`: Enables the OBEX client for object push, file transfer and phonebook access.
 //! * `serde`: Enables serialization and deserialization of some data types.
+//! * `mock`: Enables the [mock Bluetooth daemon](mock) for testing.
 //!
 //! To enable all crate features specify the `full` crate feature.
 //!
@@ -78,8 +131,8 @@
 //! Then obtain a Bluetooth adapter using [Session::adapter].
 //! From there on you can access most of the functionality using the methods provided by [Adapter].
 //!
-//! ## L2CAP and RFCOMM sockets
-//! Refer to the [l2cap] and [rfcomm] modules.
+//! ## L2CAP, RFCOMM, SCO and ISO sockets
+//! Refer to the [l2cap], [rfcomm], [sco] and [iso] modules.
 //! No [Session] and therefore no running Bluetooth daemon is required.
 //!
 //! [Linux Bluetooth protocol stack (BlueZ)]: http://www.bluez.org/
@@ -122,6 +175,8 @@ use tokio::task::JoinError;
 pub(crate) const SERVICE_NAME: &str = "org.bluez";
 #[cfg(feature = "bluetoothd")]
 pub(crate) const ERR_PREFIX: &str = "org.bluez.Error.";
+#[cfg(feature = "obex")]
+pub(crate) const OBEX_ERR_PREFIX: &str = "org.bluez.obex.Error.";
 #[cfg(feature = "bluetoothd")]
 pub(crate) const TIMEOUT: Duration = Duration::from_secs(120);
 
@@ -159,7 +214,16 @@ macro_rules! dbus_interface {
                     log::trace!("{}: {}.{} = {:?}", &self.proxy().path, &interface, &name, &value);
                     Ok(Some(value))
                 }
-                Err(err) if err.name() == Some("org.freedesktop.DBus.Error.InvalidArgs") => {
+                Err(err)
+                    if matches!(
+                        err.name(),
+                        Some(
+                            "org.freedesktop.DBus.Error.InvalidArgs"
+                                | "org.freedesktop.DBus.Error.UnknownInterface"
+                                | "org.freedesktop.DBus.Error.UnknownProperty"
+                        )
+                    ) =>
+                {
                     log::trace!("{}: {}.{} = None", &self.proxy().path, &interface, &name);
                     Ok(None)
                 }
@@ -413,6 +477,20 @@ where
     hm
 }
 
+#[cfg(feature = "bluetoothd")]
+fn variant_path_hashmap(a: &(dyn RefArg + 'static)) -> HashMap<dbus::Path<'static>, dbus::arg::PropMap> {
+    let mut hm = HashMap::new();
+
+    let mut iter = a.as_iter().unwrap();
+    while let (Some(key), Some(value)) = (iter.next(), iter.next()) {
+        if let Some(path) = key.as_str().and_then(|s| dbus::Path::new(s.to_string()).ok()) {
+            hm.insert(path, variant_hashmap::<String>(&*value.box_clone()));
+        }
+    }
+
+    hm
+}
+
 #[cfg(feature = "bluetoothd")]
 pub(crate) fn with_variant_property_cast<T, R>(a: &(dyn RefArg + 'static), f: impl FnOnce(Option<&T>) -> R) -> R
 where
@@ -433,6 +511,9 @@ where
             } else if a.signature().starts_with("a{sv") {
                 let hm = variant_hashmap::<String>(a);
                 f((&hm as &dyn Any).downcast_ref())
+            } else if a.signature().starts_with("a{oa{sv}") {
+                let hm = variant_path_hashmap(a);
+                f((&hm as &dyn Any).downcast_ref())
             } else {
                 log::warn!("unimplemented D-Bus type signature: {}", a.signature());
                 f(None)
@@ -514,10 +595,22 @@ macro_rules! read_opt_prop {
     };
 }
 
-#[cfg(any(feature = "l2cap", feature = "rfcomm"))]
+#[cfg(any(
+    feature = "l2cap",
+    feature = "rfcomm",
+    feature = "sco",
+    feature = "iso",
+    feature = "hci",
+    feature = "mgmt"
+))]
+#[cfg_attr(
+    not(any(feature = "l2cap", feature = "rfcomm", feature = "sco", feature = "iso")),
+    allow(dead_code, unused_macros)
+)]
 #[macro_use]
 mod sock;
 
+pub mod ad;
 #[cfg(feature = "bluetoothd")]
 mod adapter;
 #[cfg(feature = "bluetoothd")]
@@ -527,28 +620,64 @@ pub mod adv;
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
 pub mod agent;
 #[cfg(feature = "bluetoothd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+pub mod battery;
+pub mod beacon;
+#[cfg(feature = "bluetoothd")]
 mod device;
 #[cfg(feature = "bluetoothd")]
+mod device_set;
+#[cfg(feature = "bluetoothd")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
 pub mod gatt;
+#[cfg(feature = "hci")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hci")))]
+pub mod hci;
+#[cfg(any(feature = "hci", feature = "mgmt"))]
+#[cfg_attr(not(all(feature = "hci", feature = "mgmt")), allow(dead_code))]
+mod hci_util;
+#[cfg(feature = "hid")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hid")))]
+pub mod hid;
+#[cfg(feature = "iso")]
+#[cfg_attr(docsrs, doc(cfg(feature = "iso")))]
+pub mod iso;
 #[cfg(feature = "l2cap")]
 #[cfg_attr(docsrs, doc(cfg(feature = "l2cap")))]
 pub mod l2cap;
+#[cfg(feature = "media")]
+#[cfg_attr(docsrs, doc(cfg(feature = "media")))]
+pub mod media;
 #[cfg(feature = "mesh")]
 #[cfg_attr(docsrs, doc(cfg(feature = "mesh")))]
 pub mod mesh;
+#[cfg(feature = "mgmt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mgmt")))]
+pub mod mgmt;
+#[cfg(feature = "mock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+pub mod mock;
 #[cfg(feature = "bluetoothd")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
 pub mod monitor;
+#[cfg(feature = "bluetoothd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+pub mod network;
+#[cfg(feature = "obex")]
+#[cfg_attr(docsrs, doc(cfg(feature = "obex")))]
+pub mod obex;
 #[cfg(feature = "rfcomm")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rfcomm")))]
 pub mod rfcomm;
+#[cfg(feature = "sco")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sco")))]
+pub mod sco;
 #[cfg(feature = "bluetoothd")]
 mod session;
 mod sys;
 
 #[cfg(feature = "bluetoothd")]
-pub use crate::{adapter::*, device::*, session::*};
+pub use crate::{adapter::*, device::*, device_set::*, session::*};
 
 #[doc(no_inline)]
 pub use uuid::Uuid;
@@ -657,6 +786,16 @@ pub enum ErrorKind {
     #[cfg_attr(docsrs, doc(cfg(feature = "mesh")))]
     #[strum(disabled)]
     MeshElementUnpublished,
+    /// mesh configuration request failed: {0}
+    #[cfg(feature = "mesh")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mesh")))]
+    #[strum(disabled)]
+    MeshConfigFailed(mesh::config::Status),
+    /// no response received from mesh node
+    #[cfg(feature = "mesh")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mesh")))]
+    #[strum(disabled)]
+    MeshResponseTimeout,
     /// internal error: {0}
     #[strum(disabled)]
     Internal(InternalErrorKind),
@@ -722,7 +861,13 @@ impl From<dbus::Error> for Error {
         }
         let kind = match err
             .name()
-            .and_then(|name| name.strip_prefix(ERR_PREFIX))
+            .and_then(|name| {
+                #[cfg(feature = "obex")]
+                if let Some(s) = name.strip_prefix(OBEX_ERR_PREFIX) {
+                    return Some(s);
+                }
+                name.strip_prefix(ERR_PREFIX)
+            })
             .and_then(|s| ErrorKind::from_str(s).ok())
         {
             Some(kind) => kind,
@@ -798,6 +943,10 @@ impl From<Error> for std::io::Error {
             ErrorKind::MeshAddNodeFailed(_) => E::ConnectionRefused,
             #[cfg(feature = "mesh")]
             ErrorKind::MeshElementUnpublished => E::InvalidInput,
+            #[cfg(feature = "mesh")]
+            ErrorKind::MeshConfigFailed(_) => E::Other,
+            #[cfg(feature = "mesh")]
+            ErrorKind::MeshResponseTimeout => E::TimedOut,
             ErrorKind::Internal(InternalErrorKind::Io(err)) => err,
             ErrorKind::Internal(_) => E::Other,
         };
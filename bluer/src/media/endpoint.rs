@@ -0,0 +1,281 @@
+//! Local media endpoints.
+
+use dbus::{
+    arg::{PropMap, RefArg, Variant},
+    nonblock::Proxy,
+    Path,
+};
+use dbus_crossroads::{Crossroads, IfaceBuilder, IfaceToken, MethodErr};
+use futures::Future;
+use std::{fmt, pin::Pin, sync::Arc};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use super::{transport::Transport, ReqError, ReqResult, INTERFACE as MEDIA_INTERFACE};
+use crate::{method_call, Adapter, Address, Result, SessionInner, SERVICE_NAME, TIMEOUT};
+
+pub(crate) const INTERFACE: &str = "org.bluez.MediaEndpoint1";
+pub(crate) const ENDPOINT_PREFIX: &str = publish_path!("media/endpoint/");
+
+/// Arguments for a select configuration request.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct SelectConfiguration {
+    /// Adapter making the request.
+    pub adapter: String,
+    /// Capabilities of the remote endpoint.
+    pub capabilities: Vec<u8>,
+}
+
+/// Function handling a select configuration request.
+///
+/// It must return the configuration chosen from the capabilities.
+pub type SelectConfigurationFn =
+    Box<dyn (Fn(SelectConfiguration) -> Pin<Box<dyn Future<Output = ReqResult<Vec<u8>>> + Send>>) + Send + Sync>;
+
+/// Arguments for a set configuration request.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct SetConfiguration {
+    /// Adapter making the request.
+    pub adapter: String,
+    /// Address of remote device, or `None` for a broadcast transport.
+    pub device: Option<Address>,
+    /// Transport that has been configured.
+    pub transport: Transport,
+    /// UUID of the profile.
+    pub uuid: Uuid,
+    /// Assigned number of the codec.
+    pub codec: u8,
+    /// Codec configuration.
+    pub configuration: Vec<u8>,
+}
+
+/// Function handling a set configuration request.
+pub type SetConfigurationFn =
+    Box<dyn (Fn(SetConfiguration) -> Pin<Box<dyn Future<Output = ReqResult<()>> + Send>>) + Send + Sync>;
+
+/// Arguments for a clear configuration request.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ClearConfiguration {
+    /// Adapter making the request.
+    pub adapter: String,
+    /// Address of remote device, or `None` for a broadcast transport.
+    pub device: Option<Address>,
+    /// Transport whose configuration has been cleared.
+    pub transport: Transport,
+}
+
+/// Function handling a clear configuration request.
+pub type ClearConfigurationFn =
+    Box<dyn (Fn(ClearConfiguration) -> Pin<Box<dyn Future<Output = ReqResult<()>> + Send>>) + Send + Sync>;
+
+/// Local media endpoint.
+///
+/// Each request handler that is set to [None] will reject the request,
+/// except for [clear_configuration](Self::clear_configuration), which is ignored.
+///
+/// Use [Adapter::register_media_endpoint](crate::Adapter::register_media_endpoint) to register the endpoint.
+#[derive(Default)]
+pub struct Endpoint {
+    /// UUID of the profile the endpoint is for.
+    ///
+    /// For example `0000110a-0000-1000-8000-00805f9b34fb` for an A2DP source
+    /// or `0000110b-0000-1000-8000-00805f9b34fb` for an A2DP sink.
+    pub uuid: Uuid,
+    /// Assigned number of the codec.
+    ///
+    /// See [codec](super::codec) for known values.
+    pub codec: u8,
+    /// Vendor-specific company id and codec id.
+    ///
+    /// Only used if [codec](Self::codec) is [VENDOR](super::codec::VENDOR).
+    pub vendor: Option<u32>,
+    /// Codec capabilities.
+    pub capabilities: Vec<u8>,
+    /// Metadata, used by LE Audio endpoints.
+    pub metadata: Option<Vec<u8>>,
+    /// Whether the endpoint supports delay reporting.
+    pub delay_reporting: bool,
+    /// Select preferable configuration from the supported capabilities.
+    ///
+    /// Note: There is no need to cache the selected
+    /// configuration since on success the configuration
+    /// is sent back as parameter of SetConfiguration.
+    pub select_configuration: Option<SelectConfigurationFn>,
+    /// Set configuration for the transport.
+    ///
+    /// This is called once the codec configuration has been negotiated
+    /// with the remote device and the transport has been created.
+    pub set_configuration: Option<SetConfigurationFn>,
+    /// Clear transport configuration.
+    pub clear_configuration: Option<ClearConfigurationFn>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Endpoint {
+    fn properties(&self) -> PropMap {
+        let mut props = PropMap::new();
+        props.insert("UUID".to_string(), Variant(self.uuid.to_string().box_clone()));
+        props.insert("Codec".to_string(), Variant(self.codec.box_clone()));
+        if let Some(vendor) = self.vendor {
+            props.insert("Vendor".to_string(), Variant(vendor.box_clone()));
+        }
+        props.insert("Capabilities".to_string(), Variant(self.capabilities.box_clone()));
+        if let Some(metadata) = &self.metadata {
+            props.insert("Metadata".to_string(), Variant(metadata.box_clone()));
+        }
+        props.insert("DelayReporting".to_string(), Variant(self.delay_reporting.box_clone()));
+        props
+    }
+}
+
+pub(crate) struct RegisteredEndpoint {
+    inner: Arc<SessionInner>,
+    adapter_name: String,
+    e: Endpoint,
+}
+
+impl RegisteredEndpoint {
+    fn parse_transport_path(inner: &Arc<SessionInner>, transport: &Path<'static>) -> ReqResult<Transport> {
+        match Transport::from_dbus_path(inner.clone(), transport) {
+            Some(transport) => Ok(transport),
+            None => {
+                log::error!("Cannot parse transport path {}", &transport);
+                Err(ReqError::InvalidArguments)
+            }
+        }
+    }
+
+    pub(crate) fn register_interface(cr: &mut Crossroads) -> IfaceToken<Arc<Self>> {
+        cr.register(INTERFACE, |ib: &mut IfaceBuilder<Arc<Self>>| {
+            ib.method_with_cr_async(
+                "SelectConfiguration",
+                ("capabilities",),
+                ("configuration",),
+                |ctx, cr, (capabilities,): (Vec<u8>,)| {
+                    method_call(ctx, cr, |reg: Arc<Self>| async move {
+                        let adapter = reg.adapter_name.clone();
+                        match &reg.e.select_configuration {
+                            Some(f) => Ok((f(SelectConfiguration { adapter, capabilities }).await?,)),
+                            None => Err(ReqError::Rejected.into()),
+                        }
+                    })
+                },
+            );
+            ib.method_with_cr_async(
+                "SetConfiguration",
+                ("transport", "properties"),
+                (),
+                |ctx, cr, (transport, properties): (Path<'static>, PropMap)| {
+                    method_call(ctx, cr, |reg: Arc<Self>| async move {
+                        let transport = Self::parse_transport_path(&reg.inner, &transport)?;
+                        let req = SetConfiguration {
+                            adapter: transport.adapter_name().to_string(),
+                            device: transport.device_address(),
+                            uuid: read_prop!(&properties, "UUID", String)
+                                .parse()
+                                .map_err(|_| MethodErr::invalid_arg("UUID"))?,
+                            codec: read_prop!(&properties, "Codec", u8),
+                            configuration: read_prop!(&properties, "Configuration", Vec<u8>),
+                            transport,
+                        };
+                        match &reg.e.set_configuration {
+                            Some(f) => Ok(f(req).await?),
+                            None => Err(ReqError::Rejected.into()),
+                        }
+                    })
+                },
+            );
+            ib.method_with_cr_async(
+                "ClearConfiguration",
+                ("transport",),
+                (),
+                |ctx, cr, (transport,): (Path<'static>,)| {
+                    method_call(ctx, cr, |reg: Arc<Self>| async move {
+                        let transport = Self::parse_transport_path(&reg.inner, &transport)?;
+                        let req = ClearConfiguration {
+                            adapter: transport.adapter_name().to_string(),
+                            device: transport.device_address(),
+                            transport,
+                        };
+                        if let Some(f) = &reg.e.clear_configuration {
+                            f(req).await?;
+                        }
+                        Ok(())
+                    })
+                },
+            );
+            ib.method_with_cr_async("Release", (), (), |ctx, cr, ()| {
+                method_call(ctx, cr, |_reg: Arc<Self>| async move { Ok(()) })
+            });
+        })
+    }
+
+    pub(crate) async fn register(
+        endpoint: Endpoint, inner: Arc<SessionInner>, adapter_name: &str,
+    ) -> Result<EndpointHandle> {
+        let name = Path::new(format!("{}{}", ENDPOINT_PREFIX, Uuid::new_v4().as_simple())).unwrap();
+        let properties = endpoint.properties();
+        log::trace!("Publishing media endpoint at {}", &name);
+
+        {
+            let mut cr = inner.crossroads.lock().await;
+            cr.insert(
+                name.clone(),
+                &[inner.media_endpoint_token],
+                Arc::new(Self { inner: inner.clone(), adapter_name: adapter_name.to_string(), e: endpoint }),
+            );
+        }
+
+        log::trace!("Registering media endpoint at {}", &name);
+        let proxy =
+            Proxy::new(SERVICE_NAME, Adapter::dbus_path(adapter_name)?, TIMEOUT, inner.connection.clone());
+        let result: Result<()> = proxy
+            .method_call(MEDIA_INTERFACE, "RegisterEndpoint", (name.clone(), properties))
+            .await
+            .map_err(Into::into);
+
+        let (drop_tx, drop_rx) = oneshot::channel();
+        let unreg_name = name.clone();
+        let registered = result.is_ok();
+        tokio::spawn(async move {
+            let _ = drop_rx.await;
+
+            if registered {
+                log::trace!("Unregistering media endpoint at {}", &unreg_name);
+                let _: std::result::Result<(), dbus::Error> =
+                    proxy.method_call(MEDIA_INTERFACE, "UnregisterEndpoint", (unreg_name.clone(),)).await;
+            }
+
+            log::trace!("Unpublishing media endpoint at {}", &unreg_name);
+            let mut cr = inner.crossroads.lock().await;
+            let _: Option<Arc<Self>> = cr.remove(&unreg_name);
+        });
+
+        result?;
+        Ok(EndpointHandle { name, _drop_tx: drop_tx })
+    }
+}
+
+/// Handle to registered media endpoint.
+///
+/// Drop to unregister media endpoint.
+pub struct EndpointHandle {
+    name: Path<'static>,
+    _drop_tx: oneshot::Sender<()>,
+}
+
+impl Drop for EndpointHandle {
+    fn drop(&mut self) {
+        // required for drop order
+    }
+}
+
+impl fmt::Debug for EndpointHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EndpointHandle {{ {} }}", &self.name)
+    }
+}
@@ -0,0 +1,121 @@
+//! Media endpoints, transports and players.
+//!
+//! This provides access to the BlueZ media API, which allows implementing
+//! audio profiles such as A2DP source and sink or LE Audio in an application.
+//!
+//! A local [Endpoint](endpoint::Endpoint) is registered using
+//! [Adapter::register_media_endpoint](crate::Adapter::register_media_endpoint).
+//! When a remote device connects, BlueZ negotiates the codec configuration with
+//! the endpoint and creates a [Transport](transport::Transport), whose
+//! file descriptor can be acquired to stream audio data.
+//!
+//! Media players of remote devices, for example a phone playing music, are controlled
+//! using [Player](player::Player).
+
+use dbus::Path;
+use strum::IntoStaticStr;
+
+use crate::{Adapter, Address, Device, ERR_PREFIX};
+
+pub mod endpoint;
+pub mod player;
+pub mod transport;
+
+pub(crate) const INTERFACE: &str = "org.bluez.Media1";
+
+/// A2DP codec identifiers.
+pub mod codec {
+    /// SBC codec.
+    pub const SBC: u8 = 0x00;
+    /// MPEG-1,2 audio codec.
+    pub const MPEG12: u8 = 0x01;
+    /// MPEG-2,4 AAC codec.
+    pub const MPEG24: u8 = 0x02;
+    /// ATRAC family codec.
+    pub const ATRAC: u8 = 0x04;
+    /// LC3 codec, used by LE Audio.
+    pub const LC3: u8 = 0x06;
+    /// Vendor-specific codec.
+    pub const VENDOR: u8 = 0xff;
+}
+
+/// Error response from us to a media request.
+#[derive(
+    Clone, Copy, Debug, Default, displaydoc::Display, Eq, PartialEq, Ord, PartialOrd, Hash, IntoStaticStr,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ReqError {
+    /// Request was rejected.
+    #[default]
+    Rejected,
+    /// Invalid arguments.
+    InvalidArguments,
+    /// Not supported.
+    NotSupported,
+}
+
+impl std::error::Error for ReqError {}
+
+impl From<ReqError> for dbus::MethodErr {
+    fn from(err: ReqError) -> Self {
+        let name: &'static str = err.into();
+        Self::from((ERR_PREFIX.to_string() + name, &err.to_string()))
+    }
+}
+
+/// Result of a media request to us.
+pub type ReqResult<T> = std::result::Result<T, ReqError>;
+
+/// Splits the D-Bus path of a media object below a device into adapter name,
+/// device address and object name relative to the device.
+pub(crate) fn parse_device_object_path<'a>(path: &'a Path) -> Option<(&'a str, Address, &'a str)> {
+    match Device::parse_dbus_path_prefix(path) {
+        Some(((adapter_name, address), p)) => match p.strip_prefix('/') {
+            Some(name) if !name.is_empty() => Some((adapter_name, address, name)),
+            _ => None,
+        },
+        None => None,
+    }
+}
+
+/// Splits the D-Bus path of a media object below a device or directly below an adapter
+/// into adapter name, device address and object name relative to its parent.
+///
+/// Broadcast transports live below the adapter and thus have no device address.
+pub(crate) fn parse_media_object_path<'a>(path: &'a Path) -> Option<(&'a str, Option<Address>, &'a str)> {
+    if Device::parse_dbus_path_prefix(path).is_some() {
+        return parse_device_object_path(path)
+            .map(|(adapter_name, address, name)| (adapter_name, Some(address), name));
+    }
+    match Adapter::parse_dbus_path_prefix(path) {
+        Some((adapter_name, p)) => match p.strip_prefix('/') {
+            Some(name) if !name.is_empty() => Some((adapter_name, None, name)),
+            _ => None,
+        },
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn media_object_paths() {
+        let address: Address = "00:11:22:33:44:55".parse().unwrap();
+
+        let path = Path::new("/org/bluez/hci0/dev_00_11_22_33_44_55/sep1/fd0").unwrap();
+        assert_eq!(parse_media_object_path(&path), Some(("hci0", Some(address), "sep1/fd0")));
+
+        let path = Path::new("/org/bluez/hci0/pac_bcast0/fd1").unwrap();
+        assert_eq!(parse_media_object_path(&path), Some(("hci0", None, "pac_bcast0/fd1")));
+
+        let path = Path::new("/org/bluez/hci1/dev_00_11_22_33_44_55").unwrap();
+        assert_eq!(parse_media_object_path(&path), None);
+        let path = Path::new("/org/bluez/hci1").unwrap();
+        assert_eq!(parse_media_object_path(&path), None);
+        let path = Path::new("/org/example/fd0").unwrap();
+        assert_eq!(parse_media_object_path(&path), None);
+    }
+}
@@ -0,0 +1,332 @@
+//! Media players of remote devices.
+
+use dbus::{
+    arg::PropMap,
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use futures::{stream, Stream, StreamExt};
+use std::{fmt, sync::Arc, time::Duration};
+use strum::{Display, EnumString};
+
+use crate::{
+    Address, Device, Error, ErrorKind, Event, InternalErrorKind, Result, SessionInner, SERVICE_NAME, TIMEOUT,
+};
+
+pub(crate) const INTERFACE: &str = "org.bluez.MediaPlayer1";
+
+/// Playback status of a media player.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum PlayerStatus {
+    /// Playing.
+    #[strum(serialize = "playing")]
+    Playing,
+    /// Stopped.
+    #[strum(serialize = "stopped")]
+    Stopped,
+    /// Paused.
+    #[strum(serialize = "paused")]
+    Paused,
+    /// Seeking forward.
+    #[strum(serialize = "forward-seek")]
+    ForwardSeek,
+    /// Seeking backward.
+    #[strum(serialize = "reverse-seek")]
+    ReverseSeek,
+    /// Error.
+    #[strum(serialize = "error")]
+    Error,
+}
+
+/// Repeat setting of a media player.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Repeat {
+    /// Repeat is off.
+    #[strum(serialize = "off")]
+    Off,
+    /// Repeat the current track.
+    #[strum(serialize = "singletrack")]
+    SingleTrack,
+    /// Repeat all tracks.
+    #[strum(serialize = "alltracks")]
+    AllTracks,
+    /// Repeat the current group.
+    #[strum(serialize = "group")]
+    Group,
+}
+
+/// Shuffle setting of a media player.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Shuffle {
+    /// Shuffle is off.
+    #[strum(serialize = "off")]
+    Off,
+    /// Shuffle all tracks.
+    #[strum(serialize = "alltracks")]
+    AllTracks,
+    /// Shuffle the current group.
+    #[strum(serialize = "group")]
+    Group,
+}
+
+/// Metadata of the current track of a media player.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Track {
+    /// Track title name.
+    pub title: Option<String>,
+    /// Track artist name.
+    pub artist: Option<String>,
+    /// Track album name.
+    pub album: Option<String>,
+    /// Track genre name.
+    pub genre: Option<String>,
+    /// Number of tracks in total.
+    pub number_of_tracks: Option<u32>,
+    /// Track number.
+    pub track_number: Option<u32>,
+    /// Track duration.
+    pub duration: Option<Duration>,
+}
+
+impl Track {
+    fn from_dict(dict: &PropMap) -> Self {
+        Self {
+            title: read_opt_prop!(dict, "Title", String),
+            artist: read_opt_prop!(dict, "Artist", String),
+            album: read_opt_prop!(dict, "Album", String),
+            genre: read_opt_prop!(dict, "Genre", String),
+            number_of_tracks: read_opt_prop!(dict, "NumberOfTracks", u32),
+            track_number: read_opt_prop!(dict, "TrackNumber", u32),
+            duration: read_opt_prop!(dict, "Duration", u32).map(|ms| Duration::from_millis(ms.into())),
+        }
+    }
+}
+
+/// Interface to a media player of a remote device.
+#[derive(Clone)]
+pub struct Player {
+    inner: Arc<SessionInner>,
+    dbus_path: Path<'static>,
+    adapter_name: Arc<String>,
+    device_address: Address,
+    name: String,
+}
+
+impl fmt::Debug for Player {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Player {{ adapter_name: {}, device_address: {}, name: {} }}",
+            self.adapter_name(),
+            self.device_address(),
+            self.name()
+        )
+    }
+}
+
+impl Player {
+    pub(crate) fn new(
+        inner: Arc<SessionInner>, adapter_name: Arc<String>, device_address: Address, name: &str,
+    ) -> Result<Self> {
+        Ok(Self {
+            inner,
+            dbus_path: Self::dbus_path(&adapter_name, device_address, name)?,
+            adapter_name,
+            device_address,
+            name: name.to_string(),
+        })
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(SERVICE_NAME, &self.dbus_path, TIMEOUT, &*self.inner.connection)
+    }
+
+    pub(crate) fn dbus_path(adapter_name: &str, device_address: Address, name: &str) -> Result<Path<'static>> {
+        let device_path = Device::dbus_path(adapter_name, device_address)?;
+        Path::new(format!("{device_path}/{name}"))
+            .map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))
+    }
+
+    /// The Bluetooth adapter name.
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    /// The Bluetooth address of the remote device this player belongs to.
+    pub fn device_address(&self) -> Address {
+        self.device_address
+    }
+
+    /// The name of this player object relative to its device, for example `player0`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Streams player property changes, including track and status changes.
+    ///
+    /// The stream ends when the player is removed.
+    pub async fn events(&self) -> Result<impl Stream<Item = PlayerEvent>> {
+        let events = self.inner.events(self.dbus_path.clone(), false).await?;
+        let stream = events.flat_map(move |event| match event {
+            Event::PropertiesChanged { interface, changed, .. } if interface == INTERFACE => {
+                stream::iter(PlayerProperty::from_prop_map(changed).into_iter().map(PlayerEvent::PropertyChanged))
+                    .boxed()
+            }
+            _ => stream::empty().boxed(),
+        });
+
+        Ok(stream)
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(INTERFACE);
+
+    // ===========================================================================================
+    // Methods
+    // ===========================================================================================
+
+    /// Resume playback.
+    pub async fn play(&self) -> Result<()> {
+        self.call_method("Play", ()).await
+    }
+
+    /// Pause playback.
+    pub async fn pause(&self) -> Result<()> {
+        self.call_method("Pause", ()).await
+    }
+
+    /// Stop playback.
+    pub async fn stop(&self) -> Result<()> {
+        self.call_method("Stop", ()).await
+    }
+
+    /// Next item.
+    pub async fn next(&self) -> Result<()> {
+        self.call_method("Next", ()).await
+    }
+
+    /// Previous item.
+    pub async fn previous(&self) -> Result<()> {
+        self.call_method("Previous", ()).await
+    }
+
+    /// Fast forward playback, this action is only stopped
+    /// when another method in this interface is called.
+    pub async fn fast_forward(&self) -> Result<()> {
+        self.call_method("FastForward", ()).await
+    }
+
+    /// Rewind playback, this action is only stopped
+    /// when another method in this interface is called.
+    pub async fn rewind(&self) -> Result<()> {
+        self.call_method("Rewind", ()).await
+    }
+}
+
+define_properties!(
+    Player,
+    /// Media player property.
+    pub PlayerProperty => {
+        /// Repeat setting.
+        property(
+            Repeat, Repeat,
+            dbus: (INTERFACE, "Repeat", String, OPTIONAL),
+            get: (repeat, v => {v.parse()?}),
+            set: (set_repeat, v => {v.to_string()}),
+        );
+
+        /// Shuffle setting.
+        property(
+            Shuffle, Shuffle,
+            dbus: (INTERFACE, "Shuffle", String, OPTIONAL),
+            get: (shuffle, v => {v.parse()?}),
+            set: (set_shuffle, v => {v.to_string()}),
+        );
+
+        /// Playback status.
+        property(
+            Status, PlayerStatus,
+            dbus: (INTERFACE, "Status", String, MANDATORY),
+            get: (status, v => {v.parse()?}),
+        );
+
+        /// Playback position.
+        ///
+        /// The position is not updated continuously during playback
+        /// but only when the status changes or the remote device reports it.
+        property(
+            Position, Duration,
+            dbus: (INTERFACE, "Position", u32, MANDATORY),
+            get: (position, v => {Duration::from_millis((*v).into())}),
+        );
+
+        /// Metadata of the current track.
+        property(
+            Track, Track,
+            dbus: (INTERFACE, "Track", PropMap, MANDATORY),
+            get: (track, v => {Track::from_dict(v)}),
+        );
+
+        /// Address of the device this player belongs to.
+        property(
+            Device, Address,
+            dbus: (INTERFACE, "Device", Path, MANDATORY),
+            get: (device, v => {
+                Device::parse_dbus_path(v).map(|(_, address)| address)
+                    .ok_or_else(|| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))?
+            }),
+        );
+
+        /// Player name.
+        property(
+            Name, String,
+            dbus: (INTERFACE, "Name", String, OPTIONAL),
+            get: (player_name, v => {v.to_owned()}),
+        );
+
+        /// Player type, for example "Audio" or "Video".
+        property(
+            Type, String,
+            dbus: (INTERFACE, "Type", String, OPTIONAL),
+            get: (player_type, v => {v.to_owned()}),
+        );
+
+        /// Player subtype, for example "Audio Book" or "Podcast".
+        property(
+            Subtype, String,
+            dbus: (INTERFACE, "Subtype", String, OPTIONAL),
+            get: (player_subtype, v => {v.to_owned()}),
+        );
+
+        /// Indicates whether the player supports browsing.
+        property(
+            Browsable, bool,
+            dbus: (INTERFACE, "Browsable", bool, OPTIONAL),
+            get: (is_browsable, v => {v.to_owned()}),
+        );
+
+        /// Indicates whether the player supports searching.
+        property(
+            Searchable, bool,
+            dbus: (INTERFACE, "Searchable", bool, OPTIONAL),
+            get: (is_searchable, v => {v.to_owned()}),
+        );
+    }
+);
+
+/// Media player event.
+#[cfg_attr(docsrs, doc(cfg(feature = "media")))]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PlayerEvent {
+    /// Property changed.
+    PropertyChanged(PlayerProperty),
+}
@@ -0,0 +1,469 @@
+//! Media transports.
+
+use dbus::{
+    arg::OwnedFd,
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use futures::{ready, stream, Stream, StreamExt};
+use pin_project::pin_project;
+use std::{
+    fmt,
+    mem::MaybeUninit,
+    os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use strum::{Display, EnumString};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::UnixStream,
+};
+use uuid::Uuid;
+
+use super::parse_media_object_path;
+use crate::{
+    Adapter, Address, Device, Error, ErrorKind, Event, InternalErrorKind, Result, SessionInner, SERVICE_NAME,
+    TIMEOUT,
+};
+
+pub(crate) const INTERFACE: &str = "org.bluez.MediaTransport1";
+
+/// Media transport state.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum TransportState {
+    /// Not streaming.
+    #[strum(serialize = "idle")]
+    Idle,
+    /// Streaming but not acquired.
+    #[strum(serialize = "pending")]
+    Pending,
+    /// Streaming as a broadcast source.
+    #[strum(serialize = "broadcasting")]
+    Broadcasting,
+    /// Streaming and acquired.
+    #[strum(serialize = "active")]
+    Active,
+}
+
+/// Interface to a media transport.
+///
+/// A transport is created by BlueZ once a [media endpoint](super::endpoint::Endpoint)
+/// has been configured for a remote device or for a broadcast stream.
+#[derive(Clone)]
+pub struct Transport {
+    inner: Arc<SessionInner>,
+    dbus_path: Path<'static>,
+    adapter_name: Arc<String>,
+    device_address: Option<Address>,
+    name: String,
+}
+
+impl fmt::Debug for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Transport {{ adapter_name: {}, device_address: {:?}, name: {} }}",
+            self.adapter_name(),
+            self.device_address(),
+            self.name()
+        )
+    }
+}
+
+impl Transport {
+    pub(crate) fn new(
+        inner: Arc<SessionInner>, adapter_name: Arc<String>, device_address: Address, name: &str,
+    ) -> Result<Self> {
+        let device_path = Device::dbus_path(&adapter_name, device_address)?;
+        Ok(Self {
+            inner,
+            dbus_path: Path::new(format!("{device_path}/{name}"))
+                .map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))?,
+            adapter_name,
+            device_address: Some(device_address),
+            name: name.to_string(),
+        })
+    }
+
+    pub(crate) fn from_dbus_path(inner: Arc<SessionInner>, path: &Path) -> Option<Self> {
+        let (adapter_name, device_address, name) = parse_media_object_path(path)?;
+        Some(Self {
+            inner,
+            dbus_path: path.clone().into_static(),
+            adapter_name: Arc::new(adapter_name.to_string()),
+            device_address,
+            name: name.to_string(),
+        })
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(SERVICE_NAME, &self.dbus_path, TIMEOUT, &*self.inner.connection)
+    }
+
+    /// The Bluetooth adapter name.
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    /// The Bluetooth address of the remote device this transport belongs to.
+    ///
+    /// This is `None` for broadcast transports, which belong to the adapter.
+    pub fn device_address(&self) -> Option<Address> {
+        self.device_address
+    }
+
+    /// The name of this transport relative to its device or adapter, for example `sep1/fd0`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Streams transport property changes.
+    ///
+    /// The stream ends when the transport is removed.
+    pub async fn events(&self) -> Result<impl Stream<Item = TransportEvent>> {
+        let events = self.inner.events(self.dbus_path.clone(), false).await?;
+        let stream = events.flat_map(move |event| match event {
+            Event::PropertiesChanged { interface, changed, .. } if interface == INTERFACE => stream::iter(
+                TransportProperty::from_prop_map(changed).into_iter().map(TransportEvent::PropertyChanged),
+            )
+            .boxed(),
+            _ => stream::empty().boxed(),
+        });
+
+        Ok(stream)
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(INTERFACE);
+
+    // ===========================================================================================
+    // Methods
+    // ===========================================================================================
+
+    /// Acquire the transport file descriptor for streaming media data.
+    ///
+    /// Call [release](Self::release) once streaming is finished.
+    pub async fn acquire(&self) -> Result<TransportStream> {
+        let (fd, read_mtu, write_mtu): (OwnedFd, u16, u16) = self.call_method("Acquire", ()).await?;
+        self.make_stream(fd, read_mtu, write_mtu)
+    }
+
+    /// Acquire the transport file descriptor only if the transport is in
+    /// [pending](TransportState::Pending) state at the time the request is received by BlueZ.
+    ///
+    /// Otherwise an error of kind [NotAvailable](ErrorKind::NotAvailable) is returned.
+    pub async fn try_acquire(&self) -> Result<TransportStream> {
+        let (fd, read_mtu, write_mtu): (OwnedFd, u16, u16) = self.call_method("TryAcquire", ()).await?;
+        self.make_stream(fd, read_mtu, write_mtu)
+    }
+
+    /// Releases the transport file descriptor.
+    pub async fn release(&self) -> Result<()> {
+        self.call_method("Release", ()).await
+    }
+
+    fn make_stream(&self, fd: OwnedFd, read_mtu: u16, write_mtu: u16) -> Result<TransportStream> {
+        let stream = unsafe { std::os::unix::net::UnixStream::from_raw_fd(fd.into_fd()) };
+        stream.set_nonblocking(true)?;
+        let stream = UnixStream::from_std(stream)?;
+        Ok(TransportStream {
+            adapter_name: self.adapter_name().to_string(),
+            device_address: self.device_address,
+            read_mtu: read_mtu.into(),
+            write_mtu: write_mtu.into(),
+            stream,
+            buf: Vec::new(),
+        })
+    }
+}
+
+/// Parses the `Device` property of a transport, which is the path of
+/// the adapter for broadcast transports.
+fn parse_device_property(path: &Path) -> Result<Option<Address>> {
+    match Device::parse_dbus_path(path) {
+        Some((_, address)) => Ok(Some(address)),
+        None if Adapter::parse_dbus_path(path).is_some() => Ok(None),
+        None => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+    }
+}
+
+define_properties!(
+    Transport,
+    /// Media transport property.
+    pub TransportProperty => {
+        /// Address of the device this transport belongs to.
+        ///
+        /// `None` for broadcast transports, which belong to the adapter.
+        property(
+            Device, Option<Address>,
+            dbus: (INTERFACE, "Device", Path, MANDATORY),
+            get: (device, v => {parse_device_property(v)?}),
+        );
+
+        /// UUID of the profile this transport is for.
+        property(
+            Uuid, Uuid,
+            dbus: (INTERFACE, "UUID", String, MANDATORY),
+            get: (uuid, v => {v.parse().map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidUuid(v.to_string()))))?}),
+        );
+
+        /// Assigned number of the codec that the transport supports.
+        ///
+        /// See [codec](super::codec) for known values.
+        property(
+            Codec, u8,
+            dbus: (INTERFACE, "Codec", u8, MANDATORY),
+            get: (codec, v => {v.to_owned()}),
+        );
+
+        /// Configuration blob; it is used as it is so the size and
+        /// byte order must match.
+        property(
+            Configuration, Vec<u8>,
+            dbus: (INTERFACE, "Configuration", Vec<u8>, MANDATORY),
+            get: (configuration, v => {v.to_owned()}),
+        );
+
+        /// Indicates the state of the transport.
+        property(
+            State, TransportState,
+            dbus: (INTERFACE, "State", String, MANDATORY),
+            get: (state, v => {v.parse()?}),
+        );
+
+        /// Optional. Transport delay in 1/10 of millisecond.
+        ///
+        /// This property is only writeable when the transport was
+        /// acquired by the sender.
+        property(
+            Delay, u16,
+            dbus: (INTERFACE, "Delay", u16, OPTIONAL),
+            get: (delay, v => {v.to_owned()}),
+            set: (set_delay, v => {v}),
+        );
+
+        /// Optional. Indicates volume level of the transport.
+        ///
+        /// Possible values are 0-127.
+        property(
+            Volume, u16,
+            dbus: (INTERFACE, "Volume", u16, OPTIONAL),
+            get: (volume, v => {v.to_owned()}),
+            set: (set_volume, v => {v}),
+        );
+    }
+);
+
+/// Media transport event.
+#[cfg_attr(docsrs, doc(cfg(feature = "media")))]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TransportEvent {
+    /// Property changed.
+    PropertyChanged(TransportProperty),
+}
+
+/// Streams media data to and from an acquired media transport.
+///
+/// Each read and write operation corresponds to a single media packet.
+/// Dropping this does not release the transport; use [Transport::release] for that.
+#[pin_project]
+#[derive(Debug)]
+pub struct TransportStream {
+    adapter_name: String,
+    device_address: Option<Address>,
+    read_mtu: usize,
+    write_mtu: usize,
+    #[pin]
+    stream: UnixStream,
+    buf: Vec<u8>,
+}
+
+impl TransportStream {
+    /// Name of adapter.
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    /// Address of remote device.
+    ///
+    /// This is `None` for broadcast transports.
+    pub fn device_address(&self) -> Option<Address> {
+        self.device_address
+    }
+
+    /// Maximum size of a received packet.
+    pub fn read_mtu(&self) -> usize {
+        self.read_mtu
+    }
+
+    /// Maximum size of a sent packet.
+    pub fn write_mtu(&self) -> usize {
+        self.write_mtu
+    }
+
+    /// Wait for a packet to become available.
+    pub async fn recvable(&self) -> std::io::Result<()> {
+        self.stream.readable().await
+    }
+
+    /// Try to receive a single packet.
+    ///
+    /// Does not wait for new data to arrive.
+    pub fn try_recv(&self) -> std::io::Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(self.read_mtu);
+        let n = self.stream.try_read_buf(&mut buf)?;
+        buf.truncate(n);
+        Ok(buf)
+    }
+
+    /// Receive a single packet.
+    ///
+    /// Waits for data to arrive.
+    pub async fn recv(&self) -> std::io::Result<Vec<u8>> {
+        loop {
+            self.recvable().await?;
+            match self.try_recv() {
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => continue,
+                res => return res,
+            }
+        }
+    }
+
+    /// Waits for send space to become available.
+    pub async fn sendable(&self) -> std::io::Result<()> {
+        self.stream.writable().await
+    }
+
+    /// Tries to send a single packet.
+    ///
+    /// The length of `buf` must not exceed [Self::write_mtu].
+    ///
+    /// Does not wait for send space to become available.
+    pub fn try_send(&self, buf: &[u8]) -> std::io::Result<()> {
+        if buf.len() > self.write_mtu {
+            return Err(std::io::Error::new(std::io::ErrorKind::WriteZero, "data length exceeds MTU"));
+        }
+        match self.stream.try_write(buf) {
+            Ok(n) if n == buf.len() => Ok(()),
+            Ok(_) => Err(std::io::Error::new(std::io::ErrorKind::Other, "partial write occured")),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Send a single packet.
+    ///
+    /// The length of `buf` must not exceed [Self::write_mtu].
+    ///
+    /// Waits for send space to become available.
+    pub async fn send(&self, buf: &[u8]) -> std::io::Result<()> {
+        loop {
+            self.sendable().await?;
+            match self.try_send(buf) {
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => continue,
+                res => return res,
+            }
+        }
+    }
+
+    /// Consumes this object, returning the raw underlying file descriptor.
+    pub fn into_raw_fd(self) -> std::io::Result<RawFd> {
+        Ok(self.stream.into_std()?.into_raw_fd())
+    }
+}
+
+impl AsyncRead for TransportStream {
+    /// Attempts to read a packet into `buf`.
+    ///
+    /// When a buffer of size less than [read_mtu] bytes is provided, the received
+    /// packet will be buffered internally and split over multiple read operations.
+    /// Thus, for best efficiency, provide a buffer of at least [read_mtu] bytes.
+    ///
+    /// [read_mtu]: TransportStream::read_mtu
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut ReadBuf) -> Poll<std::io::Result<()>> {
+        let buf_space = buf.remaining();
+        if !self.buf.is_empty() {
+            // Return buffered data first, if any.
+            let to_read = buf_space.min(self.buf.len());
+            let remaining = self.buf.split_off(to_read);
+            buf.put_slice(&self.buf);
+            self.buf = remaining;
+            Poll::Ready(Ok(()))
+        } else if buf_space < self.read_mtu {
+            let this = self.project();
+
+            // If provided buffer is too small, read into temporary buffer.
+            let mut mtu_buf: Vec<MaybeUninit<u8>> = vec![MaybeUninit::uninit(); *this.read_mtu];
+            let mut mtu_read_buf = ReadBuf::uninit(&mut mtu_buf);
+            ready!(this.stream.poll_read(cx, &mut mtu_read_buf))?;
+            let n = mtu_read_buf.filled().len();
+            mtu_buf.truncate(n);
+            let mut mtu_buf: Vec<u8> = mtu_buf.into_iter().map(|v| unsafe { v.assume_init() }).collect();
+
+            // Then fill provided buffer appropriately and keep the rest in
+            // our internal buffer.
+            *this.buf = mtu_buf.split_off(buf_space.min(n));
+            buf.put_slice(&mtu_buf);
+
+            Poll::Ready(Ok(()))
+        } else {
+            self.project().stream.poll_read(cx, buf)
+        }
+    }
+}
+
+impl AsyncWrite for TransportStream {
+    /// Attempt to write a packet from `buf`.
+    ///
+    /// A single write operation will send no more than [write_mtu](TransportStream::write_mtu) bytes.
+    /// However, attempting to send a larger buffer will not result in an error but a partial send.
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        let max_len = buf.len().min(self.write_mtu);
+        let buf = &buf[..max_len];
+        self.project().stream.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<std::io::Result<()>> {
+        self.project().stream.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<std::io::Result<()>> {
+        self.project().stream.poll_shutdown(cx)
+    }
+}
+
+impl AsRawFd for TransportStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.stream.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for TransportStream {
+    fn into_raw_fd(self) -> RawFd {
+        self.into_raw_fd().expect("into_raw_fd failed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn device_property() {
+        let path = Path::new("/org/bluez/hci0/dev_00_11_22_33_44_55").unwrap();
+        assert_eq!(parse_device_property(&path).unwrap(), Some("00:11:22:33:44:55".parse().unwrap()));
+
+        let path = Path::new("/org/bluez/hci0").unwrap();
+        assert_eq!(parse_device_property(&path).unwrap(), None);
+
+        let path = Path::new("/org/bluez/hci0/pac_bcast0").unwrap();
+        assert_eq!(
+            parse_device_property(&path).unwrap_err().kind,
+            ErrorKind::Internal(InternalErrorKind::InvalidValue)
+        );
+    }
+}
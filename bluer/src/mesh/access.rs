@@ -0,0 +1,626 @@
+//! Bluetooth mesh access layer messages.
+//!
+//! Access layer messages consist of an [Opcode] followed by the message parameters.
+//! Types implementing [MeshMessage] define how the parameters of a particular
+//! message are encoded and decoded.
+//!
+//! A [Dispatcher] receives the messages addressed to an element and routes them to
+//! the [ModelHandler]s registered for the models of the element.
+//! Using the [DispatcherHandle] messages can be sent and requests can be made,
+//! whose replies are awaited.
+
+use futures::{future, Future, StreamExt};
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::oneshot;
+
+use super::{
+    element::{ElementControl, ElementEvent, ElementRef, Model, VendorModel},
+    node::Node,
+};
+use crate::{Error, ErrorKind, Result};
+
+/// Default time to wait for a reply to a request.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Access layer message opcode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Opcode {
+    /// Opcode defined by the Bluetooth SIG.
+    ///
+    /// Values from 0x00 to 0x7e are encoded as one octet and
+    /// values from 0x8000 to 0xbfff are encoded as two octets.
+    Sig(u16),
+    /// Vendor-specific opcode encoded as three octets.
+    Vendor {
+        /// Vendor-assigned opcode (0x00 to 0x3f).
+        opcode: u8,
+        /// Company identifier assigned by the Bluetooth SIG.
+        company_id: u16,
+    },
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Sig(opcode) if opcode < 0x80 => write!(f, "{opcode:#04x}"),
+            Self::Sig(opcode) => write!(f, "{opcode:#06x}"),
+            Self::Vendor { opcode, company_id } => write!(f, "{opcode:#04x} of company {company_id:#06x}"),
+        }
+    }
+}
+
+impl Opcode {
+    /// Appends the encoded opcode to the buffer.
+    ///
+    /// Fails with [ErrorKind::InvalidArguments] if the opcode has no valid encoding,
+    /// i.e. for the reserved SIG opcode 0x7f, for SIG opcodes from 0x80 to 0x7fff
+    /// and above 0xbfff, and for vendor opcodes above 0x3f.
+    pub fn encode(&self, buf: &mut Vec<u8>) -> Result<()> {
+        match *self {
+            Self::Sig(opcode @ 0x00..=0x7e) => buf.push(opcode as u8),
+            Self::Sig(opcode @ 0x8000..=0xbfff) => buf.extend_from_slice(&opcode.to_be_bytes()),
+            Self::Vendor { opcode: opcode @ 0x00..=0x3f, company_id } => {
+                buf.push(0xc0 | opcode);
+                buf.extend_from_slice(&company_id.to_le_bytes());
+            }
+            _ => return Err(Error::new(ErrorKind::InvalidArguments)),
+        }
+        Ok(())
+    }
+
+    /// Splits the opcode from an access layer message and returns it
+    /// together with the message parameters.
+    ///
+    /// Fails with [ErrorKind::NotSupported] for the reserved opcode 0x7f and
+    /// with [ErrorKind::InvalidLength] if the message is too short.
+    pub fn decode(data: &[u8]) -> Result<(Self, &[u8])> {
+        match data {
+            [0x7f, ..] => Err(Error::new(ErrorKind::NotSupported)),
+            [op, rest @ ..] if op & 0x80 == 0 => Ok((Self::Sig(*op as u16), rest)),
+            [op1, op2, rest @ ..] if op1 & 0xc0 == 0x80 => {
+                Ok((Self::Sig(u16::from_be_bytes([*op1, *op2])), rest))
+            }
+            [op, cid1, cid2, rest @ ..] if op & 0xc0 == 0xc0 => {
+                Ok((Self::Vendor { opcode: op & 0x3f, company_id: u16::from_le_bytes([*cid1, *cid2]) }, rest))
+            }
+            _ => Err(Error::new(ErrorKind::InvalidLength)),
+        }
+    }
+}
+
+/// Model identifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ModelId {
+    /// Bluetooth SIG model.
+    Sig(u16),
+    /// Vendor model.
+    Vendor {
+        /// Company identifier assigned by the Bluetooth SIG.
+        company_id: u16,
+        /// Vendor-assigned model identifier.
+        model_id: u16,
+    },
+}
+
+impl ModelId {
+    /// Appends the encoded model identifier to the buffer.
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        match *self {
+            Self::Sig(id) => buf.extend_from_slice(&id.to_le_bytes()),
+            Self::Vendor { company_id, model_id } => {
+                buf.extend_from_slice(&company_id.to_le_bytes());
+                buf.extend_from_slice(&model_id.to_le_bytes());
+            }
+        }
+    }
+}
+
+impl From<&Model> for ModelId {
+    fn from(model: &Model) -> Self {
+        Self::Sig(model.id)
+    }
+}
+
+impl From<&VendorModel> for ModelId {
+    fn from(model: &VendorModel) -> Self {
+        Self::Vendor { company_id: model.vendor, model_id: model.id }
+    }
+}
+
+/// An access layer message with a fixed opcode.
+pub trait MeshMessage: Sized {
+    /// Opcode of the message.
+    const OPCODE: Opcode;
+
+    /// Appends the encoded message parameters to the buffer.
+    fn encode_params(&self, buf: &mut Vec<u8>);
+
+    /// Decodes the message parameters.
+    fn decode_params(params: &[u8]) -> Result<Self>;
+
+    /// Encodes the message including its opcode.
+    ///
+    /// Fails with [ErrorKind::InvalidArguments] if the opcode has no valid encoding.
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        Self::OPCODE.encode(&mut buf)?;
+        self.encode_params(&mut buf);
+        Ok(buf)
+    }
+
+    /// Decodes a message including its opcode.
+    ///
+    /// Fails with [ErrorKind::InvalidArguments] if the opcode does not match.
+    fn decode(data: &[u8]) -> Result<Self> {
+        let (opcode, params) = Opcode::decode(data)?;
+        if opcode != Self::OPCODE {
+            return Err(Error::new(ErrorKind::InvalidArguments));
+        }
+        Self::decode_params(params)
+    }
+}
+
+/// Key used to encrypt a received message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AccessKey {
+    /// Application key.
+    App {
+        /// Index of the application key.
+        key_index: u16,
+        /// Destination address of the message.
+        destination: u16,
+    },
+    /// Device key.
+    Device {
+        /// Whether the device key of the remote node was used.
+        remote: bool,
+        /// Index of the subnet the message was received on.
+        net_index: u16,
+    },
+}
+
+/// Access layer message received by an element.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AccessMessage {
+    /// Unicast address of the remote element that sent the message.
+    pub source: u16,
+    /// Key used to encrypt the message.
+    ///
+    /// Replies must be sent using the same key.
+    pub key: AccessKey,
+    /// Opcode.
+    pub opcode: Opcode,
+    /// Message parameters.
+    pub params: Vec<u8>,
+}
+
+impl AccessMessage {
+    fn from_event(event: ElementEvent) -> Result<Self> {
+        let (source, key, data) = match event {
+            ElementEvent::MessageReceived(msg) => {
+                (msg.source, AccessKey::App { key_index: msg.key_index, destination: msg.destination }, msg.data)
+            }
+            ElementEvent::DevKeyMessageReceived(msg) => {
+                (msg.source, AccessKey::Device { remote: msg.remote, net_index: msg.net_index }, msg.data)
+            }
+        };
+        let (opcode, params) = Opcode::decode(&data)?;
+        Ok(Self { source, key, opcode, params: params.to_vec() })
+    }
+
+    /// Decodes the message parameters.
+    ///
+    /// Fails with [ErrorKind::InvalidArguments] if the opcode does not match.
+    pub fn decode<M: MeshMessage>(&self) -> Result<M> {
+        if self.opcode != M::OPCODE {
+            return Err(Error::new(ErrorKind::InvalidArguments));
+        }
+        M::decode_params(&self.params)
+    }
+}
+
+/// Model handler function.
+///
+/// It is called for each received message and returns the encoded reply, if any.
+pub type ModelHandlerFun =
+    Box<dyn (Fn(AccessMessage) -> Pin<Box<dyn Future<Output = Option<Vec<u8>>> + Send>>) + Send + Sync>;
+
+/// Handler for the messages of a model.
+pub struct ModelHandler {
+    /// Model identifier.
+    pub model_id: ModelId,
+    /// Opcodes of the messages handled by the model.
+    pub opcodes: Vec<Opcode>,
+    /// Function called for each received message with one of the opcodes.
+    pub fun: ModelHandlerFun,
+}
+
+impl fmt::Debug for ModelHandler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ModelHandler").field("model_id", &self.model_id).field("opcodes", &self.opcodes).finish()
+    }
+}
+
+/// Routes the messages received by an element to the handlers of its models.
+///
+/// Use [run](Self::run) to start dispatching.
+pub struct Dispatcher {
+    node: Node,
+    element_control: ElementControl,
+    handlers: Vec<ModelHandler>,
+}
+
+impl fmt::Debug for Dispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Dispatcher").field("handlers", &self.handlers).finish()
+    }
+}
+
+impl Dispatcher {
+    /// Creates a dispatcher for the messages received by the element controlled by `element_control`.
+    pub fn new(node: Node, element_control: ElementControl) -> Self {
+        Self { node, element_control, handlers: Vec::new() }
+    }
+
+    /// Registers the handler of a model.
+    ///
+    /// Only one handler may be registered for each model.
+    /// If multiple handlers handle the same opcode, the first registered handler is used.
+    pub fn add_model(&mut self, handler: ModelHandler) -> Result<()> {
+        if self.handlers.iter().any(|h| h.model_id == handler.model_id) {
+            return Err(Error::new(ErrorKind::AlreadyExists));
+        }
+        self.handlers.push(handler);
+        Ok(())
+    }
+
+    /// Starts dispatching received messages.
+    ///
+    /// Replies returned by the model handlers are sent back to the source of
+    /// the message using the same key.
+    /// Received messages that are replies to pending requests made using the returned
+    /// handle are not passed to the model handlers.
+    ///
+    /// Dispatching stops when the handle is dropped.
+    pub fn run(self) -> DispatcherHandle {
+        let Self { node, mut element_control, handlers } = self;
+        let element_ref = element_control.element_ref();
+        let pending: Arc<Mutex<Vec<PendingReply>>> = Arc::new(Mutex::new(Vec::new()));
+        let (drop_tx, mut drop_rx) = oneshot::channel();
+
+        let handle = DispatcherHandle {
+            node: node.clone(),
+            element_ref: element_ref.clone(),
+            pending: pending.clone(),
+            timeout: DEFAULT_TIMEOUT,
+            _drop_tx: drop_tx,
+        };
+
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    event = element_control.next() => match event {
+                        Some(event) => event,
+                        None => break,
+                    },
+                    _ = &mut drop_rx => break,
+                };
+
+                let msg = match AccessMessage::from_event(event) {
+                    Ok(msg) => msg,
+                    Err(err) => {
+                        log::trace!("Discarding invalid mesh access message: {}", &err);
+                        continue;
+                    }
+                };
+
+                let reply_tx = {
+                    let mut pending = pending.lock().unwrap();
+                    pending.retain(|p| !p.tx.is_closed());
+                    pending.iter().position(|p| p.matches(&msg)).map(|pos| pending.remove(pos).tx)
+                };
+                if let Some(reply_tx) = reply_tx {
+                    let _ = reply_tx.send(msg);
+                    continue;
+                }
+
+                let Some(handler) = handlers.iter().find(|h| h.opcodes.contains(&msg.opcode)) else {
+                    log::trace!("No mesh model handles opcode {}", &msg.opcode);
+                    continue;
+                };
+                log::trace!(
+                    "Dispatching mesh message with opcode {} to model {:?}",
+                    &msg.opcode,
+                    &handler.model_id
+                );
+
+                let (source, key) = (msg.source, msg.key);
+                let reply = (handler.fun)(msg);
+                let node = node.clone();
+                let element_ref = element_ref.clone();
+                tokio::spawn(async move {
+                    let Some(data) = reply.await else { return };
+                    let res = match key {
+                        AccessKey::App { key_index, .. } => {
+                            node.send(&element_ref, source, key_index, &data).await
+                        }
+                        AccessKey::Device { remote, net_index } => {
+                            node.dev_key_send(&element_ref, source, remote, net_index, &data).await
+                        }
+                    };
+                    if let Err(err) = res {
+                        log::warn!("Sending mesh reply to {:#06x} failed: {}", source, &err);
+                    }
+                });
+            }
+        });
+
+        handle
+    }
+}
+
+struct PendingReply {
+    source: Option<u16>,
+    opcode: Opcode,
+    tx: oneshot::Sender<AccessMessage>,
+}
+
+impl PendingReply {
+    fn matches(&self, msg: &AccessMessage) -> bool {
+        msg.opcode == self.opcode && self.source.map(|source| source == msg.source).unwrap_or(true)
+    }
+}
+
+/// Source a reply to a request sent to `destination` must originate from.
+///
+/// Replies to requests sent to unicast addresses must come from the destination,
+/// while replies to requests sent to group or virtual addresses may come from any node.
+fn reply_source(destination: u16) -> Option<u16> {
+    (0x0001..=0x7fff).contains(&destination).then_some(destination)
+}
+
+/// Handle to a running [Dispatcher].
+///
+/// Drop to stop dispatching.
+pub struct DispatcherHandle {
+    node: Node,
+    element_ref: ElementRef,
+    pending: Arc<Mutex<Vec<PendingReply>>>,
+    timeout: Duration,
+    _drop_tx: oneshot::Sender<()>,
+}
+
+impl fmt::Debug for DispatcherHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DispatcherHandle")
+            .field("element_ref", &self.element_ref)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl DispatcherHandle {
+    /// Returns a reference to the element.
+    pub fn element_ref(&self) -> ElementRef {
+        self.element_ref.clone()
+    }
+
+    /// Sets the time to wait for a reply to a request.
+    ///
+    /// Requests fail with [ErrorKind::MeshResponseTimeout] when it elapses.
+    /// The default is [DEFAULT_TIMEOUT].
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Sends a message to the specified destination using the specified application key.
+    pub async fn send<M: MeshMessage>(&self, destination: u16, key_index: u16, msg: &M) -> Result<()> {
+        self.node.send(&self.element_ref, destination, key_index, &msg.encode()?).await
+    }
+
+    /// Publishes a message originated by the specified model of the element.
+    ///
+    /// The destination and application key are taken from the publication
+    /// of the model configured on the node.
+    pub async fn publish<M: MeshMessage>(&self, model_id: ModelId, msg: &M) -> Result<()> {
+        match model_id {
+            ModelId::Sig(model_id) => self.node.publish(&self.element_ref, model_id, &msg.encode()?).await,
+            ModelId::Vendor { company_id, model_id } => {
+                self.node.vendor_publish(&self.element_ref, company_id, model_id, &msg.encode()?).await
+            }
+        }
+    }
+
+    /// Sends a request to the specified destination and waits for the reply.
+    ///
+    /// If the destination is a unicast address, only a reply from it is accepted.
+    /// Otherwise the first reply received from any node is returned.
+    pub async fn request<Req, Resp>(&self, destination: u16, key_index: u16, request: &Req) -> Result<Resp>
+    where
+        Req: MeshMessage,
+        Resp: MeshMessage,
+    {
+        let (tx, rx) = oneshot::channel();
+        let source = reply_source(destination);
+        self.pending.lock().unwrap().push(PendingReply { source, opcode: Resp::OPCODE, tx });
+
+        self.send(destination, key_index, request).await?;
+
+        let reply = tokio::time::timeout(self.timeout, rx)
+            .await
+            .map_err(|_| Error::new(ErrorKind::MeshResponseTimeout))?
+            .map_err(|_| Error::new(ErrorKind::NotRegistered))?;
+        reply.decode()
+    }
+
+    /// Waits for a message with the specified opcode from the specified source, if any.
+    ///
+    /// The message is not passed to the model handlers.
+    pub async fn receive(&self, source: Option<u16>, opcode: Opcode) -> Result<AccessMessage> {
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().push(PendingReply { source, opcode, tx });
+
+        tokio::time::timeout(self.timeout, rx)
+            .await
+            .map_err(|_| Error::new(ErrorKind::MeshResponseTimeout))?
+            .map_err(|_| Error::new(ErrorKind::NotRegistered))
+    }
+}
+
+/// Creates a model handler for a single message type.
+///
+/// The handler function receives the decoded message and the received access message
+/// and returns the reply, if any.
+/// Messages that cannot be decoded are discarded.
+pub fn handler_fun<M, R, F, Fut>(f: F) -> ModelHandlerFun
+where
+    M: MeshMessage,
+    R: MeshMessage,
+    F: Fn(M, AccessMessage) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Option<R>> + Send + 'static,
+{
+    Box::new(move |msg: AccessMessage| match msg.decode::<M>() {
+        Ok(decoded) => {
+            let fut = f(decoded, msg);
+            Box::pin(async move {
+                match fut.await?.encode() {
+                    Ok(reply) => Some(reply),
+                    Err(err) => {
+                        log::warn!("Discarding unencodable mesh reply with opcode {}: {}", &R::OPCODE, &err);
+                        None
+                    }
+                }
+            })
+        }
+        Err(err) => {
+            log::trace!("Discarding undecodable mesh message with opcode {}: {}", &msg.opcode, &err);
+            Box::pin(future::ready(None))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opcode() {
+        let cases: [(&[u8], Opcode); 4] = [
+            (&[0x00], Opcode::Sig(0x00)),
+            (&[0x52], Opcode::Sig(0x52)),
+            (&[0x82, 0x01], Opcode::Sig(0x8201)),
+            (&[0xc1, 0x59, 0x00], Opcode::Vendor { opcode: 0x01, company_id: 0x0059 }),
+        ];
+        for (data, opcode) in cases {
+            let mut buf = Vec::new();
+            opcode.encode(&mut buf).unwrap();
+            assert_eq!(buf, data);
+            assert_eq!(Opcode::decode(&[data, &[0xaa][..]].concat()).unwrap(), (opcode, &[0xaa][..]));
+        }
+
+        assert_eq!(Opcode::decode(&[]).unwrap_err().kind, ErrorKind::InvalidLength);
+        assert_eq!(Opcode::decode(&[0x82]).unwrap_err().kind, ErrorKind::InvalidLength);
+        assert_eq!(Opcode::decode(&[0xc1, 0x59]).unwrap_err().kind, ErrorKind::InvalidLength);
+        assert_eq!(Opcode::decode(&[0x7f]).unwrap_err().kind, ErrorKind::NotSupported);
+    }
+
+    #[test]
+    fn opcode_encoding_boundaries() {
+        let valid: [(Opcode, &[u8]); 4] = [
+            (Opcode::Sig(0x7e), &[0x7e]),
+            (Opcode::Sig(0x8000), &[0x80, 0x00]),
+            (Opcode::Sig(0xbfff), &[0xbf, 0xff]),
+            (Opcode::Vendor { opcode: 0x3f, company_id: 0x0059 }, &[0xff, 0x59, 0x00]),
+        ];
+        for (opcode, data) in valid {
+            let mut buf = Vec::new();
+            opcode.encode(&mut buf).unwrap();
+            assert_eq!(buf, data);
+        }
+
+        let invalid = [
+            Opcode::Sig(0x7f),
+            Opcode::Sig(0x80),
+            Opcode::Sig(0x3fff),
+            Opcode::Sig(0x7fff),
+            Opcode::Sig(0xc000),
+            Opcode::Vendor { opcode: 0x40, company_id: 0x0059 },
+        ];
+        for opcode in invalid {
+            let mut buf = Vec::new();
+            assert_eq!(opcode.encode(&mut buf).unwrap_err().kind, ErrorKind::InvalidArguments);
+            assert!(buf.is_empty());
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Set(u8);
+
+    impl MeshMessage for Set {
+        const OPCODE: Opcode = Opcode::Sig(0x8202);
+
+        fn encode_params(&self, buf: &mut Vec<u8>) {
+            buf.push(self.0);
+        }
+
+        fn decode_params(params: &[u8]) -> Result<Self> {
+            match params {
+                [value] => Ok(Self(*value)),
+                _ => Err(Error::new(ErrorKind::InvalidLength)),
+            }
+        }
+    }
+
+    #[test]
+    fn mesh_message() {
+        assert_eq!(Set(1).encode().unwrap(), [0x82, 0x02, 0x01]);
+        assert_eq!(Set::decode(&[0x82, 0x02, 0x01]).unwrap(), Set(1));
+        assert_eq!(Set::decode(&[0x82, 0x03, 0x01]).unwrap_err().kind, ErrorKind::InvalidArguments);
+        assert_eq!(Set::decode(&[0x82, 0x02]).unwrap_err().kind, ErrorKind::InvalidLength);
+    }
+
+    fn message(source: u16, opcode: Opcode) -> AccessMessage {
+        AccessMessage {
+            source,
+            key: AccessKey::App { key_index: 0, destination: 0x0003 },
+            opcode,
+            params: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn pending_reply() {
+        let (tx, _rx) = oneshot::channel();
+        let from_source = PendingReply { source: Some(0x0002), opcode: Opcode::Sig(0x8204), tx };
+        assert!(from_source.matches(&message(0x0002, Opcode::Sig(0x8204))));
+        assert!(!from_source.matches(&message(0x0004, Opcode::Sig(0x8204))));
+        assert!(!from_source.matches(&message(0x0002, Opcode::Sig(0x8202))));
+
+        let (tx, _rx) = oneshot::channel();
+        let from_any = PendingReply { source: None, opcode: Opcode::Sig(0x8204), tx };
+        assert!(from_any.matches(&message(0x0002, Opcode::Sig(0x8204))));
+        assert!(from_any.matches(&message(0x0004, Opcode::Sig(0x8204))));
+        assert!(!from_any.matches(&message(0x0002, Opcode::Sig(0x8202))));
+    }
+
+    #[test]
+    fn request_reply_source() {
+        // Unicast destinations must reply themselves.
+        assert_eq!(reply_source(0x0001), Some(0x0001));
+        assert_eq!(reply_source(0x7fff), Some(0x7fff));
+        // Virtual and group destinations may be answered by any node.
+        assert_eq!(reply_source(0x8000), None);
+        assert_eq!(reply_source(0xc000), None);
+        assert_eq!(reply_source(0xffff), None);
+        // The unassigned address is not unicast.
+        assert_eq!(reply_source(0x0000), None);
+    }
+}
@@ -12,7 +12,7 @@ use uuid::Uuid;
 
 use super::{
     agent::{ProvisionAgent, RegisteredProvisionAgent},
-    management::{AddNodeFailedReason, NodeAdded},
+    management::{AddNodeFailedReason, NodeAdded, UnprovisionedDevice},
     provisioner::{Provisioner, RegisteredProvisioner},
 };
 use crate::{
@@ -101,6 +101,7 @@ pub(crate) struct RegisteredApplication {
     properties: Properties,
     join_result_tx: mpsc::Sender<std::result::Result<u64, JoinFailedReason>>,
     pub(crate) add_node_result_tx: broadcast::Sender<(Uuid, std::result::Result<NodeAdded, AddNodeFailedReason>)>,
+    pub(crate) scan_result_tx: broadcast::Sender<UnprovisionedDevice>,
 }
 
 impl RegisteredApplication {
@@ -167,6 +168,7 @@ impl RegisteredApplication {
 
         let (join_result_tx, join_result_rx) = mpsc::channel(1);
         let (add_node_result_tx, add_node_result_rx) = broadcast::channel(1024);
+        let (scan_result_tx, scan_result_rx) = broadcast::channel(1024);
         let this = Arc::new(Self {
             inner: inner.clone(),
             device_id,
@@ -174,8 +176,9 @@ impl RegisteredApplication {
             properties,
             join_result_tx,
             add_node_result_tx,
+            scan_result_tx,
         });
-        let app_inner = Arc::new(ApplicationInner { add_node_result_rx });
+        let app_inner = Arc::new(ApplicationInner { add_node_result_rx, scan_result_rx });
 
         let root_path = this.dbus_path();
         log::trace!("Publishing mesh application at {}", &root_path);
@@ -199,7 +202,7 @@ impl RegisteredApplication {
             if this.provisioner.is_some() {
                 ifaces.push(inner.provisioner_token);
             }
-            cr.insert(this.app_dbus_path(), &[inner.application_token], this.clone());
+            cr.insert(this.app_dbus_path(), &ifaces, this.clone());
 
             // register elements
             for (element_idx, element) in elements.into_iter().enumerate() {
@@ -232,6 +235,7 @@ impl RegisteredApplication {
 
 pub(crate) struct ApplicationInner {
     pub add_node_result_rx: broadcast::Receiver<(Uuid, std::result::Result<NodeAdded, AddNodeFailedReason>)>,
+    pub scan_result_rx: broadcast::Receiver<UnprovisionedDevice>,
 }
 
 /// Handle to Bluetooth mesh application.
@@ -259,7 +263,9 @@ impl fmt::Debug for ApplicationHandle {
 impl ApplicationHandle {
     /// Token.
     ///
-    /// Only available when application was registered using [`Network::join`](super::network::Network::join).
+    /// Only available when application was registered using [`Network::join`](super::network::Network::join),
+    /// [`Network::create_network`](super::network::Network::create_network) or
+    /// [`Network::import`](super::network::Network::import).
     ///
     /// The token parameter serves as a unique identifier of the
     /// particular node. The token must be preserved by the application
@@ -0,0 +1,1099 @@
+//! Bluetooth mesh Configuration Client model.
+//!
+//! The Configuration Client model configures the Configuration Server model
+//! of provisioned nodes, i.e. their application keys, model bindings, publications,
+//! subscriptions and features.
+//!
+//! Requests are encoded using [ConfigRequest] and replies are decoded into [ConfigResponse].
+//! [ConfigClient] sends requests encrypted with the device key of the remote node
+//! and waits for the corresponding replies.
+
+use futures::StreamExt;
+use std::{fmt, time::Duration};
+
+use super::{
+    access::{ModelId, Opcode},
+    element::{ElementControl, ElementEvent, ElementRef},
+    node::Node,
+};
+use crate::{Error, ErrorKind, InternalErrorKind, Result};
+
+/// SIG model id of the Configuration Server model.
+pub const CONFIG_SERVER_MODEL_ID: u16 = 0x0000;
+
+/// SIG model id of the Configuration Client model.
+pub const CONFIG_CLIENT_MODEL_ID: u16 = 0x0001;
+
+/// Default time to wait for a reply from a Configuration Server.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const OP_APP_KEY_ADD: Opcode = Opcode::Sig(0x00);
+const OP_COMPOSITION_DATA_STATUS: Opcode = Opcode::Sig(0x02);
+const OP_MODEL_PUBLICATION_SET: Opcode = Opcode::Sig(0x03);
+const OP_APP_KEY_STATUS: Opcode = Opcode::Sig(0x8003);
+const OP_COMPOSITION_DATA_GET: Opcode = Opcode::Sig(0x8008);
+const OP_DEFAULT_TTL_GET: Opcode = Opcode::Sig(0x800c);
+const OP_DEFAULT_TTL_SET: Opcode = Opcode::Sig(0x800d);
+const OP_DEFAULT_TTL_STATUS: Opcode = Opcode::Sig(0x800e);
+const OP_FRIEND_GET: Opcode = Opcode::Sig(0x800f);
+const OP_FRIEND_SET: Opcode = Opcode::Sig(0x8010);
+const OP_FRIEND_STATUS: Opcode = Opcode::Sig(0x8011);
+const OP_GATT_PROXY_GET: Opcode = Opcode::Sig(0x8012);
+const OP_GATT_PROXY_SET: Opcode = Opcode::Sig(0x8013);
+const OP_GATT_PROXY_STATUS: Opcode = Opcode::Sig(0x8014);
+const OP_MODEL_PUBLICATION_STATUS: Opcode = Opcode::Sig(0x8019);
+const OP_MODEL_SUBSCRIPTION_ADD: Opcode = Opcode::Sig(0x801b);
+const OP_MODEL_SUBSCRIPTION_DELETE: Opcode = Opcode::Sig(0x801c);
+const OP_MODEL_SUBSCRIPTION_STATUS: Opcode = Opcode::Sig(0x801f);
+const OP_RELAY_GET: Opcode = Opcode::Sig(0x8026);
+const OP_RELAY_SET: Opcode = Opcode::Sig(0x8027);
+const OP_RELAY_STATUS: Opcode = Opcode::Sig(0x8028);
+const OP_MODEL_APP_BIND: Opcode = Opcode::Sig(0x803d);
+const OP_MODEL_APP_STATUS: Opcode = Opcode::Sig(0x803e);
+const OP_MODEL_APP_UNBIND: Opcode = Opcode::Sig(0x803f);
+const OP_NODE_RESET: Opcode = Opcode::Sig(0x8049);
+const OP_NODE_RESET_STATUS: Opcode = Opcode::Sig(0x804a);
+
+/// Status code of a Configuration Server reply.
+#[derive(Clone, Copy, Debug, displaydoc::Display, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Status {
+    /// success
+    Success,
+    /// invalid address
+    InvalidAddress,
+    /// invalid model
+    InvalidModel,
+    /// invalid application key index
+    InvalidAppKeyIndex,
+    /// invalid network key index
+    InvalidNetKeyIndex,
+    /// insufficient resources
+    InsufficientResources,
+    /// key index already stored
+    KeyIndexAlreadyStored,
+    /// invalid publish parameters
+    InvalidPublishParameters,
+    /// not a subscribe model
+    NotASubscribeModel,
+    /// storage failure
+    StorageFailure,
+    /// feature not supported
+    FeatureNotSupported,
+    /// cannot update
+    CannotUpdate,
+    /// cannot remove
+    CannotRemove,
+    /// cannot bind
+    CannotBind,
+    /// temporarily unable to change state
+    TemporarilyUnableToChangeState,
+    /// cannot set
+    CannotSet,
+    /// unspecified error
+    UnspecifiedError,
+    /// invalid binding
+    InvalidBinding,
+    /// unknown status {0:#04x}
+    Unknown(u8),
+}
+
+impl From<u8> for Status {
+    fn from(code: u8) -> Self {
+        match code {
+            0x00 => Self::Success,
+            0x01 => Self::InvalidAddress,
+            0x02 => Self::InvalidModel,
+            0x03 => Self::InvalidAppKeyIndex,
+            0x04 => Self::InvalidNetKeyIndex,
+            0x05 => Self::InsufficientResources,
+            0x06 => Self::KeyIndexAlreadyStored,
+            0x07 => Self::InvalidPublishParameters,
+            0x08 => Self::NotASubscribeModel,
+            0x09 => Self::StorageFailure,
+            0x0a => Self::FeatureNotSupported,
+            0x0b => Self::CannotUpdate,
+            0x0c => Self::CannotRemove,
+            0x0d => Self::CannotBind,
+            0x0e => Self::TemporarilyUnableToChangeState,
+            0x0f => Self::CannotSet,
+            0x10 => Self::UnspecifiedError,
+            0x11 => Self::InvalidBinding,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl Status {
+    /// Converts a status other than [Status::Success] into an error.
+    fn check(self) -> Result<()> {
+        match self {
+            Self::Success => Ok(()),
+            other => Err(Error::new(ErrorKind::MeshConfigFailed(other))),
+        }
+    }
+}
+
+/// State of a node feature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FeatureState {
+    /// Feature is supported but disabled.
+    Disabled,
+    /// Feature is supported and enabled.
+    Enabled,
+    /// Feature is not supported.
+    ///
+    /// This cannot be set.
+    NotSupported,
+}
+
+impl FeatureState {
+    fn from_u8(value: u8) -> Result<Self> {
+        match value {
+            0x00 => Ok(Self::Disabled),
+            0x01 => Ok(Self::Enabled),
+            0x02 => Ok(Self::NotSupported),
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Disabled => 0x00,
+            Self::Enabled => 0x01,
+            Self::NotSupported => 0x02,
+        }
+    }
+}
+
+/// Number of retransmissions and interval between them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Retransmit {
+    /// Number of retransmissions (0 to 7).
+    pub count: u8,
+    /// Number of interval steps between retransmissions (0 to 31).
+    ///
+    /// The interval is `(interval_steps + 1) * 50 ms` for publications
+    /// and `(interval_steps + 1) * 10 ms` for relayed messages.
+    pub interval_steps: u8,
+}
+
+impl Retransmit {
+    fn from_u8(value: u8) -> Self {
+        Self { count: value & 0x07, interval_steps: value >> 3 }
+    }
+
+    fn to_u8(self) -> u8 {
+        (self.count & 0x07) | (self.interval_steps << 3)
+    }
+}
+
+/// Relay feature state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Relay {
+    /// Relay state.
+    pub state: FeatureState,
+    /// Retransmission of relayed messages.
+    pub retransmit: Retransmit,
+}
+
+/// Node features.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Features {
+    /// Relay feature is supported.
+    pub relay: bool,
+    /// Proxy feature is supported.
+    pub proxy: bool,
+    /// Friend feature is supported.
+    pub friend: bool,
+    /// Low power feature is supported.
+    pub low_power: bool,
+}
+
+impl Features {
+    fn from_u16(value: u16) -> Self {
+        Self {
+            relay: value & 0x0001 != 0,
+            proxy: value & 0x0002 != 0,
+            friend: value & 0x0004 != 0,
+            low_power: value & 0x0008 != 0,
+        }
+    }
+}
+
+/// Element description within composition data.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompositionElement {
+    /// Location descriptor as defined in the GATT Bluetooth Namespace
+    /// Descriptors section of the Bluetooth SIG Assigned Numbers.
+    pub location: u16,
+    /// SIG and vendor models of the element.
+    pub models: Vec<ModelId>,
+}
+
+/// Composition data page 0 of a node.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompositionData {
+    /// Company identifier assigned by the Bluetooth SIG.
+    pub company_id: u16,
+    /// Vendor-assigned product identifier.
+    pub product_id: u16,
+    /// Vendor-assigned product version identifier.
+    pub version_id: u16,
+    /// Minimum number of replay protection list entries.
+    pub replay_protection: u16,
+    /// Supported features.
+    pub features: Features,
+    /// Elements of the node, starting with the primary element.
+    pub elements: Vec<CompositionElement>,
+}
+
+impl CompositionData {
+    fn decode(r: &mut Reader) -> Result<Self> {
+        let company_id = r.u16()?;
+        let product_id = r.u16()?;
+        let version_id = r.u16()?;
+        let replay_protection = r.u16()?;
+        let features = Features::from_u16(r.u16()?);
+
+        let mut elements = Vec::new();
+        while !r.is_empty() {
+            let location = r.u16()?;
+            let num_sig = r.u8()?;
+            let num_vendor = r.u8()?;
+            let mut models = Vec::new();
+            for _ in 0..num_sig {
+                models.push(ModelId::Sig(r.u16()?));
+            }
+            for _ in 0..num_vendor {
+                models.push(ModelId::Vendor { company_id: r.u16()?, model_id: r.u16()? });
+            }
+            elements.push(CompositionElement { location, models });
+        }
+
+        Ok(Self { company_id, product_id, version_id, replay_protection, features, elements })
+    }
+}
+
+/// Model publication parameters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Publication {
+    /// Publish address.
+    ///
+    /// Zero disables publication.
+    pub address: u16,
+    /// Index of the application key used for publishing.
+    pub app_index: u16,
+    /// Use friendship security credentials instead of master security credentials.
+    pub friendship_credentials: bool,
+    /// Time to live of published messages.
+    ///
+    /// 0xff uses the default TTL of the node.
+    pub ttl: u8,
+    /// Publish period.
+    ///
+    /// The number of steps is stored in the 6 least significant bits and
+    /// the step resolution (100 ms, 1 s, 10 s or 10 min) in the 2 most significant bits.
+    pub period: u8,
+    /// Retransmission of published messages.
+    pub retransmit: Retransmit,
+}
+
+impl Publication {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.address.to_le_bytes());
+        let index = (self.app_index & 0x0fff) | if self.friendship_credentials { 0x1000 } else { 0 };
+        buf.extend_from_slice(&index.to_le_bytes());
+        buf.push(self.ttl);
+        buf.push(self.period);
+        buf.push(self.retransmit.to_u8());
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self> {
+        let address = r.u16()?;
+        let index = r.u16()?;
+        Ok(Self {
+            address,
+            app_index: index & 0x0fff,
+            friendship_credentials: index & 0x1000 != 0,
+            ttl: r.u8()?,
+            period: r.u8()?,
+            retransmit: Retransmit::from_u8(r.u8()?),
+        })
+    }
+}
+
+/// Reply to adding an application key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AppKeyStatus {
+    /// Status code.
+    pub status: Status,
+    /// Network key index.
+    pub net_index: u16,
+    /// Application key index.
+    pub app_index: u16,
+}
+
+/// Reply to binding or unbinding an application key to a model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModelAppStatus {
+    /// Status code.
+    pub status: Status,
+    /// Address of the element.
+    pub element_address: u16,
+    /// Application key index.
+    pub app_index: u16,
+    /// Model identifier.
+    pub model_id: ModelId,
+}
+
+/// Reply to setting the publication of a model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModelPublicationStatus {
+    /// Status code.
+    pub status: Status,
+    /// Address of the element.
+    pub element_address: u16,
+    /// Publication parameters.
+    pub publication: Publication,
+    /// Model identifier.
+    pub model_id: ModelId,
+}
+
+/// Reply to changing the subscriptions of a model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModelSubscriptionStatus {
+    /// Status code.
+    pub status: Status,
+    /// Address of the element.
+    pub element_address: u16,
+    /// Subscription address.
+    pub address: u16,
+    /// Model identifier.
+    pub model_id: ModelId,
+}
+
+/// Request sent by the Configuration Client to a Configuration Server.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ConfigRequest {
+    /// Get a page of the composition data.
+    CompositionDataGet {
+        /// Page number.
+        page: u8,
+    },
+    /// Add an application key.
+    ///
+    /// Since application keys are held by the mesh daemon, use
+    /// [ConfigClient::app_key_add] to send this request to a node.
+    AppKeyAdd {
+        /// Index of the network key the application key is bound to.
+        net_index: u16,
+        /// Application key index.
+        app_index: u16,
+        /// Application key.
+        key: [u8; 16],
+    },
+    /// Bind an application key to a model.
+    ModelAppBind {
+        /// Address of the element.
+        element_address: u16,
+        /// Application key index.
+        app_index: u16,
+        /// Model identifier.
+        model_id: ModelId,
+    },
+    /// Unbind an application key from a model.
+    ModelAppUnbind {
+        /// Address of the element.
+        element_address: u16,
+        /// Application key index.
+        app_index: u16,
+        /// Model identifier.
+        model_id: ModelId,
+    },
+    /// Set the publication of a model.
+    ModelPublicationSet {
+        /// Address of the element.
+        element_address: u16,
+        /// Publication parameters.
+        publication: Publication,
+        /// Model identifier.
+        model_id: ModelId,
+    },
+    /// Add an address to the subscription list of a model.
+    ModelSubscriptionAdd {
+        /// Address of the element.
+        element_address: u16,
+        /// Subscription address.
+        address: u16,
+        /// Model identifier.
+        model_id: ModelId,
+    },
+    /// Remove an address from the subscription list of a model.
+    ModelSubscriptionDelete {
+        /// Address of the element.
+        element_address: u16,
+        /// Subscription address.
+        address: u16,
+        /// Model identifier.
+        model_id: ModelId,
+    },
+    /// Get the relay state.
+    RelayGet,
+    /// Set the relay state.
+    RelaySet(Relay),
+    /// Get the GATT proxy state.
+    GattProxyGet,
+    /// Set the GATT proxy state.
+    GattProxySet(FeatureState),
+    /// Get the friend state.
+    FriendGet,
+    /// Set the friend state.
+    FriendSet(FeatureState),
+    /// Get the default time to live.
+    DefaultTtlGet,
+    /// Set the default time to live.
+    DefaultTtlSet(u8),
+    /// Reset the node and remove it from the network.
+    NodeReset,
+}
+
+impl ConfigRequest {
+    fn opcode(&self) -> Opcode {
+        match self {
+            Self::CompositionDataGet { .. } => OP_COMPOSITION_DATA_GET,
+            Self::AppKeyAdd { .. } => OP_APP_KEY_ADD,
+            Self::ModelAppBind { .. } => OP_MODEL_APP_BIND,
+            Self::ModelAppUnbind { .. } => OP_MODEL_APP_UNBIND,
+            Self::ModelPublicationSet { .. } => OP_MODEL_PUBLICATION_SET,
+            Self::ModelSubscriptionAdd { .. } => OP_MODEL_SUBSCRIPTION_ADD,
+            Self::ModelSubscriptionDelete { .. } => OP_MODEL_SUBSCRIPTION_DELETE,
+            Self::RelayGet => OP_RELAY_GET,
+            Self::RelaySet(_) => OP_RELAY_SET,
+            Self::GattProxyGet => OP_GATT_PROXY_GET,
+            Self::GattProxySet(_) => OP_GATT_PROXY_SET,
+            Self::FriendGet => OP_FRIEND_GET,
+            Self::FriendSet(_) => OP_FRIEND_SET,
+            Self::DefaultTtlGet => OP_DEFAULT_TTL_GET,
+            Self::DefaultTtlSet(_) => OP_DEFAULT_TTL_SET,
+            Self::NodeReset => OP_NODE_RESET,
+        }
+    }
+
+    /// Opcode of the reply to this request.
+    fn reply_opcode(&self) -> Opcode {
+        match self {
+            Self::CompositionDataGet { .. } => OP_COMPOSITION_DATA_STATUS,
+            Self::AppKeyAdd { .. } => OP_APP_KEY_STATUS,
+            Self::ModelAppBind { .. } | Self::ModelAppUnbind { .. } => OP_MODEL_APP_STATUS,
+            Self::ModelPublicationSet { .. } => OP_MODEL_PUBLICATION_STATUS,
+            Self::ModelSubscriptionAdd { .. } | Self::ModelSubscriptionDelete { .. } => {
+                OP_MODEL_SUBSCRIPTION_STATUS
+            }
+            Self::RelayGet | Self::RelaySet(_) => OP_RELAY_STATUS,
+            Self::GattProxyGet | Self::GattProxySet(_) => OP_GATT_PROXY_STATUS,
+            Self::FriendGet | Self::FriendSet(_) => OP_FRIEND_STATUS,
+            Self::DefaultTtlGet | Self::DefaultTtlSet(_) => OP_DEFAULT_TTL_STATUS,
+            Self::NodeReset => OP_NODE_RESET_STATUS,
+        }
+    }
+
+    /// Encodes the request into an access layer message including its opcode.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.opcode().encode(&mut buf)?;
+        match self {
+            Self::CompositionDataGet { page } => buf.push(*page),
+            Self::AppKeyAdd { net_index, app_index, key } => {
+                buf.extend_from_slice(&pack_key_indices(*net_index, *app_index));
+                buf.extend_from_slice(key);
+            }
+            Self::ModelAppBind { element_address, app_index, model_id }
+            | Self::ModelAppUnbind { element_address, app_index, model_id } => {
+                buf.extend_from_slice(&element_address.to_le_bytes());
+                buf.extend_from_slice(&app_index.to_le_bytes());
+                model_id.encode(&mut buf);
+            }
+            Self::ModelPublicationSet { element_address, publication, model_id } => {
+                buf.extend_from_slice(&element_address.to_le_bytes());
+                publication.encode(&mut buf);
+                model_id.encode(&mut buf);
+            }
+            Self::ModelSubscriptionAdd { element_address, address, model_id }
+            | Self::ModelSubscriptionDelete { element_address, address, model_id } => {
+                buf.extend_from_slice(&element_address.to_le_bytes());
+                buf.extend_from_slice(&address.to_le_bytes());
+                model_id.encode(&mut buf);
+            }
+            Self::RelaySet(relay) => {
+                buf.push(relay.state.to_u8());
+                buf.push(relay.retransmit.to_u8());
+            }
+            Self::GattProxySet(state) | Self::FriendSet(state) => buf.push(state.to_u8()),
+            Self::DefaultTtlSet(ttl) => buf.push(*ttl),
+            Self::RelayGet | Self::GattProxyGet | Self::FriendGet | Self::DefaultTtlGet | Self::NodeReset => (),
+        }
+        Ok(buf)
+    }
+}
+
+/// Reply sent by a Configuration Server to the Configuration Client.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ConfigResponse {
+    /// Composition data page 0.
+    CompositionData(CompositionData),
+    /// Application key status.
+    AppKeyStatus(AppKeyStatus),
+    /// Model application key binding status.
+    ModelAppStatus(ModelAppStatus),
+    /// Model publication status.
+    ModelPublicationStatus(ModelPublicationStatus),
+    /// Model subscription status.
+    ModelSubscriptionStatus(ModelSubscriptionStatus),
+    /// Relay state.
+    RelayStatus(Relay),
+    /// GATT proxy state.
+    GattProxyStatus(FeatureState),
+    /// Friend state.
+    FriendStatus(FeatureState),
+    /// Default time to live.
+    DefaultTtlStatus(u8),
+    /// Node has been reset.
+    NodeResetStatus,
+}
+
+impl ConfigResponse {
+    /// Decodes a reply from an access layer message including its opcode.
+    ///
+    /// Fails with [ErrorKind::NotSupported] if the opcode is not a supported
+    /// Configuration Server reply and with [ErrorKind::InvalidLength] if the
+    /// message is truncated or too long.
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let (opcode, params) = Opcode::decode(data)?;
+        let mut r = Reader(params);
+        let this = match opcode {
+            OP_COMPOSITION_DATA_STATUS => {
+                if r.u8()? != 0 {
+                    return Err(Error::new(ErrorKind::NotSupported));
+                }
+                Self::CompositionData(CompositionData::decode(&mut r)?)
+            }
+            OP_APP_KEY_STATUS => {
+                let status = r.u8()?.into();
+                let (net_index, app_index) = r.key_indices()?;
+                Self::AppKeyStatus(AppKeyStatus { status, net_index, app_index })
+            }
+            OP_MODEL_APP_STATUS => Self::ModelAppStatus(ModelAppStatus {
+                status: r.u8()?.into(),
+                element_address: r.u16()?,
+                app_index: r.u16()?,
+                model_id: r.model_id()?,
+            }),
+            OP_MODEL_PUBLICATION_STATUS => Self::ModelPublicationStatus(ModelPublicationStatus {
+                status: r.u8()?.into(),
+                element_address: r.u16()?,
+                publication: Publication::decode(&mut r)?,
+                model_id: r.model_id()?,
+            }),
+            OP_MODEL_SUBSCRIPTION_STATUS => Self::ModelSubscriptionStatus(ModelSubscriptionStatus {
+                status: r.u8()?.into(),
+                element_address: r.u16()?,
+                address: r.u16()?,
+                model_id: r.model_id()?,
+            }),
+            OP_RELAY_STATUS => Self::RelayStatus(Relay {
+                state: FeatureState::from_u8(r.u8()?)?,
+                retransmit: Retransmit::from_u8(r.u8()?),
+            }),
+            OP_GATT_PROXY_STATUS => Self::GattProxyStatus(FeatureState::from_u8(r.u8()?)?),
+            OP_FRIEND_STATUS => Self::FriendStatus(FeatureState::from_u8(r.u8()?)?),
+            OP_DEFAULT_TTL_STATUS => Self::DefaultTtlStatus(r.u8()?),
+            OP_NODE_RESET_STATUS => Self::NodeResetStatus,
+            _ => return Err(Error::new(ErrorKind::NotSupported)),
+        };
+        r.finish()?;
+        Ok(this)
+    }
+}
+
+/// Packs two 12-bit key indices into three octets.
+fn pack_key_indices(first: u16, second: u16) -> [u8; 3] {
+    let packed = (first as u32 & 0xfff) | ((second as u32 & 0xfff) << 12);
+    let [b0, b1, b2, _] = packed.to_le_bytes();
+    [b0, b1, b2]
+}
+
+/// Reads little-endian message parameters.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(Error::new(ErrorKind::InvalidLength));
+        }
+        let (data, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(data)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let data = self.take(2)?;
+        Ok(u16::from_le_bytes([data[0], data[1]]))
+    }
+
+    fn key_indices(&mut self) -> Result<(u16, u16)> {
+        let data = self.take(3)?;
+        let packed = u32::from_le_bytes([data[0], data[1], data[2], 0]);
+        Ok(((packed & 0xfff) as u16, (packed >> 12) as u16))
+    }
+
+    /// Reads a SIG or vendor model id, depending on the remaining length.
+    fn model_id(&mut self) -> Result<ModelId> {
+        match self.0.len() {
+            2 => Ok(ModelId::Sig(self.u16()?)),
+            4 => Ok(ModelId::Vendor { company_id: self.u16()?, model_id: self.u16()? }),
+            _ => Err(Error::new(ErrorKind::InvalidLength)),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn finish(self) -> Result<()> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => Err(Error::new(ErrorKind::InvalidLength)),
+        }
+    }
+}
+
+/// Bluetooth mesh Configuration Client.
+///
+/// Requests are sent from an element of the local node to the Configuration Server
+/// of the remote node, which is addressed by the unicast address of its primary element.
+/// The sending element should contain the [CONFIG_CLIENT_MODEL_ID] model.
+///
+/// The client takes over the [ElementControl] of the sending element and discards
+/// all other messages received by it.
+pub struct ConfigClient {
+    node: Node,
+    element_control: ElementControl,
+    element_ref: ElementRef,
+    net_index: u16,
+    timeout: Duration,
+}
+
+impl fmt::Debug for ConfigClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConfigClient")
+            .field("element_ref", &self.element_ref)
+            .field("net_index", &self.net_index)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl ConfigClient {
+    /// Creates a Configuration Client sending from the element controlled by `element_control`.
+    ///
+    /// Requests are sent on the primary subnet and replies are awaited for [DEFAULT_TIMEOUT].
+    pub fn new(node: Node, element_control: ElementControl) -> Self {
+        let element_ref = element_control.element_ref();
+        Self { node, element_control, element_ref, net_index: 0, timeout: DEFAULT_TIMEOUT }
+    }
+
+    /// Sets the index of the subnet requests are sent on.
+    pub fn set_net_index(&mut self, net_index: u16) {
+        self.net_index = net_index;
+    }
+
+    /// Sets the time to wait for a reply.
+    ///
+    /// Requests fail with [ErrorKind::MeshResponseTimeout] when it elapses.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Sends a request to the node with the specified primary element address and waits for its reply.
+    ///
+    /// The status code contained in the reply is not checked.
+    pub async fn request(&mut self, destination: u16, request: &ConfigRequest) -> Result<ConfigResponse> {
+        self.node.dev_key_send(&self.element_ref, destination, true, self.net_index, &request.encode()?).await?;
+        self.reply(destination, request.reply_opcode()).await
+    }
+
+    /// Waits for a reply with the specified opcode from the specified node.
+    async fn reply(&mut self, source: u16, opcode: Opcode) -> Result<ConfigResponse> {
+        let element_control = &mut self.element_control;
+        let recv = async {
+            while let Some(event) = element_control.next().await {
+                match event {
+                    ElementEvent::DevKeyMessageReceived(msg)
+                        if msg.source == source
+                            && msg.remote
+                            && Opcode::decode(&msg.data).map(|(op, _)| op).ok() == Some(opcode) =>
+                    {
+                        return ConfigResponse::decode(&msg.data);
+                    }
+                    event => log::trace!("Discarding mesh element event: {:?}", event),
+                }
+            }
+            Err(Error::new(ErrorKind::NotRegistered))
+        };
+        tokio::time::timeout(self.timeout, recv).await.map_err(|_| Error::new(ErrorKind::MeshResponseTimeout))?
+    }
+
+    /// Gets composition data page 0 of a node.
+    pub async fn composition_data(&mut self, destination: u16) -> Result<CompositionData> {
+        match self.request(destination, &ConfigRequest::CompositionDataGet { page: 0 }).await? {
+            ConfigResponse::CompositionData(data) => Ok(data),
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    /// Adds the application key with the specified index, which must be known
+    /// to the local node, to a node.
+    pub async fn app_key_add(
+        &mut self, destination: u16, net_index: u16, app_index: u16,
+    ) -> Result<AppKeyStatus> {
+        self.node.add_app_key(&self.element_ref, destination, app_index, net_index, false).await?;
+        match self.reply(destination, OP_APP_KEY_STATUS).await? {
+            ConfigResponse::AppKeyStatus(status) => {
+                status.status.check()?;
+                Ok(status)
+            }
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    /// Binds an application key to a model of a node.
+    pub async fn model_app_bind(
+        &mut self, destination: u16, element_address: u16, app_index: u16, model_id: ModelId,
+    ) -> Result<ModelAppStatus> {
+        let request = ConfigRequest::ModelAppBind { element_address, app_index, model_id };
+        match self.request(destination, &request).await? {
+            ConfigResponse::ModelAppStatus(status) => {
+                status.status.check()?;
+                Ok(status)
+            }
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    /// Sets the publication of a model of a node.
+    pub async fn model_publication_set(
+        &mut self, destination: u16, element_address: u16, publication: Publication, model_id: ModelId,
+    ) -> Result<ModelPublicationStatus> {
+        let request = ConfigRequest::ModelPublicationSet { element_address, publication, model_id };
+        match self.request(destination, &request).await? {
+            ConfigResponse::ModelPublicationStatus(status) => {
+                status.status.check()?;
+                Ok(status)
+            }
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    /// Adds an address to the subscription list of a model of a node.
+    pub async fn model_subscription_add(
+        &mut self, destination: u16, element_address: u16, address: u16, model_id: ModelId,
+    ) -> Result<ModelSubscriptionStatus> {
+        let request = ConfigRequest::ModelSubscriptionAdd { element_address, address, model_id };
+        match self.request(destination, &request).await? {
+            ConfigResponse::ModelSubscriptionStatus(status) => {
+                status.status.check()?;
+                Ok(status)
+            }
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    /// Gets the relay state of a node.
+    pub async fn relay(&mut self, destination: u16) -> Result<Relay> {
+        match self.request(destination, &ConfigRequest::RelayGet).await? {
+            ConfigResponse::RelayStatus(relay) => Ok(relay),
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    /// Sets the relay state of a node and returns the resulting state.
+    pub async fn set_relay(&mut self, destination: u16, relay: Relay) -> Result<Relay> {
+        match self.request(destination, &ConfigRequest::RelaySet(relay)).await? {
+            ConfigResponse::RelayStatus(relay) => Ok(relay),
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    /// Gets the GATT proxy state of a node.
+    pub async fn gatt_proxy(&mut self, destination: u16) -> Result<FeatureState> {
+        match self.request(destination, &ConfigRequest::GattProxyGet).await? {
+            ConfigResponse::GattProxyStatus(state) => Ok(state),
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    /// Sets the GATT proxy state of a node and returns the resulting state.
+    pub async fn set_gatt_proxy(&mut self, destination: u16, state: FeatureState) -> Result<FeatureState> {
+        match self.request(destination, &ConfigRequest::GattProxySet(state)).await? {
+            ConfigResponse::GattProxyStatus(state) => Ok(state),
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    /// Gets the friend state of a node.
+    pub async fn friend(&mut self, destination: u16) -> Result<FeatureState> {
+        match self.request(destination, &ConfigRequest::FriendGet).await? {
+            ConfigResponse::FriendStatus(state) => Ok(state),
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    /// Sets the friend state of a node and returns the resulting state.
+    pub async fn set_friend(&mut self, destination: u16, state: FeatureState) -> Result<FeatureState> {
+        match self.request(destination, &ConfigRequest::FriendSet(state)).await? {
+            ConfigResponse::FriendStatus(state) => Ok(state),
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    /// Gets the default time to live of a node.
+    pub async fn default_ttl(&mut self, destination: u16) -> Result<u8> {
+        match self.request(destination, &ConfigRequest::DefaultTtlGet).await? {
+            ConfigResponse::DefaultTtlStatus(ttl) => Ok(ttl),
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    /// Sets the default time to live of a node and returns the resulting value.
+    pub async fn set_default_ttl(&mut self, destination: u16, ttl: u8) -> Result<u8> {
+        match self.request(destination, &ConfigRequest::DefaultTtlSet(ttl)).await? {
+            ConfigResponse::DefaultTtlStatus(ttl) => Ok(ttl),
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+
+    /// Resets a node, which removes it from the network.
+    pub async fn node_reset(&mut self, destination: u16) -> Result<()> {
+        self.request(destination, &ConfigRequest::NodeReset).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_index_packing() {
+        // NetKeyIndex 0x456 and AppKeyIndex 0x123 of the Config AppKey Add sample message
+        // of the Mesh Profile specification.
+        assert_eq!(pack_key_indices(0x456, 0x123), [0x56, 0x34, 0x12]);
+        assert_eq!(Reader(&[0x56, 0x34, 0x12]).key_indices().unwrap(), (0x456, 0x123));
+
+        // The first index occupies the lower 12 bits of the little-endian 24-bit value.
+        assert_eq!(pack_key_indices(0x123, 0x456), [0x23, 0x61, 0x45]);
+        assert_eq!(Reader(&[0x23, 0x61, 0x45]).key_indices().unwrap(), (0x123, 0x456));
+        assert_eq!(pack_key_indices(0xfff, 0x000), [0xff, 0x0f, 0x00]);
+    }
+
+    #[test]
+    fn app_key_add() {
+        // Access payload of the Config AppKey Add sample message of the Mesh Profile specification.
+        let request = ConfigRequest::AppKeyAdd {
+            net_index: 0x456,
+            app_index: 0x123,
+            key: [0x63, 0x96, 0x47, 0x71, 0x73, 0x4f, 0xbd, 0x76, 0xe3, 0xb4, 0x05, 0x19, 0xd1, 0xd9, 0x4a, 0x48],
+        };
+        assert_eq!(
+            request.encode().unwrap(),
+            [
+                0x00, 0x56, 0x34, 0x12, 0x63, 0x96, 0x47, 0x71, 0x73, 0x4f, 0xbd, 0x76, 0xe3, 0xb4, 0x05, 0x19,
+                0xd1, 0xd9, 0x4a, 0x48
+            ]
+        );
+    }
+
+    #[test]
+    fn app_key_status() {
+        let response = ConfigResponse::decode(&[0x80, 0x03, 0x00, 0x56, 0x34, 0x12]).unwrap();
+        assert_eq!(
+            response,
+            ConfigResponse::AppKeyStatus(AppKeyStatus {
+                status: Status::Success,
+                net_index: 0x456,
+                app_index: 0x123
+            })
+        );
+    }
+
+    #[test]
+    fn composition_data_status() {
+        let data = [
+            0x02, 0x00, // opcode, page
+            0x0c, 0x00, 0x1a, 0x00, 0x01, 0x00, 0x08, 0x00, 0x03, 0x00, // CID, PID, VID, CRPL, features
+            0x00, 0x01, 0x03, 0x01, // Loc, NumS, NumV
+            0x00, 0x00, 0x01, 0x00, 0x02, 0x10, // SIG models
+            0x3f, 0x00, 0x2a, 0x00, // vendor model
+            0x00, 0x00, 0x01, 0x00, // Loc, NumS, NumV
+            0x00, 0x10, // SIG model
+        ];
+        let response = ConfigResponse::decode(&data).unwrap();
+        assert_eq!(
+            response,
+            ConfigResponse::CompositionData(CompositionData {
+                company_id: 0x000c,
+                product_id: 0x001a,
+                version_id: 0x0001,
+                replay_protection: 8,
+                features: Features { relay: true, proxy: true, friend: false, low_power: false },
+                elements: vec![
+                    CompositionElement {
+                        location: 0x0100,
+                        models: vec![
+                            ModelId::Sig(CONFIG_SERVER_MODEL_ID),
+                            ModelId::Sig(CONFIG_CLIENT_MODEL_ID),
+                            ModelId::Sig(0x1002),
+                            ModelId::Vendor { company_id: 0x003f, model_id: 0x002a },
+                        ],
+                    },
+                    CompositionElement { location: 0x0000, models: vec![ModelId::Sig(0x1000)] },
+                ],
+            })
+        );
+
+        assert_eq!(ConfigResponse::decode(&data[..data.len() - 1]).unwrap_err().kind, ErrorKind::InvalidLength);
+    }
+
+    #[test]
+    fn model_app_bind() {
+        let request = ConfigRequest::ModelAppBind {
+            element_address: 0x0102,
+            app_index: 0x0003,
+            model_id: ModelId::Sig(0x1000),
+        };
+        assert_eq!(request.encode().unwrap(), [0x80, 0x3d, 0x02, 0x01, 0x03, 0x00, 0x00, 0x10]);
+
+        let response = ConfigResponse::decode(&[0x80, 0x3e, 0x0d, 0x02, 0x01, 0x03, 0x00, 0x00, 0x10]).unwrap();
+        assert_eq!(
+            response,
+            ConfigResponse::ModelAppStatus(ModelAppStatus {
+                status: Status::CannotBind,
+                element_address: 0x0102,
+                app_index: 0x0003,
+                model_id: ModelId::Sig(0x1000),
+            })
+        );
+    }
+
+    #[test]
+    fn model_publication() {
+        let publication = Publication {
+            address: 0xc000,
+            app_index: 0x0001,
+            friendship_credentials: true,
+            ttl: 0xff,
+            period: 0x41,
+            retransmit: Retransmit { count: 2, interval_steps: 4 },
+        };
+        let model_id = ModelId::Vendor { company_id: 0x05f1, model_id: 0x0001 };
+        let request = ConfigRequest::ModelPublicationSet { element_address: 0x0102, publication, model_id };
+        let params = [0x02, 0x01, 0x00, 0xc0, 0x01, 0x10, 0xff, 0x41, 0x22, 0xf1, 0x05, 0x01, 0x00];
+        assert_eq!(request.encode().unwrap(), [&[0x03][..], &params].concat());
+
+        let response = ConfigResponse::decode(&[&[0x80, 0x19, 0x00][..], &params].concat()).unwrap();
+        assert_eq!(
+            response,
+            ConfigResponse::ModelPublicationStatus(ModelPublicationStatus {
+                status: Status::Success,
+                element_address: 0x0102,
+                publication,
+                model_id,
+            })
+        );
+    }
+
+    #[test]
+    fn model_subscription() {
+        let request = ConfigRequest::ModelSubscriptionAdd {
+            element_address: 0x0102,
+            address: 0xc001,
+            model_id: ModelId::Sig(0x1000),
+        };
+        assert_eq!(request.encode().unwrap(), [0x80, 0x1b, 0x02, 0x01, 0x01, 0xc0, 0x00, 0x10]);
+
+        let response = ConfigResponse::decode(&[0x80, 0x1f, 0x01, 0x02, 0x01, 0x01, 0xc0, 0x00, 0x10]).unwrap();
+        assert_eq!(
+            response,
+            ConfigResponse::ModelSubscriptionStatus(ModelSubscriptionStatus {
+                status: Status::InvalidAddress,
+                element_address: 0x0102,
+                address: 0xc001,
+                model_id: ModelId::Sig(0x1000),
+            })
+        );
+    }
+
+    #[test]
+    fn features() {
+        let relay =
+            Relay { state: FeatureState::Enabled, retransmit: Retransmit { count: 3, interval_steps: 2 } };
+        assert_eq!(ConfigRequest::RelayGet.encode().unwrap(), [0x80, 0x26]);
+        assert_eq!(ConfigRequest::RelaySet(relay).encode().unwrap(), [0x80, 0x27, 0x01, 0x13]);
+        assert_eq!(
+            ConfigResponse::decode(&[0x80, 0x28, 0x01, 0x13]).unwrap(),
+            ConfigResponse::RelayStatus(relay)
+        );
+
+        assert_eq!(ConfigRequest::GattProxySet(FeatureState::Disabled).encode().unwrap(), [0x80, 0x13, 0x00]);
+        assert_eq!(
+            ConfigResponse::decode(&[0x80, 0x14, 0x02]).unwrap(),
+            ConfigResponse::GattProxyStatus(FeatureState::NotSupported)
+        );
+
+        assert_eq!(ConfigRequest::FriendGet.encode().unwrap(), [0x80, 0x0f]);
+        assert_eq!(
+            ConfigResponse::decode(&[0x80, 0x11, 0x01]).unwrap(),
+            ConfigResponse::FriendStatus(FeatureState::Enabled)
+        );
+        assert!(ConfigResponse::decode(&[0x80, 0x11, 0x03]).is_err());
+
+        assert_eq!(ConfigRequest::DefaultTtlSet(7).encode().unwrap(), [0x80, 0x0d, 0x07]);
+        assert_eq!(ConfigResponse::decode(&[0x80, 0x0e, 0x07]).unwrap(), ConfigResponse::DefaultTtlStatus(7));
+    }
+
+    #[test]
+    fn node_reset() {
+        assert_eq!(ConfigRequest::NodeReset.encode().unwrap(), [0x80, 0x49]);
+        assert_eq!(ConfigResponse::decode(&[0x80, 0x4a]).unwrap(), ConfigResponse::NodeResetStatus);
+        assert_eq!(ConfigResponse::decode(&[0x80, 0x4a, 0x00]).unwrap_err().kind, ErrorKind::InvalidLength);
+    }
+
+    #[test]
+    fn invalid_messages() {
+        assert_eq!(ConfigResponse::decode(&[]).unwrap_err().kind, ErrorKind::InvalidLength);
+        assert_eq!(ConfigResponse::decode(&[0x80]).unwrap_err().kind, ErrorKind::InvalidLength);
+        assert_eq!(ConfigResponse::decode(&[0x7f]).unwrap_err().kind, ErrorKind::NotSupported);
+        assert_eq!(ConfigResponse::decode(&[0xc1, 0x59, 0x00]).unwrap_err().kind, ErrorKind::NotSupported);
+        assert_eq!(ConfigResponse::decode(&[0x80, 0x08, 0x00]).unwrap_err().kind, ErrorKind::NotSupported);
+        assert_eq!(Status::from(0x42), Status::Unknown(0x42));
+    }
+}
@@ -1,18 +1,20 @@
 //! Bluetooth mesh management.
 
 use dbus::{
-    arg::{RefArg, Variant},
+    arg::{PropMap, RefArg, Variant},
     nonblock::{Proxy, SyncConnection},
     Path,
 };
+use futures::{stream, Stream};
 use std::{collections::HashMap, sync::Arc};
 use strum::EnumString;
+use tokio::sync::broadcast::error::RecvError;
 use uuid::Uuid;
 
 use super::application::ApplicationInner;
 use crate::{
     mesh::{SERVICE_NAME, TIMEOUT},
-    Error, ErrorKind, Result, SessionInner,
+    variant_hashmap, Error, ErrorKind, InternalErrorKind, Result, SessionInner,
 };
 
 pub(crate) const INTERFACE: &str = "org.bluez.mesh.Management1";
@@ -35,7 +37,7 @@ impl Management {
         let mut rx = self.app_inner.add_node_result_rx.resubscribe();
 
         let opts = HashMap::<String, Variant<Box<dyn RefArg + 'static>>>::new();
-        self.call_method("AddNode", (uuid.as_bytes().to_vec(), opts)).await?;
+        self.call_method::<_, ()>("AddNode", (uuid.as_bytes().to_vec(), opts)).await?;
 
         loop {
             match rx.recv().await {
@@ -49,6 +51,127 @@ impl Management {
         }
     }
 
+    /// Scan for unprovisioned devices.
+    ///
+    /// Scanning stops after the specified number of seconds.
+    /// If zero is specified, scanning continues until it is cancelled
+    /// using [unprovisioned_scan_cancel](Self::unprovisioned_scan_cancel).
+    ///
+    /// Returns a stream of unprovisioned devices received by the application's
+    /// [provisioner](super::application::Application::provisioner).
+    /// The stream ends when the application is unregistered.
+    pub async fn unprovisioned_scan(&self, seconds: u16) -> Result<impl Stream<Item = UnprovisionedDevice>> {
+        let rx = self.app_inner.scan_result_rx.resubscribe();
+
+        let mut opts = PropMap::new();
+        opts.insert("Seconds".to_string(), Variant(Box::new(seconds)));
+        self.call_method::<_, ()>("UnprovisionedScan", (opts,)).await?;
+
+        Ok(stream::unfold(rx, |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(dev) => return Some((dev, rx)),
+                    Err(RecvError::Lagged(_)) => (),
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }))
+    }
+
+    /// Cancel scanning for unprovisioned devices.
+    pub async fn unprovisioned_scan_cancel(&self) -> Result<()> {
+        self.call_method("UnprovisionedScanCancel", ()).await
+    }
+
+    /// Generate a new network subnet key and store it with the specified index.
+    pub async fn create_subnet(&self, net_index: u16) -> Result<()> {
+        self.call_method("CreateSubnet", (net_index,)).await
+    }
+
+    /// Store the specified network subnet key with the specified index.
+    ///
+    /// This is used to add a subnet key that was generated elsewhere.
+    pub async fn import_subnet(&self, net_index: u16, net_key: [u8; 16]) -> Result<()> {
+        self.call_method("ImportSubnet", (net_index, net_key.to_vec())).await
+    }
+
+    /// Generate a new network subnet key and start the key refresh procedure
+    /// for the subnet with the specified index.
+    ///
+    /// The subnet enters [KeyPhase::Phase1].
+    pub async fn update_subnet(&self, net_index: u16) -> Result<()> {
+        self.call_method("UpdateSubnet", (net_index,)).await
+    }
+
+    /// Delete the network subnet key with the specified index and all
+    /// application keys bound to it.
+    pub async fn delete_subnet(&self, net_index: u16) -> Result<()> {
+        self.call_method("DeleteSubnet", (net_index,)).await
+    }
+
+    /// Set the key refresh phase of the subnet with the specified index.
+    ///
+    /// The following phases can be set:
+    ///
+    ///   * [KeyPhase::Normal] cancels the key refresh procedure,
+    ///   * [KeyPhase::Phase2] starts using the new keys for transmission,
+    ///   * [KeyPhase::Phase3] completes the key refresh procedure and revokes the old keys.
+    ///
+    /// Setting [KeyPhase::Phase1] is not allowed; use [update_subnet](Self::update_subnet) instead.
+    pub async fn set_key_phase(&self, net_index: u16, phase: KeyPhase) -> Result<()> {
+        if phase == KeyPhase::Phase1 {
+            return Err(Error::new(ErrorKind::InvalidArguments));
+        }
+        self.call_method("SetKeyPhase", (net_index, u8::from(phase))).await
+    }
+
+    /// Generate a new application key with the specified index and bind it
+    /// to the network subnet key with the specified index.
+    pub async fn create_app_key(&self, net_index: u16, app_index: u16) -> Result<()> {
+        self.call_method("CreateAppKey", (net_index, app_index)).await
+    }
+
+    /// Store the specified application key with the specified index and bind it
+    /// to the network subnet key with the specified index.
+    ///
+    /// This is used to add an application key that was generated elsewhere.
+    pub async fn import_app_key(&self, net_index: u16, app_index: u16, app_key: [u8; 16]) -> Result<()> {
+        self.call_method("ImportAppKey", (net_index, app_index, app_key.to_vec())).await
+    }
+
+    /// Generate a new application key for the application key with the specified index.
+    ///
+    /// The bound network subnet key must be in [KeyPhase::Phase1].
+    pub async fn update_app_key(&self, app_index: u16) -> Result<()> {
+        self.call_method("UpdateAppKey", (app_index,)).await
+    }
+
+    /// Delete the application key with the specified index.
+    pub async fn delete_app_key(&self, app_index: u16) -> Result<()> {
+        self.call_method("DeleteAppKey", (app_index,)).await
+    }
+
+    /// Add the device key of a remote node that was provisioned elsewhere.
+    ///
+    /// The node uses `count` consecutive unicast addresses starting at `primary`.
+    pub async fn import_remote_node(&self, primary: u16, count: u8, device_key: [u8; 16]) -> Result<()> {
+        self.call_method("ImportRemoteNode", (primary, count, device_key.to_vec())).await
+    }
+
+    /// Delete the device key of a remote node.
+    ///
+    /// The node uses `count` consecutive unicast addresses starting at `primary`.
+    pub async fn delete_remote_node(&self, primary: u16, count: u8) -> Result<()> {
+        self.call_method("DeleteRemoteNode", (primary, count)).await
+    }
+
+    /// Export the network subnet keys, application keys and device keys
+    /// stored in the key database of the node.
+    pub async fn export_keys(&self) -> Result<ExportedKeys> {
+        let (dict,): (PropMap,) = self.call_method("ExportKeys", ()).await?;
+        ExportedKeys::from_dict(&dict)
+    }
+
     fn proxy(&self) -> Proxy<'_, &SyncConnection> {
         Proxy::new(SERVICE_NAME, self.path.clone(), TIMEOUT, &*self.inner.connection)
     }
@@ -99,3 +222,337 @@ pub enum AddNodeFailedReason {
     /// unknown reason
     Unknown,
 }
+
+/// Unprovisioned device found by [Management::unprovisioned_scan].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct UnprovisionedDevice {
+    /// Received signal strength indicator.
+    pub rssi: i16,
+    /// Device UUID.
+    pub uuid: Uuid,
+    /// Out-of-band information available for provisioning.
+    ///
+    /// `None` if the device advertises only its UUID.
+    pub oob_info: Option<u16>,
+    /// Hash of the URI advertised by the device.
+    pub uri_hash: Option<[u8; 4]>,
+}
+
+impl UnprovisionedDevice {
+    /// Parses the data of an unprovisioned device beacon.
+    pub(crate) fn from_scan_result(rssi: i16, data: &[u8]) -> Option<Self> {
+        if data.len() < 16 || data.len() == 17 {
+            return None;
+        }
+        Some(Self {
+            rssi,
+            uuid: Uuid::from_slice(&data[..16]).ok()?,
+            oob_info: data.get(16..18).map(|oob| u16::from_be_bytes([oob[0], oob[1]])),
+            uri_hash: data.get(18..22).map(|hash| hash.try_into().unwrap()),
+        })
+    }
+}
+
+/// Key refresh phase of a network subnet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeyPhase {
+    /// Normal operation, no key refresh procedure in progress.
+    Normal,
+    /// New keys are being distributed, old keys are used for transmission.
+    Phase1,
+    /// New keys are used for transmission, old and new keys for reception.
+    Phase2,
+    /// Key refresh procedure is completed and the old keys are revoked.
+    Phase3,
+}
+
+impl From<KeyPhase> for u8 {
+    fn from(phase: KeyPhase) -> Self {
+        match phase {
+            KeyPhase::Normal => 0,
+            KeyPhase::Phase1 => 1,
+            KeyPhase::Phase2 => 2,
+            KeyPhase::Phase3 => 3,
+        }
+    }
+}
+
+impl TryFrom<u8> for KeyPhase {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::Normal),
+            1 => Ok(Self::Phase1),
+            2 => Ok(Self::Phase2),
+            3 => Ok(Self::Phase3),
+            _ => Err(Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))),
+        }
+    }
+}
+
+/// Keys exported by [Management::export_keys].
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ExportedKeys {
+    /// Network subnet keys with their bound application keys.
+    pub net_keys: Vec<ExportedNetKey>,
+    /// Device keys of remote nodes.
+    pub dev_keys: Vec<ExportedDevKey>,
+}
+
+/// Exported network subnet key.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ExportedNetKey {
+    /// Network key index.
+    pub index: u16,
+    /// Key refresh phase.
+    pub phase: KeyPhase,
+    /// Network key.
+    pub key: [u8; 16],
+    /// Previous network key, if a key refresh procedure is in progress.
+    pub old_key: Option<[u8; 16]>,
+    /// Application keys bound to this network key.
+    pub app_keys: Vec<ExportedAppKey>,
+}
+
+/// Exported application key.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ExportedAppKey {
+    /// Application key index.
+    pub index: u16,
+    /// Application key.
+    pub key: [u8; 16],
+    /// Previous application key, if a key refresh procedure is in progress.
+    pub old_key: Option<[u8; 16]>,
+}
+
+/// Exported device key of a remote node.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ExportedDevKey {
+    /// Primary unicast address of the node.
+    pub address: u16,
+    /// Device key.
+    pub key: [u8; 16],
+}
+
+impl ExportedKeys {
+    fn from_dict(dict: &PropMap) -> Result<Self> {
+        Ok(Self {
+            net_keys: dict_array(dict, "NetKeys")?
+                .iter()
+                .map(ExportedNetKey::from_dict)
+                .collect::<Result<_>>()?,
+            dev_keys: dict_array(dict, "DevKeys")?
+                .iter()
+                .map(ExportedDevKey::from_dict)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl ExportedNetKey {
+    fn from_dict(dict: &PropMap) -> Result<Self> {
+        Ok(Self {
+            index: dict_u16(dict, "Index")?,
+            phase: u8::try_from(dict_u64(dict, "Phase")?)
+                .map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))?
+                .try_into()?,
+            key: dict_key(dict, "Key")?,
+            old_key: dict_opt_key(dict, "OldKey")?,
+            app_keys: dict_array(dict, "AppKeys")?
+                .iter()
+                .map(ExportedAppKey::from_dict)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl ExportedAppKey {
+    fn from_dict(dict: &PropMap) -> Result<Self> {
+        Ok(Self {
+            index: dict_u16(dict, "Index")?,
+            key: dict_key(dict, "Key")?,
+            old_key: dict_opt_key(dict, "OldKey")?,
+        })
+    }
+}
+
+impl ExportedDevKey {
+    fn from_dict(dict: &PropMap) -> Result<Self> {
+        Ok(Self { address: dict_u16(dict, "Address")?, key: dict_key(dict, "Key")? })
+    }
+}
+
+fn missing_key(key: &str) -> Error {
+    Error::new(ErrorKind::Internal(InternalErrorKind::MissingKey(key.to_string())))
+}
+
+fn dict_u64(dict: &PropMap, key: &str) -> Result<u64> {
+    dict.get(key).ok_or_else(|| missing_key(key))?.0.as_u64().ok_or_else(|| missing_key(key))
+}
+
+fn dict_u16(dict: &PropMap, key: &str) -> Result<u16> {
+    u16::try_from(dict_u64(dict, key)?)
+        .map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))
+}
+
+fn dict_opt_key(dict: &PropMap, key: &str) -> Result<Option<[u8; 16]>> {
+    let Some(value) = dict.get(key) else { return Ok(None) };
+    let bytes: Option<Vec<u8>> = value
+        .0
+        .as_iter()
+        .ok_or_else(|| missing_key(key))?
+        .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+        .collect();
+    let key = bytes
+        .and_then(|bytes| <[u8; 16]>::try_from(bytes).ok())
+        .ok_or_else(|| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))?;
+    Ok(Some(key))
+}
+
+fn dict_key(dict: &PropMap, key: &str) -> Result<[u8; 16]> {
+    dict_opt_key(dict, key)?.ok_or_else(|| missing_key(key))
+}
+
+fn dict_array(dict: &PropMap, key: &str) -> Result<Vec<PropMap>> {
+    match dict.get(key) {
+        Some(value) => Ok(value
+            .0
+            .as_iter()
+            .ok_or_else(|| missing_key(key))?
+            .map(|v| variant_hashmap(&*v.box_clone()))
+            .collect()),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dbus::Message;
+
+    use super::*;
+
+    const UUID: [u8; 16] =
+        [0x70, 0xcf, 0x7c, 0x97, 0x32, 0xa3, 0x45, 0xb6, 0x91, 0x49, 0x48, 0x10, 0xd2, 0xe9, 0xcb, 0xf4];
+
+    #[test]
+    fn unprovisioned_device_beacon() {
+        let mut data = UUID.to_vec();
+        let dev = UnprovisionedDevice::from_scan_result(-70, &data).unwrap();
+        assert_eq!(dev.rssi, -70);
+        assert_eq!(dev.uuid, Uuid::from_bytes(UUID));
+        assert_eq!(dev.oob_info, None);
+        assert_eq!(dev.uri_hash, None);
+
+        data.extend_from_slice(&[0x40, 0x20]);
+
+        let dev = UnprovisionedDevice::from_scan_result(-60, &data).unwrap();
+        assert_eq!(dev.rssi, -60);
+        assert_eq!(dev.uuid, Uuid::from_bytes(UUID));
+        assert_eq!(dev.oob_info, Some(0x4020));
+        assert_eq!(dev.uri_hash, None);
+
+        data.extend_from_slice(&[0xd9, 0x74, 0x78, 0xb3]);
+        let dev = UnprovisionedDevice::from_scan_result(-42, &data).unwrap();
+        assert_eq!(dev.uuid, Uuid::from_bytes(UUID));
+        assert_eq!(dev.oob_info, Some(0x4020));
+        assert_eq!(dev.uri_hash, Some([0xd9, 0x74, 0x78, 0xb3]));
+
+        assert!(UnprovisionedDevice::from_scan_result(-42, &data[..17]).is_none());
+        assert!(UnprovisionedDevice::from_scan_result(-42, &data[..15]).is_none());
+    }
+
+    fn key(first: u8) -> Vec<u8> {
+        (first..first + 16).collect()
+    }
+
+    fn dict(entries: Vec<(&str, Box<dyn RefArg>)>) -> PropMap {
+        entries.into_iter().map(|(k, v)| (k.to_string(), Variant(v))).collect()
+    }
+
+    /// Passes the dictionary through a D-Bus message, as received from the mesh daemon.
+    fn received(dict: PropMap) -> PropMap {
+        Message::new_method_call(SERVICE_NAME, "/", INTERFACE, "ExportKeys")
+            .unwrap()
+            .append1(dict)
+            .read1()
+            .unwrap()
+    }
+
+    #[test]
+    fn exported_keys() {
+        let net_key_refresh = dict(vec![
+            ("Index", Box::new(0u16)),
+            ("Phase", Box::new(1u8)),
+            ("Key", Box::new(key(0x10))),
+            ("OldKey", Box::new(key(0x20))),
+        ]);
+        let app_key = dict(vec![("Index", Box::new(5u16)), ("Key", Box::new(key(0x30)))]);
+        let net_key = dict(vec![
+            ("Index", Box::new(1u16)),
+            ("Phase", Box::new(0u8)),
+            ("Key", Box::new(key(0x40))),
+            ("AppKeys", Box::new(vec![app_key])),
+        ]);
+        let dev_key = dict(vec![("Address", Box::new(0x0100u16)), ("Key", Box::new(key(0x50)))]);
+        let keys = received(dict(vec![
+            ("NetKeys", Box::new(vec![net_key_refresh, net_key])),
+            ("DevKeys", Box::new(vec![dev_key])),
+        ]));
+
+        let keys = ExportedKeys::from_dict(&keys).unwrap();
+        assert_eq!(keys.net_keys.len(), 2);
+
+        let net_key = &keys.net_keys[0];
+        assert_eq!(net_key.index, 0);
+        assert_eq!(net_key.phase, KeyPhase::Phase1);
+        assert_eq!(net_key.key.to_vec(), key(0x10));
+        assert_eq!(net_key.old_key.map(|k| k.to_vec()), Some(key(0x20)));
+        assert!(net_key.app_keys.is_empty());
+
+        let net_key = &keys.net_keys[1];
+        assert_eq!(net_key.index, 1);
+        assert_eq!(net_key.phase, KeyPhase::Normal);
+        assert_eq!(net_key.old_key, None);
+        assert_eq!(net_key.app_keys.len(), 1);
+        assert_eq!(net_key.app_keys[0].index, 5);
+        assert_eq!(net_key.app_keys[0].key.to_vec(), key(0x30));
+        assert_eq!(net_key.app_keys[0].old_key, None);
+
+        assert_eq!(keys.dev_keys.len(), 1);
+        assert_eq!(keys.dev_keys[0].address, 0x0100);
+        assert_eq!(keys.dev_keys[0].key.to_vec(), key(0x50));
+    }
+
+    #[test]
+    fn exported_keys_malformed() {
+        let keys = ExportedKeys::from_dict(&received(PropMap::new())).unwrap();
+        assert!(keys.net_keys.is_empty());
+        assert!(keys.dev_keys.is_empty());
+
+        let net_keys = |net_key: PropMap| received(dict(vec![("NetKeys", Box::new(vec![net_key]))]));
+
+        let short_key =
+            dict(vec![("Index", Box::new(0u16)), ("Phase", Box::new(0u8)), ("Key", Box::new(vec![0u8; 15]))]);
+        assert_eq!(
+            ExportedKeys::from_dict(&net_keys(short_key)).unwrap_err().kind,
+            ErrorKind::Internal(InternalErrorKind::InvalidValue)
+        );
+
+        let bad_phase =
+            dict(vec![("Index", Box::new(0u16)), ("Phase", Box::new(4u8)), ("Key", Box::new(key(0)))]);
+        assert!(ExportedKeys::from_dict(&net_keys(bad_phase)).is_err());
+
+        let missing_key = dict(vec![("Index", Box::new(0u16)), ("Phase", Box::new(0u8))]);
+        assert_eq!(
+            ExportedKeys::from_dict(&net_keys(missing_key)).unwrap_err().kind,
+            ErrorKind::Internal(InternalErrorKind::MissingKey("Key".to_string()))
+        );
+    }
+}
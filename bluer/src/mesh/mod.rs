@@ -3,10 +3,13 @@
 //! The current implementation is experimental, incomplete and subject to change.
 //!
 
+pub mod access;
 pub mod agent;
 pub mod application;
+pub mod config;
 pub mod element;
 pub mod management;
+pub mod models;
 pub mod network;
 pub mod node;
 pub mod provisioner;
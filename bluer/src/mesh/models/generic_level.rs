@@ -0,0 +1,357 @@
+//! Generic Level model.
+//!
+//! The Generic Level state is a signed 16-bit integer representing the level of an element.
+//!
+//! Generic Move messages are not supported.
+
+use futures::future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, watch};
+
+use super::{
+    invalid_length, status_handler, ClientTarget, Received, Target, Transactions, Transition, TransitionTime,
+};
+use crate::{
+    mesh::access::{AccessMessage, DispatcherHandle, MeshMessage, ModelHandler, ModelId, Opcode},
+    Result,
+};
+
+/// SIG model id of the Generic Level Server model.
+pub const GENERIC_LEVEL_SERVER: u16 = 0x1002;
+
+/// SIG model id of the Generic Level Client model.
+pub const GENERIC_LEVEL_CLIENT: u16 = 0x1003;
+
+/// Generic Level Get message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LevelGet;
+
+impl MeshMessage for LevelGet {
+    const OPCODE: Opcode = Opcode::Sig(0x8205);
+
+    fn encode_params(&self, _buf: &mut Vec<u8>) {}
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        match params {
+            [] => Ok(Self),
+            _ => Err(invalid_length()),
+        }
+    }
+}
+
+/// Generic Level Set message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LevelSet {
+    /// Target level.
+    pub level: i16,
+    /// Transaction identifier.
+    pub tid: u8,
+    /// Transition parameters.
+    pub transition: Option<Transition>,
+}
+
+impl MeshMessage for LevelSet {
+    const OPCODE: Opcode = Opcode::Sig(0x8206);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.level.to_le_bytes());
+        buf.push(self.tid);
+        Transition::encode(&self.transition, buf);
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        match params {
+            [l1, l2, tid, transition @ ..] => Ok(Self {
+                level: i16::from_le_bytes([*l1, *l2]),
+                tid: *tid,
+                transition: Transition::decode(transition)?,
+            }),
+            _ => Err(invalid_length()),
+        }
+    }
+}
+
+/// Generic Level Set Unacknowledged message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LevelSetUnack(pub LevelSet);
+
+impl MeshMessage for LevelSetUnack {
+    const OPCODE: Opcode = Opcode::Sig(0x8207);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        self.0.encode_params(buf)
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        Ok(Self(LevelSet::decode_params(params)?))
+    }
+}
+
+/// Generic Level Status message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LevelStatus {
+    /// Present level.
+    pub present: i16,
+    /// Target level, if a transition is in progress.
+    pub target: Option<Target<i16>>,
+}
+
+impl MeshMessage for LevelStatus {
+    const OPCODE: Opcode = Opcode::Sig(0x8208);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.present.to_le_bytes());
+        if let Some(target) = &self.target {
+            buf.extend_from_slice(&target.value.to_le_bytes());
+            buf.push(target.remaining_time.0);
+        }
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        match params {
+            [p1, p2] => Ok(Self { present: i16::from_le_bytes([*p1, *p2]), target: None }),
+            [p1, p2, t1, t2, remaining_time] => Ok(Self {
+                present: i16::from_le_bytes([*p1, *p2]),
+                target: Some(Target {
+                    value: i16::from_le_bytes([*t1, *t2]),
+                    remaining_time: TransitionTime(*remaining_time),
+                }),
+            }),
+            _ => Err(invalid_length()),
+        }
+    }
+}
+
+/// Generic Delta Set message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LevelDeltaSet {
+    /// Change of the level.
+    ///
+    /// Retransmissions and further messages with the same transaction identifier
+    /// change the level relative to its value before the transaction started.
+    pub delta: i32,
+    /// Transaction identifier.
+    pub tid: u8,
+    /// Transition parameters.
+    pub transition: Option<Transition>,
+}
+
+impl MeshMessage for LevelDeltaSet {
+    const OPCODE: Opcode = Opcode::Sig(0x8209);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.delta.to_le_bytes());
+        buf.push(self.tid);
+        Transition::encode(&self.transition, buf);
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        match params {
+            [d1, d2, d3, d4, tid, transition @ ..] => Ok(Self {
+                delta: i32::from_le_bytes([*d1, *d2, *d3, *d4]),
+                tid: *tid,
+                transition: Transition::decode(transition)?,
+            }),
+            _ => Err(invalid_length()),
+        }
+    }
+}
+
+/// Generic Delta Set Unacknowledged message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LevelDeltaSetUnack(pub LevelDeltaSet);
+
+impl MeshMessage for LevelDeltaSetUnack {
+    const OPCODE: Opcode = Opcode::Sig(0x820a);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        self.0.encode_params(buf)
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        Ok(Self(LevelDeltaSet::decode_params(params)?))
+    }
+}
+
+#[derive(Debug, Default)]
+struct LevelTransactions {
+    transactions: Transactions,
+    /// Level before the most recent transaction started.
+    base: i16,
+}
+
+/// Generic Level Server model.
+#[derive(Clone, Debug)]
+pub struct LevelServer {
+    state: Arc<watch::Sender<i16>>,
+    transactions: Arc<Mutex<LevelTransactions>>,
+}
+
+impl LevelServer {
+    /// Creates a server with the specified initial level.
+    pub fn new(level: i16) -> Self {
+        Self { state: Arc::new(watch::channel(level).0), transactions: Default::default() }
+    }
+
+    /// Present level.
+    pub fn get(&self) -> i16 {
+        *self.state.borrow()
+    }
+
+    /// Sets the level locally.
+    pub fn set(&self, level: i16) {
+        self.state.send_replace(level);
+    }
+
+    /// Receiver for changes of the level.
+    pub fn subscribe(&self) -> watch::Receiver<i16> {
+        self.state.subscribe()
+    }
+
+    /// Status message containing the present level, for example for publication.
+    pub fn status(&self) -> LevelStatus {
+        LevelStatus { present: self.get(), target: None }
+    }
+
+    /// Handler of the server model for the [Dispatcher](crate::mesh::access::Dispatcher).
+    pub fn handler(&self) -> ModelHandler {
+        let this = self.clone();
+        ModelHandler {
+            model_id: ModelId::Sig(GENERIC_LEVEL_SERVER),
+            opcodes: vec![
+                LevelGet::OPCODE,
+                LevelSet::OPCODE,
+                LevelSetUnack::OPCODE,
+                LevelDeltaSet::OPCODE,
+                LevelDeltaSetUnack::OPCODE,
+            ],
+            fun: Box::new(move |msg| Box::pin(future::ready(this.handle(&msg)))),
+        }
+    }
+
+    fn handle(&self, msg: &AccessMessage) -> Option<Vec<u8>> {
+        let ack = match msg.opcode {
+            LevelGet::OPCODE => return self.status().encode().ok(),
+            LevelSet::OPCODE | LevelSetUnack::OPCODE => {
+                let set =
+                    msg.decode::<LevelSet>().or_else(|_| msg.decode::<LevelSetUnack>().map(|m| m.0)).ok()?;
+                let mut transactions = self.transactions.lock().unwrap();
+                if !transactions.transactions.is_retransmission(msg.source, set.tid) {
+                    transactions.base = self.get();
+                    self.set(set.level);
+                }
+                msg.opcode == LevelSet::OPCODE
+            }
+            LevelDeltaSet::OPCODE | LevelDeltaSetUnack::OPCODE => {
+                let set = msg
+                    .decode::<LevelDeltaSet>()
+                    .or_else(|_| msg.decode::<LevelDeltaSetUnack>().map(|m| m.0))
+                    .ok()?;
+                let mut transactions = self.transactions.lock().unwrap();
+                if !transactions.transactions.is_retransmission(msg.source, set.tid) {
+                    transactions.base = self.get();
+                }
+                let level =
+                    (transactions.base as i32).saturating_add(set.delta).clamp(i16::MIN as i32, i16::MAX as i32);
+                self.set(level as i16);
+                msg.opcode == LevelDeltaSet::OPCODE
+            }
+            _ => return None,
+        };
+        ack.then(|| self.status().encode().ok()).flatten()
+    }
+}
+
+/// Generic Level Client model.
+#[derive(Debug)]
+pub struct LevelClient {
+    target: ClientTarget,
+}
+
+impl LevelClient {
+    /// Creates a client sending requests to the specified destination using the specified application key.
+    pub fn new(dispatcher: Arc<DispatcherHandle>, destination: u16, key_index: u16) -> Self {
+        Self { target: ClientTarget::new(dispatcher, destination, key_index) }
+    }
+
+    /// Handler of the client model receiving status messages published by servers.
+    pub fn status_handler() -> (ModelHandler, mpsc::Receiver<Received<LevelStatus>>) {
+        status_handler(GENERIC_LEVEL_CLIENT)
+    }
+
+    /// Gets the level.
+    pub async fn get(&self) -> Result<LevelStatus> {
+        self.target.request(&LevelGet).await
+    }
+
+    /// Sets the level and waits for the resulting status.
+    pub async fn set(&self, level: i16, transition: Option<Transition>) -> Result<LevelStatus> {
+        self.target.request(&LevelSet { level, tid: self.target.next_tid(), transition }).await
+    }
+
+    /// Sets the level without waiting for a status.
+    pub async fn set_unack(&self, level: i16, transition: Option<Transition>) -> Result<()> {
+        self.target.send(&LevelSetUnack(LevelSet { level, tid: self.target.next_tid(), transition })).await
+    }
+
+    /// Changes the level by the specified amount and waits for the resulting status.
+    pub async fn delta_set(&self, delta: i32, transition: Option<Transition>) -> Result<LevelStatus> {
+        self.target.request(&LevelDeltaSet { delta, tid: self.target.next_tid(), transition }).await
+    }
+
+    /// Changes the level by the specified amount without waiting for a status.
+    pub async fn delta_set_unack(&self, delta: i32, transition: Option<Transition>) -> Result<()> {
+        self.target
+            .send(&LevelDeltaSetUnack(LevelDeltaSet { delta, tid: self.target.next_tid(), transition }))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::models::access_message;
+
+    #[test]
+    fn messages() {
+        let set = LevelSet { level: -2, tid: 0x07, transition: None };
+        assert_eq!(set.encode().unwrap(), [0x82, 0x06, 0xfe, 0xff, 0x07]);
+        assert_eq!(LevelSet::decode(&set.encode().unwrap()).unwrap(), set);
+
+        let delta = LevelDeltaSet {
+            delta: 0x0001_0000,
+            tid: 0x08,
+            transition: Some(Transition { time: TransitionTime(0x0a), delay: 0 }),
+        };
+        assert_eq!(delta.encode().unwrap(), [0x82, 0x09, 0x00, 0x00, 0x01, 0x00, 0x08, 0x0a, 0x00]);
+        assert_eq!(LevelDeltaSetUnack::decode(&LevelDeltaSetUnack(delta).encode().unwrap()).unwrap().0, delta);
+
+        let status = LevelStatus {
+            present: 0x0100,
+            target: Some(Target { value: 0x7fff, remaining_time: TransitionTime(0x45) }),
+        };
+        assert_eq!(status.encode().unwrap(), [0x82, 0x08, 0x00, 0x01, 0xff, 0x7f, 0x45]);
+        assert_eq!(LevelStatus::decode(&status.encode().unwrap()).unwrap(), status);
+        assert!(LevelStatus::decode(&[0x82, 0x08, 0x00]).is_err());
+    }
+
+    #[test]
+    fn server_delta() {
+        let server = LevelServer::new(100);
+
+        let delta = |delta, tid| {
+            access_message(&LevelDeltaSetUnack(LevelDeltaSet { delta, tid, transition: None }).encode().unwrap())
+        };
+        assert_eq!(server.handle(&delta(10, 1)), None);
+        assert_eq!(server.get(), 110);
+        // Same transaction is relative to the level before it started.
+        assert_eq!(server.handle(&delta(20, 1)), None);
+        assert_eq!(server.get(), 120);
+        assert_eq!(server.handle(&delta(100_000, 2)), None);
+        assert_eq!(server.get(), i16::MAX);
+
+        let reply =
+            server.handle(&access_message(&LevelSet { level: -5, tid: 3, transition: None }.encode().unwrap()));
+        assert_eq!(reply, Some(LevelStatus { present: -5, target: None }.encode().unwrap()));
+    }
+}
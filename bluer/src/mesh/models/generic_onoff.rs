@@ -0,0 +1,262 @@
+//! Generic OnOff model.
+//!
+//! The Generic OnOff state is a boolean representing whether an element is on or off.
+
+use futures::future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, watch};
+
+use super::{
+    invalid_length, invalid_value, status_handler, ClientTarget, Received, Target, Transactions, Transition,
+    TransitionTime,
+};
+use crate::{
+    mesh::access::{AccessMessage, DispatcherHandle, MeshMessage, ModelHandler, ModelId, Opcode},
+    Result,
+};
+
+/// SIG model id of the Generic OnOff Server model.
+pub const GENERIC_ONOFF_SERVER: u16 = 0x1000;
+
+/// SIG model id of the Generic OnOff Client model.
+pub const GENERIC_ONOFF_CLIENT: u16 = 0x1001;
+
+fn decode_onoff(value: u8) -> Result<bool> {
+    match value {
+        0x00 => Ok(false),
+        0x01 => Ok(true),
+        _ => Err(invalid_value()),
+    }
+}
+
+/// Generic OnOff Get message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OnOffGet;
+
+impl MeshMessage for OnOffGet {
+    const OPCODE: Opcode = Opcode::Sig(0x8201);
+
+    fn encode_params(&self, _buf: &mut Vec<u8>) {}
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        match params {
+            [] => Ok(Self),
+            _ => Err(invalid_length()),
+        }
+    }
+}
+
+/// Generic OnOff Set message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OnOffSet {
+    /// Target state.
+    pub on: bool,
+    /// Transaction identifier.
+    pub tid: u8,
+    /// Transition parameters.
+    pub transition: Option<Transition>,
+}
+
+impl MeshMessage for OnOffSet {
+    const OPCODE: Opcode = Opcode::Sig(0x8202);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&[self.on as u8, self.tid]);
+        Transition::encode(&self.transition, buf);
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        match params {
+            [on, tid, transition @ ..] => {
+                Ok(Self { on: decode_onoff(*on)?, tid: *tid, transition: Transition::decode(transition)? })
+            }
+            _ => Err(invalid_length()),
+        }
+    }
+}
+
+/// Generic OnOff Set Unacknowledged message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OnOffSetUnack(pub OnOffSet);
+
+impl MeshMessage for OnOffSetUnack {
+    const OPCODE: Opcode = Opcode::Sig(0x8203);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        self.0.encode_params(buf)
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        Ok(Self(OnOffSet::decode_params(params)?))
+    }
+}
+
+/// Generic OnOff Status message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OnOffStatus {
+    /// Present state.
+    pub present: bool,
+    /// Target state, if a transition is in progress.
+    pub target: Option<Target<bool>>,
+}
+
+impl MeshMessage for OnOffStatus {
+    const OPCODE: Opcode = Opcode::Sig(0x8204);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        buf.push(self.present as u8);
+        if let Some(target) = &self.target {
+            buf.extend_from_slice(&[target.value as u8, target.remaining_time.0]);
+        }
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        match params {
+            [present] => Ok(Self { present: decode_onoff(*present)?, target: None }),
+            [present, target, remaining_time] => Ok(Self {
+                present: decode_onoff(*present)?,
+                target: Some(Target {
+                    value: decode_onoff(*target)?,
+                    remaining_time: TransitionTime(*remaining_time),
+                }),
+            }),
+            _ => Err(invalid_length()),
+        }
+    }
+}
+
+/// Generic OnOff Server model.
+#[derive(Clone, Debug)]
+pub struct OnOffServer {
+    state: Arc<watch::Sender<bool>>,
+    transactions: Arc<Mutex<Transactions>>,
+}
+
+impl OnOffServer {
+    /// Creates a server with the specified initial state.
+    pub fn new(on: bool) -> Self {
+        Self { state: Arc::new(watch::channel(on).0), transactions: Default::default() }
+    }
+
+    /// Present state.
+    pub fn get(&self) -> bool {
+        *self.state.borrow()
+    }
+
+    /// Sets the state locally.
+    pub fn set(&self, on: bool) {
+        self.state.send_replace(on);
+    }
+
+    /// Receiver for changes of the state.
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.state.subscribe()
+    }
+
+    /// Status message containing the present state, for example for publication.
+    pub fn status(&self) -> OnOffStatus {
+        OnOffStatus { present: self.get(), target: None }
+    }
+
+    /// Handler of the server model for the [Dispatcher](crate::mesh::access::Dispatcher).
+    pub fn handler(&self) -> ModelHandler {
+        let this = self.clone();
+        ModelHandler {
+            model_id: ModelId::Sig(GENERIC_ONOFF_SERVER),
+            opcodes: vec![OnOffGet::OPCODE, OnOffSet::OPCODE, OnOffSetUnack::OPCODE],
+            fun: Box::new(move |msg| Box::pin(future::ready(this.handle(&msg)))),
+        }
+    }
+
+    fn handle(&self, msg: &AccessMessage) -> Option<Vec<u8>> {
+        let (set, ack) = match msg.opcode {
+            OnOffGet::OPCODE => return self.status().encode().ok(),
+            OnOffSet::OPCODE => (msg.decode::<OnOffSet>().ok()?, true),
+            OnOffSetUnack::OPCODE => (msg.decode::<OnOffSetUnack>().ok()?.0, false),
+            _ => return None,
+        };
+        if !self.transactions.lock().unwrap().is_retransmission(msg.source, set.tid) {
+            self.set(set.on);
+        }
+        ack.then(|| self.status().encode().ok()).flatten()
+    }
+}
+
+/// Generic OnOff Client model.
+#[derive(Debug)]
+pub struct OnOffClient {
+    target: ClientTarget,
+}
+
+impl OnOffClient {
+    /// Creates a client sending requests to the specified destination using the specified application key.
+    pub fn new(dispatcher: Arc<DispatcherHandle>, destination: u16, key_index: u16) -> Self {
+        Self { target: ClientTarget::new(dispatcher, destination, key_index) }
+    }
+
+    /// Handler of the client model receiving status messages published by servers.
+    pub fn status_handler() -> (ModelHandler, mpsc::Receiver<Received<OnOffStatus>>) {
+        status_handler(GENERIC_ONOFF_CLIENT)
+    }
+
+    /// Gets the state.
+    pub async fn get(&self) -> Result<OnOffStatus> {
+        self.target.request(&OnOffGet).await
+    }
+
+    /// Sets the state and waits for the resulting status.
+    pub async fn set(&self, on: bool, transition: Option<Transition>) -> Result<OnOffStatus> {
+        self.target.request(&OnOffSet { on, tid: self.target.next_tid(), transition }).await
+    }
+
+    /// Sets the state without waiting for a status.
+    pub async fn set_unack(&self, on: bool, transition: Option<Transition>) -> Result<()> {
+        self.target.send(&OnOffSetUnack(OnOffSet { on, tid: self.target.next_tid(), transition })).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::models::access_message;
+
+    #[test]
+    fn messages() {
+        assert_eq!(OnOffGet.encode().unwrap(), [0x82, 0x01]);
+
+        let set = OnOffSet {
+            on: true,
+            tid: 0x2a,
+            transition: Some(Transition { time: TransitionTime(0x41), delay: 0x02 }),
+        };
+        assert_eq!(set.encode().unwrap(), [0x82, 0x02, 0x01, 0x2a, 0x41, 0x02]);
+        assert_eq!(OnOffSet::decode(&set.encode().unwrap()).unwrap(), set);
+        assert_eq!(OnOffSetUnack(set).encode().unwrap(), [0x82, 0x03, 0x01, 0x2a, 0x41, 0x02]);
+        assert!(OnOffSet::decode(&[0x82, 0x02, 0x02, 0x2a]).is_err());
+        assert!(OnOffSet::decode(&[0x82, 0x02, 0x01, 0x2a, 0x41]).is_err());
+
+        let status = OnOffStatus {
+            present: false,
+            target: Some(Target { value: true, remaining_time: TransitionTime(0x05) }),
+        };
+        assert_eq!(status.encode().unwrap(), [0x82, 0x04, 0x00, 0x01, 0x05]);
+        assert_eq!(OnOffStatus::decode(&status.encode().unwrap()).unwrap(), status);
+    }
+
+    #[test]
+    fn server() {
+        let server = OnOffServer::new(false);
+
+        assert_eq!(server.handle(&access_message(&[0x82, 0x01])), Some(vec![0x82, 0x04, 0x00]));
+        assert_eq!(server.handle(&access_message(&[0x82, 0x02, 0x01, 0x01])), Some(vec![0x82, 0x04, 0x01]));
+        assert!(server.get());
+
+        // Local change followed by a retransmission of the previous set message.
+        server.set(false);
+        assert_eq!(server.handle(&access_message(&[0x82, 0x03, 0x01, 0x01])), None);
+        assert!(!server.get());
+
+        assert_eq!(server.handle(&access_message(&[0x82, 0x03, 0x01, 0x02])), None);
+        assert!(server.get());
+    }
+}
@@ -0,0 +1,496 @@
+//! Light Lightness model.
+//!
+//! The Light Lightness Actual state is an unsigned 16-bit integer representing the
+//! perceived lightness of a light, where zero means off.
+//! The Light Lightness Linear state is derived from it and represents the measured
+//! lightness.
+//! The Light Lightness Last state is the last non-zero lightness.
+//!
+//! The Light Lightness Default and Range states are not supported.
+
+use futures::future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, watch};
+
+use super::{
+    invalid_length, status_handler, ClientTarget, Received, Target, Transactions, Transition, TransitionTime,
+};
+use crate::{
+    mesh::access::{AccessMessage, DispatcherHandle, MeshMessage, ModelHandler, ModelId, Opcode},
+    Result,
+};
+
+/// SIG model id of the Light Lightness Server model.
+pub const LIGHT_LIGHTNESS_SERVER: u16 = 0x1300;
+
+/// SIG model id of the Light Lightness Client model.
+pub const LIGHT_LIGHTNESS_CLIENT: u16 = 0x1302;
+
+/// Converts a Light Lightness Actual value into a Light Lightness Linear value.
+pub fn actual_to_linear(actual: u16) -> u16 {
+    let actual = actual as u64;
+    ((actual * actual + 65534) / 65535) as u16
+}
+
+/// Converts a Light Lightness Linear value into a Light Lightness Actual value.
+pub fn linear_to_actual(linear: u16) -> u16 {
+    let square = 65535 * linear as u64;
+    let mut root = (square as f64).sqrt() as u64;
+    while root * root > square {
+        root -= 1;
+    }
+    while (root + 1) * (root + 1) <= square {
+        root += 1;
+    }
+    root as u16
+}
+
+fn encode_set(value: u16, tid: u8, transition: &Option<Transition>, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&value.to_le_bytes());
+    buf.push(tid);
+    Transition::encode(transition, buf);
+}
+
+fn decode_set(params: &[u8]) -> Result<(u16, u8, Option<Transition>)> {
+    match params {
+        [v1, v2, tid, transition @ ..] => {
+            Ok((u16::from_le_bytes([*v1, *v2]), *tid, Transition::decode(transition)?))
+        }
+        _ => Err(invalid_length()),
+    }
+}
+
+fn encode_status(present: u16, target: &Option<Target<u16>>, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&present.to_le_bytes());
+    if let Some(target) = target {
+        buf.extend_from_slice(&target.value.to_le_bytes());
+        buf.push(target.remaining_time.0);
+    }
+}
+
+fn decode_status(params: &[u8]) -> Result<(u16, Option<Target<u16>>)> {
+    match params {
+        [p1, p2] => Ok((u16::from_le_bytes([*p1, *p2]), None)),
+        [p1, p2, t1, t2, remaining_time] => Ok((
+            u16::from_le_bytes([*p1, *p2]),
+            Some(Target {
+                value: u16::from_le_bytes([*t1, *t2]),
+                remaining_time: TransitionTime(*remaining_time),
+            }),
+        )),
+        _ => Err(invalid_length()),
+    }
+}
+
+fn decode_empty(params: &[u8]) -> Result<()> {
+    match params {
+        [] => Ok(()),
+        _ => Err(invalid_length()),
+    }
+}
+
+/// Light Lightness Get message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LightnessGet;
+
+impl MeshMessage for LightnessGet {
+    const OPCODE: Opcode = Opcode::Sig(0x824b);
+
+    fn encode_params(&self, _buf: &mut Vec<u8>) {}
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        decode_empty(params).map(|()| Self)
+    }
+}
+
+/// Light Lightness Set message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LightnessSet {
+    /// Target lightness.
+    pub lightness: u16,
+    /// Transaction identifier.
+    pub tid: u8,
+    /// Transition parameters.
+    pub transition: Option<Transition>,
+}
+
+impl MeshMessage for LightnessSet {
+    const OPCODE: Opcode = Opcode::Sig(0x824c);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        encode_set(self.lightness, self.tid, &self.transition, buf)
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        let (lightness, tid, transition) = decode_set(params)?;
+        Ok(Self { lightness, tid, transition })
+    }
+}
+
+/// Light Lightness Set Unacknowledged message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LightnessSetUnack(pub LightnessSet);
+
+impl MeshMessage for LightnessSetUnack {
+    const OPCODE: Opcode = Opcode::Sig(0x824d);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        self.0.encode_params(buf)
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        Ok(Self(LightnessSet::decode_params(params)?))
+    }
+}
+
+/// Light Lightness Status message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LightnessStatus {
+    /// Present lightness.
+    pub present: u16,
+    /// Target lightness, if a transition is in progress.
+    pub target: Option<Target<u16>>,
+}
+
+impl MeshMessage for LightnessStatus {
+    const OPCODE: Opcode = Opcode::Sig(0x824e);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        encode_status(self.present, &self.target, buf)
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        let (present, target) = decode_status(params)?;
+        Ok(Self { present, target })
+    }
+}
+
+/// Light Lightness Linear Get message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LightnessLinearGet;
+
+impl MeshMessage for LightnessLinearGet {
+    const OPCODE: Opcode = Opcode::Sig(0x824f);
+
+    fn encode_params(&self, _buf: &mut Vec<u8>) {}
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        decode_empty(params).map(|()| Self)
+    }
+}
+
+/// Light Lightness Linear Set message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LightnessLinearSet {
+    /// Target linear lightness.
+    pub lightness: u16,
+    /// Transaction identifier.
+    pub tid: u8,
+    /// Transition parameters.
+    pub transition: Option<Transition>,
+}
+
+impl MeshMessage for LightnessLinearSet {
+    const OPCODE: Opcode = Opcode::Sig(0x8250);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        encode_set(self.lightness, self.tid, &self.transition, buf)
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        let (lightness, tid, transition) = decode_set(params)?;
+        Ok(Self { lightness, tid, transition })
+    }
+}
+
+/// Light Lightness Linear Set Unacknowledged message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LightnessLinearSetUnack(pub LightnessLinearSet);
+
+impl MeshMessage for LightnessLinearSetUnack {
+    const OPCODE: Opcode = Opcode::Sig(0x8251);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        self.0.encode_params(buf)
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        Ok(Self(LightnessLinearSet::decode_params(params)?))
+    }
+}
+
+/// Light Lightness Linear Status message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LightnessLinearStatus {
+    /// Present linear lightness.
+    pub present: u16,
+    /// Target linear lightness, if a transition is in progress.
+    pub target: Option<Target<u16>>,
+}
+
+impl MeshMessage for LightnessLinearStatus {
+    const OPCODE: Opcode = Opcode::Sig(0x8252);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        encode_status(self.present, &self.target, buf)
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        let (present, target) = decode_status(params)?;
+        Ok(Self { present, target })
+    }
+}
+
+/// Light Lightness Last Get message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LightnessLastGet;
+
+impl MeshMessage for LightnessLastGet {
+    const OPCODE: Opcode = Opcode::Sig(0x8253);
+
+    fn encode_params(&self, _buf: &mut Vec<u8>) {}
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        decode_empty(params).map(|()| Self)
+    }
+}
+
+/// Light Lightness Last Status message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LightnessLastStatus {
+    /// Last non-zero lightness.
+    pub lightness: u16,
+}
+
+impl MeshMessage for LightnessLastStatus {
+    const OPCODE: Opcode = Opcode::Sig(0x8254);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.lightness.to_le_bytes());
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        match params {
+            [l1, l2] => Ok(Self { lightness: u16::from_le_bytes([*l1, *l2]) }),
+            _ => Err(invalid_length()),
+        }
+    }
+}
+
+/// State of a Light Lightness Server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LightnessState {
+    /// Light Lightness Actual state.
+    pub actual: u16,
+    /// Light Lightness Last state.
+    pub last: u16,
+}
+
+impl LightnessState {
+    /// Light Lightness Linear state.
+    pub fn linear(&self) -> u16 {
+        actual_to_linear(self.actual)
+    }
+}
+
+/// Light Lightness Server model.
+#[derive(Clone, Debug)]
+pub struct LightnessServer {
+    state: Arc<watch::Sender<LightnessState>>,
+    transactions: Arc<Mutex<Transactions>>,
+}
+
+impl LightnessServer {
+    /// Creates a server with the specified initial lightness.
+    pub fn new(actual: u16) -> Self {
+        let state = LightnessState { actual, last: if actual == 0 { 0xffff } else { actual } };
+        Self { state: Arc::new(watch::channel(state).0), transactions: Default::default() }
+    }
+
+    /// Present state.
+    pub fn get(&self) -> LightnessState {
+        *self.state.borrow()
+    }
+
+    /// Sets the Light Lightness Actual state locally.
+    ///
+    /// The Light Lightness Last state is updated if the lightness is not zero.
+    pub fn set(&self, actual: u16) {
+        self.state.send_modify(|state| {
+            state.actual = actual;
+            if actual != 0 {
+                state.last = actual;
+            }
+        });
+    }
+
+    /// Receiver for changes of the state.
+    pub fn subscribe(&self) -> watch::Receiver<LightnessState> {
+        self.state.subscribe()
+    }
+
+    /// Status message containing the present lightness, for example for publication.
+    pub fn status(&self) -> LightnessStatus {
+        LightnessStatus { present: self.get().actual, target: None }
+    }
+
+    /// Handler of the server model for the [Dispatcher](crate::mesh::access::Dispatcher).
+    pub fn handler(&self) -> ModelHandler {
+        let this = self.clone();
+        ModelHandler {
+            model_id: ModelId::Sig(LIGHT_LIGHTNESS_SERVER),
+            opcodes: vec![
+                LightnessGet::OPCODE,
+                LightnessSet::OPCODE,
+                LightnessSetUnack::OPCODE,
+                LightnessLinearGet::OPCODE,
+                LightnessLinearSet::OPCODE,
+                LightnessLinearSetUnack::OPCODE,
+                LightnessLastGet::OPCODE,
+            ],
+            fun: Box::new(move |msg| Box::pin(future::ready(this.handle(&msg)))),
+        }
+    }
+
+    fn linear_status(&self) -> LightnessLinearStatus {
+        LightnessLinearStatus { present: self.get().linear(), target: None }
+    }
+
+    fn apply(&self, source: u16, tid: u8, actual: u16) {
+        if !self.transactions.lock().unwrap().is_retransmission(source, tid) {
+            self.set(actual);
+        }
+    }
+
+    fn handle(&self, msg: &AccessMessage) -> Option<Vec<u8>> {
+        match msg.opcode {
+            LightnessGet::OPCODE => self.status().encode().ok(),
+            LightnessSet::OPCODE => {
+                let set = msg.decode::<LightnessSet>().ok()?;
+                self.apply(msg.source, set.tid, set.lightness);
+                self.status().encode().ok()
+            }
+            LightnessSetUnack::OPCODE => {
+                let set = msg.decode::<LightnessSetUnack>().ok()?.0;
+                self.apply(msg.source, set.tid, set.lightness);
+                None
+            }
+            LightnessLinearGet::OPCODE => self.linear_status().encode().ok(),
+            LightnessLinearSet::OPCODE => {
+                let set = msg.decode::<LightnessLinearSet>().ok()?;
+                self.apply(msg.source, set.tid, linear_to_actual(set.lightness));
+                self.linear_status().encode().ok()
+            }
+            LightnessLinearSetUnack::OPCODE => {
+                let set = msg.decode::<LightnessLinearSetUnack>().ok()?.0;
+                self.apply(msg.source, set.tid, linear_to_actual(set.lightness));
+                None
+            }
+            LightnessLastGet::OPCODE => LightnessLastStatus { lightness: self.get().last }.encode().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Light Lightness Client model.
+#[derive(Debug)]
+pub struct LightnessClient {
+    target: ClientTarget,
+}
+
+impl LightnessClient {
+    /// Creates a client sending requests to the specified destination using the specified application key.
+    pub fn new(dispatcher: Arc<DispatcherHandle>, destination: u16, key_index: u16) -> Self {
+        Self { target: ClientTarget::new(dispatcher, destination, key_index) }
+    }
+
+    /// Handler of the client model receiving status messages published by servers.
+    pub fn status_handler() -> (ModelHandler, mpsc::Receiver<Received<LightnessStatus>>) {
+        status_handler(LIGHT_LIGHTNESS_CLIENT)
+    }
+
+    /// Gets the lightness.
+    pub async fn get(&self) -> Result<LightnessStatus> {
+        self.target.request(&LightnessGet).await
+    }
+
+    /// Sets the lightness and waits for the resulting status.
+    pub async fn set(&self, lightness: u16, transition: Option<Transition>) -> Result<LightnessStatus> {
+        self.target.request(&LightnessSet { lightness, tid: self.target.next_tid(), transition }).await
+    }
+
+    /// Sets the lightness without waiting for a status.
+    pub async fn set_unack(&self, lightness: u16, transition: Option<Transition>) -> Result<()> {
+        self.target
+            .send(&LightnessSetUnack(LightnessSet { lightness, tid: self.target.next_tid(), transition }))
+            .await
+    }
+
+    /// Gets the linear lightness.
+    pub async fn linear_get(&self) -> Result<LightnessLinearStatus> {
+        self.target.request(&LightnessLinearGet).await
+    }
+
+    /// Sets the linear lightness and waits for the resulting status.
+    pub async fn linear_set(
+        &self, lightness: u16, transition: Option<Transition>,
+    ) -> Result<LightnessLinearStatus> {
+        self.target.request(&LightnessLinearSet { lightness, tid: self.target.next_tid(), transition }).await
+    }
+
+    /// Gets the last non-zero lightness.
+    pub async fn last_get(&self) -> Result<u16> {
+        let status: LightnessLastStatus = self.target.request(&LightnessLastGet).await?;
+        Ok(status.lightness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::models::access_message;
+
+    #[test]
+    fn conversion() {
+        assert_eq!(actual_to_linear(0), 0);
+        assert_eq!(actual_to_linear(1), 1);
+        assert_eq!(actual_to_linear(0x8000), 0x4001);
+        assert_eq!(actual_to_linear(0xffff), 0xffff);
+        assert_eq!(linear_to_actual(0), 0);
+        assert_eq!(linear_to_actual(0x4001), 0x8000);
+        assert_eq!(linear_to_actual(0xffff), 0xffff);
+    }
+
+    #[test]
+    fn messages() {
+        let set = LightnessSet { lightness: 0x1234, tid: 0x01, transition: None };
+        assert_eq!(set.encode().unwrap(), [0x82, 0x4c, 0x34, 0x12, 0x01]);
+        assert_eq!(LightnessSet::decode(&set.encode().unwrap()).unwrap(), set);
+        assert_eq!(
+            LightnessLinearStatus::decode(&[0x82, 0x52, 0x00, 0x00, 0xff, 0xff, 0x0a]).unwrap(),
+            LightnessLinearStatus {
+                present: 0,
+                target: Some(Target { value: 0xffff, remaining_time: TransitionTime(0x0a) })
+            }
+        );
+        assert_eq!(LightnessLastStatus { lightness: 0xabcd }.encode().unwrap(), [0x82, 0x54, 0xcd, 0xab]);
+    }
+
+    #[test]
+    fn server() {
+        let server = LightnessServer::new(0);
+
+        assert_eq!(
+            server.handle(&access_message(&LightnessLastGet.encode().unwrap())),
+            Some(vec![0x82, 0x54, 0xff, 0xff])
+        );
+
+        let set = LightnessLinearSet { lightness: 0x4001, tid: 1, transition: None };
+        assert_eq!(server.handle(&access_message(&set.encode().unwrap())), Some(vec![0x82, 0x52, 0x01, 0x40]));
+        assert_eq!(server.get(), LightnessState { actual: 0x8000, last: 0x8000 });
+
+        let set = LightnessSetUnack(LightnessSet { lightness: 0, tid: 2, transition: None });
+        assert_eq!(server.handle(&access_message(&set.encode().unwrap())), None);
+        assert_eq!(server.get(), LightnessState { actual: 0, last: 0x8000 });
+    }
+}
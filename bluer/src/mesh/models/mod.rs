@@ -0,0 +1,233 @@
+//! Bluetooth mesh models.
+//!
+//! Server and client implementations of the following models defined by the
+//! Bluetooth Mesh Model specification are provided:
+//!
+//!   * [Generic OnOff](generic_onoff)
+//!   * [Generic Level](generic_level)
+//!   * [Light Lightness](light_lightness)
+//!   * [Sensor](sensor)
+//!
+//! All messages implement [MeshMessage].
+//!
+//! A server keeps the state of its model and provides a [ModelHandler], which must be
+//! added to the [Dispatcher](super::access::Dispatcher) of the element containing the server model.
+//! Servers apply state transitions immediately.
+//! Cloning a server yields another reference to the same state.
+//!
+//! A client sends requests to a server using a [DispatcherHandle] and provides a
+//! [ModelHandler] for receiving status messages published by servers.
+
+use std::{
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+
+use super::access::{handler_fun, AccessMessage, DispatcherHandle, MeshMessage, ModelHandler, ModelId};
+use crate::{Error, ErrorKind, InternalErrorKind, Result};
+
+pub mod generic_level;
+pub mod generic_onoff;
+pub mod light_lightness;
+pub mod sensor;
+
+/// Time within which a set message with the same transaction identifier
+/// from the same source is considered a retransmission.
+const TRANSACTION_TIMEOUT: Duration = Duration::from_secs(6);
+
+/// Transition time of a state.
+///
+/// The number of steps is stored in the 6 least significant bits and
+/// the step resolution (100 ms, 1 s, 10 s or 10 min) in the 2 most significant bits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransitionTime(pub u8);
+
+impl TransitionTime {
+    /// Immediate transition.
+    pub const IMMEDIATE: Self = Self(0x00);
+
+    /// Unknown transition time.
+    pub const UNKNOWN: Self = Self(0x3f);
+
+    const STEP_MS: [u64; 4] = [100, 1_000, 10_000, 600_000];
+
+    /// Converts a duration into a transition time.
+    ///
+    /// The duration is rounded down to the step resolution and limited to 620 minutes.
+    pub fn from_duration(duration: Duration) -> Self {
+        let ms = duration.as_millis().min(u64::MAX as u128) as u64;
+        for (resolution, step_ms) in Self::STEP_MS.into_iter().enumerate() {
+            if ms / step_ms <= 0x3e {
+                return Self((resolution as u8) << 6 | (ms / step_ms) as u8);
+            }
+        }
+        Self(0xfe)
+    }
+
+    /// Duration of the transition.
+    ///
+    /// `None` if unknown.
+    pub fn duration(self) -> Option<Duration> {
+        match self.0 & 0x3f {
+            0x3f => None,
+            steps => Some(Duration::from_millis(steps as u64 * Self::STEP_MS[(self.0 >> 6) as usize])),
+        }
+    }
+}
+
+/// Transition parameters of a set message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transition {
+    /// Transition time.
+    pub time: TransitionTime,
+    /// Delay before the transition starts in steps of 5 ms.
+    pub delay: u8,
+}
+
+impl Transition {
+    fn encode(transition: &Option<Self>, buf: &mut Vec<u8>) {
+        if let Some(Self { time, delay }) = transition {
+            buf.extend_from_slice(&[time.0, *delay]);
+        }
+    }
+
+    fn decode(params: &[u8]) -> Result<Option<Self>> {
+        match params {
+            [] => Ok(None),
+            [time, delay] => Ok(Some(Self { time: TransitionTime(*time), delay: *delay })),
+            _ => Err(invalid_length()),
+        }
+    }
+}
+
+/// Target state of a transition in progress.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Target<T> {
+    /// Target value.
+    pub value: T,
+    /// Remaining time of the transition.
+    pub remaining_time: TransitionTime,
+}
+
+/// A status message received by a client model.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Received<M> {
+    /// Unicast address of the element that sent the message.
+    pub source: u16,
+    /// Received message.
+    pub message: M,
+}
+
+fn invalid_length() -> Error {
+    Error::new(ErrorKind::InvalidLength)
+}
+
+fn invalid_value() -> Error {
+    Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue))
+}
+
+/// Most recent transaction received by a server.
+#[derive(Debug, Default)]
+struct Transactions(Option<(u16, u8, Instant)>);
+
+impl Transactions {
+    /// Records a transaction and returns whether it is a retransmission of the most recent one.
+    fn is_retransmission(&mut self, source: u16, tid: u8) -> bool {
+        let now = Instant::now();
+        let retransmission = matches!(self.0,
+            Some((last_source, last_tid, at)) if last_source == source && last_tid == tid && now - at < TRANSACTION_TIMEOUT);
+        self.0 = Some((source, tid, now));
+        retransmission
+    }
+}
+
+/// Creates a handler for a client model that forwards the received status messages.
+fn status_handler<M>(model_id: u16) -> (ModelHandler, mpsc::Receiver<Received<M>>)
+where
+    M: MeshMessage + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(16);
+    let handler = ModelHandler {
+        model_id: ModelId::Sig(model_id),
+        opcodes: vec![M::OPCODE],
+        fun: handler_fun(move |message: M, msg: AccessMessage| {
+            if tx.try_send(Received { source: msg.source, message }).is_err() {
+                log::trace!("Discarding mesh status message from {:#06x}", msg.source);
+            }
+            async { None::<M> }
+        }),
+    };
+    (handler, rx)
+}
+
+/// Destination of the requests of a client model.
+#[derive(Debug)]
+struct ClientTarget {
+    dispatcher: Arc<DispatcherHandle>,
+    destination: u16,
+    key_index: u16,
+    tid: AtomicU8,
+}
+
+impl ClientTarget {
+    fn new(dispatcher: Arc<DispatcherHandle>, destination: u16, key_index: u16) -> Self {
+        Self { dispatcher, destination, key_index, tid: AtomicU8::new(0) }
+    }
+
+    /// Returns the transaction identifier for the next set message.
+    fn next_tid(&self) -> u8 {
+        self.tid.fetch_add(1, Ordering::Relaxed)
+    }
+
+    async fn send<M: MeshMessage>(&self, msg: &M) -> Result<()> {
+        self.dispatcher.send(self.destination, self.key_index, msg).await
+    }
+
+    async fn request<Req: MeshMessage, Resp: MeshMessage>(&self, request: &Req) -> Result<Resp> {
+        self.dispatcher.request(self.destination, self.key_index, request).await
+    }
+}
+
+/// Access message with the specified opcode and parameters received by a server in tests.
+#[cfg(test)]
+pub(super) fn access_message(data: &[u8]) -> AccessMessage {
+    let (opcode, params) = super::access::Opcode::decode(data).unwrap();
+    AccessMessage {
+        source: 0x0002,
+        key: super::access::AccessKey::App { key_index: 0, destination: 0x0003 },
+        opcode,
+        params: params.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transition_time() {
+        assert_eq!(TransitionTime::from_duration(Duration::ZERO), TransitionTime::IMMEDIATE);
+        assert_eq!(TransitionTime::from_duration(Duration::from_millis(500)), TransitionTime(0x05));
+        assert_eq!(TransitionTime::from_duration(Duration::from_secs(10)), TransitionTime(0x4a));
+        assert_eq!(TransitionTime::from_duration(Duration::from_secs(3600)), TransitionTime(0xc6));
+        assert_eq!(TransitionTime::from_duration(Duration::from_secs(86400)), TransitionTime(0xfe));
+        assert_eq!(TransitionTime(0x4a).duration(), Some(Duration::from_secs(10)));
+        assert_eq!(TransitionTime::UNKNOWN.duration(), None);
+    }
+
+    #[test]
+    fn transactions() {
+        let mut transactions = Transactions::default();
+        assert!(!transactions.is_retransmission(0x0002, 1));
+        assert!(transactions.is_retransmission(0x0002, 1));
+        assert!(!transactions.is_retransmission(0x0003, 1));
+        assert!(!transactions.is_retransmission(0x0003, 2));
+    }
+}
@@ -0,0 +1,487 @@
+//! Sensor model.
+//!
+//! A sensor server exposes a set of sensors, each identified by a [PropertyId].
+//! The format of a sensor value is defined by its device property in the
+//! Bluetooth Mesh Device Properties specification.
+//!
+//! Sensor cadence, settings, columns and series are not supported.
+
+use futures::future;
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::mpsc;
+
+use super::{invalid_length, status_handler, ClientTarget, Received};
+use crate::{
+    mesh::access::{AccessMessage, DispatcherHandle, MeshMessage, ModelHandler, ModelId, Opcode},
+    Error, ErrorKind, Result,
+};
+
+/// SIG model id of the Sensor Server model.
+pub const SENSOR_SERVER: u16 = 0x1100;
+
+/// SIG model id of the Sensor Setup Server model.
+pub const SENSOR_SETUP_SERVER: u16 = 0x1101;
+
+/// SIG model id of the Sensor Client model.
+pub const SENSOR_CLIENT: u16 = 0x1102;
+
+/// Maximum length of a sensor value.
+pub const MAX_VALUE_LEN: usize = 127;
+
+/// Device property identifier.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PropertyId(pub u16);
+
+impl PropertyId {
+    /// Motion Sensed (percentage in steps of 0.5 %, 1 octet).
+    pub const MOTION_SENSED: Self = Self(0x0042);
+    /// People Count (count, 2 octets).
+    pub const PEOPLE_COUNT: Self = Self(0x004c);
+    /// Presence Detected (boolean, 1 octet).
+    pub const PRESENCE_DETECTED: Self = Self(0x004d);
+    /// Present Ambient Light Level (illuminance in steps of 0.01 lux, 3 octets).
+    pub const PRESENT_AMBIENT_LIGHT_LEVEL: Self = Self(0x004e);
+    /// Present Ambient Temperature (temperature in steps of 0.5 °C, signed, 1 octet).
+    pub const PRESENT_AMBIENT_TEMPERATURE: Self = Self(0x004f);
+}
+
+impl fmt::Display for PropertyId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#06x}", self.0)
+    }
+}
+
+/// Sensor descriptor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SensorDescriptor {
+    /// Property of the sensor.
+    pub property_id: PropertyId,
+    /// Positive tolerance of the sensor (12 bits).
+    ///
+    /// Zero means unspecified.
+    pub positive_tolerance: u16,
+    /// Negative tolerance of the sensor (12 bits).
+    ///
+    /// Zero means unspecified.
+    pub negative_tolerance: u16,
+    /// Sampling function applied to the measured values.
+    ///
+    /// Zero means unspecified.
+    pub sampling_function: u8,
+    /// Measurement period as an exponent of 1.1 seconds.
+    ///
+    /// Zero means not applicable.
+    pub measurement_period: u8,
+    /// Update interval as an exponent of 1.1 seconds.
+    ///
+    /// Zero means not applicable.
+    pub update_interval: u8,
+}
+
+impl SensorDescriptor {
+    /// Creates a descriptor with unspecified parameters.
+    pub fn new(property_id: PropertyId) -> Self {
+        Self { property_id, ..Default::default() }
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.property_id.0.to_le_bytes());
+        let tolerance =
+            (self.positive_tolerance as u32 & 0xfff) | ((self.negative_tolerance as u32 & 0xfff) << 12);
+        buf.extend_from_slice(&tolerance.to_le_bytes()[..3]);
+        buf.extend_from_slice(&[self.sampling_function, self.measurement_period, self.update_interval]);
+    }
+
+    fn decode(data: &[u8]) -> Result<Self> {
+        match data {
+            [p1, p2, t1, t2, t3, sampling_function, measurement_period, update_interval] => {
+                let tolerance = u32::from_le_bytes([*t1, *t2, *t3, 0]);
+                Ok(Self {
+                    property_id: PropertyId(u16::from_le_bytes([*p1, *p2])),
+                    positive_tolerance: (tolerance & 0xfff) as u16,
+                    negative_tolerance: (tolerance >> 12) as u16,
+                    sampling_function: *sampling_function,
+                    measurement_period: *measurement_period,
+                    update_interval: *update_interval,
+                })
+            }
+            _ => Err(invalid_length()),
+        }
+    }
+}
+
+/// Value of a sensor.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SensorValue {
+    /// Property of the sensor.
+    pub property_id: PropertyId,
+    /// Raw value of at most [MAX_VALUE_LEN] octets.
+    ///
+    /// Empty if the sensor is not present or its value is unknown.
+    pub value: Vec<u8>,
+}
+
+impl SensorValue {
+    /// Appends the value in marshalled format.
+    ///
+    /// The short format A is used when possible, otherwise the long format B.
+    fn encode(&self, buf: &mut Vec<u8>) {
+        let value = &self.value[..self.value.len().min(MAX_VALUE_LEN)];
+        if (1..=16).contains(&value.len()) && self.property_id.0 < 0x0800 {
+            let header = ((value.len() as u16 - 1) << 1) | (self.property_id.0 << 5);
+            buf.extend_from_slice(&header.to_le_bytes());
+        } else {
+            let len = if value.is_empty() { 0x7f } else { value.len() as u8 - 1 };
+            buf.push(0x01 | (len << 1));
+            buf.extend_from_slice(&self.property_id.0.to_le_bytes());
+        }
+        buf.extend_from_slice(value);
+    }
+
+    /// Decodes a value in marshalled format and returns it together with the remaining data.
+    fn decode(data: &[u8]) -> Result<(Self, &[u8])> {
+        let (property_id, len, rest) = match data {
+            [h1, h2, rest @ ..] if h1 & 0x01 == 0 => {
+                let header = u16::from_le_bytes([*h1, *h2]);
+                (header >> 5, ((header >> 1) & 0x0f) as usize + 1, rest)
+            }
+            [h, p1, p2, rest @ ..] if h & 0x01 != 0 => {
+                let len = match h >> 1 {
+                    0x7f => 0,
+                    len => len as usize + 1,
+                };
+                (u16::from_le_bytes([*p1, *p2]), len, rest)
+            }
+            _ => return Err(invalid_length()),
+        };
+        if rest.len() < len {
+            return Err(invalid_length());
+        }
+        let (value, rest) = rest.split_at(len);
+        Ok((Self { property_id: PropertyId(property_id), value: value.to_vec() }, rest))
+    }
+}
+
+/// Sensor Descriptor Get message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SensorDescriptorGet {
+    /// Property to get the descriptor of.
+    ///
+    /// `None` gets the descriptors of all sensors.
+    pub property_id: Option<PropertyId>,
+}
+
+impl MeshMessage for SensorDescriptorGet {
+    const OPCODE: Opcode = Opcode::Sig(0x8230);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        if let Some(property_id) = self.property_id {
+            buf.extend_from_slice(&property_id.0.to_le_bytes());
+        }
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        match params {
+            [] => Ok(Self { property_id: None }),
+            [p1, p2] => Ok(Self { property_id: Some(PropertyId(u16::from_le_bytes([*p1, *p2]))) }),
+            _ => Err(invalid_length()),
+        }
+    }
+}
+
+/// Sensor Descriptor Status message.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SensorDescriptorStatus {
+    /// Descriptors of the requested sensors.
+    Descriptors(Vec<SensorDescriptor>),
+    /// The requested sensor does not exist.
+    NotFound(PropertyId),
+}
+
+impl MeshMessage for SensorDescriptorStatus {
+    const OPCODE: Opcode = Opcode::Sig(0x51);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::Descriptors(descriptors) => descriptors.iter().for_each(|descriptor| descriptor.encode(buf)),
+            Self::NotFound(property_id) => buf.extend_from_slice(&property_id.0.to_le_bytes()),
+        }
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        match params {
+            [p1, p2] => Ok(Self::NotFound(PropertyId(u16::from_le_bytes([*p1, *p2])))),
+            _ if params.len() % 8 == 0 => {
+                Ok(Self::Descriptors(params.chunks(8).map(SensorDescriptor::decode).collect::<Result<_>>()?))
+            }
+            _ => Err(invalid_length()),
+        }
+    }
+}
+
+/// Sensor Get message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SensorGet {
+    /// Property to get the value of.
+    ///
+    /// `None` gets the values of all sensors.
+    pub property_id: Option<PropertyId>,
+}
+
+impl MeshMessage for SensorGet {
+    const OPCODE: Opcode = Opcode::Sig(0x8231);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        if let Some(property_id) = self.property_id {
+            buf.extend_from_slice(&property_id.0.to_le_bytes());
+        }
+    }
+
+    fn decode_params(params: &[u8]) -> Result<Self> {
+        match params {
+            [] => Ok(Self { property_id: None }),
+            [p1, p2] => Ok(Self { property_id: Some(PropertyId(u16::from_le_bytes([*p1, *p2]))) }),
+            _ => Err(invalid_length()),
+        }
+    }
+}
+
+/// Sensor Status message.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SensorStatus {
+    /// Sensor values.
+    pub values: Vec<SensorValue>,
+}
+
+impl SensorStatus {
+    /// Value of the specified sensor, if present and known.
+    pub fn value(&self, property_id: PropertyId) -> Option<&[u8]> {
+        self.values
+            .iter()
+            .find(|value| value.property_id == property_id && !value.value.is_empty())
+            .map(|value| value.value.as_slice())
+    }
+}
+
+impl MeshMessage for SensorStatus {
+    const OPCODE: Opcode = Opcode::Sig(0x52);
+
+    fn encode_params(&self, buf: &mut Vec<u8>) {
+        self.values.iter().for_each(|value| value.encode(buf));
+    }
+
+    fn decode_params(mut params: &[u8]) -> Result<Self> {
+        let mut values = Vec::new();
+        while !params.is_empty() {
+            let (value, rest) = SensorValue::decode(params)?;
+            values.push(value);
+            params = rest;
+        }
+        Ok(Self { values })
+    }
+}
+
+/// Descriptor and raw value of a sensor provided by a server.
+type Sensor = (SensorDescriptor, Vec<u8>);
+
+/// Sensor Server model.
+#[derive(Clone, Debug)]
+pub struct SensorServer {
+    sensors: Arc<Mutex<Vec<Sensor>>>,
+}
+
+impl SensorServer {
+    /// Creates a server providing sensors with the specified descriptors.
+    ///
+    /// The values of all sensors are initially unknown.
+    pub fn new(descriptors: impl IntoIterator<Item = SensorDescriptor>) -> Self {
+        let sensors = descriptors.into_iter().map(|descriptor| (descriptor, Vec::new())).collect();
+        Self { sensors: Arc::new(Mutex::new(sensors)) }
+    }
+
+    /// Sets the raw value of a sensor.
+    ///
+    /// Fails with [ErrorKind::NotFound] if the server does not provide the sensor and
+    /// with [ErrorKind::InvalidLength] if the value is longer than [MAX_VALUE_LEN] octets.
+    pub fn set_value(&self, property_id: PropertyId, value: Vec<u8>) -> Result<()> {
+        if value.len() > MAX_VALUE_LEN {
+            return Err(invalid_length());
+        }
+        let mut sensors = self.sensors.lock().unwrap();
+        let (_, sensor_value) = sensors
+            .iter_mut()
+            .find(|(descriptor, _)| descriptor.property_id == property_id)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound))?;
+        *sensor_value = value;
+        Ok(())
+    }
+
+    /// Status message containing the values of all sensors, for example for publication.
+    pub fn status(&self) -> SensorStatus {
+        self.status_of(None)
+    }
+
+    fn status_of(&self, property_id: Option<PropertyId>) -> SensorStatus {
+        let sensors = self.sensors.lock().unwrap();
+        let values = match property_id {
+            Some(property_id) => vec![SensorValue {
+                property_id,
+                value: sensors
+                    .iter()
+                    .find(|(descriptor, _)| descriptor.property_id == property_id)
+                    .map(|(_, value)| value.clone())
+                    .unwrap_or_default(),
+            }],
+            None => sensors
+                .iter()
+                .map(|(descriptor, value)| SensorValue {
+                    property_id: descriptor.property_id,
+                    value: value.clone(),
+                })
+                .collect(),
+        };
+        SensorStatus { values }
+    }
+
+    fn descriptor_status(&self, property_id: Option<PropertyId>) -> SensorDescriptorStatus {
+        let sensors = self.sensors.lock().unwrap();
+        let mut descriptors = sensors.iter().map(|(descriptor, _)| *descriptor);
+        match property_id {
+            Some(property_id) => match descriptors.find(|descriptor| descriptor.property_id == property_id) {
+                Some(descriptor) => SensorDescriptorStatus::Descriptors(vec![descriptor]),
+                None => SensorDescriptorStatus::NotFound(property_id),
+            },
+            None => SensorDescriptorStatus::Descriptors(descriptors.collect()),
+        }
+    }
+
+    /// Handler of the server model for the [Dispatcher](crate::mesh::access::Dispatcher).
+    pub fn handler(&self) -> ModelHandler {
+        let this = self.clone();
+        ModelHandler {
+            model_id: ModelId::Sig(SENSOR_SERVER),
+            opcodes: vec![SensorDescriptorGet::OPCODE, SensorGet::OPCODE],
+            fun: Box::new(move |msg| Box::pin(future::ready(this.handle(&msg)))),
+        }
+    }
+
+    fn handle(&self, msg: &AccessMessage) -> Option<Vec<u8>> {
+        match msg.opcode {
+            SensorDescriptorGet::OPCODE => {
+                let get = msg.decode::<SensorDescriptorGet>().ok()?;
+                self.descriptor_status(get.property_id).encode().ok()
+            }
+            SensorGet::OPCODE => {
+                let get = msg.decode::<SensorGet>().ok()?;
+                self.status_of(get.property_id).encode().ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Sensor Client model.
+#[derive(Debug)]
+pub struct SensorClient {
+    target: ClientTarget,
+}
+
+impl SensorClient {
+    /// Creates a client sending requests to the specified destination using the specified application key.
+    pub fn new(dispatcher: Arc<DispatcherHandle>, destination: u16, key_index: u16) -> Self {
+        Self { target: ClientTarget::new(dispatcher, destination, key_index) }
+    }
+
+    /// Handler of the client model receiving status messages published by servers.
+    pub fn status_handler() -> (ModelHandler, mpsc::Receiver<Received<SensorStatus>>) {
+        status_handler(SENSOR_CLIENT)
+    }
+
+    /// Gets the descriptor of the specified sensor or of all sensors.
+    pub async fn descriptors(&self, property_id: Option<PropertyId>) -> Result<SensorDescriptorStatus> {
+        self.target.request(&SensorDescriptorGet { property_id }).await
+    }
+
+    /// Gets the value of the specified sensor or of all sensors.
+    pub async fn get(&self, property_id: Option<PropertyId>) -> Result<SensorStatus> {
+        self.target.request(&SensorGet { property_id }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::models::access_message;
+
+    #[test]
+    fn marshalled_data() {
+        // Format A.
+        let status = SensorStatus {
+            values: vec![SensorValue { property_id: PropertyId::PRESENT_AMBIENT_TEMPERATURE, value: vec![0x2a] }],
+        };
+        assert_eq!(status.encode().unwrap(), [0x52, 0xe0, 0x09, 0x2a]);
+        assert_eq!(SensorStatus::decode(&status.encode().unwrap()).unwrap(), status);
+        assert_eq!(status.value(PropertyId::PRESENT_AMBIENT_TEMPERATURE), Some(&[0x2a][..]));
+
+        // Format B because of the property id, the length and a zero-length value.
+        let status = SensorStatus {
+            values: vec![
+                SensorValue { property_id: PropertyId(0x0800), value: vec![0x01, 0x02] },
+                SensorValue { property_id: PropertyId::PEOPLE_COUNT, value: vec![0x00; 17] },
+                SensorValue { property_id: PropertyId::MOTION_SENSED, value: vec![] },
+            ],
+        };
+        let data = status.encode().unwrap();
+        assert_eq!(data[1..6], [0x03, 0x00, 0x08, 0x01, 0x02]);
+        assert_eq!(data[6..9], [0x21, 0x4c, 0x00]);
+        assert_eq!(data[26..], [0xff, 0x42, 0x00]);
+        assert_eq!(SensorStatus::decode(&data).unwrap(), status);
+        assert_eq!(status.value(PropertyId::MOTION_SENSED), None);
+
+        assert!(SensorStatus::decode(&[0x52, 0xe2, 0x09, 0x2a]).is_err());
+    }
+
+    #[test]
+    fn descriptors() {
+        let descriptor = SensorDescriptor {
+            property_id: PropertyId::PRESENT_AMBIENT_TEMPERATURE,
+            positive_tolerance: 0x123,
+            negative_tolerance: 0x456,
+            sampling_function: 0x01,
+            measurement_period: 0x40,
+            update_interval: 0x41,
+        };
+        let status = SensorDescriptorStatus::Descriptors(vec![descriptor]);
+        assert_eq!(status.encode().unwrap(), [0x51, 0x4f, 0x00, 0x23, 0x61, 0x45, 0x01, 0x40, 0x41]);
+        assert_eq!(SensorDescriptorStatus::decode(&status.encode().unwrap()).unwrap(), status);
+        assert_eq!(
+            SensorDescriptorStatus::decode(&[0x51, 0x42, 0x00]).unwrap(),
+            SensorDescriptorStatus::NotFound(PropertyId::MOTION_SENSED)
+        );
+    }
+
+    #[test]
+    fn server() {
+        let server = SensorServer::new([SensorDescriptor::new(PropertyId::PRESENT_AMBIENT_TEMPERATURE)]);
+        server.set_value(PropertyId::PRESENT_AMBIENT_TEMPERATURE, vec![0x2a]).unwrap();
+        assert!(server.set_value(PropertyId::MOTION_SENSED, vec![0x00]).is_err());
+
+        assert_eq!(server.status_of(None).encode().unwrap(), [0x52, 0xe0, 0x09, 0x2a]);
+        assert_eq!(server.status_of(Some(PropertyId::MOTION_SENSED)).encode().unwrap(), [0x52, 0xff, 0x42, 0x00]);
+        assert_eq!(
+            server.descriptor_status(Some(PropertyId::MOTION_SENSED)),
+            SensorDescriptorStatus::NotFound(PropertyId::MOTION_SENSED)
+        );
+
+        let get = SensorGet { property_id: Some(PropertyId::PRESENT_AMBIENT_TEMPERATURE) };
+        assert_eq!(server.handle(&access_message(&get.encode().unwrap())), Some(vec![0x52, 0xe0, 0x09, 0x2a]));
+        let get = SensorDescriptorGet { property_id: Some(PropertyId::MOTION_SENSED) };
+        assert_eq!(server.handle(&access_message(&get.encode().unwrap())), Some(vec![0x51, 0x42, 0x00]));
+    }
+}
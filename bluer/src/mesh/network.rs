@@ -1,6 +1,7 @@
 //! Implement Network bluetooth mesh interface
 
 use dbus::{
+    arg::{PropMap, Variant},
     nonblock::{Proxy, SyncConnection},
     Path,
 };
@@ -19,6 +20,25 @@ use crate::{
 
 pub(crate) const INTERFACE: &str = "org.bluez.mesh.Network1";
 
+/// Flags of an imported mesh network.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImportFlags {
+    /// IV update procedure is in progress.
+    pub iv_update: bool,
+    /// Key refresh procedure is in progress.
+    pub key_refresh: bool,
+}
+
+impl ImportFlags {
+    fn to_dict(self) -> PropMap {
+        let mut pm = PropMap::new();
+        pm.insert("IvUpdate".to_string(), Variant(Box::new(self.iv_update)));
+        pm.insert("KeyRefresh".to_string(), Variant(Box::new(self.key_refresh)));
+        pm
+    }
+}
+
 /// Interface to a Bluetooth mesh network.
 ///
 /// Use [`Session::mesh`](crate::Session::mesh) to obtain an instance.
@@ -63,18 +83,85 @@ impl Network {
             }
         });
 
-        self.call_method("Join", (app_hnd.name.clone(), app_hnd.device_id.as_bytes().to_vec())).await?;
+        self.call_method::<_, ()>("Join", (app_hnd.name.clone(), app_hnd.device_id.as_bytes().to_vec())).await?;
 
-        let result = match app_hnd.join_result_rx.recv().await {
+        let result = Self::join_complete(&mut app_hnd).await;
+        let _ = done_tx.send(());
+        result.map(|()| app_hnd)
+    }
+
+    /// Cancel joining a mesh network.
+    ///
+    /// Cancels an outstanding provisioning request initiated by [join](Self::join).
+    pub async fn cancel(&self) -> Result<()> {
+        self.call_method("Cancel", ()).await
+    }
+
+    /// Create a new mesh network.
+    ///
+    /// The application becomes the first node of the new network,
+    /// with primary unicast address `0x0001` and a freshly generated
+    /// primary network key.
+    /// The application should provide a [provisioner](Application::provisioner)
+    /// to be able to add further nodes.
+    ///
+    /// The returned handle provides the [token](ApplicationHandle::token)
+    /// of the new node, which must be used to [attach](Self::attach) to the network.
+    pub async fn create_network(&self, app: Application) -> Result<ApplicationHandle> {
+        let mut app_hnd = self.application(app).await?;
+
+        self.call_method::<_, ()>("CreateNetwork", (app_hnd.name.clone(), app_hnd.device_id.as_bytes().to_vec()))
+            .await?;
+
+        Self::join_complete(&mut app_hnd).await?;
+        Ok(app_hnd)
+    }
+
+    /// Import an existing mesh node.
+    ///
+    /// Creates a self-provisioned node using the specified
+    /// device key, primary network key and its index,
+    /// IV index and primary unicast address.
+    /// This allows a node to be restored from a backup of the network configuration.
+    ///
+    /// The returned handle provides the [token](ApplicationHandle::token)
+    /// of the imported node, which must be used to [attach](Self::attach) to the network.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn import(
+        &self, app: Application, dev_key: [u8; 16], net_key: [u8; 16], net_index: u16, flags: ImportFlags,
+        iv_index: u32, unicast: u16,
+    ) -> Result<ApplicationHandle> {
+        let mut app_hnd = self.application(app).await?;
+
+        self.call_method::<_, ()>(
+            "Import",
+            (
+                app_hnd.name.clone(),
+                app_hnd.device_id.as_bytes().to_vec(),
+                dev_key.to_vec(),
+                net_key.to_vec(),
+                net_index,
+                flags.to_dict(),
+                iv_index,
+                unicast,
+            ),
+        )
+        .await?;
+
+        Self::join_complete(&mut app_hnd).await?;
+        Ok(app_hnd)
+    }
+
+    /// Wait for the node token assigned by the daemon.
+    async fn join_complete(app_hnd: &mut ApplicationHandle) -> Result<()> {
+        match app_hnd.join_result_rx.recv().await {
             Some(Ok(token)) => {
                 app_hnd.token = Some(token);
-                Ok(app_hnd)
+                Ok(())
             }
             Some(Err(reason)) => Err(reason.into()),
             None => Err(Error::new(ErrorKind::Failed)),
-        };
-        let _ = done_tx.send(());
-        result
+        }
     }
 
     /// Attach to mesh network.
@@ -57,7 +57,31 @@ impl Node {
             &options,
             data
         );
-        self.call_method("Publish", (path, model_id, options, data.to_vec())).await?;
+        self.call_method::<_, ()>("Publish", (path, model_id, options, data.to_vec())).await?;
+
+        Ok(())
+    }
+
+    /// Send a publication originated by a local vendor model.
+    ///
+    /// Like [publish](Self::publish), but for the vendor model `model_id`
+    /// of the company `company_id`.
+    pub async fn vendor_publish(
+        &self, element_ref: &ElementRef, company_id: u16, model_id: u16, data: &[u8],
+    ) -> Result<()> {
+        let path = element_ref.path()?;
+        let mut options: HashMap<&'static str, Variant<Box<dyn RefArg>>> = HashMap::new();
+        options.insert("Vendor", Variant(Box::new(company_id)));
+
+        log::trace!(
+            "Publishing vendor message: path={:?} company_id={:?} model_id={:?} options={:?} data={:?}",
+            &path,
+            company_id,
+            model_id,
+            &options,
+            data
+        );
+        self.call_method::<_, ()>("Publish", (path, model_id, options, data.to_vec())).await?;
 
         Ok(())
     }
@@ -77,7 +101,7 @@ impl Node {
             &options,
             data
         );
-        self.call_method("Send", (path, destination, key_index, options, data.to_vec())).await?;
+        self.call_method::<_, ()>("Send", (path, destination, key_index, options, data.to_vec())).await?;
 
         Ok(())
     }
@@ -99,7 +123,8 @@ impl Node {
             &options,
             data
         );
-        self.call_method("DevKeySend", (path, destination, remote, net_index, options, data.to_vec())).await?;
+        self.call_method::<_, ()>("DevKeySend", (path, destination, remote, net_index, options, data.to_vec()))
+            .await?;
 
         Ok(())
     }
@@ -118,7 +143,7 @@ impl Node {
             net_index,
             update
         );
-        self.call_method("AddAppKey", (path, destination, app_key, net_index, update)).await?;
+        self.call_method::<_, ()>("AddAppKey", (path, destination, app_key, net_index, update)).await?;
 
         Ok(())
     }
@@ -9,7 +9,7 @@ use uuid::Uuid;
 use super::application::RegisteredApplication;
 use crate::{
     mesh::{
-        management::{AddNodeFailedReason, NodeAdded},
+        management::{AddNodeFailedReason, NodeAdded, UnprovisionedDevice},
         ReqError, PATH, SERVICE_NAME, TIMEOUT,
     },
     method_call, SessionInner,
@@ -49,6 +49,19 @@ impl RegisteredProvisioner {
 
     pub(crate) fn register_interface(cr: &mut Crossroads) -> IfaceToken<Arc<RegisteredApplication>> {
         cr.register(INTERFACE, |ib: &mut IfaceBuilder<Arc<RegisteredApplication>>| {
+            ib.method_with_cr_async(
+                "ScanResult",
+                ("rssi", "data", "options"),
+                (),
+                |ctx, cr, (rssi, data, _options): (i16, Vec<u8>, dbus::arg::PropMap)| {
+                    method_call(ctx, cr, move |reg: Arc<RegisteredApplication>| async move {
+                        let dev = UnprovisionedDevice::from_scan_result(rssi, &data).ok_or(ReqError::Failed)?;
+                        let _ = reg.scan_result_tx.send(dev);
+                        Ok(())
+                    })
+                },
+            );
+
             ib.method_with_cr_async(
                 "AddNodeComplete",
                 ("uuid", "unicast", "count"),
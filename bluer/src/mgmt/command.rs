@@ -0,0 +1,431 @@
+//! Management commands and their return parameters.
+
+use num_derive::FromPrimitive;
+use std::io::{Error, ErrorKind, Result};
+
+use super::Message;
+use crate::{
+    hci_util::{put_address, Reader},
+    Address, AddressType,
+};
+
+/// Management command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Command {
+    /// Read management version information.
+    ReadVersion,
+    /// Read controller index list.
+    ReadIndexList,
+    /// Read controller information.
+    ReadInfo,
+    /// Set powered.
+    SetPowered(bool),
+    /// Enable or disable Bluetooth Low Energy.
+    SetLowEnergy(bool),
+    /// Enable or disable classic Bluetooth (BR/EDR).
+    SetBrEdr(bool),
+    /// Set static random address.
+    SetStaticAddress(Address),
+    /// Set privacy.
+    SetPrivacy {
+        /// Privacy mode.
+        privacy: Privacy,
+        /// Local identity resolving key.
+        irk: [u8; 16],
+    },
+    /// Load long term keys.
+    LoadLongTermKeys(Vec<LongTermKey>),
+    /// Load connection parameters.
+    LoadConnParams(Vec<ConnParams>),
+    /// Get PHY configuration.
+    GetPhyConfiguration,
+    /// Set selected PHYs.
+    SetPhyConfiguration(u32),
+    /// Read controller capabilities.
+    ReadControllerCapabilities,
+}
+
+impl Command {
+    /// Command opcode.
+    pub fn opcode(&self) -> u16 {
+        match self {
+            Self::ReadVersion => 0x0001,
+            Self::ReadIndexList => 0x0003,
+            Self::ReadInfo => 0x0004,
+            Self::SetPowered(_) => 0x0005,
+            Self::SetLowEnergy(_) => 0x000d,
+            Self::LoadLongTermKeys(_) => 0x0013,
+            Self::SetBrEdr(_) => 0x002a,
+            Self::SetStaticAddress(_) => 0x002b,
+            Self::SetPrivacy { .. } => 0x0033,
+            Self::LoadConnParams(_) => 0x0035,
+            Self::GetPhyConfiguration => 0x0044,
+            Self::SetPhyConfiguration(_) => 0x0045,
+            Self::ReadControllerCapabilities => 0x0048,
+        }
+    }
+
+    /// Encodes the command parameters.
+    pub fn encode_params(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        match self {
+            Self::ReadVersion
+            | Self::ReadIndexList
+            | Self::ReadInfo
+            | Self::GetPhyConfiguration
+            | Self::ReadControllerCapabilities => (),
+            Self::SetPowered(v) | Self::SetLowEnergy(v) | Self::SetBrEdr(v) => buf.push(*v as u8),
+            Self::SetStaticAddress(addr) => put_address(&mut buf, *addr),
+            Self::SetPrivacy { privacy, irk } => {
+                buf.push(*privacy as u8);
+                buf.extend_from_slice(irk);
+            }
+            Self::LoadLongTermKeys(keys) => {
+                let count = u16::try_from(keys.len())
+                    .map_err(|_| Error::new(ErrorKind::InvalidInput, "too many long term keys"))?;
+                buf.extend_from_slice(&count.to_le_bytes());
+                for key in keys {
+                    key.encode(&mut buf);
+                }
+            }
+            Self::LoadConnParams(params) => {
+                let count = u16::try_from(params.len())
+                    .map_err(|_| Error::new(ErrorKind::InvalidInput, "too many connection parameters"))?;
+                buf.extend_from_slice(&count.to_le_bytes());
+                for param in params {
+                    param.encode(&mut buf);
+                }
+            }
+            Self::SetPhyConfiguration(phys) => buf.extend_from_slice(&phys.to_le_bytes()),
+        }
+        Ok(buf)
+    }
+
+    /// Converts the command into a message for the controller with the specified index.
+    pub fn to_message(&self, index: u16) -> Result<Message> {
+        Ok(Message { code: self.opcode(), index, params: self.encode_params()? })
+    }
+}
+
+/// LE privacy mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, FromPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum Privacy {
+    /// Privacy disabled.
+    #[default]
+    Disabled = 0x00,
+    /// Privacy enabled.
+    Enabled = 0x01,
+    /// Limited privacy, using the identity address when discoverable.
+    Limited = 0x02,
+}
+
+/// Type of a long term key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, FromPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum LongTermKeyType {
+    /// Unauthenticated key from legacy pairing.
+    #[default]
+    Unauthenticated = 0x00,
+    /// Authenticated key from legacy pairing.
+    Authenticated = 0x01,
+    /// Unauthenticated key from LE Secure Connections pairing.
+    UnauthenticatedP256 = 0x02,
+    /// Authenticated key from LE Secure Connections pairing.
+    AuthenticatedP256 = 0x03,
+    /// Debug key from LE Secure Connections pairing.
+    DebugP256 = 0x04,
+}
+
+/// Long term key for LE encryption.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LongTermKey {
+    /// Address of remote device.
+    pub address: Address,
+    /// Address type of remote device.
+    pub address_type: AddressType,
+    /// Key type.
+    pub key_type: LongTermKeyType,
+    /// Whether the key is used when the local device is central.
+    pub central: bool,
+    /// Encryption key size.
+    pub enc_size: u8,
+    /// Encrypted diversifier.
+    pub ediv: u16,
+    /// Random number.
+    pub rand: u64,
+    /// Key value.
+    pub value: [u8; 16],
+}
+
+impl LongTermKey {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        put_address(buf, self.address);
+        buf.push(self.address_type as u8);
+        buf.push(self.key_type as u8);
+        buf.push(self.central as u8);
+        buf.push(self.enc_size);
+        buf.extend_from_slice(&self.ediv.to_le_bytes());
+        buf.extend_from_slice(&self.rand.to_le_bytes());
+        buf.extend_from_slice(&self.value);
+    }
+
+    pub(crate) fn decode(r: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            address: r.address()?,
+            address_type: r.address_type()?,
+            key_type: num_traits::FromPrimitive::from_u8(r.u8()?)
+                .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid long term key type"))?,
+            central: r.u8()? != 0,
+            enc_size: r.u8()?,
+            ediv: r.u16()?,
+            rand: r.u64()?,
+            value: r.array()?,
+        })
+    }
+}
+
+/// Preferred LE connection parameters of a remote device.
+///
+/// Intervals are specified in units of 1.25 ms and the supervision timeout
+/// in units of 10 ms.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConnParams {
+    /// Address of remote device.
+    pub address: Address,
+    /// Address type of remote device.
+    pub address_type: AddressType,
+    /// Minimum connection interval.
+    pub min_interval: u16,
+    /// Maximum connection interval.
+    pub max_interval: u16,
+    /// Peripheral latency in number of connection events.
+    pub latency: u16,
+    /// Supervision timeout.
+    pub timeout: u16,
+}
+
+impl ConnParams {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        put_address(buf, self.address);
+        buf.push(self.address_type as u8);
+        buf.extend_from_slice(&self.min_interval.to_le_bytes());
+        buf.extend_from_slice(&self.max_interval.to_le_bytes());
+        buf.extend_from_slice(&self.latency.to_le_bytes());
+        buf.extend_from_slice(&self.timeout.to_le_bytes());
+    }
+}
+
+/// Management interface version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Version {
+    /// Version.
+    pub version: u8,
+    /// Revision.
+    pub revision: u16,
+}
+
+impl Version {
+    pub(crate) fn decode(params: &[u8]) -> Result<Self> {
+        let mut r = Reader::new(params);
+        let this = Self { version: r.u8()?, revision: r.u16()? };
+        r.finish()?;
+        Ok(this)
+    }
+}
+
+/// Controller information.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControllerInfo {
+    /// Public address.
+    pub address: Address,
+    /// Bluetooth version.
+    pub bluetooth_version: u8,
+    /// Manufacturer id.
+    pub manufacturer: u16,
+    /// Supported [settings](super::settings).
+    pub supported_settings: u32,
+    /// Current [settings](super::settings).
+    pub current_settings: u32,
+    /// Class of device.
+    pub class: u32,
+    /// Name.
+    pub name: String,
+    /// Short name.
+    pub short_name: String,
+}
+
+impl ControllerInfo {
+    pub(crate) fn decode(params: &[u8]) -> Result<Self> {
+        let mut r = Reader::new(params);
+        let this = Self {
+            address: r.address()?,
+            bluetooth_version: r.u8()?,
+            manufacturer: r.u16()?,
+            supported_settings: r.u32()?,
+            current_settings: r.u32()?,
+            class: r.u24()?,
+            name: r.string(NAME_LEN)?,
+            short_name: r.string(SHORT_NAME_LEN)?,
+        };
+        r.finish()?;
+        Ok(this)
+    }
+}
+
+/// Length of name field.
+pub(crate) const NAME_LEN: usize = 249;
+
+/// Length of short name field.
+pub(crate) const SHORT_NAME_LEN: usize = 11;
+
+/// PHY configuration of a controller.
+///
+/// Possible values are defined in the [phy](super::phy) module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhyConfiguration {
+    /// Supported PHYs.
+    pub supported_phys: u32,
+    /// PHYs that can be configured.
+    pub configurable_phys: u32,
+    /// Selected PHYs.
+    pub selected_phys: u32,
+}
+
+impl PhyConfiguration {
+    pub(crate) fn decode(params: &[u8]) -> Result<Self> {
+        let mut r = Reader::new(params);
+        let this = Self { supported_phys: r.u32()?, configurable_phys: r.u32()?, selected_phys: r.u32()? };
+        r.finish()?;
+        Ok(this)
+    }
+}
+
+pub(crate) fn decode_index_list(params: &[u8]) -> Result<Vec<u16>> {
+    let mut r = Reader::new(params);
+    let count = r.u16()?;
+    let indices = (0..count).map(|_| r.u16()).collect::<Result<_>>()?;
+    r.finish()?;
+    Ok(indices)
+}
+
+pub(crate) fn decode_settings(params: &[u8]) -> Result<u32> {
+    let mut r = Reader::new(params);
+    let settings = r.u32()?;
+    r.finish()?;
+    Ok(settings)
+}
+
+pub(crate) fn decode_capabilities(params: &[u8]) -> Result<Vec<u8>> {
+    let mut r = Reader::new(params);
+    let len = r.u16()?;
+    let caps = r.bytes(len.into())?.to_vec();
+    r.finish()?;
+    Ok(caps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_set_powered() {
+        let msg = Command::SetPowered(true).to_message(0).unwrap();
+        assert_eq!(msg.encode().unwrap(), [0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01]);
+    }
+
+    #[test]
+    fn encode_set_static_address() {
+        let params = Command::SetStaticAddress(Address::new([0xc0, 0x11, 0x22, 0x33, 0x44, 0x55]))
+            .encode_params()
+            .unwrap();
+        assert_eq!(params, [0x55, 0x44, 0x33, 0x22, 0x11, 0xc0]);
+    }
+
+    #[test]
+    fn encode_load_long_term_keys() {
+        let key = LongTermKey {
+            address: Address::new([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]),
+            address_type: AddressType::LeRandom,
+            key_type: LongTermKeyType::AuthenticatedP256,
+            central: true,
+            enc_size: 16,
+            ediv: 0x1234,
+            rand: 0x0102030405060708,
+            value: [0xaa; 16],
+        };
+        let params = Command::LoadLongTermKeys(vec![key.clone()]).encode_params().unwrap();
+        assert_eq!(params.len(), 2 + 36);
+        assert_eq!(&params[..2], [0x01, 0x00]);
+        assert_eq!(&params[2..12], [0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x02, 0x03, 0x01, 0x10]);
+
+        let mut r = Reader::new(&params[2..]);
+        assert_eq!(LongTermKey::decode(&mut r).unwrap(), key);
+        r.finish().unwrap();
+    }
+
+    #[test]
+    fn encode_too_many_entries() {
+        let keys = vec![LongTermKey::default(); 0x10000];
+        assert_eq!(Command::LoadLongTermKeys(keys).encode_params().unwrap_err().kind(), ErrorKind::InvalidInput);
+        let params = vec![ConnParams::default(); 0x10000];
+        assert_eq!(Command::LoadConnParams(params).encode_params().unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn encode_load_conn_params() {
+        let params = Command::LoadConnParams(vec![ConnParams {
+            address: Address::new([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]),
+            address_type: AddressType::LePublic,
+            min_interval: 0x0018,
+            max_interval: 0x0028,
+            latency: 0,
+            timeout: 0x002a,
+        }])
+        .encode_params()
+        .unwrap();
+        assert_eq!(
+            params,
+            [
+                0x01, 0x00, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x01, 0x18, 0x00, 0x28, 0x00, 0x00, 0x00, 0x2a,
+                0x00
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_controller_info() {
+        let mut params = vec![0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x0b, 0x02, 0x00];
+        params.extend_from_slice(&0x0001_ffffu32.to_le_bytes());
+        params.extend_from_slice(&0x0000_0281u32.to_le_bytes());
+        params.extend_from_slice(&[0x0c, 0x01, 0x1c]);
+        let mut name = b"test".to_vec();
+        name.resize(NAME_LEN, 0);
+        params.extend_from_slice(&name);
+        params.extend_from_slice(&[0; SHORT_NAME_LEN]);
+
+        let info = ControllerInfo::decode(&params).unwrap();
+        assert_eq!(info.address, Address::new([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]));
+        assert_eq!(info.bluetooth_version, 0x0b);
+        assert_eq!(info.manufacturer, 2);
+        assert_eq!(info.current_settings, super::super::settings::POWERED | 0x280);
+        assert_eq!(info.class, 0x1c010c);
+        assert_eq!(info.name, "test");
+        assert_eq!(info.short_name, "");
+    }
+
+    #[test]
+    fn decode_truncated() {
+        assert_eq!(Version::decode(&[0x01, 0x16]).unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(decode_index_list(&[0x02, 0x00, 0x00, 0x00]).unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(decode_index_list(&[0x02, 0x00, 0x00, 0x00, 0x01, 0x00]).unwrap(), [0, 1]);
+    }
+}
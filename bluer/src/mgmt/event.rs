@@ -0,0 +1,186 @@
+//! Management events.
+
+use std::io::Result;
+
+use super::{
+    command::{LongTermKey, NAME_LEN, SHORT_NAME_LEN},
+    Status,
+};
+use crate::{hci_util::Reader, Address, AddressType};
+
+/// Management event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    /// A command has completed.
+    CommandComplete {
+        /// Command opcode.
+        opcode: u16,
+        /// Command status.
+        status: Status,
+        /// Return parameters.
+        params: Vec<u8>,
+    },
+    /// A command has failed or is pending.
+    CommandStatus {
+        /// Command opcode.
+        opcode: u16,
+        /// Command status.
+        status: Status,
+    },
+    /// A controller error occurred.
+    ControllerError {
+        /// Error code.
+        code: u8,
+    },
+    /// A controller was added.
+    IndexAdded,
+    /// A controller was removed.
+    IndexRemoved,
+    /// Controller [settings](super::settings) have changed.
+    NewSettings(u32),
+    /// Class of device has changed.
+    ClassOfDeviceChanged(u32),
+    /// Local name has changed.
+    LocalNameChanged {
+        /// Name.
+        name: String,
+        /// Short name.
+        short_name: String,
+    },
+    /// A new long term key was created.
+    NewLongTermKey {
+        /// Whether the key should be stored persistently.
+        store_hint: bool,
+        /// Key.
+        key: LongTermKey,
+    },
+    /// A remote device has connected.
+    DeviceConnected {
+        /// Address of remote device.
+        address: Address,
+        /// Address type of remote device.
+        address_type: AddressType,
+        /// Connection flags.
+        flags: u32,
+        /// Extended inquiry response or advertising data.
+        eir: Vec<u8>,
+    },
+    /// A remote device has disconnected.
+    DeviceDisconnected {
+        /// Address of remote device.
+        address: Address,
+        /// Address type of remote device.
+        address_type: AddressType,
+        /// Disconnection reason.
+        reason: u8,
+    },
+    /// Selected [PHYs](super::phy) have changed.
+    PhyConfigurationChanged(u32),
+    /// Event not decoded by this library.
+    Unknown {
+        /// Event code.
+        code: u16,
+        /// Raw parameters.
+        params: Vec<u8>,
+    },
+}
+
+impl Event {
+    /// Decodes the event with the specified code from its parameters.
+    pub fn decode(code: u16, params: &[u8]) -> Result<Self> {
+        let mut r = Reader::new(params);
+        let event = match code {
+            0x0001 => {
+                Self::CommandComplete { opcode: r.u16()?, status: r.u8()?.into(), params: r.rest().to_vec() }
+            }
+            0x0002 => Self::CommandStatus { opcode: r.u16()?, status: r.u8()?.into() },
+            0x0003 => Self::ControllerError { code: r.u8()? },
+            0x0004 => Self::IndexAdded,
+            0x0005 => Self::IndexRemoved,
+            0x0006 => Self::NewSettings(r.u32()?),
+            0x0007 => Self::ClassOfDeviceChanged(r.u24()?),
+            0x0008 => Self::LocalNameChanged { name: r.string(NAME_LEN)?, short_name: r.string(SHORT_NAME_LEN)? },
+            0x000a => Self::NewLongTermKey { store_hint: r.u8()? != 0, key: LongTermKey::decode(&mut r)? },
+            0x000b => {
+                let address = r.address()?;
+                let address_type = r.address_type()?;
+                let flags = r.u32()?;
+                let eir_len = r.u16()?;
+                let eir = r.bytes(eir_len.into())?.to_vec();
+                Self::DeviceConnected { address, address_type, flags, eir }
+            }
+            0x000c => Self::DeviceDisconnected {
+                address: r.address()?,
+                address_type: r.address_type()?,
+                reason: r.u8()?,
+            },
+            0x0026 => Self::PhyConfigurationChanged(r.u32()?),
+            code => Self::Unknown { code, params: r.rest().to_vec() },
+        };
+        r.finish()?;
+        Ok(event)
+    }
+}
+
+/// Management event together with the index of the controller it originates from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ControllerEvent {
+    /// Controller index or [INDEX_NONE](super::INDEX_NONE).
+    pub index: u16,
+    /// Event.
+    pub event: Event,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mgmt::Message;
+
+    #[test]
+    fn decode_command_complete() {
+        let msg =
+            Message::decode(&[0x01, 0x00, 0xff, 0xff, 0x06, 0x00, 0x01, 0x00, 0x00, 0x01, 0x16, 0x00]).unwrap();
+        assert_eq!(msg.index, crate::mgmt::INDEX_NONE);
+        assert_eq!(
+            Event::decode(msg.code, &msg.params).unwrap(),
+            Event::CommandComplete { opcode: 0x0001, status: Status::Success, params: vec![0x01, 0x16, 0x00] }
+        );
+    }
+
+    #[test]
+    fn decode_command_status() {
+        assert_eq!(
+            Event::decode(0x0002, &[0x2b, 0x00, 0x0a]).unwrap(),
+            Event::CommandStatus { opcode: 0x002b, status: Status::Busy }
+        );
+        assert_eq!(
+            Event::decode(0x0002, &[0x2b, 0x00, 0x7f]).unwrap(),
+            Event::CommandStatus { opcode: 0x002b, status: Status::Other(0x7f) }
+        );
+    }
+
+    #[test]
+    fn decode_device_connected() {
+        let params =
+            [0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x02, 0x01, 0x06];
+        assert_eq!(
+            Event::decode(0x000b, &params).unwrap(),
+            Event::DeviceConnected {
+                address: Address::new([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]),
+                address_type: AddressType::LePublic,
+                flags: 0,
+                eir: vec![0x02, 0x01, 0x06],
+            }
+        );
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert!(Event::decode(0x0006, &[0x01, 0x02]).is_err());
+        assert!(Event::decode(0x0004, &[0x00]).is_err());
+        assert!(Event::decode(0x000c, &[0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x05, 0x00]).is_err());
+        assert!(Message::decode(&[0x06, 0x00, 0x00, 0x00, 0x04, 0x00, 0x01]).is_err());
+        assert_eq!(Event::decode(0x00ff, &[0x01]).unwrap(), Event::Unknown { code: 0x00ff, params: vec![0x01] });
+    }
+}
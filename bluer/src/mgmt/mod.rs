@@ -0,0 +1,545 @@
+//! Bluetooth management (mgmt) interface of the Linux kernel.
+//!
+//! The management interface provides access to controller configuration that is not
+//! available through the Bluetooth daemon, such as static addresses, privacy,
+//! LE-only mode, connection parameters, default PHYs and key loading.
+//!
+//! Use a [Client] to send [commands](Command) to the kernel and receive [events](Event).
+//! The low-level [Socket] and [Message] types allow to exchange raw management messages.
+//!
+//! Opening the management socket requires the `CAP_NET_ADMIN` capability.
+//! Note that modifying controller configuration may interfere with the operation of
+//! the Bluetooth daemon, which uses the same interface.
+//!
+//! No [Session](crate::Session) and therefore no running Bluetooth daemon is required.
+//!
+
+use futures::Stream;
+use libc::{AF_BLUETOOTH, SOCK_RAW};
+use std::{
+    fmt,
+    io::{Error, ErrorKind, Result},
+    os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
+    sync::Arc,
+};
+use tokio::{io::unix::AsyncFd, task::JoinHandle};
+
+use crate::{
+    hci_util::{self, CommandDispatcher, HciSocketAddr, Reader},
+    sock::{self, OwnedFd},
+    sys::{BTPROTO_HCI, HCI_CHANNEL_CONTROL, HCI_DEV_NONE},
+    Address,
+};
+
+mod command;
+mod event;
+
+pub use command::{
+    Command, ConnParams, ControllerInfo, LongTermKey, LongTermKeyType, PhyConfiguration, Privacy, Version,
+};
+pub use event::{ControllerEvent, Event};
+
+/// Controller index for commands and events that are not related to a controller.
+pub const INDEX_NONE: u16 = HCI_DEV_NONE;
+
+/// Possible bit values for controller settings.
+pub mod settings {
+    /// Controller is powered.
+    pub const POWERED: u32 = 1 << 0;
+    /// Controller is connectable.
+    pub const CONNECTABLE: u32 = 1 << 1;
+    /// Fast connectable mode.
+    pub const FAST_CONNECTABLE: u32 = 1 << 2;
+    /// Controller is discoverable.
+    pub const DISCOVERABLE: u32 = 1 << 3;
+    /// Controller is bondable.
+    pub const BONDABLE: u32 = 1 << 4;
+    /// Link level security.
+    pub const LINK_SECURITY: u32 = 1 << 5;
+    /// Secure Simple Pairing.
+    pub const SSP: u32 = 1 << 6;
+    /// Classic Bluetooth (BR/EDR) is enabled.
+    pub const BREDR: u32 = 1 << 7;
+    /// High Speed.
+    pub const HS: u32 = 1 << 8;
+    /// Bluetooth Low Energy is enabled.
+    pub const LE: u32 = 1 << 9;
+    /// Controller is advertising.
+    pub const ADVERTISING: u32 = 1 << 10;
+    /// Secure Connections.
+    pub const SECURE_CONN: u32 = 1 << 11;
+    /// Debug keys.
+    pub const DEBUG_KEYS: u32 = 1 << 12;
+    /// LE privacy.
+    pub const PRIVACY: u32 = 1 << 13;
+    /// Controller configuration.
+    pub const CONFIGURATION: u32 = 1 << 14;
+    /// Static address.
+    pub const STATIC_ADDRESS: u32 = 1 << 15;
+    /// PHY configuration.
+    pub const PHY_CONFIGURATION: u32 = 1 << 16;
+    /// Wideband speech.
+    pub const WIDEBAND_SPEECH: u32 = 1 << 17;
+}
+
+/// Possible bit values for [PHY configuration](PhyConfiguration).
+pub mod phy {
+    /// BR 1M 1-slot.
+    pub const BR_1M_1SLOT: u32 = 1 << 0;
+    /// BR 1M 3-slot.
+    pub const BR_1M_3SLOT: u32 = 1 << 1;
+    /// BR 1M 5-slot.
+    pub const BR_1M_5SLOT: u32 = 1 << 2;
+    /// EDR 2M 1-slot.
+    pub const EDR_2M_1SLOT: u32 = 1 << 3;
+    /// EDR 2M 3-slot.
+    pub const EDR_2M_3SLOT: u32 = 1 << 4;
+    /// EDR 2M 5-slot.
+    pub const EDR_2M_5SLOT: u32 = 1 << 5;
+    /// EDR 3M 1-slot.
+    pub const EDR_3M_1SLOT: u32 = 1 << 6;
+    /// EDR 3M 3-slot.
+    pub const EDR_3M_3SLOT: u32 = 1 << 7;
+    /// EDR 3M 5-slot.
+    pub const EDR_3M_5SLOT: u32 = 1 << 8;
+    /// LE 1M transmit.
+    pub const LE_1M_TX: u32 = 1 << 9;
+    /// LE 1M receive.
+    pub const LE_1M_RX: u32 = 1 << 10;
+    /// LE 2M transmit.
+    pub const LE_2M_TX: u32 = 1 << 11;
+    /// LE 2M receive.
+    pub const LE_2M_RX: u32 = 1 << 12;
+    /// LE Coded transmit.
+    pub const LE_CODED_TX: u32 = 1 << 13;
+    /// LE Coded receive.
+    pub const LE_CODED_RX: u32 = 1 << 14;
+}
+
+/// Status of a management command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Status {
+    /// Success.
+    Success,
+    /// Unknown command.
+    UnknownCommand,
+    /// Not connected.
+    NotConnected,
+    /// Failed.
+    Failed,
+    /// Connect failed.
+    ConnectFailed,
+    /// Authentication failed.
+    AuthenticationFailed,
+    /// Not paired.
+    NotPaired,
+    /// No resources.
+    NoResources,
+    /// Timeout.
+    Timeout,
+    /// Already connected.
+    AlreadyConnected,
+    /// Busy.
+    Busy,
+    /// Rejected.
+    Rejected,
+    /// Not supported.
+    NotSupported,
+    /// Invalid parameters.
+    InvalidParameters,
+    /// Disconnected.
+    Disconnected,
+    /// Not powered.
+    NotPowered,
+    /// Cancelled.
+    Cancelled,
+    /// Invalid index.
+    InvalidIndex,
+    /// Blocked through rfkill.
+    RfKilled,
+    /// Already paired.
+    AlreadyPaired,
+    /// Permission denied.
+    PermissionDenied,
+    /// Other status code.
+    Other(u8),
+}
+
+impl From<u8> for Status {
+    fn from(value: u8) -> Self {
+        match value {
+            0x00 => Self::Success,
+            0x01 => Self::UnknownCommand,
+            0x02 => Self::NotConnected,
+            0x03 => Self::Failed,
+            0x04 => Self::ConnectFailed,
+            0x05 => Self::AuthenticationFailed,
+            0x06 => Self::NotPaired,
+            0x07 => Self::NoResources,
+            0x08 => Self::Timeout,
+            0x09 => Self::AlreadyConnected,
+            0x0a => Self::Busy,
+            0x0b => Self::Rejected,
+            0x0c => Self::NotSupported,
+            0x0d => Self::InvalidParameters,
+            0x0e => Self::Disconnected,
+            0x0f => Self::NotPowered,
+            0x10 => Self::Cancelled,
+            0x11 => Self::InvalidIndex,
+            0x12 => Self::RfKilled,
+            0x13 => Self::AlreadyPaired,
+            0x14 => Self::PermissionDenied,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Other(code) => write!(f, "management command failed with status 0x{code:02x}"),
+            status => write!(f, "management command failed: {status:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Status {}
+
+impl From<Status> for Error {
+    fn from(status: Status) -> Self {
+        let kind = match status {
+            Status::UnknownCommand | Status::NotSupported => ErrorKind::Unsupported,
+            Status::InvalidParameters | Status::InvalidIndex => ErrorKind::InvalidInput,
+            Status::Timeout => ErrorKind::TimedOut,
+            Status::PermissionDenied => ErrorKind::PermissionDenied,
+            _ => ErrorKind::Other,
+        };
+        Error::new(kind, status)
+    }
+}
+
+/// A raw management message, i.e. a command or an event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    /// Command opcode or event code.
+    pub code: u16,
+    /// Controller index or [INDEX_NONE].
+    pub index: u16,
+    /// Parameters.
+    pub params: Vec<u8>,
+}
+
+impl Message {
+    /// Length of message header.
+    pub const HEADER_LEN: usize = 6;
+
+    /// Encodes the message including its header.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let len: u16 = self
+            .params
+            .len()
+            .try_into()
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, "parameters too long"))?;
+        let mut buf = Vec::with_capacity(Self::HEADER_LEN + self.params.len());
+        buf.extend_from_slice(&self.code.to_le_bytes());
+        buf.extend_from_slice(&self.index.to_le_bytes());
+        buf.extend_from_slice(&len.to_le_bytes());
+        buf.extend_from_slice(&self.params);
+        Ok(buf)
+    }
+
+    /// Decodes a message including its header.
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        let mut r = Reader::new(buf);
+        let code = r.u16()?;
+        let index = r.u16()?;
+        let len = r.u16()?;
+        let params = r.bytes(len.into())?.to_vec();
+        r.finish()?;
+        Ok(Self { code, index, params })
+    }
+}
+
+/// A socket bound to the management control channel.
+///
+/// Each sent or received datagram contains one [Message].
+pub struct Socket {
+    fd: AsyncFd<OwnedFd>,
+}
+
+impl fmt::Debug for Socket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Socket").field("fd", &self.fd.as_raw_fd()).finish()
+    }
+}
+
+impl Socket {
+    /// Maximum size of a management message.
+    const MAX_MESSAGE_LEN: usize = Message::HEADER_LEN + u16::MAX as usize;
+
+    /// Opens the management control channel.
+    ///
+    /// This requires the `CAP_NET_ADMIN` capability.
+    pub fn new() -> Result<Self> {
+        let fd = sock::socket(AF_BLUETOOTH, SOCK_RAW, BTPROTO_HCI)?;
+        sock::bind(&fd, HciSocketAddr { dev: HCI_DEV_NONE, channel: HCI_CHANNEL_CONTROL })?;
+        Ok(Self { fd: AsyncFd::new(fd)? })
+    }
+
+    /// Sends a message.
+    pub async fn send(&self, msg: &Message) -> Result<()> {
+        hci_util::send_datagram(&self.fd, &msg.encode()?).await
+    }
+
+    /// Receives a message.
+    pub async fn recv(&self) -> Result<Message> {
+        let buf = hci_util::recv_datagram(&self.fd, Self::MAX_MESSAGE_LEN).await?;
+        Message::decode(&buf)
+    }
+
+    /// Constructs a new [Socket] from the given raw file descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// This function *consumes ownership* of the specified file descriptor.
+    /// The returned object will take responsibility for closing it when the object goes out of scope.
+    ///
+    /// # Safety
+    /// If the passed file descriptor is invalid, undefined behavior may occur.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(OwnedFd::new(fd))? })
+    }
+}
+
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for Socket {
+    fn into_raw_fd(self) -> RawFd {
+        self.fd.into_inner().into_raw_fd()
+    }
+}
+
+impl FromRawFd for Socket {
+    /// Constructs a new instance of `Self` from the given raw file
+    /// descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// # Panics
+    /// Panics when the conversion fails.
+    /// Use [Socket::from_raw_fd] for a non-panicking variant.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_raw_fd(fd).expect("from_raw_fd failed")
+    }
+}
+
+struct ClientInner {
+    socket: Socket,
+    /// Commands are identified by their opcode and controller index.
+    commands: CommandDispatcher<(u16, u16), ControllerEvent>,
+}
+
+impl ClientInner {
+    async fn recv_task(self: Arc<Self>) {
+        loop {
+            let msg = match self.socket.recv().await {
+                Ok(msg) => msg,
+                Err(err) => {
+                    log::warn!("Receiving management message failed: {}", &err);
+                    self.commands.fail(err);
+                    break;
+                }
+            };
+
+            let event = match Event::decode(msg.code, &msg.params) {
+                Ok(event) => event,
+                Err(err) => {
+                    log::warn!("Invalid management event 0x{:04x}: {}", msg.code, &err);
+                    continue;
+                }
+            };
+            log::trace!("Management event on index {}: {:?}", msg.index, &event);
+
+            match &event {
+                Event::CommandComplete { opcode, status, params } => {
+                    let result = match status {
+                        Status::Success => Ok(params.clone()),
+                        status => Err((*status).into()),
+                    };
+                    self.commands.complete((*opcode, msg.index), result);
+                }
+                Event::CommandStatus { opcode, status } if *status != Status::Success => {
+                    self.commands.complete((*opcode, msg.index), Err((*status).into()));
+                }
+                _ => (),
+            }
+
+            self.commands.publish(ControllerEvent { index: msg.index, event });
+        }
+    }
+}
+
+/// Client of the Bluetooth management interface.
+///
+/// Commands are sent one at a time; concurrent calls are queued.
+pub struct Client {
+    inner: Arc<ClientInner>,
+    recv_task: JoinHandle<()>,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Client").field("socket", &self.inner.socket).finish()
+    }
+}
+
+impl Client {
+    /// Opens the management control channel and starts receiving events.
+    ///
+    /// This requires the `CAP_NET_ADMIN` capability.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn new() -> Result<Self> {
+        Ok(Self::from_socket(Socket::new()?))
+    }
+
+    /// Creates a client using the specified management socket.
+    pub fn from_socket(socket: Socket) -> Self {
+        let inner = Arc::new(ClientInner { socket, commands: CommandDispatcher::new() });
+        let recv_task = tokio::spawn(inner.clone().recv_task());
+        Self { inner, recv_task }
+    }
+
+    /// Sends a command to the controller with the specified index and waits for its completion.
+    ///
+    /// Use [INDEX_NONE] for commands not related to a controller.
+    /// Returns the raw return parameters of the command.
+    pub async fn send_command(&self, index: u16, command: &Command) -> Result<Vec<u8>> {
+        let msg = command.to_message(index)?;
+        let pending = self.inner.commands.begin((msg.code, index)).await;
+
+        log::trace!("Management command on index {}: {:?}", index, command);
+        self.inner.socket.send(&msg).await?;
+
+        pending.reply("management socket closed").await
+    }
+
+    /// Stream of management events.
+    ///
+    /// This includes replies to commands sent by any client.
+    pub fn events(&self) -> impl Stream<Item = ControllerEvent> {
+        self.inner.commands.subscribe()
+    }
+
+    /// Reads the version of the management interface.
+    pub async fn read_version(&self) -> Result<Version> {
+        let params = self.send_command(INDEX_NONE, &Command::ReadVersion).await?;
+        Version::decode(&params)
+    }
+
+    /// Reads the indices of all controllers.
+    pub async fn read_index_list(&self) -> Result<Vec<u16>> {
+        let params = self.send_command(INDEX_NONE, &Command::ReadIndexList).await?;
+        command::decode_index_list(&params)
+    }
+
+    /// Reads the information of the controller with the specified index.
+    pub async fn read_info(&self, index: u16) -> Result<ControllerInfo> {
+        let params = self.send_command(index, &Command::ReadInfo).await?;
+        ControllerInfo::decode(&params)
+    }
+
+    /// Reads the capabilities of the controller with the specified index.
+    ///
+    /// The capabilities are returned in extended inquiry response (EIR) format.
+    pub async fn read_controller_capabilities(&self, index: u16) -> Result<Vec<u8>> {
+        let params = self.send_command(index, &Command::ReadControllerCapabilities).await?;
+        command::decode_capabilities(&params)
+    }
+
+    /// Powers the controller on or off.
+    ///
+    /// Returns the current [settings] of the controller.
+    pub async fn set_powered(&self, index: u16, powered: bool) -> Result<u32> {
+        let params = self.send_command(index, &Command::SetPowered(powered)).await?;
+        command::decode_settings(&params)
+    }
+
+    /// Enables or disables Bluetooth Low Energy.
+    ///
+    /// Returns the current [settings] of the controller.
+    pub async fn set_low_energy(&self, index: u16, enabled: bool) -> Result<u32> {
+        let params = self.send_command(index, &Command::SetLowEnergy(enabled)).await?;
+        command::decode_settings(&params)
+    }
+
+    /// Enables or disables classic Bluetooth (BR/EDR).
+    ///
+    /// Disabling BR/EDR on a dual-mode controller puts it into LE-only mode.
+    /// This can only be done while the controller is powered off.
+    ///
+    /// Returns the current [settings] of the controller.
+    pub async fn set_br_edr(&self, index: u16, enabled: bool) -> Result<u32> {
+        let params = self.send_command(index, &Command::SetBrEdr(enabled)).await?;
+        command::decode_settings(&params)
+    }
+
+    /// Sets the static random address of the controller.
+    ///
+    /// Specify [Address::any] to remove the static address.
+    /// This can only be done while the controller is powered off.
+    ///
+    /// Returns the current [settings] of the controller.
+    pub async fn set_static_address(&self, index: u16, address: Address) -> Result<u32> {
+        let params = self.send_command(index, &Command::SetStaticAddress(address)).await?;
+        command::decode_settings(&params)
+    }
+
+    /// Configures LE privacy using the specified local identity resolving key (IRK).
+    ///
+    /// This can only be done while the controller is powered off.
+    ///
+    /// Returns the current [settings] of the controller.
+    pub async fn set_privacy(&self, index: u16, privacy: Privacy, irk: [u8; 16]) -> Result<u32> {
+        let params = self.send_command(index, &Command::SetPrivacy { privacy, irk }).await?;
+        command::decode_settings(&params)
+    }
+
+    /// Loads long term keys into the kernel, replacing all previously loaded keys.
+    pub async fn load_long_term_keys(&self, index: u16, keys: Vec<LongTermKey>) -> Result<()> {
+        self.send_command(index, &Command::LoadLongTermKeys(keys)).await?;
+        Ok(())
+    }
+
+    /// Loads preferred LE connection parameters for remote devices.
+    pub async fn load_conn_params(&self, index: u16, params: Vec<ConnParams>) -> Result<()> {
+        self.send_command(index, &Command::LoadConnParams(params)).await?;
+        Ok(())
+    }
+
+    /// Gets the PHY configuration of the controller.
+    pub async fn phy_configuration(&self, index: u16) -> Result<PhyConfiguration> {
+        let params = self.send_command(index, &Command::GetPhyConfiguration).await?;
+        PhyConfiguration::decode(&params)
+    }
+
+    /// Sets the default PHYs used by the controller.
+    ///
+    /// Possible values are defined in the [phy] module.
+    pub async fn set_phy_configuration(&self, index: u16, selected_phys: u32) -> Result<()> {
+        self.send_command(index, &Command::SetPhyConfiguration(selected_phys)).await?;
+        Ok(())
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.recv_task.abort();
+    }
+}
@@ -0,0 +1,525 @@
+//! Mock Bluetooth adapter.
+
+use dbus::{
+    arg::{OwnedFd, PropMap, RefArg, Variant},
+    nonblock::{
+        stdintf::org_freedesktop_dbus::{ObjectManager, Properties},
+        Proxy, SyncConnection,
+    },
+    MethodErr, Path,
+};
+use dbus_crossroads::{Crossroads, IfaceBuilder, IfaceToken};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::{Arc, Mutex, Weak},
+};
+use uuid::Uuid;
+
+use super::{bluez_err, client_err, device::DeviceState, MockDevice, MockInner, VirtualDevice};
+use crate::{
+    adapter::INTERFACE,
+    adv::{self, Advertisement, ADVERTISEMENT_INTERFACE},
+    gatt::{local, CHARACTERISTIC_INTERFACE},
+    method_call, Adapter, Address, Device, Error, ErrorKind, Result, TIMEOUT,
+};
+
+/// Maximum number of simultaneously registered advertisements.
+const SUPPORTED_INSTANCES: u8 = 5;
+
+/// MTU of the link to a device accessing a registered GATT application.
+const GATT_MTU: u16 = 247;
+
+struct AdapterProps {
+    alias: String,
+    class: u32,
+    powered: bool,
+    discoverable: bool,
+    discoverable_timeout: u32,
+    pairable: bool,
+    pairable_timeout: u32,
+    discovering: bool,
+}
+
+/// A registered advertisement.
+struct RegisteredAdvertisement {
+    sender: String,
+    path: Path<'static>,
+    advertisement: Advertisement,
+}
+
+/// A registered GATT application.
+struct RegisteredApplication {
+    sender: String,
+    path: Path<'static>,
+}
+
+/// State of a mock adapter shared with D-Bus.
+pub(crate) struct AdapterState {
+    inner: Weak<MockInner>,
+    pub name: String,
+    pub path: Path<'static>,
+    address: Address,
+    props: Mutex<AdapterProps>,
+    devices: Mutex<BTreeMap<Address, Arc<DeviceState>>>,
+    advertisements: Mutex<Vec<RegisteredAdvertisement>>,
+    applications: Mutex<Vec<RegisteredApplication>>,
+}
+
+impl AdapterState {
+    pub(crate) fn new(inner: &Arc<MockInner>, name: &str, address: Address) -> Result<Arc<Self>> {
+        Ok(Arc::new(Self {
+            inner: Arc::downgrade(inner),
+            name: name.to_string(),
+            path: Adapter::dbus_path(name)?,
+            address,
+            props: Mutex::new(AdapterProps {
+                alias: format!("BlueR mock {name}"),
+                class: 0,
+                powered: true,
+                discoverable: false,
+                discoverable_timeout: 180,
+                pairable: true,
+                pairable_timeout: 0,
+                discovering: false,
+            }),
+            devices: Mutex::new(BTreeMap::new()),
+            advertisements: Mutex::new(Vec::new()),
+            applications: Mutex::new(Vec::new()),
+        }))
+    }
+
+    fn inner(&self) -> std::result::Result<Arc<MockInner>, MethodErr> {
+        self.inner.upgrade().ok_or_else(|| MethodErr::failed("mock daemon terminated"))
+    }
+
+    fn property_changed(&self, interface: &str, name: &str, value: impl RefArg + 'static) {
+        if let Some(inner) = self.inner.upgrade() {
+            inner.property_changed(&self.path, interface, name, value);
+        }
+    }
+
+    /// Sets whether the adapter is powered.
+    ///
+    /// Powering off stops a device discovery in progress.
+    fn set_powered(&self, powered: bool) {
+        let stopped_discovery = {
+            let mut props = self.props.lock().unwrap();
+            props.powered = powered;
+            let stopped_discovery = !powered && props.discovering;
+            if stopped_discovery {
+                props.discovering = false;
+            }
+            stopped_discovery
+        };
+        if stopped_discovery {
+            self.property_changed(INTERFACE, "Discovering", false);
+        }
+    }
+
+    pub(crate) fn device(&self, address: Address) -> Option<Arc<DeviceState>> {
+        self.devices.lock().unwrap().get(&address).cloned()
+    }
+
+    pub(crate) fn device_addresses(&self) -> Vec<Address> {
+        self.devices.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn add_device(self: &Arc<Self>, device: VirtualDevice) -> Result<Arc<DeviceState>> {
+        let inner = self.inner.upgrade().ok_or_else(|| Error::new(ErrorKind::NotReady))?;
+
+        let mut devices = self.devices.lock().unwrap();
+        if devices.contains_key(&device.address) {
+            return Err(Error::new(ErrorKind::AlreadyExists));
+        }
+
+        let state = DeviceState::new(&inner, self, device)?;
+        {
+            let mut cr = inner.crossroads.lock().unwrap();
+            cr.insert(state.path.clone(), &[inner.device_token], state.clone());
+        }
+        devices.insert(state.address, state.clone());
+
+        Ok(state)
+    }
+
+    pub(crate) fn remove_device(&self, address: Address) -> Result<()> {
+        let inner = self.inner.upgrade().ok_or_else(|| Error::new(ErrorKind::NotReady))?;
+
+        let state = match self.devices.lock().unwrap().remove(&address) {
+            Some(state) => state,
+            None => return Err(Error::new(ErrorKind::DoesNotExist)),
+        };
+        state.unpublish_services();
+
+        let mut cr = inner.crossroads.lock().unwrap();
+        let _: Option<Arc<DeviceState>> = cr.remove(&state.path);
+        Ok(())
+    }
+
+    /// Calls the specified acquire method of the characteristic with the specified UUID
+    /// of a registered GATT application on behalf of the device with the specified path.
+    pub(crate) async fn acquire_characteristic(
+        &self, device_path: &Path<'static>, uuid: Uuid, method: &str,
+    ) -> Result<(OwnedFd, u16)> {
+        let inner = self.inner.upgrade().ok_or_else(|| Error::new(ErrorKind::NotReady))?;
+        let apps: Vec<_> =
+            self.applications.lock().unwrap().iter().map(|app| (app.sender.clone(), app.path.clone())).collect();
+
+        for (sender, path) in apps {
+            let proxy = Proxy::new(sender.as_str(), &path, TIMEOUT, &*inner.connection);
+            let characteristics = fetch_characteristics(&proxy).await?;
+            let Some((char_path, _)) = characteristics.into_iter().find(|(_, char_uuid)| *char_uuid == uuid)
+            else {
+                continue;
+            };
+
+            let mut options = PropMap::new();
+            options.insert("device".to_string(), Variant(Box::new(device_path.clone())));
+            options.insert("mtu".to_string(), Variant(Box::new(GATT_MTU)));
+            options.insert("link".to_string(), Variant(Box::new("LE".to_string())));
+            let proxy = Proxy::new(sender.as_str(), char_path, TIMEOUT, &*inner.connection);
+            let (fd, mtu): (OwnedFd, u16) =
+                proxy.method_call(CHARACTERISTIC_INTERFACE, method, (options,)).await?;
+            return Ok((fd, mtu));
+        }
+
+        Err(Error::new(ErrorKind::DoesNotExist))
+    }
+
+    pub(crate) fn register_interface(cr: &mut Crossroads) -> IfaceToken<Arc<Self>> {
+        cr.register(INTERFACE, |ib: &mut IfaceBuilder<Arc<Self>>| {
+            ib.property("Address").get(|_, st| Ok(st.address.to_string()));
+            ib.property("AddressType").get(|_, _| Ok("public".to_string()));
+            ib.property("Name").get(|_, st| Ok(format!("BlueR mock {}", &st.name)));
+            ib.property("Alias").get(|_, st| Ok(st.props.lock().unwrap().alias.clone())).set(
+                |_, st, v: String| {
+                    st.props.lock().unwrap().alias = v.clone();
+                    Ok(Some(v))
+                },
+            );
+            ib.property("Class").get(|_, st| Ok(st.props.lock().unwrap().class));
+            ib.property("Powered").get(|_, st| Ok(st.props.lock().unwrap().powered)).set(|_, st, v: bool| {
+                st.set_powered(v);
+                Ok(Some(v))
+            });
+            ib.property("Discoverable").get(|_, st| Ok(st.props.lock().unwrap().discoverable)).set(
+                |_, st, v: bool| {
+                    st.props.lock().unwrap().discoverable = v;
+                    Ok(Some(v))
+                },
+            );
+            ib.property("DiscoverableTimeout")
+                .get(|_, st| Ok(st.props.lock().unwrap().discoverable_timeout))
+                .set(|_, st, v: u32| {
+                    st.props.lock().unwrap().discoverable_timeout = v;
+                    Ok(Some(v))
+                });
+            ib.property("Pairable").get(|_, st| Ok(st.props.lock().unwrap().pairable)).set(|_, st, v: bool| {
+                st.props.lock().unwrap().pairable = v;
+                Ok(Some(v))
+            });
+            ib.property("PairableTimeout").get(|_, st| Ok(st.props.lock().unwrap().pairable_timeout)).set(
+                |_, st, v: u32| {
+                    st.props.lock().unwrap().pairable_timeout = v;
+                    Ok(Some(v))
+                },
+            );
+            ib.property("Discovering").get(|_, st| Ok(st.props.lock().unwrap().discovering));
+            ib.property("UUIDs").get(|_, _| Ok(Vec::<String>::new()));
+            ib.property("Modalias").get(|_, _| Ok("usb:v1D6Bp0246d0540".to_string()));
+
+            ib.method("StartDiscovery", (), (), |_, st, ()| {
+                let mut props = st.props.lock().unwrap();
+                if !props.powered {
+                    return Err(bluez_err("NotReady", "Resource Not Ready"));
+                }
+                if !props.discovering {
+                    props.discovering = true;
+                    st.property_changed(INTERFACE, "Discovering", true);
+                }
+                Ok(())
+            });
+            ib.method("StopDiscovery", (), (), |_, st, ()| {
+                let mut props = st.props.lock().unwrap();
+                if !props.discovering {
+                    return Err(bluez_err("Failed", "No discovery started"));
+                }
+                props.discovering = false;
+                st.property_changed(INTERFACE, "Discovering", false);
+                Ok(())
+            });
+            ib.method("SetDiscoveryFilter", ("properties",), (), |_, _, (_filter,): (PropMap,)| Ok(()));
+            ib.method("GetDiscoveryFilters", (), ("filters",), |_, _, ()| {
+                Ok((["UUIDs", "RSSI", "Pathloss", "Transport", "DuplicateData", "Discoverable", "Pattern"]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>(),))
+            });
+            // Methods modifying the object tree must run after the crossroads lock has been released.
+            ib.method_with_cr_async("RemoveDevice", ("device",), (), |ctx, cr, (path,): (Path<'static>,)| {
+                method_call(ctx, cr, |st: Arc<Self>| async move {
+                    let address = match Device::parse_dbus_path(&path) {
+                        Some((adapter, address)) if adapter == st.name => address,
+                        _ => return Err(bluez_err("InvalidArguments", "Invalid arguments in method call")),
+                    };
+                    st.remove_device(address).map_err(|_| bluez_err("DoesNotExist", "Does Not Exist"))
+                })
+            });
+            ib.method_with_cr_async(
+                "ConnectDevice",
+                ("properties",),
+                ("device",),
+                |ctx, cr, (props,): (PropMap,)| {
+                    let address =
+                        props.get("Address").and_then(|v| v.0.as_str()).and_then(|s| s.parse::<Address>().ok());
+                    method_call(ctx, cr, move |st: Arc<Self>| async move {
+                        let address = address
+                            .ok_or_else(|| bluez_err("InvalidArguments", "Invalid arguments in method call"))?;
+                        let device = st.device(address).ok_or_else(|| bluez_err("Failed", "Device not found"))?;
+                        device.connect()?;
+                        Ok((device.path.clone(),))
+                    })
+                },
+            );
+        })
+    }
+
+    pub(crate) fn register_adv_manager_interface(cr: &mut Crossroads) -> IfaceToken<Arc<Self>> {
+        cr.register(adv::MANAGER_INTERFACE, |ib: &mut IfaceBuilder<Arc<Self>>| {
+            ib.property("ActiveInstances").get(|_, st| Ok(st.advertisements.lock().unwrap().len() as u8));
+            ib.property("SupportedInstances")
+                .get(|_, st| Ok(SUPPORTED_INSTANCES - st.advertisements.lock().unwrap().len() as u8));
+            ib.property("SupportedIncludes").get(|_, _| {
+                Ok(["tx-power", "appearance", "local-name"].iter().map(|s| s.to_string()).collect::<Vec<_>>())
+            });
+            ib.property("SupportedSecondaryChannels")
+                .get(|_, _| Ok(["1M", "2M", "Coded"].iter().map(|s| s.to_string()).collect::<Vec<_>>()));
+
+            ib.method_with_cr_async(
+                "RegisterAdvertisement",
+                ("advertisement", "options"),
+                (),
+                |ctx, cr, (path, _options): (Path<'static>, PropMap)| {
+                    let sender = ctx.message().sender().map(|s| s.to_string()).unwrap_or_default();
+                    method_call(ctx, cr, |st: Arc<Self>| async move {
+                        {
+                            let advs = st.advertisements.lock().unwrap();
+                            if advs.iter().any(|adv| adv.sender == sender && adv.path == path) {
+                                return Err(bluez_err("AlreadyExists", "Already Exists"));
+                            }
+                            if advs.len() >= SUPPORTED_INSTANCES.into() {
+                                return Err(bluez_err("NotPermitted", "Maximum advertisements reached"));
+                            }
+                        }
+
+                        let inner = st.inner()?;
+                        let proxy = Proxy::new(sender.as_str(), &path, TIMEOUT, &*inner.connection);
+                        let advertisement = fetch_advertisement(&proxy).await.map_err(client_err)?;
+
+                        let active = {
+                            let mut advs = st.advertisements.lock().unwrap();
+                            advs.push(RegisteredAdvertisement { sender, path, advertisement });
+                            advs.len() as u8
+                        };
+                        st.property_changed(adv::MANAGER_INTERFACE, "ActiveInstances", active);
+                        st.property_changed(
+                            adv::MANAGER_INTERFACE,
+                            "SupportedInstances",
+                            SUPPORTED_INSTANCES - active,
+                        );
+                        Ok(())
+                    })
+                },
+            );
+            ib.method("UnregisterAdvertisement", ("advertisement",), (), |ctx, st, (path,): (Path<'static>,)| {
+                let sender = ctx.message().sender().map(|s| s.to_string()).unwrap_or_default();
+                let active = {
+                    let mut advs = st.advertisements.lock().unwrap();
+                    let len = advs.len();
+                    advs.retain(|adv| !(adv.sender == sender && adv.path == path));
+                    if advs.len() == len {
+                        return Err(bluez_err("DoesNotExist", "Does Not Exist"));
+                    }
+                    advs.len() as u8
+                };
+                st.property_changed(adv::MANAGER_INTERFACE, "ActiveInstances", active);
+                st.property_changed(adv::MANAGER_INTERFACE, "SupportedInstances", SUPPORTED_INSTANCES - active);
+                Ok(())
+            });
+        })
+    }
+
+    pub(crate) fn register_gatt_manager_interface(cr: &mut Crossroads) -> IfaceToken<Arc<Self>> {
+        cr.register(local::MANAGER_INTERFACE, |ib: &mut IfaceBuilder<Arc<Self>>| {
+            ib.method_with_cr_async(
+                "RegisterApplication",
+                ("application", "options"),
+                (),
+                |ctx, cr, (path, _options): (Path<'static>, PropMap)| {
+                    let sender = ctx.message().sender().map(|s| s.to_string()).unwrap_or_default();
+                    method_call(ctx, cr, |st: Arc<Self>| async move {
+                        if st
+                            .applications
+                            .lock()
+                            .unwrap()
+                            .iter()
+                            .any(|app| app.sender == sender && app.path == path)
+                        {
+                            return Err(bluez_err("AlreadyExists", "Already Exists"));
+                        }
+
+                        // Like BlueZ, read the objects of the application while registering it.
+                        let inner = st.inner()?;
+                        let proxy = Proxy::new(sender.as_str(), &path, TIMEOUT, &*inner.connection);
+                        fetch_characteristics(&proxy).await.map_err(client_err)?;
+
+                        st.applications.lock().unwrap().push(RegisteredApplication { sender, path });
+                        Ok(())
+                    })
+                },
+            );
+            ib.method("UnregisterApplication", ("application",), (), |ctx, st, (path,): (Path<'static>,)| {
+                let sender = ctx.message().sender().map(|s| s.to_string()).unwrap_or_default();
+                let mut apps = st.applications.lock().unwrap();
+                let len = apps.len();
+                apps.retain(|app| !(app.sender == sender && app.path == path));
+                if apps.len() == len {
+                    return Err(bluez_err("DoesNotExist", "Does Not Exist"));
+                }
+                Ok(())
+            });
+        })
+    }
+}
+
+/// Reads the paths and UUIDs of the characteristics of a registered GATT application.
+async fn fetch_characteristics(
+    proxy: &Proxy<'_, &SyncConnection>,
+) -> std::result::Result<Vec<(Path<'static>, Uuid)>, dbus::Error> {
+    let objects = proxy.get_managed_objects().await?;
+    Ok(objects
+        .into_iter()
+        .filter_map(|(path, interfaces)| {
+            let uuid = interfaces.get(CHARACTERISTIC_INTERFACE)?.get("UUID")?.0.as_str()?.parse().ok()?;
+            Some((path, uuid))
+        })
+        .collect())
+}
+
+/// Reads the properties of a registered advertisement.
+async fn fetch_advertisement(
+    proxy: &Proxy<'_, &SyncConnection>,
+) -> std::result::Result<Advertisement, dbus::Error> {
+    let advertisement_type: String = proxy.get(ADVERTISEMENT_INTERFACE, "Type").await?;
+    let service_uuids: Option<Vec<String>> = proxy.get(ADVERTISEMENT_INTERFACE, "ServiceUUIDs").await.ok();
+    let manufacturer_data: Option<HashMap<u16, Variant<Vec<u8>>>> =
+        proxy.get(ADVERTISEMENT_INTERFACE, "ManufacturerData").await.ok();
+    let solicit_uuids: Option<Vec<String>> = proxy.get(ADVERTISEMENT_INTERFACE, "SolicitUUIDs").await.ok();
+    let service_data: Option<HashMap<String, Variant<Vec<u8>>>> =
+        proxy.get(ADVERTISEMENT_INTERFACE, "ServiceData").await.ok();
+    let advertising_data: Option<HashMap<u8, Variant<Vec<u8>>>> =
+        proxy.get(ADVERTISEMENT_INTERFACE, "Data").await.ok();
+    let discoverable: Option<bool> = proxy.get(ADVERTISEMENT_INTERFACE, "Discoverable").await.ok();
+    let local_name: Option<String> = proxy.get(ADVERTISEMENT_INTERFACE, "LocalName").await.ok();
+    let appearance: Option<u16> = proxy.get(ADVERTISEMENT_INTERFACE, "Appearance").await.ok();
+    let tx_power: Option<i16> = proxy.get(ADVERTISEMENT_INTERFACE, "TxPower").await.ok();
+
+    Ok(Advertisement {
+        advertisement_type: advertisement_type.parse().unwrap_or_default(),
+        service_uuids: service_uuids.unwrap_or_default().iter().filter_map(|s| s.parse().ok()).collect(),
+        manufacturer_data: manufacturer_data.unwrap_or_default().into_iter().map(|(k, v)| (k, v.0)).collect(),
+        solicit_uuids: solicit_uuids.unwrap_or_default().iter().filter_map(|s| s.parse().ok()).collect(),
+        service_data: service_data
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(k, v)| Some((k.parse().ok()?, v.0)))
+            .collect(),
+        advertising_data: advertising_data.unwrap_or_default().into_iter().map(|(k, v)| (k, v.0)).collect(),
+        discoverable,
+        local_name,
+        appearance,
+        tx_power,
+        ..Default::default()
+    })
+}
+
+/// Mock Bluetooth adapter.
+///
+/// Use this to add virtual devices and inspect the adapter state.
+/// The adapter is accessed through [Session::adapter](crate::Session::adapter)
+/// by the code under test.
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+#[derive(Clone)]
+pub struct MockAdapter {
+    state: Arc<AdapterState>,
+}
+
+impl fmt::Debug for MockAdapter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MockAdapter {{ {} }}", &self.state.name)
+    }
+}
+
+impl MockAdapter {
+    pub(crate) fn new(state: Arc<AdapterState>) -> Self {
+        Self { state }
+    }
+
+    /// The Bluetooth adapter name.
+    pub fn name(&self) -> &str {
+        &self.state.name
+    }
+
+    /// The Bluetooth adapter address.
+    pub fn address(&self) -> Address {
+        self.state.address
+    }
+
+    /// Whether the adapter is powered.
+    pub fn is_powered(&self) -> bool {
+        self.state.props.lock().unwrap().powered
+    }
+
+    /// Sets whether the adapter is powered.
+    ///
+    /// Powering off stops a device discovery in progress.
+    pub fn set_powered(&self, powered: bool) {
+        self.state.set_powered(powered);
+        self.state.property_changed(INTERFACE, "Powered", powered);
+    }
+
+    /// Whether a device discovery is in progress.
+    pub fn is_discovering(&self) -> bool {
+        self.state.props.lock().unwrap().discovering
+    }
+
+    /// Adds a virtual device.
+    ///
+    /// The device becomes visible immediately, as if it had been discovered.
+    pub fn add_device(&self, device: VirtualDevice) -> Result<MockDevice> {
+        Ok(MockDevice::new(self.state.add_device(device)?))
+    }
+
+    /// Gets the virtual device with the specified address.
+    pub fn device(&self, address: Address) -> Option<MockDevice> {
+        self.state.device(address).map(MockDevice::new)
+    }
+
+    /// Addresses of all virtual devices.
+    pub fn device_addresses(&self) -> Vec<Address> {
+        self.state.device_addresses()
+    }
+
+    /// Removes the virtual device with the specified address.
+    pub fn remove_device(&self, address: Address) -> Result<()> {
+        self.state.remove_device(address)
+    }
+
+    /// Currently registered LE advertisements.
+    ///
+    /// Only the advertisement data is provided; advertising parameters are not included.
+    pub fn advertisements(&self) -> Vec<Advertisement> {
+        self.state.advertisements.lock().unwrap().iter().map(|adv| adv.advertisement.clone()).collect()
+    }
+}
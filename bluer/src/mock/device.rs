@@ -0,0 +1,544 @@
+//! Mock Bluetooth device.
+
+use dbus::{
+    arg::{OwnedFd, PropMap, RefArg, Variant},
+    MethodErr, Path,
+};
+use dbus_crossroads::{Crossroads, IfaceBuilder, IfaceToken};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    sync::{Arc, Mutex, Weak},
+};
+use uuid::Uuid;
+
+use super::{
+    adapter::AdapterState,
+    bluez_err,
+    gatt::{MockCharacteristic, ServiceState},
+    MockInner, VirtualService,
+};
+use crate::{
+    device::INTERFACE,
+    gatt::{CharacteristicReader, CharacteristicWriter},
+    method_call, Address, AddressType, Device, Error, ErrorKind, Result,
+};
+
+const AGENT_INTERFACE: &str = "org.bluez.Agent1";
+
+/// Pairing behavior of a virtual device.
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Pairing {
+    /// Pairing succeeds without user interaction ("just works").
+    #[default]
+    Accept,
+    /// Pairing is rejected by the device.
+    Reject,
+    /// Numeric comparison.
+    ///
+    /// The registered authorization agent is asked to confirm the passkey.
+    /// Pairing succeeds if the agent confirms.
+    Confirm(u32),
+    /// Passkey entry.
+    ///
+    /// The registered authorization agent is asked for the passkey.
+    /// Pairing succeeds if the agent returns the specified passkey.
+    Passkey(u32),
+    /// Legacy PIN code entry.
+    ///
+    /// The registered authorization agent is asked for the PIN code.
+    /// Pairing succeeds if the agent returns the specified PIN code.
+    PinCode(String),
+}
+
+/// Definition of a virtual Bluetooth device.
+///
+/// Use [MockAdapter::add_device](super::MockAdapter::add_device) to add it to a mock adapter.
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+#[derive(Clone, Debug)]
+pub struct VirtualDevice {
+    /// Device address.
+    pub address: Address,
+    /// Device address type.
+    pub address_type: AddressType,
+    /// Remote device name.
+    pub name: Option<String>,
+    /// Bluetooth class of device.
+    pub class: Option<u32>,
+    /// External appearance of device, as found on GAP service.
+    pub appearance: Option<u16>,
+    /// Proposed icon name.
+    pub icon: Option<String>,
+    /// Advertised service UUIDs.
+    ///
+    /// The UUIDs of the GATT services are included automatically.
+    pub uuids: BTreeSet<Uuid>,
+    /// Received signal strength indicator.
+    pub rssi: Option<i16>,
+    /// Advertised transmitted power level.
+    pub tx_power: Option<i16>,
+    /// Advertised manufacturer specific data.
+    pub manufacturer_data: BTreeMap<u16, Vec<u8>>,
+    /// Advertised service data.
+    pub service_data: BTreeMap<Uuid, Vec<u8>>,
+    /// GATT services.
+    ///
+    /// These are published once the device is connected.
+    pub services: Vec<VirtualService>,
+    /// Pairing behavior.
+    pub pairing: Pairing,
+    /// Whether the device uses legacy pairing.
+    pub legacy_pairing: bool,
+    /// Whether connection attempts to the device fail.
+    pub reject_connect: bool,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Default for VirtualDevice {
+    fn default() -> Self {
+        Self {
+            address: Address::default(),
+            address_type: AddressType::LePublic,
+            name: None,
+            class: None,
+            appearance: None,
+            icon: None,
+            uuids: BTreeSet::new(),
+            rssi: None,
+            tx_power: None,
+            manufacturer_data: BTreeMap::new(),
+            service_data: BTreeMap::new(),
+            services: Vec::new(),
+            pairing: Pairing::default(),
+            legacy_pairing: false,
+            reject_connect: false,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+struct DeviceProps {
+    name: Option<String>,
+    alias: Option<String>,
+    uuids: Vec<String>,
+    rssi: Option<i16>,
+    tx_power: Option<i16>,
+    manufacturer_data: BTreeMap<u16, Vec<u8>>,
+    service_data: BTreeMap<Uuid, Vec<u8>>,
+    paired: bool,
+    connected: bool,
+    trusted: bool,
+    blocked: bool,
+    services_resolved: bool,
+}
+
+/// State of a virtual device shared with D-Bus.
+pub(crate) struct DeviceState {
+    inner: Weak<MockInner>,
+    pub path: Path<'static>,
+    pub address: Address,
+    adapter_path: Path<'static>,
+    device: VirtualDevice,
+    props: Mutex<DeviceProps>,
+    services: Vec<Arc<ServiceState>>,
+}
+
+impl DeviceState {
+    pub(crate) fn new(
+        inner: &Arc<MockInner>, adapter: &AdapterState, device: VirtualDevice,
+    ) -> Result<Arc<Self>> {
+        let path = Device::dbus_path(&adapter.name, device.address)?;
+
+        let mut uuids: BTreeSet<Uuid> = device.uuids.clone();
+        uuids.extend(device.services.iter().map(|s| s.uuid));
+
+        let mut handle = 0;
+        let services = device
+            .services
+            .iter()
+            .map(|service| ServiceState::new(inner, &adapter.name, device.address, &path, service, &mut handle))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(Self {
+            inner: Arc::downgrade(inner),
+            path,
+            address: device.address,
+            adapter_path: adapter.path.clone(),
+            props: Mutex::new(DeviceProps {
+                name: device.name.clone(),
+                alias: None,
+                uuids: uuids.iter().map(|uuid| uuid.to_string()).collect(),
+                rssi: device.rssi,
+                tx_power: device.tx_power,
+                manufacturer_data: device.manufacturer_data.clone(),
+                service_data: device.service_data.clone(),
+                paired: false,
+                connected: false,
+                trusted: false,
+                blocked: false,
+                services_resolved: false,
+            }),
+            services,
+            device,
+        }))
+    }
+
+    fn inner(&self) -> std::result::Result<Arc<MockInner>, MethodErr> {
+        self.inner.upgrade().ok_or_else(|| MethodErr::failed("mock daemon terminated"))
+    }
+
+    /// Name of the adapter the device belongs to.
+    fn adapter_name(&self) -> &str {
+        Device::parse_dbus_path(&self.path).map(|(adapter_name, _)| adapter_name).unwrap_or_default()
+    }
+
+    /// Calls the specified acquire method of a characteristic of a registered GATT application.
+    async fn acquire_characteristic(&self, uuid: Uuid, method: &str) -> Result<(OwnedFd, u16)> {
+        let inner = self.inner.upgrade().ok_or_else(|| Error::new(ErrorKind::NotReady))?;
+        let adapter = inner
+            .adapters
+            .lock()
+            .unwrap()
+            .get(self.adapter_name())
+            .cloned()
+            .ok_or_else(|| Error::new(ErrorKind::NotReady))?;
+        adapter.acquire_characteristic(&self.path, uuid, method).await
+    }
+
+    fn property_changed(&self, name: &str, value: impl RefArg + 'static) {
+        if let Some(inner) = self.inner.upgrade() {
+            inner.property_changed(&self.path, INTERFACE, name, value);
+        }
+    }
+
+    fn alias(&self) -> String {
+        let props = self.props.lock().unwrap();
+        match (&props.alias, &props.name) {
+            (Some(alias), _) => alias.clone(),
+            (None, Some(name)) => name.clone(),
+            (None, None) => self.address.to_string().replace(':', "-"),
+        }
+    }
+
+    /// Connects the device and publishes its GATT services.
+    pub(crate) fn connect(&self) -> std::result::Result<(), MethodErr> {
+        if self.device.reject_connect {
+            return Err(bluez_err("Failed", "Connection refused by virtual device"));
+        }
+        if self.props.lock().unwrap().blocked {
+            return Err(bluez_err("Failed", "Device is blocked"));
+        }
+
+        {
+            let mut props = self.props.lock().unwrap();
+            if props.connected {
+                return Err(bluez_err("AlreadyConnected", "Already Connected"));
+            }
+            props.connected = true;
+        }
+        self.property_changed("Connected", true);
+
+        self.publish_services()?;
+        self.props.lock().unwrap().services_resolved = true;
+        self.property_changed("ServicesResolved", true);
+
+        Ok(())
+    }
+
+    /// Disconnects the device and unpublishes its GATT services.
+    pub(crate) fn disconnect(&self) -> std::result::Result<(), MethodErr> {
+        {
+            let mut props = self.props.lock().unwrap();
+            if !props.connected {
+                return Err(bluez_err("NotConnected", "Not Connected"));
+            }
+            props.services_resolved = false;
+        }
+        self.property_changed("ServicesResolved", false);
+
+        self.unpublish_services();
+        self.props.lock().unwrap().connected = false;
+        self.property_changed("Connected", false);
+
+        Ok(())
+    }
+
+    fn publish_services(&self) -> std::result::Result<(), MethodErr> {
+        let inner = self.inner()?;
+        let mut cr = inner.crossroads.lock().unwrap();
+        for service in &self.services {
+            service.publish(&inner, &mut cr);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn unpublish_services(&self) {
+        if let Some(inner) = self.inner.upgrade() {
+            let mut cr = inner.crossroads.lock().unwrap();
+            for service in &self.services {
+                service.unpublish(&mut cr);
+            }
+        }
+    }
+
+    async fn pair(&self) -> std::result::Result<(), MethodErr> {
+        if self.props.lock().unwrap().paired {
+            return Err(bluez_err("AlreadyExists", "Already Exists"));
+        }
+        if !self.props.lock().unwrap().connected {
+            self.connect()?;
+        }
+
+        let inner = self.inner()?;
+        let agent = || inner.agent().ok_or_else(|| bluez_err("AuthenticationFailed", "Authentication Failed"));
+        let failed = |err: dbus::Error| {
+            log::trace!("Mock: agent rejected pairing: {}", &err);
+            bluez_err("AuthenticationFailed", "Authentication Failed")
+        };
+
+        match &self.device.pairing {
+            Pairing::Accept => (),
+            Pairing::Reject => return Err(bluez_err("AuthenticationRejected", "Authentication Rejected")),
+            Pairing::Confirm(passkey) => {
+                let agent = agent()?;
+                agent
+                    .proxy(&inner.connection)
+                    .method_call::<(), _, _, _>(
+                        AGENT_INTERFACE,
+                        "RequestConfirmation",
+                        (self.path.clone(), *passkey),
+                    )
+                    .await
+                    .map_err(failed)?;
+            }
+            Pairing::Passkey(passkey) => {
+                let agent = agent()?;
+                let (entered,): (u32,) = agent
+                    .proxy(&inner.connection)
+                    .method_call(AGENT_INTERFACE, "RequestPasskey", (self.path.clone(),))
+                    .await
+                    .map_err(failed)?;
+                if entered != *passkey {
+                    return Err(bluez_err("AuthenticationFailed", "Authentication Failed"));
+                }
+            }
+            Pairing::PinCode(pin_code) => {
+                let agent = agent()?;
+                let (entered,): (String,) = agent
+                    .proxy(&inner.connection)
+                    .method_call(AGENT_INTERFACE, "RequestPinCode", (self.path.clone(),))
+                    .await
+                    .map_err(failed)?;
+                if &entered != pin_code {
+                    return Err(bluez_err("AuthenticationFailed", "Authentication Failed"));
+                }
+            }
+        }
+
+        self.props.lock().unwrap().paired = true;
+        self.property_changed("Paired", true);
+        Ok(())
+    }
+
+    pub(crate) fn register_interface(cr: &mut Crossroads) -> IfaceToken<Arc<Self>> {
+        cr.register(INTERFACE, |ib: &mut IfaceBuilder<Arc<Self>>| {
+            ib.property("Address").get(|_, st| Ok(st.address.to_string()));
+            ib.property("AddressType").get(|_, st| Ok(st.device.address_type.to_string()));
+            cr_property!(ib, "Name", st => {
+                st.props.lock().unwrap().name.clone()
+            });
+            ib.property("Alias").get(|_, st| Ok(st.alias())).set(|_, st, v: String| {
+                st.props.lock().unwrap().alias = if v.is_empty() { None } else { Some(v) };
+                Ok(Some(st.alias()))
+            });
+            cr_property!(ib, "Class", st => {
+                st.device.class
+            });
+            cr_property!(ib, "Appearance", st => {
+                st.device.appearance
+            });
+            cr_property!(ib, "Icon", st => {
+                st.device.icon.clone()
+            });
+            ib.property("UUIDs").get(|_, st| Ok(st.props.lock().unwrap().uuids.clone()));
+            ib.property("Paired").get(|_, st| Ok(st.props.lock().unwrap().paired));
+            ib.property("Connected").get(|_, st| Ok(st.props.lock().unwrap().connected));
+            ib.property("Trusted").get(|_, st| Ok(st.props.lock().unwrap().trusted)).set(|_, st, v: bool| {
+                st.props.lock().unwrap().trusted = v;
+                Ok(Some(v))
+            });
+            ib.property("Blocked").get(|_, st| Ok(st.props.lock().unwrap().blocked)).set(|_, st, v: bool| {
+                st.props.lock().unwrap().blocked = v;
+                Ok(Some(v))
+            });
+            ib.property("LegacyPairing").get(|_, st| Ok(st.device.legacy_pairing));
+            cr_property!(ib, "RSSI", st => {
+                st.props.lock().unwrap().rssi
+            });
+            cr_property!(ib, "TxPower", st => {
+                st.props.lock().unwrap().tx_power
+            });
+            cr_property!(ib, "ManufacturerData", st => {
+                let props = st.props.lock().unwrap();
+                if props.manufacturer_data.is_empty() {
+                    None
+                } else {
+                    Some(props.manufacturer_data.iter().map(|(k, v)| (*k, Variant(v.clone()))).collect::<HashMap<_, _>>())
+                }
+            });
+            cr_property!(ib, "ServiceData", st => {
+                let props = st.props.lock().unwrap();
+                if props.service_data.is_empty() {
+                    None
+                } else {
+                    Some(props.service_data.iter().map(|(k, v)| (k.to_string(), Variant(v.clone()))).collect::<HashMap<_, _>>())
+                }
+            });
+            ib.property("ServicesResolved").get(|_, st| Ok(st.props.lock().unwrap().services_resolved));
+            ib.property("Adapter").get(|_, st| Ok(st.adapter_path.clone()));
+
+            // Methods modifying the object tree must run after the crossroads lock has been released.
+            ib.method_with_cr_async("Connect", (), (), |ctx, cr, ()| {
+                method_call(ctx, cr, |st: Arc<Self>| async move { st.connect() })
+            });
+            ib.method_with_cr_async("Disconnect", (), (), |ctx, cr, ()| {
+                method_call(ctx, cr, |st: Arc<Self>| async move { st.disconnect() })
+            });
+            ib.method_with_cr_async("ConnectProfile", ("UUID",), (), |ctx, cr, (uuid,): (String,)| {
+                method_call(ctx, cr, |st: Arc<Self>| async move {
+                    if !st.props.lock().unwrap().uuids.contains(&uuid) {
+                        return Err(bluez_err("NotAvailable", "Operation currently not available"));
+                    }
+                    match st.connect() {
+                        Ok(()) => Ok(()),
+                        Err(err) if err.errorname().ends_with("AlreadyConnected") => Ok(()),
+                        Err(err) => Err(err),
+                    }
+                })
+            });
+            ib.method_with_cr_async("DisconnectProfile", ("UUID",), (), |ctx, cr, (uuid,): (String,)| {
+                method_call(ctx, cr, |st: Arc<Self>| async move {
+                    if !st.props.lock().unwrap().uuids.contains(&uuid) {
+                        return Err(bluez_err("NotAvailable", "Operation currently not available"));
+                    }
+                    st.disconnect()
+                })
+            });
+            ib.method_with_cr_async("Pair", (), (), |ctx, cr, ()| {
+                method_call(ctx, cr, |st: Arc<Self>| async move { st.pair().await })
+            });
+            ib.method("CancelPairing", (), (), |_, _, ()| Ok(()));
+        })
+    }
+}
+
+/// Virtual Bluetooth device served by the mock Bluetooth daemon.
+///
+/// Use this to inspect and modify the state of the device.
+/// The device is accessed through [Adapter::device](crate::Adapter::device)
+/// by the code under test.
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+#[derive(Clone)]
+pub struct MockDevice {
+    state: Arc<DeviceState>,
+}
+
+impl fmt::Debug for MockDevice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MockDevice {{ {} }}", &self.state.address)
+    }
+}
+
+impl MockDevice {
+    pub(crate) fn new(state: Arc<DeviceState>) -> Self {
+        Self { state }
+    }
+
+    /// The Bluetooth device address.
+    pub fn address(&self) -> Address {
+        self.state.address
+    }
+
+    /// Whether the device is connected.
+    pub fn is_connected(&self) -> bool {
+        self.state.props.lock().unwrap().connected
+    }
+
+    /// Whether the device is paired.
+    pub fn is_paired(&self) -> bool {
+        self.state.props.lock().unwrap().paired
+    }
+
+    /// Whether the device is trusted.
+    pub fn is_trusted(&self) -> bool {
+        self.state.props.lock().unwrap().trusted
+    }
+
+    /// Whether the device is blocked.
+    pub fn is_blocked(&self) -> bool {
+        self.state.props.lock().unwrap().blocked
+    }
+
+    /// Sets the remote device name.
+    pub fn set_name(&self, name: Option<String>) {
+        self.state.props.lock().unwrap().name = name.clone();
+        if let Some(name) = name {
+            self.state.property_changed("Name", name);
+        }
+        self.state.property_changed("Alias", self.state.alias());
+    }
+
+    /// Sets the received signal strength indicator, as if a new advertisement was received.
+    pub fn set_rssi(&self, rssi: Option<i16>) {
+        self.state.props.lock().unwrap().rssi = rssi;
+        if let Some(rssi) = rssi {
+            self.state.property_changed("RSSI", rssi);
+        }
+    }
+
+    /// Sets the advertised manufacturer specific data, as if a new advertisement was received.
+    pub fn set_manufacturer_data(&self, manufacturer_data: BTreeMap<u16, Vec<u8>>) {
+        let value: HashMap<u16, Variant<Box<dyn RefArg>>> =
+            manufacturer_data.iter().map(|(k, v)| (*k, Variant(v.box_clone()))).collect();
+        self.state.props.lock().unwrap().manufacturer_data = manufacturer_data;
+        self.state.property_changed("ManufacturerData", value);
+    }
+
+    /// Sets the advertised service data, as if a new advertisement was received.
+    pub fn set_service_data(&self, service_data: BTreeMap<Uuid, Vec<u8>>) {
+        let value: PropMap = service_data.iter().map(|(k, v)| (k.to_string(), Variant(v.box_clone()))).collect();
+        self.state.props.lock().unwrap().service_data = service_data;
+        self.state.property_changed("ServiceData", value);
+    }
+
+    /// Disconnects the device, as if the connection was terminated by the remote device.
+    pub fn disconnect(&self) -> Result<()> {
+        self.state.disconnect().map_err(|_| Error::new(ErrorKind::Failed))
+    }
+
+    /// Gets the characteristic with the specified UUID from any GATT service of the device.
+    pub fn characteristic(&self, uuid: Uuid) -> Option<MockCharacteristic> {
+        self.state.services.iter().find_map(|service| service.characteristic(uuid))
+    }
+
+    /// Starts writing to the characteristic with the specified UUID of a GATT application
+    /// registered on the adapter, as if the device acquired a write stream from it.
+    ///
+    /// Fails with [ErrorKind::DoesNotExist] if no registered application provides the characteristic.
+    pub async fn acquire_write(&self, uuid: Uuid) -> Result<CharacteristicWriter> {
+        let (fd, mtu) = self.state.acquire_characteristic(uuid, "AcquireWrite").await?;
+        Ok(CharacteristicWriter::from_fd(self.state.adapter_name().to_string(), self.address(), mtu.into(), fd)?)
+    }
+
+    /// Subscribes to the characteristic with the specified UUID of a GATT application
+    /// registered on the adapter, as if the device acquired a notification stream from it.
+    ///
+    /// Fails with [ErrorKind::DoesNotExist] if no registered application provides the characteristic.
+    pub async fn acquire_notify(&self, uuid: Uuid) -> Result<CharacteristicReader> {
+        let (fd, mtu) = self.state.acquire_characteristic(uuid, "AcquireNotify").await?;
+        Ok(CharacteristicReader::from_fd(self.state.adapter_name().to_string(), self.address(), mtu.into(), fd)?)
+    }
+}
@@ -0,0 +1,403 @@
+//! Mock GATT services of virtual devices.
+
+use dbus::{
+    arg::{PropMap, RefArg},
+    MethodErr, Path,
+};
+use dbus_crossroads::{Crossroads, IfaceBuilder, IfaceToken};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
+use uuid::Uuid;
+
+use super::{bluez_err, MockInner};
+use crate::{
+    gatt::{
+        remote::{Characteristic, Descriptor, Service},
+        CharacteristicFlags, CHARACTERISTIC_INTERFACE, DESCRIPTOR_INTERFACE, SERVICE_INTERFACE,
+    },
+    read_dict, Address, Result,
+};
+
+/// Definition of a GATT service of a virtual device.
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+#[derive(Clone, Debug, Default)]
+pub struct VirtualService {
+    /// 128-bit service UUID.
+    pub uuid: Uuid,
+    /// Indicates whether or not this GATT service is a
+    /// primary service.
+    pub primary: bool,
+    /// Characteristics of this service.
+    pub characteristics: Vec<VirtualCharacteristic>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Definition of a GATT characteristic of a virtual device.
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+#[derive(Clone, Debug, Default)]
+pub struct VirtualCharacteristic {
+    /// 128-bit characteristic UUID.
+    pub uuid: Uuid,
+    /// Characteristic flags.
+    ///
+    /// Read, write and notify requests are only accepted if the corresponding flag is set.
+    pub flags: CharacteristicFlags,
+    /// Initial value.
+    pub value: Vec<u8>,
+    /// Descriptors of this characteristic.
+    pub descriptors: Vec<VirtualDescriptor>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Definition of a GATT characteristic descriptor of a virtual device.
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+#[derive(Clone, Debug, Default)]
+pub struct VirtualDescriptor {
+    /// 128-bit descriptor UUID.
+    pub uuid: Uuid,
+    /// Whether clients may write the descriptor.
+    ///
+    /// Descriptors are always readable.
+    pub writable: bool,
+    /// Initial value.
+    pub value: Vec<u8>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Reads the offset option of a read or write request.
+fn offset(options: &PropMap) -> usize {
+    read_dict::<u16>(options, "offset").map(|v| *v as usize).unwrap_or_default()
+}
+
+/// Reads a value at the specified offset.
+fn read_value(value: &[u8], offset: usize) -> std::result::Result<Vec<u8>, MethodErr> {
+    match value.get(offset..) {
+        Some(v) => Ok(v.to_vec()),
+        None => Err(bluez_err("InvalidOffset", "Invalid offset")),
+    }
+}
+
+/// Writes a value at the specified offset.
+fn write_value(value: &mut Vec<u8>, offset: usize, data: &[u8]) -> std::result::Result<(), MethodErr> {
+    if offset > value.len() {
+        return Err(bluez_err("InvalidOffset", "Invalid offset"));
+    }
+    value.truncate(offset);
+    value.extend_from_slice(data);
+    Ok(())
+}
+
+/// State of a GATT service of a virtual device shared with D-Bus.
+pub(crate) struct ServiceState {
+    path: Path<'static>,
+    device_path: Path<'static>,
+    handle: u16,
+    uuid: Uuid,
+    primary: bool,
+    characteristics: Vec<Arc<CharacteristicState>>,
+}
+
+impl ServiceState {
+    pub(crate) fn new(
+        inner: &Arc<MockInner>, adapter_name: &str, address: Address, device_path: &Path<'static>,
+        service: &VirtualService, handle: &mut u16,
+    ) -> Result<Arc<Self>> {
+        *handle += 1;
+        let service_handle = *handle;
+        let path = Service::dbus_path(adapter_name, address, service_handle)?;
+
+        let mut characteristics = Vec::new();
+        for characteristic in &service.characteristics {
+            *handle += 1;
+            let char_handle = *handle;
+            let char_path = Characteristic::dbus_path(adapter_name, address, service_handle, char_handle)?;
+
+            let mut descriptors = Vec::new();
+            for descriptor in &characteristic.descriptors {
+                *handle += 1;
+                descriptors.push(Arc::new(DescriptorState {
+                    path: Descriptor::dbus_path(adapter_name, address, service_handle, char_handle, *handle)?,
+                    characteristic_path: char_path.clone(),
+                    handle: *handle,
+                    uuid: descriptor.uuid,
+                    writable: descriptor.writable,
+                    value: Mutex::new(descriptor.value.clone()),
+                }));
+            }
+
+            characteristics.push(Arc::new(CharacteristicState {
+                inner: Arc::downgrade(inner),
+                path: char_path,
+                service_path: path.clone(),
+                handle: char_handle,
+                uuid: characteristic.uuid,
+                flags: characteristic.flags,
+                value: Mutex::new(characteristic.value.clone()),
+                notifying: AtomicBool::new(false),
+                published: AtomicBool::new(false),
+                descriptors,
+            }));
+        }
+
+        Ok(Arc::new(Self {
+            path,
+            device_path: device_path.clone(),
+            handle: service_handle,
+            uuid: service.uuid,
+            primary: service.primary,
+            characteristics,
+        }))
+    }
+
+    pub(crate) fn characteristic(&self, uuid: Uuid) -> Option<MockCharacteristic> {
+        self.characteristics.iter().find(|c| c.uuid == uuid).cloned().map(|state| MockCharacteristic { state })
+    }
+
+    pub(crate) fn publish(self: &Arc<Self>, inner: &MockInner, cr: &mut Crossroads) {
+        cr.insert(self.path.clone(), &[inner.service_token], self.clone());
+        for characteristic in &self.characteristics {
+            cr.insert(characteristic.path.clone(), &[inner.characteristic_token], characteristic.clone());
+            characteristic.published.store(true, Ordering::SeqCst);
+            for descriptor in &characteristic.descriptors {
+                cr.insert(descriptor.path.clone(), &[inner.descriptor_token], descriptor.clone());
+            }
+        }
+    }
+
+    pub(crate) fn unpublish(&self, cr: &mut Crossroads) {
+        for characteristic in &self.characteristics {
+            for descriptor in &characteristic.descriptors {
+                let _: Option<Arc<DescriptorState>> = cr.remove(&descriptor.path);
+            }
+            characteristic.published.store(false, Ordering::SeqCst);
+            characteristic.notifying.store(false, Ordering::SeqCst);
+            let _: Option<Arc<CharacteristicState>> = cr.remove(&characteristic.path);
+        }
+        let _: Option<Arc<Self>> = cr.remove(&self.path);
+    }
+
+    pub(crate) fn register_interface(cr: &mut Crossroads) -> IfaceToken<Arc<Self>> {
+        cr.register(SERVICE_INTERFACE, |ib: &mut IfaceBuilder<Arc<Self>>| {
+            ib.property("UUID").get(|_, st| Ok(st.uuid.to_string()));
+            ib.property("Primary").get(|_, st| Ok(st.primary));
+            ib.property("Device").get(|_, st| Ok(st.device_path.clone()));
+            ib.property("Includes").get(|_, _| Ok(Vec::<Path<'static>>::new()));
+            ib.property("Handle").get(|_, st| Ok(st.handle));
+        })
+    }
+}
+
+/// State of a GATT characteristic of a virtual device shared with D-Bus.
+pub(crate) struct CharacteristicState {
+    inner: Weak<MockInner>,
+    path: Path<'static>,
+    service_path: Path<'static>,
+    handle: u16,
+    uuid: Uuid,
+    flags: CharacteristicFlags,
+    value: Mutex<Vec<u8>>,
+    notifying: AtomicBool,
+    published: AtomicBool,
+    descriptors: Vec<Arc<DescriptorState>>,
+}
+
+impl CharacteristicState {
+    fn property_changed(&self, name: &str, value: impl RefArg + 'static) {
+        if !self.published.load(Ordering::SeqCst) {
+            return;
+        }
+        if let Some(inner) = self.inner.upgrade() {
+            inner.property_changed(&self.path, CHARACTERISTIC_INTERFACE, name, value);
+        }
+    }
+
+    fn can_read(&self) -> bool {
+        let f = &self.flags;
+        f.read || f.encrypt_read || f.encrypt_authenticated_read || f.secure_read
+    }
+
+    fn can_write(&self) -> bool {
+        let f = &self.flags;
+        f.write
+            || f.write_without_response
+            || f.reliable_write
+            || f.authenticated_signed_writes
+            || f.encrypt_write
+            || f.encrypt_authenticated_write
+            || f.secure_write
+    }
+
+    fn can_notify(&self) -> bool {
+        self.flags.notify || self.flags.indicate
+    }
+
+    pub(crate) fn register_interface(cr: &mut Crossroads) -> IfaceToken<Arc<Self>> {
+        cr.register(CHARACTERISTIC_INTERFACE, |ib: &mut IfaceBuilder<Arc<Self>>| {
+            ib.property("UUID").get(|_, st| Ok(st.uuid.to_string()));
+            ib.property("Service").get(|_, st| Ok(st.service_path.clone()));
+            ib.property("Value").get(|_, st| Ok(st.value.lock().unwrap().clone()));
+            ib.property("Notifying").get(|_, st| Ok(st.notifying.load(Ordering::SeqCst)));
+            ib.property("Flags").get(|_, st| Ok(st.flags.as_vec()));
+            ib.property("Handle").get(|_, st| Ok(st.handle));
+
+            ib.method("ReadValue", ("options",), ("value",), |_, st, (options,): (PropMap,)| {
+                if !st.can_read() {
+                    return Err(bluez_err("NotPermitted", "Read not permitted"));
+                }
+                Ok((read_value(&st.value.lock().unwrap(), offset(&options))?,))
+            });
+            ib.method("WriteValue", ("value", "options"), (), |_, st, (data, options): (Vec<u8>, PropMap)| {
+                if !st.can_write() {
+                    return Err(bluez_err("NotPermitted", "Write not permitted"));
+                }
+                write_value(&mut st.value.lock().unwrap(), offset(&options), &data)
+            });
+            ib.method("StartNotify", (), (), |_, st, ()| {
+                if !st.can_notify() {
+                    return Err(bluez_err("NotSupported", "Operation is not supported"));
+                }
+                if !st.notifying.swap(true, Ordering::SeqCst) {
+                    st.property_changed("Notifying", true);
+                }
+                Ok(())
+            });
+            ib.method("StopNotify", (), (), |_, st, ()| {
+                if st.notifying.swap(false, Ordering::SeqCst) {
+                    st.property_changed("Notifying", false);
+                }
+                Ok(())
+            });
+            ib.method("AcquireWrite", ("options",), ("fd", "mtu"), |_, _, (_options,): (PropMap,)| {
+                Err::<(dbus::arg::OwnedFd, u16), _>(bluez_err("NotSupported", "Operation is not supported"))
+            });
+            ib.method("AcquireNotify", ("options",), ("fd", "mtu"), |_, _, (_options,): (PropMap,)| {
+                Err::<(dbus::arg::OwnedFd, u16), _>(bluez_err("NotSupported", "Operation is not supported"))
+            });
+        })
+    }
+}
+
+/// GATT characteristic of a virtual device.
+///
+/// Use this to inspect and modify the characteristic value.
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+#[derive(Clone)]
+pub struct MockCharacteristic {
+    state: Arc<CharacteristicState>,
+}
+
+impl fmt::Debug for MockCharacteristic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MockCharacteristic {{ {} }}", &self.state.uuid)
+    }
+}
+
+impl MockCharacteristic {
+    /// 128-bit characteristic UUID.
+    pub fn uuid(&self) -> Uuid {
+        self.state.uuid
+    }
+
+    /// Current value, including all writes performed by clients.
+    pub fn value(&self) -> Vec<u8> {
+        self.state.value.lock().unwrap().clone()
+    }
+
+    /// Sets the value.
+    ///
+    /// If a client has enabled notifications, it is notified of the new value.
+    pub fn set_value(&self, value: Vec<u8>) {
+        *self.state.value.lock().unwrap() = value.clone();
+        self.state.property_changed("Value", value);
+    }
+
+    /// Whether a client has enabled notifications.
+    pub fn is_notifying(&self) -> bool {
+        self.state.notifying.load(Ordering::SeqCst)
+    }
+
+    /// Gets the descriptor with the specified UUID.
+    pub fn descriptor(&self, uuid: Uuid) -> Option<MockDescriptor> {
+        self.state.descriptors.iter().find(|d| d.uuid == uuid).cloned().map(|state| MockDescriptor { state })
+    }
+}
+
+/// State of a GATT descriptor of a virtual device shared with D-Bus.
+pub(crate) struct DescriptorState {
+    path: Path<'static>,
+    characteristic_path: Path<'static>,
+    handle: u16,
+    uuid: Uuid,
+    writable: bool,
+    value: Mutex<Vec<u8>>,
+}
+
+impl DescriptorState {
+    fn flags(&self) -> Vec<String> {
+        let mut flags = vec!["read".to_string()];
+        if self.writable {
+            flags.push("write".to_string());
+        }
+        flags
+    }
+
+    pub(crate) fn register_interface(cr: &mut Crossroads) -> IfaceToken<Arc<Self>> {
+        cr.register(DESCRIPTOR_INTERFACE, |ib: &mut IfaceBuilder<Arc<Self>>| {
+            ib.property("UUID").get(|_, st| Ok(st.uuid.to_string()));
+            ib.property("Characteristic").get(|_, st| Ok(st.characteristic_path.clone()));
+            ib.property("Value").get(|_, st| Ok(st.value.lock().unwrap().clone()));
+            ib.property("Flags").get(|_, st| Ok(st.flags()));
+            ib.property("Handle").get(|_, st| Ok(st.handle));
+
+            ib.method("ReadValue", ("options",), ("value",), |_, st, (options,): (PropMap,)| {
+                Ok((read_value(&st.value.lock().unwrap(), offset(&options))?,))
+            });
+            ib.method("WriteValue", ("value", "options"), (), |_, st, (data, options): (Vec<u8>, PropMap)| {
+                if !st.writable {
+                    return Err(bluez_err("NotPermitted", "Write not permitted"));
+                }
+                write_value(&mut st.value.lock().unwrap(), offset(&options), &data)
+            });
+        })
+    }
+}
+
+/// GATT descriptor of a virtual device.
+///
+/// Use this to inspect and modify the descriptor value.
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+#[derive(Clone)]
+pub struct MockDescriptor {
+    state: Arc<DescriptorState>,
+}
+
+impl fmt::Debug for MockDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MockDescriptor {{ {} }}", &self.state.uuid)
+    }
+}
+
+impl MockDescriptor {
+    /// 128-bit descriptor UUID.
+    pub fn uuid(&self) -> Uuid {
+        self.state.uuid
+    }
+
+    /// Current value, including all writes performed by clients.
+    pub fn value(&self) -> Vec<u8> {
+        self.state.value.lock().unwrap().clone()
+    }
+
+    /// Sets the value.
+    pub fn set_value(&self, value: Vec<u8>) {
+        *self.state.value.lock().unwrap() = value;
+    }
+}
@@ -0,0 +1,655 @@
+//! Mock Bluetooth daemon for testing.
+//!
+//! This module provides an in-process fake of the BlueZ Bluetooth daemon.
+//! It serves the `org.bluez.Adapter1`, `org.bluez.Device1`,
+//! `org.bluez.GattService1`, `org.bluez.GattCharacteristic1`, `org.bluez.GattDescriptor1`,
+//! `org.bluez.GattManager1`, `org.bluez.LEAdvertisingManager1` and `org.bluez.AgentManager1` interfaces
+//! on a private D-Bus bus, thus no Bluetooth hardware and no system Bluetooth daemon
+//! are required.
+//!
+//! Use [Bluetoothd::new] to start a private D-Bus bus together with the mock daemon
+//! and obtain a [Session] connected to it using [Bluetoothd::session].
+//! Adapters are added using [Bluetoothd::add_adapter] and scripted
+//! [virtual devices](VirtualDevice), including their advertisement data, GATT tables
+//! and pairing behavior, using [MockAdapter::add_device].
+//! The returned handles allow to inspect and modify the state of the mock objects
+//! while the code under test accesses them through the regular BlueR API.
+//! A virtual device can also act as a client of the GATT applications served by the
+//! code under test using [MockDevice::acquire_write] and [MockDevice::acquire_notify].
+//!
+//! Starting a private D-Bus bus requires the `dbus-daemon` executable to be available
+//! in the search path.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use bluer::{
+//!     gatt::CharacteristicFlags,
+//!     mock::{Bluetoothd, VirtualCharacteristic, VirtualDevice, VirtualService},
+//! };
+//! # async fn example() -> bluer::Result<()> {
+//! let bluetoothd = Bluetoothd::new().await?;
+//! let mock_adapter = bluetoothd.add_adapter("hci0", "00:11:22:33:44:55".parse()?)?;
+//! mock_adapter.add_device(VirtualDevice {
+//!     address: "66:77:88:99:aa:bb".parse()?,
+//!     name: Some("Thermometer".to_string()),
+//!     services: vec![VirtualService {
+//!         uuid: "0000181a-0000-1000-8000-00805f9b34fb".parse().unwrap(),
+//!         primary: true,
+//!         characteristics: vec![VirtualCharacteristic {
+//!             uuid: "00002a6e-0000-1000-8000-00805f9b34fb".parse().unwrap(),
+//!             flags: CharacteristicFlags { read: true, notify: true, ..Default::default() },
+//!             value: vec![0x34, 0x08],
+//!             ..Default::default()
+//!         }],
+//!         ..Default::default()
+//!     }],
+//!     ..Default::default()
+//! })?;
+//!
+//! let session = bluetoothd.session().await?;
+//! let adapter = session.default_adapter().await?;
+//! let device = adapter.device("66:77:88:99:aa:bb".parse()?)?;
+//! device.connect().await?;
+//! for service in device.services().await? {
+//!     for characteristic in service.characteristics().await? {
+//!         println!("{:x?}", characteristic.read().await?);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use dbus::{
+    arg::{PropMap, RefArg, Variant},
+    channel::{Channel, Sender},
+    message::{MatchRule, SignalArgs},
+    nonblock::{stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged, Proxy, SyncConnection},
+    MethodErr, Path,
+};
+use dbus_crossroads::{Crossroads, IfaceBuilder, IfaceToken};
+use dbus_tokio::connection;
+use futures::StreamExt;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::{Arc, Mutex, Weak},
+};
+use tokio::task::{spawn_blocking, JoinHandle};
+use uuid::Uuid;
+
+use crate::{Address, Error, ErrorKind, Result, Session, ERR_PREFIX, SERVICE_NAME};
+
+mod adapter;
+mod device;
+mod gatt;
+
+pub use adapter::MockAdapter;
+pub use device::{MockDevice, Pairing, VirtualDevice};
+pub use gatt::{MockCharacteristic, MockDescriptor, VirtualCharacteristic, VirtualDescriptor, VirtualService};
+
+use adapter::AdapterState;
+use device::DeviceState;
+use gatt::{CharacteristicState, DescriptorState, ServiceState};
+
+pub(crate) const AGENT_MANAGER_PATH: &str = "/org/bluez";
+
+/// D-Bus daemon configuration for the private bus.
+const BUS_CONFIG: &str = r#"<!DOCTYPE busconfig PUBLIC "-//freedesktop//DTD D-Bus Bus Configuration 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/busconfig.dtd">
+<busconfig>
+  <type>session</type>
+  <listen>unix:tmpdir=/tmp</listen>
+  <auth>EXTERNAL</auth>
+  <policy context="default">
+    <allow send_destination="*" eavesdrop="true"/>
+    <allow eavesdrop="true"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>
+"#;
+
+/// Creates a BlueZ D-Bus error.
+pub(crate) fn bluez_err(name: &str, message: &str) -> MethodErr {
+    MethodErr::from((ERR_PREFIX.to_string() + name, message))
+}
+
+/// A private D-Bus bus run by a child `dbus-daemon` process.
+struct PrivateBus {
+    child: Child,
+    config: PathBuf,
+    address: String,
+}
+
+impl PrivateBus {
+    fn spawn() -> std::io::Result<Self> {
+        let config = std::env::temp_dir().join(format!("bluer-mock-{}.conf", Uuid::new_v4().as_simple()));
+        std::fs::write(&config, BUS_CONFIG)?;
+
+        let mut child = match Command::new("dbus-daemon")
+            .arg(format!("--config-file={}", config.display()))
+            .arg("--nofork")
+            .arg("--print-address")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) => {
+                let _ = std::fs::remove_file(&config);
+                return Err(err);
+            }
+        };
+
+        let mut address = String::new();
+        let stdout = child.stdout.take().unwrap();
+        let res = BufReader::new(stdout).read_line(&mut address);
+        let address = address.trim().to_string();
+        let bus = Self { child, config, address };
+        res?;
+        if bus.address.is_empty() {
+            return Err(std::io::Error::new(std::io::ErrorKind::Other, "dbus-daemon did not report its address"));
+        }
+
+        Ok(bus)
+    }
+}
+
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_file(&self.config);
+    }
+}
+
+/// Shared state of the mock Bluetooth daemon.
+pub(crate) struct MockInner {
+    pub connection: Arc<SyncConnection>,
+    pub crossroads: Mutex<Crossroads>,
+    pub adapter_token: IfaceToken<Arc<AdapterState>>,
+    pub adv_manager_token: IfaceToken<Arc<AdapterState>>,
+    pub gatt_manager_token: IfaceToken<Arc<AdapterState>>,
+    pub device_token: IfaceToken<Arc<DeviceState>>,
+    pub service_token: IfaceToken<Arc<ServiceState>>,
+    pub characteristic_token: IfaceToken<Arc<CharacteristicState>>,
+    pub descriptor_token: IfaceToken<Arc<DescriptorState>>,
+    pub adapters: Mutex<BTreeMap<String, Arc<AdapterState>>>,
+    pub agent: Mutex<Option<RegisteredAgent>>,
+    dbus_task: JoinHandle<connection::IOResourceError>,
+}
+
+impl MockInner {
+    /// Emits a PropertiesChanged signal for the specified object.
+    pub fn properties_changed(&self, path: &Path<'static>, interface: &str, changed: PropMap) {
+        let msg = PropertiesPropertiesChanged {
+            interface_name: interface.to_string(),
+            changed_properties: changed,
+            invalidated_properties: Vec::new(),
+        }
+        .to_emit_message(path);
+        let _ = self.connection.send(msg);
+    }
+
+    /// Emits a PropertiesChanged signal for a single property.
+    pub fn property_changed(
+        &self, path: &Path<'static>, interface: &str, name: &str, value: impl RefArg + 'static,
+    ) {
+        let mut changed = PropMap::new();
+        changed.insert(name.to_string(), Variant(Box::new(value)));
+        self.properties_changed(path, interface, changed);
+    }
+
+    /// Registered authorization agent.
+    pub fn agent(&self) -> Option<RegisteredAgent> {
+        self.agent.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockInner {
+    fn drop(&mut self) {
+        // documentation for dbus_tokio::connection::IOResource indicates it is abortable
+        self.dbus_task.abort();
+    }
+}
+
+/// An authorization agent registered with the mock Bluetooth daemon.
+#[derive(Clone, Debug)]
+pub(crate) struct RegisteredAgent {
+    pub sender: String,
+    pub path: Path<'static>,
+}
+
+impl RegisteredAgent {
+    /// Proxy for calling the agent.
+    pub fn proxy<'a>(&'a self, connection: &'a SyncConnection) -> Proxy<'a, &'a SyncConnection> {
+        Proxy::new(self.sender.as_str(), &self.path, crate::TIMEOUT, connection)
+    }
+}
+
+/// Mock Bluetooth daemon.
+///
+/// Serves a fake BlueZ D-Bus interface on a private bus.
+/// The mock daemon and, if it was started by [Bluetoothd::new], the private
+/// D-Bus bus are terminated when this is dropped.
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+pub struct Bluetoothd {
+    inner: Arc<MockInner>,
+    address: String,
+    _bus: Option<PrivateBus>,
+}
+
+impl fmt::Debug for Bluetoothd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bluetoothd {{ {} }}", &self.address)
+    }
+}
+
+impl Bluetoothd {
+    /// Starts a private D-Bus bus and serves the mock Bluetooth daemon on it.
+    ///
+    /// This requires the `dbus-daemon` executable to be available.
+    pub async fn new() -> Result<Self> {
+        let bus = spawn_blocking(PrivateBus::spawn).await??;
+        log::trace!("Started private D-Bus bus at {}", &bus.address);
+        let mut this = Self::with_address(&bus.address).await?;
+        this._bus = Some(bus);
+        Ok(this)
+    }
+
+    /// Serves the mock Bluetooth daemon on the D-Bus bus with the specified address.
+    ///
+    /// The `org.bluez` name must not be owned by another connection on that bus.
+    pub async fn with_address(address: &str) -> Result<Self> {
+        let addr = address.to_string();
+        let (resource, connection) = spawn_blocking(move || {
+            let mut channel = Channel::open_private(&addr)?;
+            channel.register()?;
+            connection::from_channel::<SyncConnection>(channel)
+        })
+        .await??;
+        let dbus_task = tokio::spawn(resource);
+        log::trace!("Mock Bluetooth daemon connected to D-Bus with unique name {}", &connection.unique_name());
+
+        let mut crossroads = Crossroads::new();
+        crossroads.set_async_support(Some((
+            connection.clone(),
+            Box::new(|x| {
+                tokio::spawn(x);
+            }),
+        )));
+        crossroads.set_object_manager_support(Some(connection.clone()));
+
+        let adapter_token = AdapterState::register_interface(&mut crossroads);
+        let adv_manager_token = AdapterState::register_adv_manager_interface(&mut crossroads);
+        let gatt_manager_token = AdapterState::register_gatt_manager_interface(&mut crossroads);
+        let device_token = DeviceState::register_interface(&mut crossroads);
+        let service_token = ServiceState::register_interface(&mut crossroads);
+        let characteristic_token = CharacteristicState::register_interface(&mut crossroads);
+        let descriptor_token = DescriptorState::register_interface(&mut crossroads);
+        let agent_manager_token = register_agent_manager_interface(&mut crossroads);
+
+        let object_manager_token = crossroads.object_manager();
+        crossroads.insert("/", &[object_manager_token], ());
+
+        let inner = Arc::new(MockInner {
+            connection: connection.clone(),
+            crossroads: Mutex::new(crossroads),
+            adapter_token,
+            adv_manager_token,
+            gatt_manager_token,
+            device_token,
+            service_token,
+            characteristic_token,
+            descriptor_token,
+            adapters: Mutex::new(BTreeMap::new()),
+            agent: Mutex::new(None),
+            dbus_task,
+        });
+
+        {
+            let mut cr = inner.crossroads.lock().unwrap();
+            cr.insert(AGENT_MANAGER_PATH, &[agent_manager_token], Arc::downgrade(&inner));
+        }
+
+        let mc_callback = connection.add_match(MatchRule::new_method_call()).await?;
+        let mc_inner = Arc::downgrade(&inner);
+        tokio::spawn(async move {
+            let (_mc_callback, mut mc_stream) = mc_callback.msg_stream();
+            while let Some(msg) = mc_stream.next().await {
+                let mc_inner = match mc_inner.upgrade() {
+                    Some(inner) => inner,
+                    None => return,
+                };
+                let mut crossroads = mc_inner.crossroads.lock().unwrap();
+                let _ = crossroads.handle_message(msg, &*mc_inner.connection);
+            }
+        });
+
+        connection.request_name(SERVICE_NAME, false, true, true).await?;
+        log::trace!("Mock Bluetooth daemon owns {}", SERVICE_NAME);
+
+        Ok(Self { inner, address: address.to_string(), _bus: None })
+    }
+
+    /// Address of the D-Bus bus the mock Bluetooth daemon is served on.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Creates a new Bluetooth session connected to the mock Bluetooth daemon.
+    pub async fn session(&self) -> Result<Session> {
+        Session::new_with_address(&self.address).await
+    }
+
+    /// Adds a mock Bluetooth adapter with the specified name and address.
+    ///
+    /// The adapter is powered on.
+    pub fn add_adapter(&self, name: &str, address: Address) -> Result<MockAdapter> {
+        let mut adapters = self.inner.adapters.lock().unwrap();
+        if adapters.contains_key(name) {
+            return Err(Error::new(ErrorKind::AlreadyExists));
+        }
+
+        let state = AdapterState::new(&self.inner, name, address)?;
+        {
+            let mut cr = self.inner.crossroads.lock().unwrap();
+            cr.insert(
+                state.path.clone(),
+                &[self.inner.adapter_token, self.inner.adv_manager_token, self.inner.gatt_manager_token],
+                state.clone(),
+            );
+        }
+        adapters.insert(name.to_string(), state.clone());
+
+        Ok(MockAdapter::new(state))
+    }
+
+    /// Gets the mock Bluetooth adapter with the specified name.
+    pub fn adapter(&self, name: &str) -> Option<MockAdapter> {
+        self.inner.adapters.lock().unwrap().get(name).cloned().map(MockAdapter::new)
+    }
+
+    /// Removes the mock Bluetooth adapter with the specified name together with all its devices.
+    pub fn remove_adapter(&self, name: &str) -> Result<()> {
+        let state = match self.inner.adapters.lock().unwrap().remove(name) {
+            Some(state) => state,
+            None => return Err(Error::new(ErrorKind::DoesNotExist)),
+        };
+
+        for address in state.device_addresses() {
+            let _ = state.remove_device(address);
+        }
+
+        let mut cr = self.inner.crossroads.lock().unwrap();
+        let _: Option<Arc<AdapterState>> = cr.remove(&state.path);
+        Ok(())
+    }
+
+    /// Whether an authorization agent is registered.
+    pub fn is_agent_registered(&self) -> bool {
+        self.inner.agent().is_some()
+    }
+}
+
+/// Registers the `org.bluez.AgentManager1` interface.
+fn register_agent_manager_interface(cr: &mut Crossroads) -> IfaceToken<Weak<MockInner>> {
+    cr.register("org.bluez.AgentManager1", |ib: &mut IfaceBuilder<Weak<MockInner>>| {
+        ib.method(
+            "RegisterAgent",
+            ("agent", "capability"),
+            (),
+            |ctx, inner, (path, capability): (Path<'static>, String)| {
+                let inner = inner.upgrade().ok_or_else(|| MethodErr::failed("mock daemon terminated"))?;
+                let sender = ctx.message().sender().map(|s| s.to_string()).unwrap_or_default();
+                let mut agent = inner.agent.lock().unwrap();
+                if agent.as_ref().map(|agent| agent.sender == sender).unwrap_or_default() {
+                    return Err(bluez_err("AlreadyExists", "Already Exists"));
+                }
+                log::trace!("Mock: agent {} registered by {} with capability {}", &path, &sender, &capability);
+                *agent = Some(RegisteredAgent { sender, path });
+                Ok(())
+            },
+        );
+        ib.method("UnregisterAgent", ("agent",), (), |ctx, inner, (path,): (Path<'static>,)| {
+            let inner = inner.upgrade().ok_or_else(|| MethodErr::failed("mock daemon terminated"))?;
+            let sender = ctx.message().sender().map(|s| s.to_string()).unwrap_or_default();
+            let mut agent = inner.agent.lock().unwrap();
+            match &*agent {
+                Some(reg) if reg.sender == sender && reg.path == path => {
+                    *agent = None;
+                    Ok(())
+                }
+                _ => Err(bluez_err("DoesNotExist", "Does Not Exist")),
+            }
+        });
+        ib.method("RequestDefaultAgent", ("agent",), (), |ctx, inner, (path,): (Path<'static>,)| {
+            let inner = inner.upgrade().ok_or_else(|| MethodErr::failed("mock daemon terminated"))?;
+            let sender = ctx.message().sender().map(|s| s.to_string()).unwrap_or_default();
+            let agent = inner.agent();
+            match agent {
+                Some(reg) if reg.sender == sender && reg.path == path => Ok(()),
+                _ => Err(bluez_err("DoesNotExist", "Does Not Exist")),
+            }
+        });
+    })
+}
+
+/// Converts a D-Bus error received from a client into a method error.
+pub(crate) fn client_err(err: dbus::Error) -> MethodErr {
+    MethodErr::from((
+        err.name().unwrap_or("org.freedesktop.DBus.Error.Failed").to_string(),
+        err.message().unwrap_or_default().to_string(),
+    ))
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use futures::{pin_mut, Stream, StreamExt};
+    use std::{future::Future, time::Duration};
+    use tokio::time::timeout;
+
+    use super::*;
+    use crate::{
+        agent::{Agent, ReqError},
+        gatt::CharacteristicFlags,
+        AdapterEvent, AdapterProperty,
+    };
+
+    const ADAPTER_ADDRESS: &str = "00:11:22:33:44:55";
+    const DEVICE_ADDRESS: &str = "66:77:88:99:aa:bb";
+    const SERVICE_UUID: Uuid = Uuid::from_u128(0x0000181a_0000_1000_8000_00805f9b34fb);
+    const TEMPERATURE_UUID: Uuid = Uuid::from_u128(0x00002a6e_0000_1000_8000_00805f9b34fb);
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    async fn within<T>(fut: impl Future<Output = T>) -> T {
+        timeout(TIMEOUT, fut).await.expect("timed out")
+    }
+
+    async fn next_matching<S: Stream + Unpin>(stream: &mut S, f: impl Fn(&S::Item) -> bool) -> S::Item {
+        within(async {
+            loop {
+                let item = stream.next().await.expect("stream ended");
+                if f(&item) {
+                    break item;
+                }
+            }
+        })
+        .await
+    }
+
+    fn thermometer(pairing: Pairing) -> VirtualDevice {
+        VirtualDevice {
+            address: DEVICE_ADDRESS.parse().unwrap(),
+            name: Some("Thermometer".to_string()),
+            services: vec![VirtualService {
+                uuid: SERVICE_UUID,
+                primary: true,
+                characteristics: vec![VirtualCharacteristic {
+                    uuid: TEMPERATURE_UUID,
+                    flags: CharacteristicFlags { read: true, write: true, notify: true, ..Default::default() },
+                    value: vec![0x34, 0x08],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            pairing,
+            ..Default::default()
+        }
+    }
+
+    /// Starts the mock daemon with one adapter and a session on a separately created connection.
+    async fn start(pairing: Pairing) -> (Bluetoothd, MockAdapter, MockDevice, Session) {
+        let bluetoothd = Bluetoothd::new().await.expect("dbus-daemon is required");
+        let mock_adapter = bluetoothd.add_adapter("hci0", ADAPTER_ADDRESS.parse().unwrap()).unwrap();
+        let mock_device = mock_adapter.add_device(thermometer(pairing)).unwrap();
+
+        let address = bluetoothd.address().to_string();
+        let (resource, connection) = spawn_blocking(move || {
+            let mut channel = Channel::open_private(&address)?;
+            channel.register()?;
+            connection::from_channel::<SyncConnection>(channel)
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        tokio::spawn(resource);
+        let session = Session::with_connection(connection).await.unwrap();
+
+        (bluetoothd, mock_adapter, mock_device, session)
+    }
+
+    #[tokio::test]
+    async fn discovery() {
+        let (_bluetoothd, mock_adapter, _mock_device, session) = start(Pairing::Accept).await;
+        let adapter = session.default_adapter().await.unwrap();
+        assert_eq!(adapter.name(), "hci0");
+        assert_eq!(adapter.address().await.unwrap(), ADAPTER_ADDRESS.parse().unwrap());
+
+        {
+            let events = adapter.discover_devices().await.unwrap();
+            pin_mut!(events);
+            let address: Address = DEVICE_ADDRESS.parse().unwrap();
+            next_matching(&mut events, |evt| matches!(evt, AdapterEvent::DeviceAdded(addr) if *addr == address))
+                .await;
+            assert!(mock_adapter.is_discovering());
+
+            let device = adapter.device(DEVICE_ADDRESS.parse().unwrap()).unwrap();
+            assert_eq!(device.name().await.unwrap().as_deref(), Some("Thermometer"));
+            assert_eq!(device.uuids().await.unwrap(), Some([SERVICE_UUID].into()));
+        }
+
+        within(async {
+            while mock_adapter.is_discovering() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn power_off_stops_discovery() {
+        let (_bluetoothd, mock_adapter, _mock_device, session) = start(Pairing::Accept).await;
+        let adapter = session.default_adapter().await.unwrap();
+        let events = adapter.events().await.unwrap();
+        pin_mut!(events);
+
+        let _discovery = adapter.discover_devices().await.unwrap();
+        assert!(mock_adapter.is_discovering());
+
+        mock_adapter.set_powered(false);
+        assert!(!mock_adapter.is_discovering());
+        next_matching(&mut events, |evt| {
+            matches!(evt, AdapterEvent::PropertyChanged(AdapterProperty::Discovering(false)))
+        })
+        .await;
+        assert!(!adapter.is_discovering().await.unwrap());
+        assert!(!adapter.is_powered().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn gatt() {
+        let (_bluetoothd, _mock_adapter, mock_device, session) = start(Pairing::Accept).await;
+        let adapter = session.default_adapter().await.unwrap();
+        let device = adapter.device(DEVICE_ADDRESS.parse().unwrap()).unwrap();
+
+        device.connect().await.unwrap();
+        assert!(mock_device.is_connected());
+        assert!(device.is_connected().await.unwrap());
+
+        let services = device.services().await.unwrap();
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].uuid().await.unwrap(), SERVICE_UUID);
+        let characteristics = services[0].characteristics().await.unwrap();
+        assert_eq!(characteristics.len(), 1);
+        let characteristic = &characteristics[0];
+        assert_eq!(characteristic.uuid().await.unwrap(), TEMPERATURE_UUID);
+
+        assert_eq!(characteristic.read().await.unwrap(), [0x34, 0x08]);
+        characteristic.write(&[0x35, 0x08]).await.unwrap();
+        let mock_characteristic = mock_device.characteristic(TEMPERATURE_UUID).unwrap();
+        assert_eq!(mock_characteristic.value(), [0x35, 0x08]);
+
+        let notifications = characteristic.notify().await.unwrap();
+        pin_mut!(notifications);
+        assert!(mock_characteristic.is_notifying());
+        mock_characteristic.set_value(vec![0x36, 0x08]);
+        assert_eq!(within(notifications.next()).await.unwrap(), [0x36, 0x08]);
+
+        mock_device.disconnect().unwrap();
+        assert!(!mock_device.is_connected());
+    }
+
+    #[tokio::test]
+    async fn pairing_confirmed_by_agent() {
+        let (bluetoothd, _mock_adapter, mock_device, session) = start(Pairing::Confirm(123456)).await;
+        let adapter = session.default_adapter().await.unwrap();
+        let device = adapter.device(DEVICE_ADDRESS.parse().unwrap()).unwrap();
+
+        let _agent = session
+            .register_agent(Agent {
+                request_confirmation: Some(Box::new(|req| {
+                    Box::pin(async move {
+                        assert_eq!(req.device, DEVICE_ADDRESS.parse().unwrap());
+                        if req.passkey == 123456 {
+                            Ok(())
+                        } else {
+                            Err(ReqError::Rejected)
+                        }
+                    })
+                })),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(bluetoothd.is_agent_registered());
+
+        device.pair().await.unwrap();
+        assert!(mock_device.is_paired());
+        assert!(device.is_paired().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn pairing_with_wrong_passkey() {
+        let (_bluetoothd, _mock_adapter, mock_device, session) = start(Pairing::Passkey(123456)).await;
+        let adapter = session.default_adapter().await.unwrap();
+        let device = adapter.device(DEVICE_ADDRESS.parse().unwrap()).unwrap();
+
+        let _agent = session
+            .register_agent(Agent {
+                request_passkey: Some(Box::new(|_req| Box::pin(async { Ok(654321) }))),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let err = device.pair().await.unwrap_err();
+        assert_eq!(err.kind, ErrorKind::AuthenticationFailed);
+        assert!(!mock_device.is_paired());
+    }
+}
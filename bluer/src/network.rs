@@ -0,0 +1,107 @@
+//! Bluetooth Personal Area Network (PAN).
+//!
+//! Use [Device::network_connect](crate::Device::network_connect) to connect to the
+//! network service of a remote device and
+//! [Adapter::register_network_server](crate::Adapter::register_network_server) to
+//! offer a network service to remote devices.
+
+use dbus::nonblock::Proxy;
+use std::{fmt, sync::Arc};
+use strum::{Display, EnumString};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::{Adapter, Result, SessionInner, UuidExt, SERVICE_NAME, TIMEOUT};
+
+pub(crate) const INTERFACE: &str = "org.bluez.Network1";
+pub(crate) const SERVER_INTERFACE: &str = "org.bluez.NetworkServer1";
+
+/// Personal Area Network role.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Role {
+    /// Personal Area Network User.
+    #[strum(serialize = "panu")]
+    Panu,
+    /// Network Access Point.
+    #[strum(serialize = "nap")]
+    Nap,
+    /// Group ad-hoc Network.
+    #[strum(serialize = "gn")]
+    Gn,
+}
+
+impl Role {
+    /// Service class UUID of the role.
+    pub fn uuid(&self) -> Uuid {
+        Uuid::from_u16(match self {
+            Self::Panu => 0x1115,
+            Self::Nap => 0x1116,
+            Self::Gn => 0x1117,
+        })
+    }
+
+    /// Role with the specified service class UUID.
+    pub fn from_uuid(uuid: Uuid) -> Option<Self> {
+        [Self::Panu, Self::Nap, Self::Gn].into_iter().find(|role| role.uuid() == uuid)
+    }
+}
+
+impl NetworkServerHandle {
+    pub(crate) async fn register(
+        inner: Arc<SessionInner>, adapter_name: Arc<String>, role: Role, bridge: &str,
+    ) -> Result<Self> {
+        log::trace!("Registering network server for {} on bridge {}", role, bridge);
+        let proxy =
+            Proxy::new(SERVICE_NAME, Adapter::dbus_path(&adapter_name)?, TIMEOUT, inner.connection.clone());
+        proxy.method_call::<(), _, _, _>(SERVER_INTERFACE, "Register", (role.to_string(), bridge)).await?;
+
+        let (drop_tx, drop_rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let _ = drop_rx.await;
+
+            log::trace!("Unregistering network server for {}", role);
+            let _: std::result::Result<(), dbus::Error> =
+                proxy.method_call(SERVER_INTERFACE, "Unregister", (role.to_string(),)).await;
+        });
+
+        Ok(Self { adapter_name, role, bridge: bridge.to_string(), _drop_tx: drop_tx })
+    }
+
+    /// Name of adapter the network server is registered on.
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    /// Role of the network server.
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// Name of the network bridge remote devices are connected to.
+    pub fn bridge(&self) -> &str {
+        &self.bridge
+    }
+}
+
+/// Handle to registered network server.
+///
+/// Drop to unregister network server.
+pub struct NetworkServerHandle {
+    adapter_name: Arc<String>,
+    role: Role,
+    bridge: String,
+    _drop_tx: oneshot::Sender<()>,
+}
+
+impl Drop for NetworkServerHandle {
+    fn drop(&mut self) {
+        // required for drop order
+    }
+}
+
+impl fmt::Debug for NetworkServerHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NetworkServerHandle {{ {}: {} on {} }}", &self.adapter_name, &self.role, &self.bridge)
+    }
+}
@@ -0,0 +1,145 @@
+//! OBEX file transfer.
+
+use dbus::{
+    arg::PropMap,
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use std::{fmt, path::Path as FsPath};
+use strum::{Display, EnumString};
+
+use super::{path_to_string, Session, Transfer, SERVICE_NAME, TIMEOUT};
+use crate::{Error, ErrorKind, InternalErrorKind, Result};
+
+pub(crate) const INTERFACE: &str = "org.bluez.obex.FileTransfer1";
+
+/// Type of a folder entry.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FolderEntryType {
+    /// Folder.
+    #[strum(serialize = "folder")]
+    Folder,
+    /// File.
+    #[strum(serialize = "file")]
+    File,
+}
+
+/// Entry of a folder listing of a remote device.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct FolderEntry {
+    /// Name of the entry.
+    pub name: String,
+    /// Type of the entry.
+    pub entry_type: FolderEntryType,
+    /// Size of a file in bytes or number of items of a folder.
+    pub size: Option<u64>,
+    /// Permissions of the entry, for example `RW`.
+    pub permission: Option<String>,
+    /// Modification time of the entry, in ISO 8601 format.
+    pub modified: Option<String>,
+    /// Access time of the entry, in ISO 8601 format.
+    pub accessed: Option<String>,
+    /// Creation time of the entry, in ISO 8601 format.
+    pub created: Option<String>,
+}
+
+impl FolderEntry {
+    fn from_dict(dict: &PropMap) -> Result<Self> {
+        let name = read_opt_prop!(dict, "Name", String)
+            .ok_or_else(|| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))?;
+        let entry_type = read_opt_prop!(dict, "Type", String)
+            .ok_or_else(|| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))?
+            .parse()?;
+        Ok(Self {
+            name,
+            entry_type,
+            size: read_opt_prop!(dict, "Size", u64),
+            permission: read_opt_prop!(dict, "User-perm", String),
+            modified: read_opt_prop!(dict, "Modified", String),
+            accessed: read_opt_prop!(dict, "Accessed", String),
+            created: read_opt_prop!(dict, "Created", String),
+        })
+    }
+}
+
+/// File transfer interface of an OBEX session.
+///
+/// Use [Session::file_transfer] to obtain it.
+#[derive(Clone)]
+pub struct FileTransfer {
+    session: Session,
+}
+
+impl fmt::Debug for FileTransfer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FileTransfer {{ {} }}", self.session.dbus_path())
+    }
+}
+
+impl FileTransfer {
+    pub(crate) fn new(session: Session) -> Self {
+        Self { session }
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(SERVICE_NAME, self.session.dbus_path(), TIMEOUT, &*self.session.inner().connection)
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(INTERFACE);
+
+    fn transfer(&self, path: Path<'static>) -> Transfer {
+        Transfer::new(self.session.inner().clone(), path)
+    }
+
+    /// Changes the current folder of the remote device.
+    pub async fn change_folder(&self, folder: &str) -> Result<()> {
+        self.call_method("ChangeFolder", (folder,)).await
+    }
+
+    /// Creates a new folder on the remote device.
+    pub async fn create_folder(&self, folder: &str) -> Result<()> {
+        self.call_method("CreateFolder", (folder,)).await
+    }
+
+    /// Returns the entries of the current folder.
+    pub async fn list_folder(&self) -> Result<Vec<FolderEntry>> {
+        let (entries,): (Vec<PropMap>,) = self.call_method("ListFolder", ()).await?;
+        entries.iter().map(FolderEntry::from_dict).collect()
+    }
+
+    /// Copies the file from the remote device to the local file.
+    ///
+    /// If `target_file` is [None], a temporary file is created by the OBEX daemon.
+    /// Its name can be obtained using [Transfer::filename].
+    pub async fn get_file(&self, target_file: Option<&FsPath>, source_file: &str) -> Result<Transfer> {
+        let target_file = target_file.map(path_to_string).transpose()?.unwrap_or_default();
+        let (path, _): (Path<'static>, PropMap) = self.call_method("GetFile", (target_file, source_file)).await?;
+        Ok(self.transfer(path))
+    }
+
+    /// Copies the local file to the remote device.
+    pub async fn put_file(&self, source_file: &FsPath, target_file: &str) -> Result<Transfer> {
+        let (path, _): (Path<'static>, PropMap) =
+            self.call_method("PutFile", (path_to_string(source_file)?, target_file)).await?;
+        Ok(self.transfer(path))
+    }
+
+    /// Copies a file within the remote device.
+    pub async fn copy_file(&self, source_file: &str, target_file: &str) -> Result<()> {
+        self.call_method("CopyFile", (source_file, target_file)).await
+    }
+
+    /// Moves a file within the remote device.
+    pub async fn move_file(&self, source_file: &str, target_file: &str) -> Result<()> {
+        self.call_method("MoveFile", (source_file, target_file)).await
+    }
+
+    /// Deletes a file or an empty folder on the remote device.
+    pub async fn delete(&self, file: &str) -> Result<()> {
+        self.call_method("Delete", (file,)).await
+    }
+}
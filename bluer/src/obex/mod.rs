@@ -0,0 +1,218 @@
+//! Object Exchange (OBEX) client.
+//!
+//! File transfer, object push and phonebook access are provided by the
+//! OBEX daemon `obexd`, which runs on the D-Bus session bus.
+//!
+//! Use [Client::new] to connect to the OBEX daemon and
+//! [Client::create_session] to establish an OBEX session with a remote device.
+//! Depending on the [target](Target) of the session, the profile-specific
+//! interfaces [ObjectPush], [FileTransfer] and [PhonebookAccess] can then be used.
+//! Each operation that transfers an object returns a [Transfer], whose progress
+//! can be monitored using [Transfer::events].
+
+use dbus::{
+    arg::{PropMap, RefArg, Variant},
+    channel::Channel,
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use dbus_tokio::connection;
+use futures::channel::{mpsc, oneshot};
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+    time::Duration,
+};
+use strum::{Display, EnumString};
+use tokio::task::{spawn_blocking, JoinHandle};
+
+use crate::{session::SubscriptionReq, Address, Error, ErrorKind, Event, InternalErrorKind, Result};
+
+mod file_transfer;
+mod object_push;
+mod phonebook;
+mod session;
+mod transfer;
+
+pub use file_transfer::*;
+pub use object_push::*;
+pub use phonebook::*;
+pub use session::*;
+pub use transfer::*;
+
+pub(crate) const SERVICE_NAME: &str = "org.bluez.obex";
+pub(crate) const PATH: &str = "/org/bluez/obex";
+pub(crate) const TIMEOUT: Duration = Duration::from_secs(120);
+pub(crate) const CLIENT_INTERFACE: &str = "org.bluez.obex.Client1";
+
+/// Shared state of all objects of an OBEX client.
+pub(crate) struct ClientInner {
+    pub connection: Arc<SyncConnection>,
+    pub event_sub_tx: mpsc::Sender<SubscriptionReq>,
+    dbus_task: Option<JoinHandle<connection::IOResourceError>>,
+}
+
+impl ClientInner {
+    pub async fn events(
+        &self, path: Path<'static>, child_objects: bool,
+    ) -> Result<mpsc::UnboundedReceiver<Event>> {
+        Event::subscribe(&mut self.event_sub_tx.clone(), path, child_objects).await
+    }
+}
+
+impl Drop for ClientInner {
+    fn drop(&mut self) {
+        // documentation for dbus_tokio::connection::IOResource indicates it is abortable
+        if let Some(dbus_task) = &self.dbus_task {
+            dbus_task.abort();
+        }
+    }
+}
+
+/// OBEX session target.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Target {
+    /// File Transfer Profile.
+    #[strum(serialize = "ftp")]
+    Ftp,
+    /// Message Access Profile.
+    #[strum(serialize = "map")]
+    Map,
+    /// Object Push Profile.
+    #[strum(serialize = "opp")]
+    Opp,
+    /// Phonebook Access Profile.
+    #[strum(serialize = "pbap")]
+    Pbap,
+    /// Synchronization Profile.
+    #[strum(serialize = "sync")]
+    Sync,
+}
+
+/// Options for creating an OBEX session.
+#[derive(Clone, Debug, Default)]
+pub struct SessionOptions {
+    /// Address of the local adapter to use.
+    ///
+    /// If unspecified, the default adapter is used.
+    pub source: Option<Address>,
+    /// RFCOMM channel to connect to.
+    ///
+    /// If unspecified, the channel is discovered using SDP.
+    pub channel: Option<u8>,
+    /// L2CAP PSM to connect to.
+    pub psm: Option<u16>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl SessionOptions {
+    fn to_dict(&self, target: Target) -> PropMap {
+        let mut dict = PropMap::new();
+        dict.insert("Target".to_string(), Variant(target.to_string().box_clone()));
+        if let Some(source) = self.source {
+            dict.insert("Source".to_string(), Variant(source.to_string().box_clone()));
+        }
+        if let Some(channel) = self.channel {
+            dict.insert("Channel".to_string(), Variant(channel.box_clone()));
+        }
+        if let Some(psm) = self.psm {
+            dict.insert("PSM".to_string(), Variant(psm.box_clone()));
+        }
+        dict
+    }
+}
+
+/// OBEX client.
+///
+/// Encapsulates a connection to the OBEX daemon.
+#[derive(Clone)]
+pub struct Client {
+    inner: Arc<ClientInner>,
+}
+
+impl Debug for Client {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Client {{ {} }}", self.inner.connection.unique_name())
+    }
+}
+
+impl Client {
+    /// Create a new OBEX client.
+    ///
+    /// This establishes a connection to the OBEX daemon over the D-Bus session bus.
+    pub async fn new() -> Result<Self> {
+        let (resource, connection) = spawn_blocking(connection::new_session_sync).await??;
+        let dbus_task = tokio::spawn(resource);
+        log::trace!("Connected to D-Bus with unique name {}", &connection.unique_name());
+        Self::from_connection(connection, Some(dbus_task)).await
+    }
+
+    /// Create a new OBEX client using the D-Bus bus at the specified address.
+    ///
+    /// The address is given in D-Bus notation, for example `unix:path=/run/user/1000/bus`.
+    /// The OBEX daemon is expected to own the `org.bluez.obex` name on that bus.
+    pub async fn new_with_address(address: &str) -> Result<Self> {
+        let address = address.to_string();
+        let (resource, connection) = spawn_blocking(move || {
+            let mut channel = Channel::open_private(&address)?;
+            channel.register()?;
+            connection::from_channel::<SyncConnection>(channel)
+        })
+        .await??;
+        let dbus_task = tokio::spawn(resource);
+        log::trace!("Connected to D-Bus with unique name {}", &connection.unique_name());
+        Self::from_connection(connection, Some(dbus_task)).await
+    }
+
+    /// Create a new OBEX client using an existing D-Bus connection.
+    ///
+    /// The caller is responsible for driving the I/O resource of the connection.
+    pub async fn with_connection(connection: Arc<SyncConnection>) -> Result<Self> {
+        Self::from_connection(connection, None).await
+    }
+
+    async fn from_connection(
+        connection: Arc<SyncConnection>, dbus_task: Option<JoinHandle<connection::IOResourceError>>,
+    ) -> Result<Self> {
+        let (event_sub_tx, event_sub_rx) = mpsc::channel(1);
+        Event::handle_connection(connection.clone(), SERVICE_NAME, event_sub_rx).await?;
+        Ok(Self { inner: Arc::new(ClientInner { connection, event_sub_tx, dbus_task }) })
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(SERVICE_NAME, PATH, TIMEOUT, &*self.inner.connection)
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(CLIENT_INTERFACE);
+
+    /// Create a new OBEX session with the remote device with the specified address.
+    ///
+    /// The session is removed when the returned [Session] and all its clones are dropped.
+    pub async fn create_session(
+        &self, destination: Address, target: Target, options: SessionOptions,
+    ) -> Result<Session> {
+        let (path,): (Path<'static>,) =
+            self.call_method("CreateSession", (destination.to_string(), options.to_dict(target))).await?;
+        let (drop_tx, drop_rx) = oneshot::channel::<()>();
+        let connection = self.inner.connection.clone();
+        let remove_path = path.clone();
+        tokio::spawn(async move {
+            let _ = drop_rx.await;
+            log::trace!("Removing OBEX session {}", &remove_path);
+            let proxy = Proxy::new(SERVICE_NAME, PATH, TIMEOUT, &*connection);
+            let _: std::result::Result<(), dbus::Error> =
+                proxy.method_call(CLIENT_INTERFACE, "RemoveSession", (remove_path.clone(),)).await;
+        });
+        Ok(Session::new(self.inner.clone(), path, Arc::new(drop_tx)))
+    }
+}
+
+fn path_to_string(path: &std::path::Path) -> Result<String> {
+    path.to_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))
+}
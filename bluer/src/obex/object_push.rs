@@ -0,0 +1,67 @@
+//! OBEX object push.
+
+use dbus::{
+    arg::PropMap,
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use std::{fmt, path::Path as FsPath};
+
+use super::{path_to_string, Session, Transfer, SERVICE_NAME, TIMEOUT};
+use crate::Result;
+
+pub(crate) const INTERFACE: &str = "org.bluez.obex.ObjectPush1";
+
+/// Object push interface of an OBEX session.
+///
+/// Use [Session::object_push] to obtain it.
+#[derive(Clone)]
+pub struct ObjectPush {
+    session: Session,
+}
+
+impl fmt::Debug for ObjectPush {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ObjectPush {{ {} }}", self.session.dbus_path())
+    }
+}
+
+impl ObjectPush {
+    pub(crate) fn new(session: Session) -> Self {
+        Self { session }
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(SERVICE_NAME, self.session.dbus_path(), TIMEOUT, &*self.session.inner().connection)
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(INTERFACE);
+
+    fn transfer(&self, path: Path<'static>) -> Transfer {
+        Transfer::new(self.session.inner().clone(), path)
+    }
+
+    /// Sends the local file to the remote device.
+    pub async fn send_file(&self, source_file: &FsPath) -> Result<Transfer> {
+        let (path, _): (Path<'static>, PropMap) =
+            self.call_method("SendFile", (path_to_string(source_file)?,)).await?;
+        Ok(self.transfer(path))
+    }
+
+    /// Requests the business card of the remote device and stores it in the local file.
+    pub async fn pull_business_card(&self, target_file: &FsPath) -> Result<Transfer> {
+        let (path, _): (Path<'static>, PropMap) =
+            self.call_method("PullBusinessCard", (path_to_string(target_file)?,)).await?;
+        Ok(self.transfer(path))
+    }
+
+    /// Pushes the local business card to the remote device and
+    /// retrieves the business card of the remote device into the local file.
+    pub async fn exchange_business_cards(&self, client_file: &FsPath, target_file: &FsPath) -> Result<Transfer> {
+        let (path, _): (Path<'static>, PropMap) = self
+            .call_method("ExchangeBusinessCards", (path_to_string(client_file)?, path_to_string(target_file)?))
+            .await?;
+        Ok(self.transfer(path))
+    }
+}
@@ -0,0 +1,235 @@
+//! OBEX phonebook access.
+
+use dbus::{
+    arg::{PropMap, RefArg, Variant},
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use std::{fmt, path::Path as FsPath};
+use strum::{Display, EnumString};
+
+use super::{path_to_string, Session, Transfer, SERVICE_NAME, TIMEOUT};
+use crate::Result;
+
+pub(crate) const INTERFACE: &str = "org.bluez.obex.PhonebookAccess1";
+
+/// Location of a phonebook on the remote device.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum PhonebookLocation {
+    /// Internal memory.
+    #[strum(serialize = "int")]
+    Internal,
+    /// First SIM card.
+    #[strum(serialize = "sim1")]
+    Sim1,
+    /// Second SIM card.
+    #[strum(serialize = "sim2")]
+    Sim2,
+    /// Third SIM card.
+    #[strum(serialize = "sim3")]
+    Sim3,
+}
+
+/// Phonebook object of the remote device.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Phonebook {
+    /// Phonebook.
+    #[strum(serialize = "pb")]
+    Phonebook,
+    /// Incoming call history.
+    #[strum(serialize = "ich")]
+    IncomingCalls,
+    /// Outgoing call history.
+    #[strum(serialize = "och")]
+    OutgoingCalls,
+    /// Missed call history.
+    #[strum(serialize = "mch")]
+    MissedCalls,
+    /// Combined call history.
+    #[strum(serialize = "cch")]
+    CombinedCalls,
+    /// Speed dials.
+    #[strum(serialize = "spd")]
+    SpeedDials,
+    /// Favorites.
+    #[strum(serialize = "fav")]
+    Favorites,
+}
+
+/// vCard format.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VcardFormat {
+    /// vCard 2.1.
+    #[strum(serialize = "vcard21")]
+    Vcard21,
+    /// vCard 3.0.
+    #[strum(serialize = "vcard30")]
+    Vcard30,
+}
+
+/// Sorting order of vCard listings.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ListOrder {
+    /// Indexed order.
+    #[strum(serialize = "indexed")]
+    Indexed,
+    /// Alphanumeric order.
+    #[strum(serialize = "alphanumeric")]
+    Alphanumeric,
+    /// Phonetic order.
+    #[strum(serialize = "phonetic")]
+    Phonetic,
+}
+
+/// Search field of a phonebook search.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SearchField {
+    /// Name.
+    #[strum(serialize = "name")]
+    Name,
+    /// Phone number.
+    #[strum(serialize = "number")]
+    Number,
+    /// Sound.
+    #[strum(serialize = "sound")]
+    Sound,
+}
+
+/// Filter for phonebook pulls, listings and searches.
+///
+/// Fields that are [None] are not included in the request.
+#[derive(Clone, Debug, Default)]
+pub struct PhonebookFilter {
+    /// vCard format.
+    pub format: Option<VcardFormat>,
+    /// Sorting order.
+    pub order: Option<ListOrder>,
+    /// Offset of the first item.
+    pub offset: Option<u16>,
+    /// Maximum number of items.
+    pub max_count: Option<u16>,
+    /// vCard fields to include, for example `VERSION` or `FN`.
+    pub fields: Option<Vec<String>>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl PhonebookFilter {
+    fn to_dict(&self) -> PropMap {
+        let mut dict = PropMap::new();
+        if let Some(format) = self.format {
+            dict.insert("Format".to_string(), Variant(format.to_string().box_clone()));
+        }
+        if let Some(order) = self.order {
+            dict.insert("Order".to_string(), Variant(order.to_string().box_clone()));
+        }
+        if let Some(offset) = self.offset {
+            dict.insert("Offset".to_string(), Variant(offset.box_clone()));
+        }
+        if let Some(max_count) = self.max_count {
+            dict.insert("MaxCount".to_string(), Variant(max_count.box_clone()));
+        }
+        if let Some(fields) = &self.fields {
+            dict.insert("Fields".to_string(), Variant(fields.box_clone()));
+        }
+        dict
+    }
+}
+
+/// Entry of a vCard listing.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct VcardEntry {
+    /// Handle of the vCard, for use with [PhonebookAccess::pull].
+    pub vcard: String,
+    /// Name of the contact.
+    pub name: String,
+}
+
+/// Phonebook access interface of an OBEX session.
+///
+/// Use [Session::phonebook_access] to obtain it.
+#[derive(Clone)]
+pub struct PhonebookAccess {
+    session: Session,
+}
+
+impl fmt::Debug for PhonebookAccess {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PhonebookAccess {{ {} }}", self.session.dbus_path())
+    }
+}
+
+impl PhonebookAccess {
+    pub(crate) fn new(session: Session) -> Self {
+        Self { session }
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(SERVICE_NAME, self.session.dbus_path(), TIMEOUT, &*self.session.inner().connection)
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(INTERFACE);
+
+    fn transfer(&self, path: Path<'static>) -> Transfer {
+        Transfer::new(self.session.inner().clone(), path)
+    }
+
+    /// Selects the phonebook object for subsequent operations.
+    pub async fn select(&self, location: PhonebookLocation, phonebook: Phonebook) -> Result<()> {
+        self.call_method("Select", (location.to_string(), phonebook.to_string())).await
+    }
+
+    /// Retrieves the entire selected phonebook into the local file.
+    ///
+    /// If `target_file` is [None], a temporary file is created by the OBEX daemon.
+    /// Its name can be obtained using [Transfer::filename].
+    pub async fn pull_all(&self, target_file: Option<&FsPath>, filter: &PhonebookFilter) -> Result<Transfer> {
+        let target_file = target_file.map(path_to_string).transpose()?.unwrap_or_default();
+        let (path, _): (Path<'static>, PropMap) =
+            self.call_method("PullAll", (target_file, filter.to_dict())).await?;
+        Ok(self.transfer(path))
+    }
+
+    /// Retrieves the vCard with the specified handle from the selected phonebook into the local file.
+    ///
+    /// If `target_file` is [None], a temporary file is created by the OBEX daemon.
+    pub async fn pull(
+        &self, vcard: &str, target_file: Option<&FsPath>, filter: &PhonebookFilter,
+    ) -> Result<Transfer> {
+        let target_file = target_file.map(path_to_string).transpose()?.unwrap_or_default();
+        let (path, _): (Path<'static>, PropMap) =
+            self.call_method("Pull", (vcard, target_file, filter.to_dict())).await?;
+        Ok(self.transfer(path))
+    }
+
+    /// Lists the vCards of the selected phonebook.
+    pub async fn list(&self, filter: &PhonebookFilter) -> Result<Vec<VcardEntry>> {
+        let (entries,): (Vec<(String, String)>,) = self.call_method("List", (filter.to_dict(),)).await?;
+        Ok(entries.into_iter().map(|(vcard, name)| VcardEntry { vcard, name }).collect())
+    }
+
+    /// Searches the selected phonebook for vCards whose field matches the value.
+    pub async fn search(
+        &self, field: SearchField, value: &str, filter: &PhonebookFilter,
+    ) -> Result<Vec<VcardEntry>> {
+        let (entries,): (Vec<(String, String)>,) =
+            self.call_method("Search", (field.to_string(), value, filter.to_dict())).await?;
+        Ok(entries.into_iter().map(|(vcard, name)| VcardEntry { vcard, name }).collect())
+    }
+
+    /// Number of entries in the selected phonebook.
+    pub async fn size(&self) -> Result<u16> {
+        let (size,): (u16,) = self.call_method("GetSize", ()).await?;
+        Ok(size)
+    }
+}
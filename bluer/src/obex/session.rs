@@ -0,0 +1,131 @@
+//! OBEX sessions.
+
+use dbus::{
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use futures::channel::oneshot;
+use std::{fmt, sync::Arc};
+use uuid::Uuid;
+
+use super::{ClientInner, FileTransfer, ObjectPush, PhonebookAccess, SERVICE_NAME, TIMEOUT};
+use crate::{Address, Error, ErrorKind, InternalErrorKind, Result};
+
+pub(crate) const INTERFACE: &str = "org.bluez.obex.Session1";
+
+/// OBEX session with a remote device.
+///
+/// Use [Client::create_session](super::Client::create_session) to create a session.
+/// The session is removed when this and all its clones are dropped.
+#[derive(Clone)]
+pub struct Session {
+    inner: Arc<ClientInner>,
+    dbus_path: Path<'static>,
+    _drop_tx: Arc<oneshot::Sender<()>>,
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Session {{ {} }}", &self.dbus_path)
+    }
+}
+
+impl Session {
+    pub(crate) fn new(
+        inner: Arc<ClientInner>, dbus_path: Path<'static>, drop_tx: Arc<oneshot::Sender<()>>,
+    ) -> Self {
+        Self { inner, dbus_path, _drop_tx: drop_tx }
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(SERVICE_NAME, &self.dbus_path, TIMEOUT, &*self.inner.connection)
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(INTERFACE);
+
+    /// D-Bus object path of the session.
+    pub fn path(&self) -> &Path<'static> {
+        &self.dbus_path
+    }
+
+    /// Object push interface of this session.
+    ///
+    /// Only available if the session was created with [Target::Opp](super::Target::Opp).
+    pub fn object_push(&self) -> ObjectPush {
+        ObjectPush::new(self.clone())
+    }
+
+    /// File transfer interface of this session.
+    ///
+    /// Only available if the session was created with [Target::Ftp](super::Target::Ftp).
+    pub fn file_transfer(&self) -> FileTransfer {
+        FileTransfer::new(self.clone())
+    }
+
+    /// Phonebook access interface of this session.
+    ///
+    /// Only available if the session was created with [Target::Pbap](super::Target::Pbap).
+    pub fn phonebook_access(&self) -> PhonebookAccess {
+        PhonebookAccess::new(self.clone())
+    }
+
+    pub(crate) fn inner(&self) -> &Arc<ClientInner> {
+        &self.inner
+    }
+
+    pub(crate) fn dbus_path(&self) -> &Path<'static> {
+        &self.dbus_path
+    }
+}
+
+define_properties!(
+    Session,
+    /// OBEX session property.
+    pub SessionProperty => {
+        /// Bluetooth address of the local adapter.
+        property(
+            Source, Address,
+            dbus: (INTERFACE, "Source", String, MANDATORY),
+            get: (source, v => {v.parse()?}),
+        );
+
+        /// Bluetooth address of the remote device.
+        property(
+            Destination, Address,
+            dbus: (INTERFACE, "Destination", String, MANDATORY),
+            get: (destination, v => {v.parse()?}),
+        );
+
+        /// RFCOMM channel of the session.
+        property(
+            Channel, u8,
+            dbus: (INTERFACE, "Channel", u8, OPTIONAL),
+            get: (channel, v => {v.to_owned()}),
+        );
+
+        /// L2CAP PSM of the session.
+        property(
+            Psm, u16,
+            dbus: (INTERFACE, "PSM", u16, OPTIONAL),
+            get: (psm, v => {v.to_owned()}),
+        );
+
+        /// Target UUID of the session.
+        property(
+            Target, Uuid,
+            dbus: (INTERFACE, "Target", String, MANDATORY),
+            get: (target, v => {
+                v.parse()
+                    .map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidUuid(v.to_string()))))?
+            }),
+        );
+
+        /// Root path of the session.
+        property(
+            Root, String,
+            dbus: (INTERFACE, "Root", String, OPTIONAL),
+            get: (root, v => {v.to_owned()}),
+        );
+    }
+);
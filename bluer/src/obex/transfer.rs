@@ -0,0 +1,191 @@
+//! OBEX transfers.
+
+use dbus::{
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use futures::{stream, Stream, StreamExt};
+use std::{fmt, sync::Arc};
+use strum::{Display, EnumString};
+
+use super::{ClientInner, SERVICE_NAME, TIMEOUT};
+use crate::{ErrorKind, Event, Result};
+
+pub(crate) const INTERFACE: &str = "org.bluez.obex.Transfer1";
+
+/// Status of an OBEX transfer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum TransferStatus {
+    /// Transfer is queued.
+    #[strum(serialize = "queued")]
+    Queued,
+    /// Transfer is in progress.
+    #[strum(serialize = "active")]
+    Active,
+    /// Transfer is suspended.
+    #[strum(serialize = "suspended")]
+    Suspended,
+    /// Transfer has completed successfully.
+    #[strum(serialize = "complete")]
+    Complete,
+    /// Transfer has failed.
+    #[strum(serialize = "error")]
+    Error,
+}
+
+impl TransferStatus {
+    /// Whether the transfer has finished, either successfully or with an error.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Complete | Self::Error)
+    }
+}
+
+/// OBEX transfer of an object.
+///
+/// Transfers are returned by the methods of
+/// [ObjectPush](super::ObjectPush), [FileTransfer](super::FileTransfer)
+/// and [PhonebookAccess](super::PhonebookAccess).
+/// The transfer object is removed by the OBEX daemon once the transfer has finished.
+#[derive(Clone)]
+pub struct Transfer {
+    inner: Arc<ClientInner>,
+    dbus_path: Path<'static>,
+}
+
+impl fmt::Debug for Transfer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Transfer {{ {} }}", &self.dbus_path)
+    }
+}
+
+impl Transfer {
+    pub(crate) fn new(inner: Arc<ClientInner>, dbus_path: Path<'static>) -> Self {
+        Self { inner, dbus_path }
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(SERVICE_NAME, &self.dbus_path, TIMEOUT, &*self.inner.connection)
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(INTERFACE);
+
+    /// D-Bus object path of the transfer.
+    pub fn path(&self) -> &Path<'static> {
+        &self.dbus_path
+    }
+
+    /// Streams transfer property changes, including progress and status changes.
+    ///
+    /// The first event is the current [status](TransferProperty::Status) of the transfer,
+    /// so that a transfer that finished before this was called is still observed.
+    /// The stream ends when the transfer object is removed,
+    /// which happens shortly after the transfer has finished.
+    /// If it has already been removed, the stream is empty.
+    pub async fn events(&self) -> Result<impl Stream<Item = TransferEvent>> {
+        // Subscribe before reading the status to not miss changes in between.
+        let events = self.inner.events(self.dbus_path.clone(), false).await?;
+        let (status, events) = match self.status().await {
+            Ok(status) => (Some(TransferEvent::PropertyChanged(TransferProperty::Status(status))), Some(events)),
+            Err(err) if err.kind == ErrorKind::NotFound => (None, None),
+            Err(err) => return Err(err),
+        };
+
+        let stream = stream::iter(events).flatten().flat_map(move |event| match event {
+            Event::PropertiesChanged { interface, changed, .. } if interface == INTERFACE => stream::iter(
+                TransferProperty::from_prop_map(changed).into_iter().map(TransferEvent::PropertyChanged),
+            )
+            .boxed(),
+            _ => stream::empty().boxed(),
+        });
+
+        Ok(stream::iter(status).chain(stream))
+    }
+
+    // ===========================================================================================
+    // Methods
+    // ===========================================================================================
+
+    /// Cancels the transfer.
+    pub async fn cancel(&self) -> Result<()> {
+        self.call_method("Cancel", ()).await
+    }
+
+    /// Suspends the transfer.
+    pub async fn suspend(&self) -> Result<()> {
+        self.call_method("Suspend", ()).await
+    }
+
+    /// Resumes a suspended transfer.
+    pub async fn resume(&self) -> Result<()> {
+        self.call_method("Resume", ()).await
+    }
+}
+
+define_properties!(
+    Transfer,
+    /// OBEX transfer property.
+    pub TransferProperty => {
+        /// Status of the transfer.
+        property(
+            Status, TransferStatus,
+            dbus: (INTERFACE, "Status", String, MANDATORY),
+            get: (status, v => {v.parse()?}),
+        );
+
+        /// Name of the transferred object.
+        property(
+            Name, String,
+            dbus: (INTERFACE, "Name", String, OPTIONAL),
+            get: (name, v => {v.to_owned()}),
+        );
+
+        /// Type of the transferred object.
+        property(
+            Type, String,
+            dbus: (INTERFACE, "Type", String, OPTIONAL),
+            get: (object_type, v => {v.to_owned()}),
+        );
+
+        /// Time of the transferred object, if provided by the remote device.
+        property(
+            Time, u64,
+            dbus: (INTERFACE, "Time", u64, OPTIONAL),
+            get: (time, v => {v.to_owned()}),
+        );
+
+        /// Size of the transferred object in bytes, if known.
+        property(
+            Size, u64,
+            dbus: (INTERFACE, "Size", u64, OPTIONAL),
+            get: (size, v => {v.to_owned()}),
+        );
+
+        /// Number of bytes transferred so far.
+        ///
+        /// Not all intermediate values are reported.
+        property(
+            Transferred, u64,
+            dbus: (INTERFACE, "Transferred", u64, OPTIONAL),
+            get: (transferred, v => {v.to_owned()}),
+        );
+
+        /// Complete name of the local file used for the transfer.
+        property(
+            Filename, String,
+            dbus: (INTERFACE, "Filename", String, OPTIONAL),
+            get: (filename, v => {v.to_owned()}),
+        );
+    }
+);
+
+/// OBEX transfer event.
+#[cfg_attr(docsrs, doc(cfg(feature = "obex")))]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TransferEvent {
+    /// Property changed.
+    PropertyChanged(TransferProperty),
+}
@@ -344,7 +344,7 @@ impl RegisteredProfile {
         log::trace!("Registering profile at {}", &name);
         let proxy = Proxy::new(SERVICE_NAME, MANAGER_PATH, TIMEOUT, inner.connection.clone());
         proxy
-            .method_call(
+            .method_call::<(), _, _, _>(
                 MANAGER_INTERFACE,
                 "RegisterProfile",
                 (name.clone(), profile.uuid.to_string(), profile.to_dict()),
@@ -0,0 +1,572 @@
+//! Synchronous connection-oriented (SCO) sockets.
+//!
+//! SCO sockets carry voice data over classic Bluetooth (BR/EDR) links.
+//! They are used by the Hands-Free Profile (HFP) and Headset Profile (HSP)
+//! for the audio connection, while the control connection is usually established
+//! using [RFCOMM](crate::rfcomm).
+//!
+//! A SCO connection transfers packets of fixed maximum length,
+//! which can be obtained using [Stream::send_mtu] and [Stream::recv_mtu]
+//! once the connection has been established.
+//! The format of the transferred data is determined by the [voice setting](Voice).
+//!
+
+use crate::{
+    sock::{self, OwnedFd},
+    sys::{
+        bt_voice, sockaddr_sco, BTPROTO_SCO, BT_DEFER_SETUP, BT_RCVMTU, BT_SNDMTU, BT_VOICE, BT_VOICE_CVSD_16BIT,
+        BT_VOICE_TRANSPARENT, SCO_CONNINFO, SCO_OPTIONS, SOL_SCO,
+    },
+    Address,
+};
+use futures::ready;
+use libc::{
+    AF_BLUETOOTH, EAGAIN, EINPROGRESS, MSG_PEEK, SHUT_RD, SHUT_RDWR, SHUT_WR, SOCK_SEQPACKET, SOL_BLUETOOTH,
+    SOL_SOCKET, SO_ERROR, SO_RCVBUF, TIOCINQ, TIOCOUTQ,
+};
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::FromPrimitive;
+use std::{
+    convert::TryInto,
+    fmt,
+    io::{Error, ErrorKind, Result},
+    mem::ManuallyDrop,
+    net::Shutdown,
+    os::{
+        raw::c_int,
+        unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
+    },
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+use tokio::io::{unix::AsyncFd, AsyncRead, AsyncWrite, ReadBuf};
+
+pub use crate::sys::{sco_conninfo as ConnInfo, sco_options as Opts};
+
+/// A SCO socket address.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SocketAddr {
+    /// Device address.
+    ///
+    /// When listening or binding, specify [Address::any] for any local adapter address.
+    pub addr: Address,
+}
+
+impl SocketAddr {
+    /// Creates a new SCO socket address.
+    pub const fn new(addr: Address) -> Self {
+        Self { addr }
+    }
+
+    /// When specified to [Socket::bind] binds to any local adapter address.
+    pub const fn any() -> Self {
+        Self { addr: Address::any() }
+    }
+}
+
+impl sock::SysSockAddr for SocketAddr {
+    type SysSockAddr = sockaddr_sco;
+
+    fn into_sys_sock_addr(self) -> Self::SysSockAddr {
+        sockaddr_sco { sco_family: AF_BLUETOOTH as _, sco_bdaddr: self.addr.into() }
+    }
+
+    fn try_from_sys_sock_addr(saddr: Self::SysSockAddr) -> Result<Self> {
+        if saddr.sco_family != AF_BLUETOOTH as _ {
+            return Err(Error::new(ErrorKind::InvalidInput, "sockaddr_sco::sco_family is not AF_BLUETOOTH"));
+        }
+        Ok(Self { addr: Address::from(saddr.sco_bdaddr) })
+    }
+}
+
+/// SCO voice setting.
+///
+/// This determines the format of the data transferred over a SCO socket.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash, FromPrimitive, ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Voice {
+    /// 16-bit linear PCM audio data, which is CVSD encoded by the controller.
+    ///
+    /// This is the default setting and is used for narrowband speech.
+    #[default]
+    Cvsd16Bit = BT_VOICE_CVSD_16BIT as _,
+    /// Transparent data, which is transmitted unmodified by the controller.
+    ///
+    /// This is used for audio data encoded by the host, for example mSBC for wideband speech.
+    Transparent = BT_VOICE_TRANSPARENT as _,
+}
+
+/// A SCO socket that has not yet been converted to a [Listener] or [Stream].
+///
+/// The primary use of this is to configure the socket before connecting or listening.
+pub struct Socket {
+    fd: AsyncFd<OwnedFd>,
+}
+
+impl fmt::Debug for Socket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Socket").field("fd", &self.fd.as_raw_fd()).finish()
+    }
+}
+
+impl Socket {
+    /// Creates a new socket of sequential packet type.
+    pub fn new() -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(sock::socket(AF_BLUETOOTH, SOCK_SEQPACKET, BTPROTO_SCO)?)? })
+    }
+
+    /// Convert the socket into a [Listener].
+    ///
+    /// `backlog` defines the maximum number of pending connections are queued by the operating system
+    /// at any given time.
+    pub fn listen(self, backlog: u32) -> Result<Listener> {
+        sock::listen(
+            self.fd.get_ref(),
+            backlog.try_into().map_err(|_| Error::new(ErrorKind::InvalidInput, "invalid backlog"))?,
+        )?;
+        Ok(Listener { socket: self })
+    }
+
+    /// Establish a connection with a peer at the specified socket address.
+    pub async fn connect(self, sa: SocketAddr) -> Result<Stream> {
+        self.connect_priv(sa).await?;
+        Stream::from_socket(self)
+    }
+
+    /// Bind the socket to the given address.
+    pub fn bind(&self, sa: SocketAddr) -> Result<()> {
+        sock::bind(self.fd.get_ref(), sa)
+    }
+
+    /// Get the local address of this socket.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        sock::getsockname(self.fd.get_ref())
+    }
+
+    /// Get the peer address of this socket.
+    fn peer_addr_priv(&self) -> Result<SocketAddr> {
+        sock::getpeername(self.fd.get_ref())
+    }
+
+    /// Get voice setting.
+    ///
+    /// This corresponds to the `BT_VOICE` socket option.
+    pub fn voice(&self) -> Result<Voice> {
+        let value: bt_voice = sock::getsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_VOICE)?;
+        Voice::from_u16(value.setting).ok_or_else(|| Error::new(ErrorKind::InvalidInput, "invalid voice setting"))
+    }
+
+    /// Set voice setting.
+    ///
+    /// This must be set before connecting.
+    /// For incoming connections it must be set on the accepted socket while
+    /// [connection setup is deferred](Self::set_defer_setup).
+    ///
+    /// This corresponds to the `BT_VOICE` socket option.
+    pub fn set_voice(&self, voice: Voice) -> Result<()> {
+        let value = bt_voice { setting: voice as _ };
+        sock::setsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_VOICE, &value)
+    }
+
+    /// Get whether connection setup is deferred.
+    ///
+    /// This corresponds to the `BT_DEFER_SETUP` socket option.
+    pub fn is_defer_setup(&self) -> Result<bool> {
+        let value: u32 = sock::getsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_DEFER_SETUP)?;
+        Ok(value != 0)
+    }
+
+    /// Set whether connection setup is deferred.
+    ///
+    /// When enabled on a listening socket, incoming connections are accepted
+    /// before they are confirmed to the remote device.
+    /// The connection is confirmed when data is first received from the accepted socket,
+    /// which allows to configure the [voice setting](Self::set_voice) before.
+    ///
+    /// This corresponds to the `BT_DEFER_SETUP` socket option.
+    pub fn set_defer_setup(&self, defer_setup: bool) -> Result<()> {
+        let value: u32 = defer_setup.into();
+        sock::setsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_DEFER_SETUP, &value)
+    }
+
+    /// Get maximum transmission unit (MTU) for sending.
+    ///
+    /// This corresponds to the `BT_SNDMTU` socket option.
+    ///
+    /// This value is only available once the connection has been established.
+    pub fn send_mtu(&self) -> Result<u16> {
+        let value: u32 = sock::getsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_SNDMTU)?;
+        Ok(value as _)
+    }
+
+    /// Get maximum transmission unit (MTU) for receiving.
+    ///
+    /// This corresponds to the `BT_RCVMTU` socket option.
+    ///
+    /// This value is only available once the connection has been established.
+    pub fn recv_mtu(&self) -> Result<u16> {
+        let value: u32 = sock::getsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_RCVMTU)?;
+        Ok(value as _)
+    }
+
+    /// Gets the maximum socket receive buffer in bytes.
+    ///
+    /// This corresponds to the `SO_RCVBUF` socket option.
+    pub fn recv_buffer(&self) -> Result<i32> {
+        sock::getsockopt(self.fd.get_ref(), SOL_SOCKET, SO_RCVBUF)
+    }
+
+    /// Sets the maximum socket receive buffer in bytes.
+    ///
+    /// This corresponds to the `SO_RCVBUF` socket option.
+    pub fn set_recv_buffer(&self, recv_buffer: i32) -> Result<()> {
+        sock::setsockopt(self.fd.get_ref(), SOL_SOCKET, SO_RCVBUF, &recv_buffer)
+    }
+
+    /// Gets the raw SCO socket options.
+    ///
+    /// This corresponds to the `SCO_OPTIONS` socket option.
+    pub fn sco_opts(&self) -> Result<Opts> {
+        sock::getsockopt(self.fd.get_ref(), SOL_SCO, SCO_OPTIONS)
+    }
+
+    /// Gets the SCO socket connection information.
+    ///
+    /// This corresponds to the `SCO_CONNINFO` socket option.
+    pub fn conn_info(&self) -> Result<ConnInfo> {
+        sock::getsockopt(self.fd.get_ref(), SOL_SCO, SCO_CONNINFO)
+    }
+
+    /// Get the number of bytes in the input buffer.
+    ///
+    /// This corresponds to the `TIOCINQ` IOCTL.
+    pub fn input_buffer(&self) -> Result<u32> {
+        let value: c_int = sock::ioctl_read(self.fd.get_ref(), TIOCINQ)?;
+        Ok(value as _)
+    }
+
+    /// Get the number of bytes in the output buffer.
+    ///
+    /// This corresponds to the `TIOCOUTQ` IOCTL.
+    pub fn output_buffer(&self) -> Result<u32> {
+        let value: c_int = sock::ioctl_read(self.fd.get_ref(), TIOCOUTQ)?;
+        Ok(value as _)
+    }
+
+    /// Constructs a new [Socket] from the given raw file descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// This function *consumes ownership* of the specified file descriptor.
+    /// The returned object will take responsibility for closing it when the object goes out of scope.
+    ///
+    /// # Safety
+    /// If the passed file descriptor is invalid, undefined behavior may occur.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(OwnedFd::new(fd))? })
+    }
+
+    fn from_owned_fd(fd: OwnedFd) -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(fd)? })
+    }
+
+    sock_priv!();
+}
+
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for Socket {
+    fn into_raw_fd(self) -> RawFd {
+        self.fd.into_inner().into_raw_fd()
+    }
+}
+
+impl FromRawFd for Socket {
+    /// Constructs a new instance of `Self` from the given raw file
+    /// descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// # Panics
+    /// Panics when the conversion fails.
+    /// Use [Socket::from_raw_fd] for a non-panicking variant.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_raw_fd(fd).expect("from_raw_fd failed")
+    }
+}
+
+/// A SCO socket server, listening for [Stream] connections.
+#[derive(Debug)]
+pub struct Listener {
+    socket: Socket,
+}
+
+impl Listener {
+    /// Creates a new Listener, which will be bound to the specified socket address.
+    ///
+    /// Specify [SocketAddr::any] for any local adapter address.
+    pub async fn bind(sa: SocketAddr) -> Result<Self> {
+        let socket = Socket::new()?;
+        socket.bind(sa)?;
+        socket.listen(1)
+    }
+
+    /// Accepts a new incoming connection from this listener.
+    pub async fn accept(&self) -> Result<(Stream, SocketAddr)> {
+        let (socket, sa) = self.socket.accept_priv().await?;
+        Ok((Stream::from_socket(socket)?, sa))
+    }
+
+    /// Polls to accept a new incoming connection to this listener.
+    pub fn poll_accept(&self, cx: &mut Context) -> Poll<Result<(Stream, SocketAddr)>> {
+        let (socket, sa) = ready!(self.socket.poll_accept_priv(cx))?;
+        Poll::Ready(Ok((Stream::from_socket(socket)?, sa)))
+    }
+
+    /// Constructs a new [Listener] from the given raw file descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// This function *consumes ownership* of the specified file descriptor.
+    /// The returned object will take responsibility for closing it when the object goes out of scope.
+    ///
+    /// # Safety
+    /// If the passed file descriptor is invalid, undefined behavior may occur.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        Ok(Self { socket: Socket::from_raw_fd(fd)? })
+    }
+}
+
+impl AsRef<Socket> for Listener {
+    fn as_ref(&self) -> &Socket {
+        &self.socket
+    }
+}
+
+impl AsRawFd for Listener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl FromRawFd for Listener {
+    /// Constructs a new instance of `Self` from the given raw file
+    /// descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// # Panics
+    /// Panics when the conversion fails.
+    /// Use [Listener::from_raw_fd] for a non-panicking variant.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_raw_fd(fd).expect("from_raw_fd failed")
+    }
+}
+
+/// A SCO connection between a local and remote socket (sequenced, two-way connection-based
+/// transmission of packets of fixed maximum length).
+///
+/// Packets can be sent and received using [send](Self::send) and [recv](Self::recv).
+/// Alternatively, [AsyncRead] and [AsyncWrite] are implemented, where each read
+/// receives one packet and each write sends one packet of at most [send_mtu](Self::send_mtu) bytes.
+#[derive(Debug)]
+pub struct Stream {
+    socket: Socket,
+    send_mtu: AtomicUsize,
+}
+
+impl Stream {
+    /// Create Stream from Socket.
+    fn from_socket(socket: Socket) -> Result<Self> {
+        Ok(Self { socket, send_mtu: 0.into() })
+    }
+
+    /// Establish a connection with a peer at the specified socket address.
+    ///
+    /// Uses any local Bluetooth adapter and the default [voice setting](Voice).
+    pub async fn connect(addr: SocketAddr) -> Result<Self> {
+        let socket = Socket::new()?;
+        socket.bind(SocketAddr::any())?;
+        socket.connect(addr).await
+    }
+
+    /// Gets the peer address of this stream.
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        self.socket.peer_addr_priv()
+    }
+
+    /// Sends a packet.
+    ///
+    /// The packet length must not exceed the [Self::send_mtu].
+    pub async fn send(&self, buf: &[u8]) -> Result<usize> {
+        self.socket.send_priv(buf).await
+    }
+
+    /// Attempts to send a packet.
+    ///
+    /// The packet length must not exceed the [Self::send_mtu].
+    pub fn poll_send(&self, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        self.socket.poll_send_priv(cx, buf)
+    }
+
+    /// Receives a packet.
+    ///
+    /// The provided buffer must be of length [Self::recv_mtu], otherwise
+    /// the packet may be truncated.
+    pub async fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+        self.socket.recv_priv(buf).await
+    }
+
+    /// Attempts to receive a packet.
+    ///
+    /// The provided buffer must be of length [Self::recv_mtu], otherwise
+    /// the packet may be truncated.
+    pub fn poll_recv(&self, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<()>> {
+        self.socket.poll_recv_priv(cx, buf)
+    }
+
+    /// Receives data on the socket from the remote address to which it is connected,
+    /// without removing that data from the queue.
+    /// On success, returns the number of bytes peeked.
+    pub async fn peek(&self, buf: &mut [u8]) -> Result<usize> {
+        self.socket.peek_priv(buf).await
+    }
+
+    /// Attempts to receive data on the socket, without removing that data from
+    /// the queue, registering the current task for wakeup if data is not yet available.
+    pub fn poll_peek(&self, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<usize>> {
+        self.socket.poll_peek_priv(cx, buf)
+    }
+
+    /// Shuts down the read, write, or both halves of this connection.
+    pub fn shutdown(&self, how: Shutdown) -> Result<()> {
+        self.socket.shutdown_priv(how)
+    }
+
+    /// Maximum transmission unit (MTU) for sending.
+    pub fn send_mtu(&self) -> Result<usize> {
+        self.socket.send_mtu().map(|v| v.into())
+    }
+
+    /// Maximum transmission unit (MTU) for receiving.
+    pub fn recv_mtu(&self) -> Result<usize> {
+        self.socket.recv_mtu().map(|v| v.into())
+    }
+
+    /// Splits the stream into a borrowed read half and a borrowed write half, which can be used
+    /// to read and write the stream concurrently.
+    #[allow(clippy::needless_lifetimes)]
+    pub fn split<'a>(&'a mut self) -> (stream::ReadHalf<'a>, stream::WriteHalf<'a>) {
+        (stream::ReadHalf(self), stream::WriteHalf(self))
+    }
+
+    /// Splits the into an owned read half and an owned write half, which can be used to read
+    /// and write the stream concurrently.
+    pub fn into_split(self) -> (stream::OwnedReadHalf, stream::OwnedWriteHalf) {
+        let stream = Arc::new(self);
+        let r = stream::OwnedReadHalf {
+            stream: ManuallyDrop::new(stream.clone()),
+            shutdown_on_drop: true,
+            drop: true,
+        };
+        let w = stream::OwnedWriteHalf { stream, shutdown_on_drop: true };
+        (r, w)
+    }
+
+    fn poll_write_priv(&self, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        // Trying to send more than the MTU on a SCO socket results in an error.
+        // Thus we truncate the send buffer appropriately and return the number of
+        // actually transmitted bytes.
+        //
+        // If the send MTU is not available yet, which is the case while connection
+        // setup is deferred, we pass the buffer unmodified to the socket.
+        let send_mtu = match self.send_mtu.load(Ordering::Acquire) {
+            0 => match self.socket.send_mtu() {
+                Ok(mtu) => {
+                    let mtu = mtu.into();
+                    log::trace!("Obtained send MTU {}", mtu);
+                    self.send_mtu.store(mtu, Ordering::Release);
+                    mtu
+                }
+                Err(_) => buf.len(),
+            },
+            mtu => mtu,
+        };
+        let max_len = buf.len().min(send_mtu);
+        let buf = &buf[..max_len];
+
+        self.socket.poll_send_priv(cx, buf)
+    }
+
+    /// Constructs a new [Stream] from the given raw file descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// This function *consumes ownership* of the specified file descriptor.
+    /// The returned object will take responsibility for closing it when the object goes out of scope.
+    ///
+    /// # Safety
+    /// If the passed file descriptor is invalid, undefined behavior may occur.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        Self::from_socket(Socket::from_raw_fd(fd)?)
+    }
+}
+
+impl AsRef<Socket> for Stream {
+    fn as_ref(&self) -> &Socket {
+        &self.socket
+    }
+}
+
+impl AsRawFd for Stream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl FromRawFd for Stream {
+    /// Constructs a new instance of `Self` from the given raw file
+    /// descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// # Panics
+    /// Panics when the conversion fails.
+    /// Use [Stream::from_raw_fd] for a non-panicking variant.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_raw_fd(fd).expect("from_raw_fd failed")
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<()>> {
+        self.socket.poll_recv_priv(cx, buf)
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        self.poll_write_priv(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+        self.socket.poll_flush_priv(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+        self.socket.poll_shutdown_priv(cx, Shutdown::Write)
+    }
+}
+
+#[allow(clippy::duplicate_mod)]
+#[path = "stream_util.rs"]
+pub mod stream;
@@ -2,6 +2,7 @@
 
 use dbus::{
     arg::Variant,
+    channel::Channel,
     message::MatchRule,
     nonblock::{
         stdintf::org_freedesktop_dbus::{
@@ -17,9 +18,9 @@ use dbus_tokio::connection;
 use futures::{
     channel::{mpsc, oneshot},
     lock::Mutex,
+    stream::SelectAll,
     Future, SinkExt, Stream, StreamExt,
 };
-use lazy_static::lazy_static;
 use std::{
     collections::{HashMap, HashSet},
     fmt::{Debug, Formatter},
@@ -29,12 +30,16 @@ use tokio::{
     select,
     task::{spawn_blocking, JoinHandle},
 };
+use tokio_stream::wrappers::ReceiverStream;
 
 use crate::{
     adapter,
     adv::Advertisement,
     agent::{Agent, AgentHandle, RegisteredAgent},
-    all_dbus_objects, gatt,
+    all_dbus_objects,
+    battery::RegisteredBattery,
+    device_set::{self, DeviceSet},
+    gatt,
     monitor::RegisteredMonitor,
     parent_path, Adapter, DiscoveryFilter, Error, ErrorKind, InternalErrorKind, Result, SERVICE_NAME,
 };
@@ -45,6 +50,11 @@ use crate::mesh::{
     network::Network, provisioner::RegisteredProvisioner,
 };
 
+#[cfg(feature = "media")]
+use crate::media::endpoint::RegisteredEndpoint;
+
+#[cfg(feature = "hid")]
+use crate::hid::{HidDevice, HidDeviceHandle};
 #[cfg(feature = "rfcomm")]
 use crate::rfcomm::{profile::RegisteredProfile, Profile, ProfileHandle};
 
@@ -61,6 +71,7 @@ pub(crate) struct SessionInner {
     pub gatt_reg_characteristic_descriptor_token: IfaceToken<Arc<gatt::local::RegisteredDescriptor>>,
     pub gatt_profile_token: IfaceToken<gatt::local::Profile>,
     pub agent_token: IfaceToken<Arc<RegisteredAgent>>,
+    pub battery_provider_token: IfaceToken<Arc<RegisteredBattery>>,
     #[cfg(feature = "mesh")]
     pub application_token: IfaceToken<Arc<RegisteredApplication>>,
     #[cfg(feature = "mesh")]
@@ -70,11 +81,13 @@ pub(crate) struct SessionInner {
     #[cfg(feature = "mesh")]
     pub provision_agent_token: IfaceToken<Arc<RegisteredProvisionAgent>>,
     pub monitor_token: IfaceToken<Arc<RegisteredMonitor>>,
+    #[cfg(feature = "media")]
+    pub media_endpoint_token: IfaceToken<Arc<RegisteredEndpoint>>,
     #[cfg(feature = "rfcomm")]
     pub profile_token: IfaceToken<Arc<RegisteredProfile>>,
     pub single_sessions: Mutex<HashMap<dbus::Path<'static>, SingleSessionTerm>>,
     pub event_sub_tx: mpsc::Sender<SubscriptionReq>,
-    dbus_task: JoinHandle<connection::IOResourceError>,
+    dbus_task: Option<JoinHandle<connection::IOResourceError>>,
     pub adapter_discovery_filter: Mutex<HashMap<String, DiscoveryFilter>>,
 }
 
@@ -147,7 +160,9 @@ impl SessionInner {
 impl Drop for SessionInner {
     fn drop(&mut self) {
         // documentation for dbus_tokio::connection::IOResource indicates it is abortable
-        self.dbus_task.abort();
+        if let Some(dbus_task) = &self.dbus_task {
+            dbus_task.abort();
+        }
     }
 }
 
@@ -179,11 +194,26 @@ impl Debug for Session {
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum SessionEvent {
     /// Adapter added.
     AdapterAdded(String),
     /// Adapter removed.
     AdapterRemoved(String),
+    /// Coordinated device set added.
+    DeviceSetAdded {
+        /// Name of the adapter the device set belongs to.
+        adapter_name: String,
+        /// Name of the device set.
+        name: String,
+    },
+    /// Coordinated device set removed.
+    DeviceSetRemoved {
+        /// Name of the adapter the device set belonged to.
+        adapter_name: String,
+        /// Name of the device set.
+        name: String,
+    },
 }
 
 impl Session {
@@ -194,7 +224,42 @@ impl Session {
         let (resource, connection) = spawn_blocking(connection::new_system_sync).await??;
         let dbus_task = tokio::spawn(resource);
         log::trace!("Connected to D-Bus with unique name {}", &connection.unique_name());
+        Self::from_connection(connection, Some(dbus_task)).await
+    }
+
+    /// Create a new Bluetooth session using the D-Bus bus at the specified address.
+    ///
+    /// The address is given in D-Bus notation, for example `unix:path=/run/dbus/system_bus_socket`.
+    /// The Bluetooth daemon is expected to own the `org.bluez` name on that bus.
+    /// This is useful for connecting to a [mock Bluetooth daemon](crate::mock).
+    pub async fn new_with_address(address: &str) -> Result<Self> {
+        let address = address.to_string();
+        let (resource, connection) = spawn_blocking(move || {
+            let mut channel = Channel::open_private(&address)?;
+            channel.register()?;
+            connection::from_channel::<SyncConnection>(channel)
+        })
+        .await??;
+        let dbus_task = tokio::spawn(resource);
+        log::trace!("Connected to D-Bus with unique name {}", &connection.unique_name());
+        Self::from_connection(connection, Some(dbus_task)).await
+    }
 
+    /// Create a new Bluetooth session using an existing D-Bus connection.
+    ///
+    /// The caller is responsible for driving the I/O resource of the connection,
+    /// i.e. the connection must have been created using [dbus_tokio::connection]
+    /// and its I/O resource must have been spawned onto the Tokio runtime.
+    ///
+    /// The session handles all method calls received on the connection,
+    /// thus the connection should not be shared with other D-Bus services.
+    pub async fn with_connection(connection: Arc<SyncConnection>) -> Result<Self> {
+        Self::from_connection(connection, None).await
+    }
+
+    async fn from_connection(
+        connection: Arc<SyncConnection>, dbus_task: Option<JoinHandle<connection::IOResourceError>>,
+    ) -> Result<Self> {
         let mut crossroads = Crossroads::new();
         crossroads.set_async_support(Some((
             connection.clone(),
@@ -213,7 +278,10 @@ impl Session {
             gatt::local::RegisteredDescriptor::register_interface(&mut crossroads);
         let gatt_profile_token = gatt::local::Profile::register_interface(&mut crossroads);
         let agent_token = RegisteredAgent::register_interface(&mut crossroads);
+        let battery_provider_token = RegisteredBattery::register_interface(&mut crossroads);
         let monitor_token = RegisteredMonitor::register_interface(&mut crossroads);
+        #[cfg(feature = "media")]
+        let media_endpoint_token = RegisteredEndpoint::register_interface(&mut crossroads);
         #[cfg(feature = "rfcomm")]
         let profile_token = RegisteredProfile::register_interface(&mut crossroads);
         #[cfg(feature = "mesh")]
@@ -226,7 +294,7 @@ impl Session {
         let provision_agent_token = RegisteredProvisionAgent::register_interface(&mut crossroads);
 
         let (event_sub_tx, event_sub_rx) = mpsc::channel(1);
-        Event::handle_connection(connection.clone(), event_sub_rx).await?;
+        Event::handle_connection(connection.clone(), SERVICE_NAME, event_sub_rx).await?;
 
         let inner = Arc::new(SessionInner {
             connection: connection.clone(),
@@ -237,6 +305,7 @@ impl Session {
             gatt_reg_characteristic_descriptor_token,
             gatt_profile_token,
             agent_token,
+            battery_provider_token,
             #[cfg(feature = "mesh")]
             application_token,
             #[cfg(feature = "mesh")]
@@ -246,6 +315,8 @@ impl Session {
             #[cfg(feature = "mesh")]
             provision_agent_token,
             monitor_token,
+            #[cfg(feature = "media")]
+            media_endpoint_token,
             #[cfg(feature = "rfcomm")]
             profile_token,
             single_sessions: Mutex::new(HashMap::new()),
@@ -352,25 +423,102 @@ impl Session {
         reg_profile.register(self.inner.clone(), profile, req_rx).await
     }
 
-    /// Stream adapter added and removed events.
+    /// This registers a [local HID device](crate::hid::HidDevice).
+    ///
+    /// The SDP record of the HID device is registered and the HIDP control and
+    /// interrupt channels are listened on.
+    /// Use the returned [HidDeviceHandle] to accept connections from hosts.
+    ///
+    /// Drop the handle to unregister the HID device.
+    #[cfg(feature = "hid")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "hid")))]
+    pub async fn register_hid_device(&self, device: HidDevice) -> Result<HidDeviceHandle> {
+        HidDeviceHandle::register(self, device).await
+    }
+
+    /// Stream adapter and coordinated device set added and removed events.
+    ///
+    /// When an adapter is added, device sets it already contains are reported
+    /// following its [AdapterAdded](SessionEvent::AdapterAdded) event.
     pub async fn events(&self) -> Result<impl Stream<Item = SessionEvent>> {
-        let obj_events = self.inner.events(adapter::PATH.into(), true).await?;
-        let events = obj_events.filter_map(|evt| async move {
-            match evt {
-                Event::ObjectAdded { object, interfaces }
-                    if interfaces.iter().any(|i| i == adapter::INTERFACE) =>
-                {
-                    Adapter::parse_dbus_path(&object).map(|name| SessionEvent::AdapterAdded(name.to_string()))
-                }
-                Event::ObjectRemoved { object, interfaces }
-                    if interfaces.iter().any(|i| i == adapter::INTERFACE) =>
-                {
-                    Adapter::parse_dbus_path(&object).map(|name| SessionEvent::AdapterRemoved(name.to_string()))
+        let mut obj_events = self.inner.events(adapter::PATH.into(), true).await?;
+        let mut set_events = SelectAll::new();
+        let mut adapters = HashSet::new();
+        for name in self.adapter_names().await? {
+            set_events.push(self.inner.events(Adapter::dbus_path(&name)?, true).await?);
+            adapters.insert(name);
+        }
+
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let inner = self.inner.clone();
+        tokio::spawn(async move {
+            let mut sets = HashSet::new();
+            loop {
+                let evts = select! {
+                    evt = obj_events.next() => match evt {
+                        Some(Event::ObjectAdded { object, interfaces }) if interfaces.contains(adapter::INTERFACE) => {
+                            let Some(name) = Adapter::parse_dbus_path(&object) else { continue };
+                            let mut evts = vec![SessionEvent::AdapterAdded(name.to_string())];
+                            if adapters.insert(name.to_string()) {
+                                // Sets added before the subscription are found by enumerating
+                                // the objects after it has been established.
+                                if let Ok(adapter_events) = inner.events(object.clone(), true).await {
+                                    set_events.push(adapter_events);
+                                }
+                                let set_names = match Adapter::new(inner.clone(), name) {
+                                    Ok(adapter) => adapter.device_set_names().await.unwrap_or_default(),
+                                    Err(_) => Vec::new(),
+                                };
+                                evts.extend(set_names.into_iter().map(|set_name| SessionEvent::DeviceSetAdded {
+                                    adapter_name: name.to_string(),
+                                    name: set_name,
+                                }));
+                            }
+                            evts
+                        }
+                        Some(Event::ObjectRemoved { object, interfaces }) if interfaces.contains(adapter::INTERFACE) => {
+                            let Some(name) = Adapter::parse_dbus_path(&object) else { continue };
+                            adapters.remove(name);
+                            vec![SessionEvent::AdapterRemoved(name.to_string())]
+                        }
+                        Some(_) => continue,
+                        None => break,
+                    },
+                    Some(evt) = set_events.next(), if !set_events.is_empty() => match evt {
+                        Event::ObjectAdded { object, interfaces } if interfaces.contains(device_set::INTERFACE) => {
+                            let Some((adapter_name, name)) = DeviceSet::parse_dbus_path(&object) else { continue };
+                            let (adapter_name, name) = (adapter_name.to_string(), name.to_string());
+                            vec![SessionEvent::DeviceSetAdded { adapter_name, name }]
+                        }
+                        Event::ObjectRemoved { object, interfaces } if interfaces.contains(device_set::INTERFACE) => {
+                            let Some((adapter_name, name)) = DeviceSet::parse_dbus_path(&object) else { continue };
+                            let (adapter_name, name) = (adapter_name.to_string(), name.to_string());
+                            vec![SessionEvent::DeviceSetRemoved { adapter_name, name }]
+                        }
+                        _ => continue,
+                    },
+                    () = tx.closed() => break,
+                };
+                for evt in evts {
+                    // A set found by enumeration may also be reported by the subscription.
+                    let new = match &evt {
+                        SessionEvent::DeviceSetAdded { adapter_name, name } => {
+                            sets.insert((adapter_name.clone(), name.clone()))
+                        }
+                        SessionEvent::DeviceSetRemoved { adapter_name, name } => {
+                            sets.remove(&(adapter_name.clone(), name.clone()));
+                            true
+                        }
+                        _ => true,
+                    };
+                    if new && tx.send(evt).await.is_err() {
+                        return;
+                    }
                 }
-                _ => None,
             }
         });
-        Ok(events)
+
+        Ok(ReceiverStream::new(rx))
     }
 }
 
@@ -412,15 +560,12 @@ pub(crate) struct SubscriptionReq {
 }
 
 impl Event {
-    /// Spawns a task that handles events for the specified connection.
+    /// Spawns a task that handles events of the specified D-Bus service for the specified connection.
     pub(crate) async fn handle_connection(
-        connection: Arc<SyncConnection>, mut sub_rx: mpsc::Receiver<SubscriptionReq>,
+        connection: Arc<SyncConnection>, service_name: &'static str, mut sub_rx: mpsc::Receiver<SubscriptionReq>,
     ) -> Result<()> {
         use dbus::message::SignalArgs;
-        lazy_static! {
-            static ref SERVICE_NAME_BUS: BusName<'static> = BusName::new(SERVICE_NAME).unwrap();
-            static ref SERVICE_NAME_REF: Option<&'static BusName<'static>> = Some(&SERVICE_NAME_BUS);
-        }
+        let service_name = BusName::new(service_name).unwrap();
 
         let (msg_tx, mut msg_rx) = mpsc::unbounded();
         let handle_msg = move |msg: Message| {
@@ -428,13 +573,13 @@ impl Event {
             true
         };
 
-        let rule_add = ObjectManagerInterfacesAdded::match_rule(*SERVICE_NAME_REF, None);
+        let rule_add = ObjectManagerInterfacesAdded::match_rule(Some(&service_name), None).static_clone();
         let msg_match_add = connection.add_match(rule_add).await?.msg_cb(handle_msg.clone());
 
-        let rule_removed = ObjectManagerInterfacesRemoved::match_rule(*SERVICE_NAME_REF, None);
+        let rule_removed = ObjectManagerInterfacesRemoved::match_rule(Some(&service_name), None).static_clone();
         let msg_match_removed = connection.add_match(rule_removed).await?.msg_cb(handle_msg.clone());
 
-        let rule_prop = PropertiesPropertiesChanged::match_rule(*SERVICE_NAME_REF, None);
+        let rule_prop = PropertiesPropertiesChanged::match_rule(Some(&service_name), None).static_clone();
         let msg_match_prop = connection.add_match(rule_prop).await?.msg_cb(handle_msg.clone());
 
         tokio::spawn(async move {
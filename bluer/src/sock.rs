@@ -3,7 +3,7 @@
 use libc::{c_int, sockaddr, socklen_t, Ioctl, SOCK_CLOEXEC, SOCK_NONBLOCK};
 use std::{
     io::{Error, ErrorKind, Result},
-    mem::{size_of, MaybeUninit},
+    mem::{size_of, size_of_val, MaybeUninit},
     os::unix::io::{AsRawFd, IntoRawFd, RawFd},
 };
 use tokio::io::ReadBuf;
@@ -48,6 +48,19 @@ pub trait SysSockAddr: Sized {
     /// System socket address type.
     type SysSockAddr: Sized + 'static;
 
+    /// Minimum length of system socket address.
+    ///
+    /// This is less than the size of [Self::SysSockAddr] for socket addresses
+    /// of variable length.
+    /// Received socket addresses that are shorter than [Self::SysSockAddr]
+    /// are padded with zeros.
+    const MIN_SYS_SOCK_ADDR_LEN: usize = size_of::<Self::SysSockAddr>();
+
+    /// Length of system socket address after conversion.
+    fn sys_sock_addr_len(&self) -> usize {
+        size_of::<Self::SysSockAddr>()
+    }
+
     /// Convert to system socket address.
     fn into_sys_sock_addr(self) -> Self::SysSockAddr;
 
@@ -55,6 +68,17 @@ pub trait SysSockAddr: Sized {
     fn try_from_sys_sock_addr(addr: Self::SysSockAddr) -> Result<Self>;
 }
 
+/// Checks the length of a system socket address returned by the kernel.
+fn check_sys_sock_addr_len<SA>(length: socklen_t, msg: &'static str) -> Result<()>
+where
+    SA: SysSockAddr,
+{
+    if (length as usize) < SA::MIN_SYS_SOCK_ADDR_LEN || (length as usize) > size_of::<SA::SysSockAddr>() {
+        return Err(Error::new(ErrorKind::InvalidInput, msg));
+    }
+    Ok(())
+}
+
 /// Creates a socket of the specified type and returns its file descriptor.
 ///
 /// The socket is set to non-blocking mode.
@@ -71,15 +95,9 @@ pub fn bind<SA>(socket: &OwnedFd, sa: SA) -> Result<()>
 where
     SA: SysSockAddr,
 {
+    let len = sa.sys_sock_addr_len();
     let addr: SA::SysSockAddr = sa.into_sys_sock_addr();
-    if unsafe {
-        libc::bind(
-            socket.as_raw_fd(),
-            &addr as *const _ as *const sockaddr,
-            size_of::<SA::SysSockAddr>() as socklen_t,
-        )
-    } == 0
-    {
+    if unsafe { libc::bind(socket.as_raw_fd(), &addr as *const _ as *const sockaddr, len as socklen_t) } == 0 {
         Ok(())
     } else {
         Err(Error::last_os_error())
@@ -91,16 +109,14 @@ pub fn getsockname<SA>(socket: &OwnedFd) -> Result<SA>
 where
     SA: SysSockAddr,
 {
-    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::uninit();
+    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::zeroed();
     let mut length = size_of::<SA::SysSockAddr>() as socklen_t;
 
     if unsafe { libc::getsockname(socket.as_raw_fd(), saddr.as_mut_ptr() as *mut _, &mut length) } == -1 {
         return Err(Error::last_os_error());
     };
 
-    if length != size_of::<SA::SysSockAddr>() as socklen_t {
-        return Err(Error::new(ErrorKind::InvalidInput, "invalid sockaddr length from getsockname"));
-    }
+    check_sys_sock_addr_len::<SA>(length, "invalid sockaddr length from getsockname")?;
     let saddr = unsafe { saddr.assume_init() };
     SA::try_from_sys_sock_addr(saddr)
 }
@@ -110,16 +126,14 @@ pub fn getpeername<SA>(socket: &OwnedFd) -> Result<SA>
 where
     SA: SysSockAddr,
 {
-    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::uninit();
+    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::zeroed();
     let mut length = size_of::<SA::SysSockAddr>() as socklen_t;
 
     if unsafe { libc::getpeername(socket.as_raw_fd(), saddr.as_mut_ptr() as *mut _, &mut length) } == -1 {
         return Err(Error::last_os_error());
     };
 
-    if length != size_of::<SA::SysSockAddr>() as socklen_t {
-        return Err(Error::new(ErrorKind::InvalidInput, "invalid sockaddr length from getpeername"));
-    }
+    check_sys_sock_addr_len::<SA>(length, "invalid sockaddr length from getpeername")?;
     let saddr = unsafe { saddr.assume_init() };
     SA::try_from_sys_sock_addr(saddr)
 }
@@ -140,7 +154,7 @@ pub fn accept<SA>(socket: &OwnedFd) -> Result<(OwnedFd, SA)>
 where
     SA: SysSockAddr,
 {
-    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::uninit();
+    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::zeroed();
     let mut length = size_of::<SA::SysSockAddr>() as socklen_t;
 
     let fd = match unsafe {
@@ -150,9 +164,7 @@ where
         fd => unsafe { OwnedFd::new(fd) },
     };
 
-    if length != size_of::<SA::SysSockAddr>() as socklen_t {
-        return Err(Error::new(ErrorKind::InvalidInput, "invalid sockaddr length"));
-    }
+    check_sys_sock_addr_len::<SA>(length, "invalid sockaddr length")?;
     let saddr = unsafe { saddr.assume_init() };
     let sa = SA::try_from_sys_sock_addr(saddr)?;
 
@@ -164,15 +176,9 @@ pub fn connect<SA>(socket: &OwnedFd, sa: SA) -> Result<()>
 where
     SA: SysSockAddr,
 {
+    let len = sa.sys_sock_addr_len();
     let addr: SA::SysSockAddr = sa.into_sys_sock_addr();
-    if unsafe {
-        libc::connect(
-            socket.as_raw_fd(),
-            &addr as *const _ as *const sockaddr,
-            size_of::<SA::SysSockAddr>() as socklen_t,
-        )
-    } == 0
-    {
+    if unsafe { libc::connect(socket.as_raw_fd(), &addr as *const _ as *const sockaddr, len as socklen_t) } == 0 {
         Ok(())
     } else {
         Err(Error::last_os_error())
@@ -192,6 +198,7 @@ pub fn sendto<SA>(socket: &OwnedFd, buf: &[u8], flags: c_int, sa: SA) -> Result<
 where
     SA: SysSockAddr,
 {
+    let len = sa.sys_sock_addr_len();
     let addr: SA::SysSockAddr = sa.into_sys_sock_addr();
     match unsafe {
         libc::sendto(
@@ -200,7 +207,7 @@ where
             buf.len(),
             flags,
             &addr as *const _ as *const sockaddr,
-            size_of::<SA::SysSockAddr>() as socklen_t,
+            len as socklen_t,
         )
     } {
         -1 => Err(Error::last_os_error()),
@@ -230,7 +237,7 @@ where
     SA: SysSockAddr,
 {
     let unfilled = unsafe { buf.unfilled_mut() };
-    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::uninit();
+    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::zeroed();
     let mut length = size_of::<SA::SysSockAddr>() as socklen_t;
     match unsafe {
         libc::recvfrom(
@@ -250,9 +257,7 @@ where
             }
             buf.advance(n);
 
-            if length != size_of::<SA::SysSockAddr>() as socklen_t {
-                return Err(Error::new(ErrorKind::InvalidInput, "invalid sockaddr length"));
-            }
+            check_sys_sock_addr_len::<SA>(length, "invalid sockaddr length")?;
             let saddr = unsafe { saddr.assume_init() };
             let sa = SA::try_from_sys_sock_addr(saddr)?;
 
@@ -287,8 +292,8 @@ pub fn getsockopt<T>(socket: &OwnedFd, level: c_int, optname: c_int) -> Result<T
 }
 
 /// Set socket option.
-pub fn setsockopt<T>(socket: &OwnedFd, level: c_int, optname: i32, optval: &T) -> Result<()> {
-    let optlen: socklen_t = size_of::<T>() as _;
+pub fn setsockopt<T: ?Sized>(socket: &OwnedFd, level: c_int, optname: i32, optval: &T) -> Result<()> {
+    let optlen: socklen_t = size_of_val(optval) as _;
     if unsafe { libc::setsockopt(socket.as_raw_fd(), level, optname, optval as *const _ as *const _, optlen) }
         == -1
     {
@@ -1,6 +1,6 @@
 //! Stream utility types.
 
-// Note that this is included as a module by both l2cap and rfcomm.
+// Note that this is included as a module by l2cap, rfcomm, sco and iso.
 
 use libc::{SHUT_RD, SHUT_WR};
 use std::{
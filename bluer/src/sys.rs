@@ -5,7 +5,9 @@ use libc::{c_int, c_ushort, sa_family_t};
 use nix::{request_code_write, sys::ioctl::ioctl_num_type};
 use std::mem::size_of;
 
+pub const SOL_HCI: i32 = 0;
 pub const SOL_L2CAP: i32 = 6;
+pub const SOL_SCO: i32 = 17;
 pub const SOL_RFCOMM: i32 = 18;
 
 /// Bluetooth security.
@@ -31,14 +33,29 @@ pub struct bt_power {
     pub force_active: u8,
 }
 
+pub const BT_DEFER_SETUP: i32 = 7;
+
 pub const BT_POWER: i32 = 9;
 pub const BT_POWER_FORCE_ACTIVE_OFF: i32 = 0;
 pub const BT_POWER_FORCE_ACTIVE_ON: i32 = 1;
 
+pub const BT_VOICE: i32 = 11;
+
+#[repr(C)]
+#[derive(Clone)]
+pub struct bt_voice {
+    pub setting: u16,
+}
+
+pub const BT_VOICE_TRANSPARENT: u16 = 0x0003;
+pub const BT_VOICE_CVSD_16BIT: u16 = 0x0060;
+
 pub const BT_SNDMTU: i32 = 12;
 pub const BT_RCVMTU: i32 = 13;
 pub const BT_PHY: i32 = 14;
 pub const BT_MODE: i32 = 15;
+pub const BT_ISO_QOS: i32 = 17;
+pub const BT_ISO_BASE: i32 = 20;
 
 /// BR1M1SLOT PHY.
 pub const BR1M1SLOT: i32 = 1 << 0;
@@ -72,12 +89,15 @@ pub const LECODEDTX: i32 = 1 << 13;
 pub const LECODEDRX: i32 = 1 << 14;
 
 pub const BTPROTO_L2CAP: i32 = 0;
+pub const BTPROTO_HCI: i32 = 1;
+pub const BTPROTO_SCO: i32 = 2;
 pub const BTPROTO_RFCOMM: i32 = 3;
+pub const BTPROTO_ISO: i32 = 8;
 
 /// Bluetooth address.
 #[repr(packed)]
 #[repr(C)]
-#[derive(Clone, Default)]
+#[derive(Clone, Copy, Default)]
 pub struct bdaddr_t {
     pub b: [u8; 6],
 }
@@ -198,3 +218,249 @@ pub struct rfcomm_dev_req {
     pub dst: bdaddr_t,
     pub channel: u8,
 }
+
+/// SCO socket address.
+#[repr(C)]
+#[derive(Clone)]
+pub struct sockaddr_sco {
+    pub sco_family: sa_family_t,
+    pub sco_bdaddr: bdaddr_t,
+}
+
+pub const SCO_OPTIONS: i32 = 0x01;
+pub const SCO_CONNINFO: i32 = 0x02;
+
+/// Raw socket options for SCO sockets.
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct sco_options {
+    /// MTU.
+    pub mtu: u16,
+}
+
+/// SCO socket connection information.
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct sco_conninfo {
+    /// Host controller interface (HCI) handle for the connection.
+    pub hci_handle: u16,
+    /// Device class.
+    pub dev_class: [u8; 3],
+}
+
+pub const ISO_MAX_NUM_BIS: usize = 0x1f;
+
+/// ISO broadcast socket address.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct sockaddr_iso_bc {
+    pub bc_bdaddr: bdaddr_t,
+    pub bc_bdaddr_type: u8,
+    pub bc_sid: u8,
+    pub bc_num_bis: u8,
+    pub bc_bis: [u8; ISO_MAX_NUM_BIS],
+}
+
+/// ISO socket address followed by broadcast socket address.
+///
+/// In C the broadcast socket address is a flexible array member.
+#[repr(C)]
+#[derive(Clone)]
+pub struct sockaddr_iso {
+    pub iso_family: sa_family_t,
+    pub iso_bdaddr: bdaddr_t,
+    pub iso_bdaddr_type: u8,
+    pub iso_bc: sockaddr_iso_bc,
+}
+
+/// Size of `struct sockaddr_iso` in C, i.e. without broadcast socket address.
+pub const SOCKADDR_ISO_SIZE: usize = 10;
+
+/// Connected isochronous group (CIG) is allocated by kernel.
+pub const BT_ISO_QOS_CIG_UNSET: u8 = 0xff;
+/// Connected isochronous stream (CIS) is allocated by kernel.
+pub const BT_ISO_QOS_CIS_UNSET: u8 = 0xff;
+/// Broadcast isochronous group (BIG) is allocated by kernel.
+pub const BT_ISO_QOS_BIG_UNSET: u8 = 0xff;
+/// Broadcast isochronous stream (BIS) is allocated by kernel.
+pub const BT_ISO_QOS_BIS_UNSET: u8 = 0xff;
+
+/// LE 1M PHY.
+pub const BT_ISO_PHY_1M: u8 = 0x01;
+/// LE 2M PHY.
+pub const BT_ISO_PHY_2M: u8 = 0x02;
+/// LE Coded PHY.
+pub const BT_ISO_PHY_CODED: u8 = 0x04;
+
+pub const BT_ISO_SYNC_TIMEOUT: u16 = 0x07d0;
+
+pub const BASE_MAX_LENGTH: usize = 252;
+
+/// ISO QoS of one direction.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct bt_iso_io_qos {
+    /// SDU interval in microseconds.
+    pub interval: u32,
+    /// Maximum transport latency in milliseconds.
+    pub latency: u16,
+    /// Maximum SDU size.
+    pub sdu: u16,
+    /// PHY bit field.
+    ///
+    /// Possible values are defined in the [phy](crate::iso::phy) module.
+    pub phy: u8,
+    /// Retransmission number.
+    pub rtn: u8,
+}
+
+impl Default for bt_iso_io_qos {
+    fn default() -> Self {
+        Self { interval: 10000, latency: 10, sdu: 40, phy: BT_ISO_PHY_2M, rtn: 2 }
+    }
+}
+
+/// ISO QoS for connected isochronous streams (CIS).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct bt_iso_ucast_qos {
+    /// Connected isochronous group (CIG) id.
+    ///
+    /// [CIG_UNSET](crate::iso::CIG_UNSET) lets the kernel allocate the CIG.
+    pub cig: u8,
+    /// Connected isochronous stream (CIS) id.
+    ///
+    /// [CIS_UNSET](crate::iso::CIS_UNSET) lets the kernel allocate the CIS.
+    pub cis: u8,
+    /// Sleep clock accuracy.
+    pub sca: u8,
+    /// Packing: 0 for sequential, 1 for interleaved.
+    pub packing: u8,
+    /// Framing: 0 for unframed, 1 for framed.
+    pub framing: u8,
+    /// QoS for receiving.
+    pub input: bt_iso_io_qos,
+    /// QoS for sending.
+    pub output: bt_iso_io_qos,
+}
+
+impl Default for bt_iso_ucast_qos {
+    fn default() -> Self {
+        Self {
+            cig: BT_ISO_QOS_CIG_UNSET,
+            cis: BT_ISO_QOS_CIS_UNSET,
+            sca: 0,
+            packing: 0,
+            framing: 0,
+            input: Default::default(),
+            output: Default::default(),
+        }
+    }
+}
+
+/// ISO QoS for broadcast isochronous streams (BIS).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct bt_iso_bcast_qos {
+    /// Broadcast isochronous group (BIG) handle.
+    ///
+    /// [BIG_UNSET](crate::iso::BIG_UNSET) lets the kernel allocate the BIG.
+    pub big: u8,
+    /// Broadcast isochronous stream (BIS) index.
+    ///
+    /// [BIS_UNSET](crate::iso::BIS_UNSET) lets the kernel allocate the BIS.
+    pub bis: u8,
+    /// Synchronization factor of periodic advertising.
+    pub sync_factor: u8,
+    /// Packing: 0 for sequential, 1 for interleaved.
+    pub packing: u8,
+    /// Framing: 0 for unframed, 1 for framed.
+    pub framing: u8,
+    /// QoS for receiving.
+    pub input: bt_iso_io_qos,
+    /// QoS for sending.
+    pub output: bt_iso_io_qos,
+    /// Whether the broadcast is encrypted.
+    pub encryption: u8,
+    /// Broadcast code used for encryption.
+    pub bcode: [u8; 16],
+    /// Periodic advertising create sync options.
+    pub options: u8,
+    /// Number of periodic advertising packets that can be skipped.
+    pub skip: u16,
+    /// Synchronization timeout for periodic advertising in units of 10 ms.
+    pub sync_timeout: u16,
+    /// Constant tone extension types that must not be synchronized to.
+    pub sync_cte_type: u8,
+    /// Maximum number of subevents.
+    pub mse: u8,
+    /// Synchronization timeout for the BIG in units of 10 ms.
+    pub timeout: u16,
+}
+
+impl Default for bt_iso_bcast_qos {
+    fn default() -> Self {
+        Self {
+            big: BT_ISO_QOS_BIG_UNSET,
+            bis: BT_ISO_QOS_BIS_UNSET,
+            sync_factor: 0x01,
+            packing: 0,
+            framing: 0,
+            input: Default::default(),
+            output: Default::default(),
+            encryption: 0,
+            bcode: [0; 16],
+            options: 0,
+            skip: 0,
+            sync_timeout: BT_ISO_SYNC_TIMEOUT,
+            sync_cte_type: 0,
+            mse: 0,
+            timeout: BT_ISO_SYNC_TIMEOUT,
+        }
+    }
+}
+
+/// ISO QoS.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union bt_iso_qos {
+    pub ucast: bt_iso_ucast_qos,
+    pub bcast: bt_iso_bcast_qos,
+}
+
+/// HCI socket address.
+#[repr(C)]
+#[derive(Clone)]
+pub struct sockaddr_hci {
+    pub hci_family: sa_family_t,
+    pub hci_dev: c_ushort,
+    pub hci_channel: c_ushort,
+}
+
+pub const HCI_DEV_NONE: u16 = 0xffff;
+
+pub const HCI_CHANNEL_RAW: u16 = 0;
+pub const HCI_CHANNEL_USER: u16 = 1;
+pub const HCI_CHANNEL_MONITOR: u16 = 2;
+pub const HCI_CHANNEL_CONTROL: u16 = 3;
+
+pub const HCI_FILTER: i32 = 2;
+
+/// HCI socket filter.
+#[repr(C)]
+#[derive(Clone, Default)]
+pub struct hci_filter {
+    pub type_mask: u32,
+    pub event_mask: [u32; 2],
+    pub opcode: u16,
+}